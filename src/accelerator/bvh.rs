@@ -20,6 +20,8 @@ impl Primitive for BVHAggregate {
         let mut nodes_to_visit = vec![0];
         let mut best_t = t_max;
         let mut best_intersection: Option<ShapeIntersection> = None;
+        let mut nodes_visited = 0;
+        let mut primitive_tests = 0;
 
         loop {
             let current_node_idx = match nodes_to_visit.pop() {
@@ -29,12 +31,14 @@ impl Primitive for BVHAggregate {
                 Some(idx) => idx,
             };
 
+            nodes_visited += 1;
             let node = &self.linear_bvh_nodes[current_node_idx];
             if !node.bounds.fast_intersect(ray, best_t, inv_dir, dir_is_neg) {
                 continue;
             }
 
             if node.primitive_num > 0 {
+                primitive_tests += node.primitive_num as u64;
                 for idx in node.offset..(node.offset + node.primitive_num as u32) {
                     let primitive = &self.ordered_primitives[idx as usize];
                     match primitive.intersect(ray, best_t) {
@@ -61,6 +65,9 @@ impl Primitive for BVHAggregate {
             }
         }
 
+        add_stat(StatCounter::BVHNodesVisited, nodes_visited);
+        add_stat(StatCounter::PrimitiveTests, primitive_tests);
+
         return best_intersection;
     }
 
@@ -74,14 +81,23 @@ impl Primitive for BVHAggregate {
         ];
 
         let mut nodes_to_visit = vec![0];
+        let mut nodes_visited = 0;
+        let mut primitive_tests = 0;
+        let record_stats = |nodes_visited: u64, primitive_tests: u64| {
+            add_stat(StatCounter::BVHNodesVisited, nodes_visited);
+            add_stat(StatCounter::PrimitiveTests, primitive_tests);
+        };
+
         loop {
             let current_node_idx = match nodes_to_visit.pop() {
                 None => {
+                    record_stats(nodes_visited, primitive_tests);
                     return false;
                 }
                 Some(idx) => idx,
             };
 
+            nodes_visited += 1;
             let node = &self.linear_bvh_nodes[current_node_idx];
             if !node.bounds.fast_intersect(ray, t_max, inv_dir, dir_is_neg) {
                 continue;
@@ -90,7 +106,9 @@ impl Primitive for BVHAggregate {
             if node.primitive_num > 0 {
                 for idx in node.offset..(node.offset + node.primitive_num as u32) {
                     let primitive = &self.ordered_primitives[idx as usize];
+                    primitive_tests += 1;
                     if primitive.fast_intersect(ray, t_max) {
+                        record_stats(nodes_visited, primitive_tests);
                         return true;
                     }
                 }
@@ -110,7 +128,7 @@ impl Primitive for BVHAggregate {
     }
 
     fn bounds(&self) -> Bounds3f {
-        panic!("not implemented");
+        return self.linear_bvh_nodes[0].bounds;
     }
 }
 
@@ -242,6 +260,12 @@ impl BVHAggregate {
         // with_capacity() to avoid re-allocations
         flatten_bvh(root, &mut linear_bvh_nodes);
 
+        add_memory(
+            MemoryCategory::BVH,
+            linear_bvh_nodes.len() * size_of::<LinearBVHNode>()
+                + ordered_primitives.len() * size_of::<Arc<dyn Primitive>>(),
+        );
+
         return Self {
             ordered_primitives,
             linear_bvh_nodes,
@@ -62,6 +62,7 @@ impl BSDF {
             mode: TransportMode::Radiance
             sample_flags: BxDFReflTransFlags::All
         */
+        add_stat(StatCounter::BSDFSamples, 1);
 
         let wo = self.render_to_local(wo_render);
         let bxdf_flags = match &self.bxdf {
@@ -98,4 +99,15 @@ impl BSDF {
         bs.wi = self.local_to_render(bs.wi);
         return Some(bs);
     }
+
+    pub fn rho(&self, wo_render: Vector3f, uc: &[f64], u2: &[Point2f]) -> SampledSpectrum {
+        let wo = self.render_to_local(wo_render);
+
+        return match &self.bxdf {
+            None => {
+                panic!("BSDF::rho(): bxdf shouldn't be None");
+            }
+            Some(_bxdf) => _bxdf.rho(wo, uc, u2),
+        };
+    }
 }
@@ -0,0 +1,405 @@
+use crate::pbrt::*;
+
+pub struct BSSRDFProbeSegment {
+    pub p0: Point3f,
+    pub p1: Point3f,
+}
+
+pub struct BSSRDFSample {
+    pub sp: SampledSpectrum,
+    pub pdf: SampledSpectrum,
+    pub sw: BSDF,
+    pub wo: Vector3f,
+}
+
+pub struct BSSRDFTable {
+    pub rho_samples: Vec<f64>,
+    pub radius_samples: Vec<f64>,
+    pub profile: Vec<f64>,
+    pub rho_eff: Vec<f64>,
+    pub profile_cdf: Vec<f64>,
+}
+
+impl BSSRDFTable {
+    pub fn new(n_rho_samples: usize, n_radius_samples: usize) -> Self {
+        return BSSRDFTable {
+            rho_samples: vec![0.0; n_rho_samples],
+            radius_samples: vec![0.0; n_radius_samples],
+            profile: vec![0.0; n_radius_samples * n_rho_samples],
+            rho_eff: vec![0.0; n_rho_samples],
+            profile_cdf: vec![0.0; n_radius_samples * n_rho_samples],
+        };
+    }
+
+    pub fn eval_profile(&self, rho_index: usize, radius_index: usize) -> f64 {
+        return self.profile[rho_index * self.radius_samples.len() + radius_index];
+    }
+}
+
+pub fn beam_diffusion_ms(sigma_s: f64, sigma_a: f64, g: f64, eta: f64, r: f64) -> f64 {
+    let n_samples = 100;
+    let mut ed = 0.0;
+
+    // Precompute information for dipole integrand
+    // Compute reduced scattering coefficients $\sigmaps, \sigmapt$ and albedo $\rhop$
+    let sigmap_s = sigma_s * (1.0 - g);
+    let sigmap_t = sigma_a + sigmap_s;
+    let rhop = sigmap_s / sigmap_t;
+
+    // Compute non-classical diffusion coefficient $D_\roman{G}$ using Equation (15.24)
+    let d_g = (2.0 * sigma_a + sigmap_s) / (3.0 * sigmap_t * sigmap_t);
+
+    // Compute effective transport coefficient $\sigmatr$ based on $D_\roman{G}$
+    let sigma_tr = safe_sqrt(sigma_a / d_g);
+
+    // Determine linear extrapolation distance $\depthextrapolation$ using Equation (15.28)
+    let fm1 = fresnel_moment1(eta);
+    let fm2 = fresnel_moment2(eta);
+    let ze = -2.0 * d_g * (1.0 + 3.0 * fm2) / (1.0 - 2.0 * fm1);
+
+    // Determine exitance scale factors using Equations (15.31) and (15.32)
+    let c_phi = 0.25 * (1.0 - 2.0 * fm1);
+    let c_e = 0.5 * (1.0 - 3.0 * fm2);
+
+    for i in 0..n_samples {
+        // Sample real point source depth $\depthreal$
+        let zr = sample_exponential((i as f64 + 0.5) / n_samples as f64, sigmap_t);
+
+        // Evaluate dipole integrand $E_{\roman{d}}$ at $\depthreal$ and add to _ed_
+        let zv = -zr + 2.0 * ze;
+        let dr = (sqr(r) + sqr(zr)).sqrt();
+        let dv = (sqr(r) + sqr(zv)).sqrt();
+
+        // Compute dipole fluence rate $\dipole(r)$ using Equation (15.27)
+        let phi_d = INV_4PI / d_g * ((-sigma_tr * dr).exp() / dr - (-sigma_tr * dv).exp() / dv);
+
+        // Compute dipole vector irradiance $-\N{}\cdot\dipoleE(r)$ using Equation (15.27)
+        let ed_n = INV_4PI
+            * (zr * (1.0 + sigma_tr * dr) * (-sigma_tr * dr).exp() / dr.powi(3)
+                - zv * (1.0 + sigma_tr * dv) * (-sigma_tr * dv).exp() / dv.powi(3));
+
+        // Add contribution from dipole for depth $\depthreal$ to _ed_
+        let e = phi_d * c_phi + ed_n * c_e;
+        let kappa = 1.0 - (-2.0 * sigmap_t * (dr + zr)).exp();
+        ed += kappa * rhop * rhop * e;
+    }
+
+    return ed / n_samples as f64;
+}
+
+pub fn beam_diffusion_ss(sigma_s: f64, sigma_a: f64, g: f64, eta: f64, r: f64) -> f64 {
+    // Compute material parameters and minimum $t$ below the critical angle
+    let sigma_t = sigma_a + sigma_s;
+    let rho = sigma_s / sigma_t;
+    let t_crit = r * safe_sqrt(sqr(eta) - 1.0);
+
+    let n_samples = 100;
+    let mut ess = 0.0;
+    for i in 0..n_samples {
+        // Evaluate single-scattering integrand and add to _ess_
+        let ti = t_crit + sample_exponential((i as f64 + 0.5) / n_samples as f64, sigma_t);
+
+        // Determine length $d$ of connecting segment and $\cos\theta_\roman{o}$
+        let d = (sqr(r) + sqr(ti)).sqrt();
+        let cos_theta_o = ti / d;
+
+        // Add contribution of single scattering at depth $t$
+        ess += rho * (-sigma_t * (d + t_crit)).exp() / sqr(d)
+            * henyey_greenstein(cos_theta_o, g)
+            * (1.0 - fr_dielectric(-cos_theta_o, eta))
+            * cos_theta_o.abs();
+    }
+
+    return ess / n_samples as f64;
+}
+
+pub fn compute_beam_diffusion_bssrdf(g: f64, eta: f64, table: &mut BSSRDFTable) {
+    // Choose radius values of the diffusion profile discretization
+    table.radius_samples[0] = 0.0;
+    table.radius_samples[1] = 2.5e-3;
+    for i in 2..table.radius_samples.len() {
+        table.radius_samples[i] = table.radius_samples[i - 1] * 1.2;
+    }
+
+    // Choose albedo values of the diffusion profile discretization
+    let n_rho_samples = table.rho_samples.len();
+    for i in 0..n_rho_samples {
+        table.rho_samples[i] =
+            (1.0 - (-8.0 * i as f64 / (n_rho_samples - 1) as f64).exp()) / (1.0 - (-8.0_f64).exp());
+    }
+
+    let n_samples = table.radius_samples.len();
+    let radius_samples = &table.radius_samples;
+    let rho_samples = &table.rho_samples;
+
+    let rows = (0..n_rho_samples)
+        .into_par_iter()
+        .map(|i| {
+            // Compute the diffusion profile for the _i_th albedo sample
+            // Compute scattering profile for chosen albedo $\rho$
+            let rho = rho_samples[i];
+            let profile = radius_samples
+                .iter()
+                .map(|&r| {
+                    2.0 * PI
+                        * r
+                        * (beam_diffusion_ss(rho, 1.0 - rho, g, eta, r)
+                            + beam_diffusion_ms(rho, 1.0 - rho, g, eta, r))
+                })
+                .collect::<Vec<f64>>();
+
+            // Compute effective albedo $\rho_{\roman{eff}}$ and CDF for importance sampling
+            let mut profile_cdf = vec![0.0; n_samples];
+            let rho_eff = integrate_catmull_rom(radius_samples, &profile, &mut profile_cdf);
+
+            (profile, profile_cdf, rho_eff)
+        })
+        .collect::<Vec<(Vec<f64>, Vec<f64>, f64)>>();
+
+    for (i, (profile, profile_cdf, rho_eff)) in rows.into_iter().enumerate() {
+        table.profile[i * n_samples..(i + 1) * n_samples].copy_from_slice(&profile);
+        table.profile_cdf[i * n_samples..(i + 1) * n_samples].copy_from_slice(&profile_cdf);
+        table.rho_eff[i] = rho_eff;
+    }
+}
+
+// invert the effective albedo to find scattering coefficients matching a
+// diffuse reflectance _rho_eff_ and mean free path _mfp_
+pub fn subsurface_from_diffuse(
+    table: &BSSRDFTable,
+    rho_eff: &SampledSpectrum,
+    mfp: &SampledSpectrum,
+) -> (SampledSpectrum, SampledSpectrum) {
+    let mut sigma_a = SampledSpectrum::same_value(0.0);
+    let mut sigma_s = SampledSpectrum::same_value(0.0);
+
+    for c in 0..NUM_SPECTRUM_SAMPLES {
+        let rho = invert_catmull_rom(&table.rho_samples, &table.rho_eff, rho_eff[c]);
+        sigma_s[c] = rho / mfp[c];
+        sigma_a[c] = (1.0 - rho) / mfp[c];
+    }
+
+    return (sigma_a, sigma_s);
+}
+
+pub struct TabulatedBSSRDF {
+    po: Point3f,
+    ns: Normal3f,
+    eta: f64,
+    sigma_t: SampledSpectrum,
+    rho: SampledSpectrum,
+    table: Arc<BSSRDFTable>,
+}
+
+impl TabulatedBSSRDF {
+    pub fn new(
+        po: Point3f,
+        ns: Normal3f,
+        eta: f64,
+        sigma_a: SampledSpectrum,
+        sigma_s: SampledSpectrum,
+        table: Arc<BSSRDFTable>,
+    ) -> Self {
+        let sigma_t = sigma_a + sigma_s;
+        let rho = sigma_s.safe_div(&sigma_t);
+
+        return TabulatedBSSRDF {
+            po,
+            ns,
+            eta,
+            sigma_t,
+            rho,
+            table,
+        };
+    }
+
+    pub fn sp(&self, pi: Point3f) -> SampledSpectrum {
+        return self.sr((self.po - pi).length());
+    }
+
+    // spline weights to interpolate the tabulated profile at wavelength _i_
+    fn profile_weights(&self, r: f64, i: usize) -> Option<((i64, [f64; 4]), (i64, [f64; 4]), f64)> {
+        // Convert $r$ into unitless optical radius $r_{\roman{optical}}$
+        let r_optical = r * self.sigma_t[i];
+
+        let rho_weights = catmull_rom_weights(&self.table.rho_samples, self.rho[i])?;
+        let radius_weights = catmull_rom_weights(&self.table.radius_samples, r_optical)?;
+
+        return Some((rho_weights, radius_weights, r_optical));
+    }
+
+    pub fn sr(&self, r: f64) -> SampledSpectrum {
+        let mut sr_spectrum = SampledSpectrum::same_value(0.0);
+
+        for i in 0..NUM_SPECTRUM_SAMPLES {
+            // Compute spline weights to interpolate BSSRDF at _i_th wavelength
+            let ((rho_offset, rho_weights), (radius_offset, radius_weights), r_optical) =
+                match self.profile_weights(r, i) {
+                    None => {
+                        continue;
+                    }
+                    Some(_weights) => _weights,
+                };
+
+            // Set BSSRDF value _sr_ using tensor spline interpolation
+            let mut sr = 0.0;
+            for j in 0..4 {
+                for k in 0..4 {
+                    // Accumulate contribution of $(j,k)$ table sample
+                    let weight = rho_weights[j] * radius_weights[k];
+                    if weight != 0.0 {
+                        sr += weight
+                            * self.table.eval_profile(
+                                (rho_offset + j as i64) as usize,
+                                (radius_offset + k as i64) as usize,
+                            );
+                    }
+                }
+            }
+
+            // Cancel marginal PDF factor from tabulated BSSRDF profile
+            if r_optical != 0.0 {
+                sr /= 2.0 * PI * r_optical;
+            }
+
+            // Transform BSSRDF value into rendering space units
+            sr_spectrum[i] = (sr * sqr(self.sigma_t[i])).max(0.0);
+        }
+
+        return sr_spectrum;
+    }
+
+    pub fn sample_sr(&self, u: f64) -> Option<f64> {
+        if self.sigma_t[0] == 0.0 {
+            return None;
+        }
+
+        let (x, _, _) = sample_catmull_rom_2d(
+            &self.table.rho_samples,
+            &self.table.radius_samples,
+            &self.table.profile,
+            &self.table.profile_cdf,
+            self.rho[0],
+            u,
+        )?;
+
+        return Some(x / self.sigma_t[0]);
+    }
+
+    pub fn pdf_sr(&self, r: f64) -> SampledSpectrum {
+        let mut pdf = SampledSpectrum::same_value(0.0);
+
+        for i in 0..NUM_SPECTRUM_SAMPLES {
+            // Compute spline weights to interpolate BSSRDF density at _i_th wavelength
+            let ((rho_offset, rho_weights), (radius_offset, radius_weights), r_optical) =
+                match self.profile_weights(r, i) {
+                    None => {
+                        continue;
+                    }
+                    Some(_weights) => _weights,
+                };
+
+            // Return BSSRDF profile density for channel _i_
+            let mut sr = 0.0;
+            let mut rho_eff = 0.0;
+            for j in 0..4 {
+                // Skip contribution of $j$th weight if zero
+                if rho_weights[j] == 0.0 {
+                    continue;
+                }
+
+                // Update _rho_eff_ and _sr_ for wavelength
+                let rho_index = (rho_offset + j as i64) as usize;
+                rho_eff += self.table.rho_eff[rho_index] * rho_weights[j];
+                for k in 0..4 {
+                    if radius_weights[k] == 0.0 {
+                        continue;
+                    }
+                    sr += self
+                        .table
+                        .eval_profile(rho_index, (radius_offset + k as i64) as usize)
+                        * rho_weights[j]
+                        * radius_weights[k];
+                }
+            }
+
+            // Cancel marginal PDF factor from tabulated BSSRDF profile
+            if r_optical != 0.0 {
+                sr /= 2.0 * PI * r_optical;
+            }
+
+            pdf[i] = (sr * sqr(self.sigma_t[i]) / rho_eff).max(0.0);
+        }
+
+        return pdf;
+    }
+
+    pub fn sample(&self, u1: f64, u2: Point2f) -> Option<BSSRDFProbeSegment> {
+        // Choose projection axis for BSSRDF sampling
+        let ns = Vector3f::from(self.ns);
+        let f = if u1 < 0.25 {
+            Frame::from_x(ns)
+        } else if u1 < 0.5 {
+            Frame::from_y(ns)
+        } else {
+            Frame::from_z(ns)
+        };
+
+        // Sample BSSRDF profile in polar coordinates
+        let r = self.sample_sr(u2.x)?;
+        let phi = 2.0 * PI * u2.y;
+
+        // Compute BSSRDF profile bounds and intersection height
+        let r_max = self.sample_sr(0.999)?;
+        if r >= r_max {
+            return None;
+        }
+        let l = 2.0 * (sqr(r_max) - sqr(r)).sqrt();
+
+        // Return BSSRDF sampling ray segment
+        let p_start = self.po + r * (f.x * phi.cos() + f.y * phi.sin()) - l * f.z / 2.0;
+        let p_target = p_start + l * f.z;
+
+        return Some(BSSRDFProbeSegment {
+            p0: p_start,
+            p1: p_target,
+        });
+    }
+
+    pub fn pdf_sp(&self, pi: Point3f, ni: Normal3f) -> SampledSpectrum {
+        // Express $\pti-\pto$ and $\bold{n}_i$ with respect to local coordinates at $\pto$
+        let d = pi - self.po;
+        let f = Frame::from_z(Vector3f::from(self.ns));
+        let d_local = f.to_local(d);
+        let n_local = f.to_local(Vector3f::from(ni));
+
+        // Compute BSSRDF profile radius under projection along each axis
+        let r_proj = [
+            (sqr(d_local.y) + sqr(d_local.z)).sqrt(),
+            (sqr(d_local.z) + sqr(d_local.x)).sqrt(),
+            (sqr(d_local.x) + sqr(d_local.y)).sqrt(),
+        ];
+
+        // Return combined probability from all BSSRDF sampling strategies
+        let mut pdf = SampledSpectrum::same_value(0.0);
+        let axis_prob = [0.25, 0.25, 0.5];
+        for axis in 0..3 {
+            pdf += self.pdf_sr(r_proj[axis]) * n_local[axis].abs() * axis_prob[axis];
+        }
+
+        return pdf;
+    }
+
+    pub fn probe_intersection_to_sample(&self, si: &SurfaceInteraction) -> BSSRDFSample {
+        let bxdf = NormalizedFresnelBxDF::new(self.eta);
+        let wo = Vector3f::from(si.shading.n);
+        let p = Point3f::from(si.interaction.pi);
+
+        return BSSRDFSample {
+            sp: self.sp(p),
+            pdf: self.pdf_sp(p, si.interaction.n),
+            sw: BSDF::new(si.shading.n, si.shading.dpdu, Some(Arc::new(bxdf))),
+            wo,
+        };
+    }
+}
@@ -84,6 +84,14 @@ pub enum BxDFFlags {
     GlossyTransmissionReflection = 1 << 3 | 1 << 1 | 1 << 0,
     SpecularReflection = 1 << 4 | 1 << 0,
     SpecularTransmission = 1 << 4 | 1 << 1,
+    // combinations produced by dielectric interfaces and layered BxDFs
+    TransmissionReflection = 1 << 1 | 1 << 0,
+    DiffuseTransmissionReflection = 1 << 2 | 1 << 1 | 1 << 0,
+    SpecularTransmissionReflection = 1 << 4 | 1 << 1 | 1 << 0,
+    SpecularDiffuseReflection = 1 << 4 | 1 << 2 | 1 << 0,
+    SpecularGlossyReflection = 1 << 4 | 1 << 3 | 1 << 0,
+    SpecularDiffuseTransmissionReflection = 1 << 4 | 1 << 2 | 1 << 1 | 1 << 0,
+    SpecularGlossyTransmissionReflection = 1 << 4 | 1 << 3 | 1 << 1 | 1 << 0,
     All = 1 << 0 | 1 << 1 | 1 << 2 | 1 << 3 | 1 << 4,
 }
 
@@ -201,4 +209,27 @@ pub trait BxDF {
         mode: TransportMode,
         sample_flags: BxDFReflTransFlags,
     ) -> f64;
+
+    fn rho(&self, wo: Vector3f, uc: &[f64], u2: &[Point2f]) -> SampledSpectrum {
+        let mut r = SampledSpectrum::same_value(0.0);
+        for i in 0..uc.len() {
+            // Compute estimate of $\rho_\roman{hd}$
+            match self.sample_f(
+                wo,
+                uc[i],
+                u2[i],
+                TransportMode::Radiance,
+                BxDFReflTransFlags::All,
+            ) {
+                None => {}
+                Some(bs) => {
+                    if bs.pdf > 0.0 {
+                        r += bs.f * bs.wi.abs_cos_theta() / bs.pdf;
+                    }
+                }
+            }
+        }
+
+        return r / uc.len() as f64;
+    }
 }
@@ -8,27 +8,33 @@ pub enum RenderingCoordinateSystem {
 
 #[derive(Copy, Clone)]
 pub struct CameraTransform {
-    pub render_from_camera: Transform,
-    pub camera_from_render: Transform,
+    pub render_from_camera: AnimatedTransform,
     pub world_from_render: Transform,
 }
 
 impl CameraTransform {
     pub fn nan() -> Self {
         return Self {
-            render_from_camera: Transform::nan(),
-            camera_from_render: Transform::nan(),
+            render_from_camera: AnimatedTransform::from_transform(Transform::nan()),
             world_from_render: Transform::nan(),
         };
     }
 
-    pub fn new(_world_from_camera: Transform, rendering_space: RenderingCoordinateSystem) -> Self {
+    pub fn new(
+        world_from_camera: AnimatedTransform,
+        rendering_space: RenderingCoordinateSystem,
+    ) -> Self {
         let world_from_render = match rendering_space {
-            RenderingCoordinateSystem::Camera => _world_from_camera,
+            RenderingCoordinateSystem::Camera => {
+                // Compute _worldFromRender_ for camera-space rendering
+                let time_mid = (world_from_camera.start_time + world_from_camera.end_time) / 2.0;
+                world_from_camera.interpolate(time_mid)
+            }
 
             RenderingCoordinateSystem::CameraWorld => {
                 // the default option
-                let p_camera = _world_from_camera.on_point3f(Point3f::new(0.0, 0.0, 0.0));
+                let time_mid = (world_from_camera.start_time + world_from_camera.end_time) / 2.0;
+                let p_camera = world_from_camera.on_point3f(Point3f::new(0.0, 0.0, 0.0), time_mid);
 
                 Transform::translate(p_camera.x, p_camera.y, p_camera.z)
             }
@@ -37,27 +43,41 @@ impl CameraTransform {
         };
 
         let render_from_world = world_from_render.inverse();
-        let render_from_camera = render_from_world * _world_from_camera;
+        let render_from_camera = AnimatedTransform::new(
+            render_from_world * world_from_camera.start_transform,
+            world_from_camera.start_time,
+            render_from_world * world_from_camera.end_transform,
+            world_from_camera.end_time,
+        );
 
         return CameraTransform {
             world_from_render,
             render_from_camera,
-            camera_from_render: render_from_camera.inverse(),
         };
     }
 
+    pub fn render_from_camera(&self, time: f64) -> Transform {
+        return self.render_from_camera.interpolate(time);
+    }
+
+    pub fn camera_from_render(&self, time: f64) -> Transform {
+        return self.render_from_camera.interpolate(time).inverse();
+    }
+
     pub fn render_from_world(&self) -> Transform {
         return self.world_from_render.inverse();
     }
 
-    pub fn camera_from_world(&self) -> Transform {
-        return (self.world_from_render * self.render_from_camera).inverse();
+    pub fn camera_from_world(&self, time: f64) -> Transform {
+        return (self.world_from_render * self.render_from_camera(time)).inverse();
     }
 }
 
 #[derive(Clone)]
 pub struct CameraBase {
     pub camera_transform: CameraTransform,
+    pub shutter_open: f64,
+    pub shutter_close: f64,
     pub min_pos_differential_x: Vector3f,
     pub min_pos_differential_y: Vector3f,
     pub min_dir_differential_x: Vector3f,
@@ -65,6 +85,10 @@ pub struct CameraBase {
 }
 
 impl CameraBase {
+    pub fn sample_time(&self, u: f64) -> f64 {
+        return lerp(u, self.shutter_open, self.shutter_close);
+    }
+
     pub fn find_minimum_differentials(&mut self, camera: &dyn Camera, resolution: Point2i) {
         self.min_pos_differential_x = Vector3f::infinity();
         self.min_pos_differential_y = Vector3f::infinity();
@@ -74,6 +98,7 @@ impl CameraBase {
         let mut sample = CameraSample {
             p_film: Point2f::new(f64::NAN, f64::NAN),
             p_lens: Point2f::new(0.5, 0.5),
+            time: 0.5,
             filter_weight: 1.0,
         };
 
@@ -87,18 +112,14 @@ impl CameraBase {
             let crd = camera.generate_camera_differential_ray(sample);
 
             let ray = crd.ray;
-            let dox = self
-                .camera_transform
-                .camera_from_render
-                .on_vector3f(ray.rx_origin - ray.ray.o);
+            let camera_from_render = self.camera_transform.camera_from_render(ray.ray.time);
+
+            let dox = camera_from_render.on_vector3f(ray.rx_origin - ray.ray.o);
             if dox.length() < self.min_pos_differential_x.length() {
                 self.min_pos_differential_x = dox;
             }
 
-            let doy = self
-                .camera_transform
-                .camera_from_render
-                .on_vector3f(ray.ry_origin - ray.ray.o);
+            let doy = camera_from_render.on_vector3f(ray.ry_origin - ray.ray.o);
             if doy.length() < self.min_pos_differential_y.length() {
                 self.min_pos_differential_y = doy;
             }
@@ -128,14 +149,16 @@ impl CameraBase {
 pub struct CameraSample {
     pub p_film: Point2f,
     pub p_lens: Point2f,
+    pub time: f64,
     pub filter_weight: f64,
 }
 
 impl CameraSample {
-    pub fn new(p_film: Point2f, p_lens: Point2f, filter_weight: f64) -> Self {
+    pub fn new(p_film: Point2f, p_lens: Point2f, time: f64, filter_weight: f64) -> Self {
         return CameraSample {
             p_film,
             p_lens,
+            time,
             filter_weight,
         };
     }
@@ -160,6 +183,7 @@ pub trait Camera: Send + Sync {
         &self,
         p: Point3f,
         n: Normal3f,
+        time: f64,
         samples_per_pixel: usize,
     ) -> (Vector3f, Vector3f);
 }
@@ -1,39 +1,176 @@
 use crate::pbrt::*;
 
+// first-hit geometry of a camera ray, written to films that store auxiliary channels
+#[derive(Clone, Copy)]
+pub struct VisibleSurface {
+    pub set: bool,
+    pub p: Point3f,
+    pub n: Normal3f,
+    pub ns: Normal3f,
+    pub uv: Point2f,
+    pub time: f64,
+    pub dpdx: Vector3f,
+    pub dpdy: Vector3f,
+    pub albedo: SampledSpectrum,
+}
+
+impl Default for VisibleSurface {
+    fn default() -> Self {
+        return VisibleSurface {
+            set: false,
+            p: Point3f::new(0.0, 0.0, 0.0),
+            n: Normal3f::new(0.0, 0.0, 0.0),
+            ns: Normal3f::new(0.0, 0.0, 0.0),
+            uv: Point2f::new(0.0, 0.0),
+            time: 0.0,
+            dpdx: Vector3f::new(0.0, 0.0, 0.0),
+            dpdy: Vector3f::new(0.0, 0.0, 0.0),
+            albedo: SampledSpectrum::same_value(0.0),
+        };
+    }
+}
+
+impl VisibleSurface {
+    pub fn new(si: &SurfaceInteraction, albedo: SampledSpectrum) -> Self {
+        return VisibleSurface {
+            set: true,
+            p: si.interaction.pi.into(),
+            n: si.interaction.n,
+            ns: si.shading.n,
+            uv: si.interaction.uv,
+            time: si.interaction.time,
+            dpdx: si.dpdx,
+            dpdy: si.dpdy,
+            albedo,
+        };
+    }
+
+    pub fn from_bsdf(si: &SurfaceInteraction, bsdf: &BSDF) -> Self {
+        // Estimate BSDF's albedo with a fixed set of stratified samples
+        const N_RHO_SAMPLES: usize = 16;
+        let mut uc = [0.0; N_RHO_SAMPLES];
+        let mut u2 = [Point2f::new(0.0, 0.0); N_RHO_SAMPLES];
+        for i in 0..N_RHO_SAMPLES {
+            uc[i] = (i as f64 + 0.5) / N_RHO_SAMPLES as f64;
+            u2[i] = Point2f::new(((i % 4) as f64 + 0.5) / 4.0, ((i / 4) as f64 + 0.5) / 4.0);
+        }
+
+        let albedo = match &bsdf.bxdf {
+            None => SampledSpectrum::same_value(0.0),
+            Some(_) => bsdf.rho(si.interaction.wo, &uc, &u2),
+        };
+
+        return VisibleSurface::new(si, albedo);
+    }
+}
+
+// copy the pixels of _tile_ out of a pixel array covering _pixel_bounds_
+pub fn crop_pixels<T: Clone>(
+    pixels: &Vec<Vec<T>>,
+    pixel_bounds: Bounds2i,
+    tile: Bounds2i,
+) -> Vec<Vec<T>> {
+    let x_start = (tile.p_min.x - pixel_bounds.p_min.x) as usize;
+    let x_end = (tile.p_max.x - pixel_bounds.p_min.x) as usize;
+
+    return (tile.p_min.y..tile.p_max.y)
+        .map(|y| pixels[(y - pixel_bounds.p_min.y) as usize][x_start..x_end].to_vec())
+        .collect();
+}
+
+// write the pixels of _tile_ back into a pixel array covering _pixel_bounds_
+pub fn paste_pixels<T: Clone>(
+    pixels: &mut Vec<Vec<T>>,
+    pixel_bounds: Bounds2i,
+    tile_pixels: &Vec<Vec<T>>,
+    tile: Bounds2i,
+) {
+    let x_start = (tile.p_min.x - pixel_bounds.p_min.x) as usize;
+    let x_end = (tile.p_max.x - pixel_bounds.p_min.x) as usize;
+
+    for (row, y) in tile_pixels.iter().zip(tile.p_min.y..tile.p_max.y) {
+        pixels[(y - pixel_bounds.p_min.y) as usize][x_start..x_end].clone_from_slice(row);
+    }
+}
+
 pub trait Film: Send + Sync {
-    fn fork(&self) -> Box<dyn Film>;
+    // a film covering only _tile_, starting from the pixels accumulated so far
+    // so that the samples of a pixel are always summed in the same order
+    fn fork_tile(&self, tile: Bounds2i) -> Box<dyn Film>;
+
+    // copy the pixels rendered by a tile film back into this one
+    fn merge_tile(&mut self, tile_film: &dyn Film);
 
     fn convert_to_rgb_film(&self) -> RGBFilm {
         panic!("you should implement this function only for RGBFilm");
     }
 
+    fn convert_to_gbuffer_film(&self) -> GBufferFilm {
+        panic!("you should implement this function only for GBufferFilm");
+    }
+
+    fn convert_to_spectral_film(&self) -> SpectralFilm {
+        panic!("you should implement this function only for SpectralFilm");
+    }
+
+    fn uses_visible_surface(&self) -> bool {
+        return false;
+    }
+
+    // gather the albedo and normal buffers the denoiser is guided by
+    fn enable_denoising_guides(&mut self) {}
+
+    // average albedo and shading normal of the first visible surface, None without guides;
+    // both are zero where camera rays hit no surface
+    fn get_pixel_guides(&self, _p: Point2i) -> Option<(RGB, Vector3f)> {
+        return None;
+    }
+
     fn get_filename(&self) -> String;
 
     fn get_resolution(&self) -> Point2i;
 
+    // the pixels actually stored and rendered, a sub-region of the full resolution
+    fn get_pixel_bounds(&self) -> Bounds2i;
+
     fn get_filter(&self) -> Arc<dyn Filter>;
 
     fn get_pixel_rgb(&self, p: Point2i) -> RGB;
 
-    fn merge(&mut self, film: &dyn Film, y_list: Vec<usize>);
+    // accumulated pixel values, saved to and restored from checkpoints
+    fn get_state(&self) -> Vec<f64>;
+
+    fn set_state(&mut self, state: &[f64]) -> Result<(), String>;
+
+    // relative standard error of the pixel value, drives adaptive sampling
+    fn get_pixel_error(&self, p: Point2i) -> f64;
 
     fn add_sample(
         &mut self,
         point_film: Point2i,
         l: &SampledSpectrum,
         lambda: &SampledWavelengths,
+        visible_surface: Option<&VisibleSurface>,
         weight: f64,
     );
 
-    fn export_image(&self, filename: &str, resolution: Point2i) {
-        let mut image = Image::new(resolution, PixelFormat::U256);
+    // RGB values of the pixel bounds
+    fn get_image(&self) -> Image {
+        let pixel_bounds = self.get_pixel_bounds();
+        let mut image = Image::new(pixel_bounds.diagonal(), PixelFormat::f64);
 
-        for y in 0..resolution.y {
-            for x in 0..resolution.x {
-                image[y as usize][x as usize] = self.get_pixel_rgb(Point2i::new(x, y));
+        for y in pixel_bounds.p_min.y..pixel_bounds.p_max.y {
+            for x in pixel_bounds.p_min.x..pixel_bounds.p_max.x {
+                image[(y - pixel_bounds.p_min.y) as usize][(x - pixel_bounds.p_min.x) as usize] =
+                    self.get_pixel_rgb(Point2i::new(x, y));
             }
         }
 
-        image.export_to_png(filename);
+        return image;
+    }
+
+    fn export_image(&self, filename: &str) {
+        // PNG has no data window: only the pixel bounds are written
+        self.get_image().export_to_png(filename);
     }
 }
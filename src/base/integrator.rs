@@ -37,14 +37,21 @@ pub trait Integrator: Send + Sync {
         ray: &DifferentialRay,
         lambda: &mut SampledWavelengths,
         sampler: &mut dyn Sampler,
+        visible_surface: Option<&mut VisibleSurface>,
     ) -> SampledSpectrum;
 }
 
 pub trait IntegratorDefaultInterface: Integrator {
     fn unoccluded(&self, p0: &Interaction, p1: &Interaction) -> bool {
         //TODO: this implementation is different from PBRT-v4
-        return !self.fast_intersect(&p0.spawn_ray_to(p1), 0.6)
-            && !self.fast_intersect(&p1.spawn_ray_to(p0), 0.6);
+        add_stat(StatCounter::ShadowRays, 1);
+        if self.fast_intersect(&p0.spawn_ray_to(p1), 0.6) {
+            return false;
+        }
+
+        // the second ray is traced only when the first one is unoccluded
+        add_stat(StatCounter::ShadowRays, 1);
+        return !self.fast_intersect(&p1.spawn_ray_to(p0), 0.6);
     }
 
     fn evaluate_pixel_sample(
@@ -61,10 +68,35 @@ pub trait IntegratorDefaultInterface: Integrator {
         let camera_sample = sampler.get_camera_sample(p_pixel.clone(), filter.clone());
 
         let camera_ray = camera.generate_camera_differential_ray(camera_sample);
+        add_stat(StatCounter::CameraRays, 1);
+
+        // Evaluate radiance along camera ray, recording the first hit if the film needs it
+        let initialize_visible_surface = film.uses_visible_surface();
+        let mut visible_surface = VisibleSurface::default();
 
-        let l = camera_ray.weight * self.li(&camera_ray.ray, &mut lambda, sampler);
+        let l = camera_ray.weight
+            * self.li(
+                &camera_ray.ray,
+                &mut lambda,
+                sampler,
+                if initialize_visible_surface {
+                    Some(&mut visible_surface)
+                } else {
+                    None
+                },
+            );
 
-        film.add_sample(p_pixel, &l, &lambda, camera_sample.filter_weight);
+        film.add_sample(
+            p_pixel,
+            &l,
+            &lambda,
+            if initialize_visible_surface {
+                Some(&visible_surface)
+            } else {
+                None
+            },
+            camera_sample.filter_weight,
+        );
     }
 }
 
@@ -6,6 +6,7 @@ pub struct Interaction {
     pub n: Normal3f,
     pub wo: Vector3f,
     pub uv: Point2f,
+    pub time: f64,
 }
 
 fn offset_ray_origin(pi: Point3fi, n: Normal3f, w: Vector3f) -> Point3f {
@@ -33,12 +34,12 @@ impl Interaction {
     }
 
     pub fn spawn_ray(&self, d: Vector3f) -> DifferentialRay {
-        return DifferentialRay::new(self.offset_ray_origin(d), d);
+        return DifferentialRay::new(self.offset_ray_origin(d), d, self.time);
     }
 
     pub fn spawn_ray_to(&self, it: &Interaction) -> Ray {
         //TODO: medium is not implemented here
-        return spawn_ray_to(self.pi, self.n, it.pi, it.n);
+        return spawn_ray_to(self.pi, self.n, self.time, it.pi, it.n);
     }
 }
 
@@ -73,6 +74,7 @@ impl SurfaceInteraction {
         dpdv: Vector3f,
         dndu: Normal3f,
         dndv: Normal3f,
+        time: f64,
     ) -> Self {
         let n = Normal3f::from(dpdu.cross(dpdv).normalize());
 
@@ -82,6 +84,7 @@ impl SurfaceInteraction {
                 n,
                 uv,
                 wo: wo.normalize(),
+                time,
             },
 
             dpdx: Vector3::nan(),
@@ -99,7 +102,7 @@ impl SurfaceInteraction {
                 dpdu,
                 dpdv,
                 dndu: dndu.into(),
-                dndv: dndu.into(),
+                dndv: dndv.into(),
             },
             material: None,
             area_light: None,
@@ -111,7 +114,7 @@ impl SurfaceInteraction {
     }
 
     pub fn spawn_ray(&self, d: Vector3f) -> DifferentialRay {
-        return DifferentialRay::new(self.offset_ray_origin(d), d);
+        return self.interaction.spawn_ray(d);
     }
 
     pub fn set_intersection_properties(
@@ -123,6 +126,36 @@ impl SurfaceInteraction {
         self.area_light = area_light;
     }
 
+    pub fn set_shading_geometry(
+        &mut self,
+        ns: Normal3f,
+        dpdus: Vector3f,
+        dpdvs: Vector3f,
+        dndus: Vector3f,
+        dndvs: Vector3f,
+        orientation_is_authoritative: bool,
+    ) {
+        // Compute _shading.n_ for _SurfaceInteraction_
+        self.shading.n = ns;
+        if orientation_is_authoritative {
+            self.interaction.n = self.interaction.n.face_forward(self.shading.n.into());
+        } else {
+            self.shading.n = self.shading.n.face_forward(self.interaction.n.into());
+        }
+
+        // Initialize _shading_ partial derivative values
+        self.shading.dpdu = dpdus;
+        self.shading.dpdv = dpdvs;
+        self.shading.dndu = dndus;
+        self.shading.dndv = dndvs;
+
+        while self.shading.dpdu.length_squared() > 1e16 || self.shading.dpdv.length_squared() > 1e16
+        {
+            self.shading.dpdu = self.shading.dpdu * 1e-8;
+            self.shading.dpdv = self.shading.dpdv * 1e-8;
+        }
+    }
+
     pub fn compute_differentials(
         &mut self,
         ray: &DifferentialRay,
@@ -152,6 +185,7 @@ impl SurfaceInteraction {
             (self.dpdx, self.dpdy) = camera.approximate_dp_dxy(
                 self.interaction.pi.into(),
                 self.interaction.n,
+                self.interaction.time,
                 samples_per_pixel,
             );
         }
@@ -209,7 +243,7 @@ impl SurfaceInteraction {
         self.compute_differentials(ray, camera, sampler.samples_per_pixel());
 
         // Resolve _MixMaterial_ if necessary
-        let material = match &self.material {
+        let mut material = match &self.material {
             None => {
                 return BSDF {
                     bxdf: None,
@@ -219,16 +253,47 @@ impl SurfaceInteraction {
             Some(_material) => _material.clone(),
         };
 
-        if material.is_mix_material() {
-            panic!("this part is not implemented");
+        while material.is_mix_material() {
+            material = material.choose_material(&MaterialEvalContext::new(&self));
+        }
+
+        // Override _bsdf_ with normal map or bump map, if specified
+        let displacement = material.get_displacement();
+        let normal_map_image = material.get_normal_map();
+        if displacement.is_some() || normal_map_image.is_some() {
+            // Get shading $\dpdu$ and $\dpdv$ using normal or bump map
+            let ctx = NormalBumpEvalContext::new(&self);
+            let (dpdu, dpdv) = match (&displacement, &normal_map_image) {
+                (Some(_displacement), _) => bump_map(_displacement.as_ref(), &ctx),
+                (None, Some(_normal_map)) => normal_map(_normal_map, &ctx),
+                (None, None) => unreachable!(),
+            };
+
+            let ns = Normal3f::from(dpdu.cross(dpdv).normalize());
+            self.set_shading_geometry(ns, dpdu, dpdv, self.shading.dndu, self.shading.dndv, false);
         }
-        // TODO: displacement and normal_map are not implemented for get_bsdf()
 
         let material_eval_context = MaterialEvalContext::new(&self);
 
         return material.get_bsdf(&material_eval_context, lambda);
     }
 
+    pub fn get_bssrdf(&self, lambda: &mut SampledWavelengths) -> Option<TabulatedBSSRDF> {
+        // Resolve _MixMaterial_ if necessary
+        let mut material = match &self.material {
+            None => {
+                return None;
+            }
+            Some(_material) => _material.clone(),
+        };
+
+        while material.is_mix_material() {
+            material = material.choose_material(&MaterialEvalContext::new(&self));
+        }
+
+        return material.get_bssrdf(&MaterialEvalContext::new(&self), lambda);
+    }
+
     pub fn le(&self, w: Vector3f, lambda: &SampledWavelengths) -> SampledSpectrum {
         return match &self.area_light {
             None => SampledSpectrum::same_value(0.0),
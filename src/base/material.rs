@@ -28,86 +28,404 @@ impl MaterialEvalContext {
     }
 }
 
+pub struct NormalBumpEvalContext {
+    pub p: Point3f,
+    pub uv: Point2f,
+    pub n: Normal3f,
+    pub shading: Shading,
+    pub dudx: f64,
+    pub dudy: f64,
+    pub dvdx: f64,
+    pub dvdy: f64,
+    pub dpdx: Vector3f,
+    pub dpdy: Vector3f,
+}
+
+impl NormalBumpEvalContext {
+    pub fn new(si: &SurfaceInteraction) -> Self {
+        return Self {
+            p: si.interaction.pi.into(),
+            uv: si.interaction.uv,
+            n: si.interaction.n,
+            shading: Shading {
+                n: si.shading.n,
+                dpdu: si.shading.dpdu,
+                dpdv: si.shading.dpdv,
+                dndu: si.shading.dndu,
+                dndv: si.shading.dndv,
+            },
+            dudx: si.dudx,
+            dudy: si.dudy,
+            dvdx: si.dvdx,
+            dvdy: si.dvdy,
+            dpdx: si.dpdx,
+            dpdy: si.dpdy,
+        };
+    }
+
+    pub fn texture_eval_context(&self) -> TextureEvalContext {
+        return TextureEvalContext {
+            p: self.p,
+            dpdx: self.dpdx,
+            dpdy: self.dpdy,
+            n: self.n,
+            uv: self.uv,
+            dudx: self.dudx,
+            dudy: self.dudy,
+            dvdx: self.dvdx,
+            dvdy: self.dvdy,
+        };
+    }
+}
+
+pub fn normal_map(normal_map: &Image, ctx: &NormalBumpEvalContext) -> (Vector3f, Vector3f) {
+    // Get normalized normal vector from normal map
+    let wrap = WrapMode2D::new([WrapMode::Repeat, WrapMode::Repeat]);
+    let uv = Point2f::new(ctx.uv[0], 1.0 - ctx.uv[1]);
+    let rgb = normal_map.bilerp(uv, wrap);
+    let ns = Vector3f::new(2.0 * rgb.r - 1.0, 2.0 * rgb.g - 1.0, 2.0 * rgb.b - 1.0).normalize();
+
+    // Transform tangent-space normal to rendering space
+    let frame = Frame::from_xz(ctx.shading.dpdu.normalize(), Vector3f::from(ctx.shading.n));
+    let ns = frame.from_local(ns);
+
+    // Find $\dpdu$ and $\dpdv$ that give shading normal
+    let ulen = ctx.shading.dpdu.length();
+    let vlen = ctx.shading.dpdv.length();
+    let dpdu = gram_schmidt(ctx.shading.dpdu, ns).normalize() * ulen;
+    let dpdv = ns.cross(dpdu).normalize() * vlen;
+
+    return (dpdu, dpdv);
+}
+
+pub fn bump_map(
+    displacement: &dyn FloatTexture,
+    ctx: &NormalBumpEvalContext,
+) -> (Vector3f, Vector3f) {
+    // Compute offset positions and evaluate displacement texture
+    let mut shifted_ctx = ctx.texture_eval_context();
+
+    // Shift _shiftedCtx_ _du_ in the $u$ direction
+    let du = {
+        let _du = 0.5 * (ctx.dudx.abs() + ctx.dudy.abs());
+        if _du == 0.0 {
+            0.0005
+        } else {
+            _du
+        }
+    };
+    shifted_ctx.p = ctx.p + du * ctx.shading.dpdu;
+    shifted_ctx.uv = ctx.uv + Vector2f::new(du, 0.0);
+    let u_displace = displacement.evaluate(&shifted_ctx);
+
+    // Shift _shiftedCtx_ _dv_ in the $v$ direction
+    let dv = {
+        let _dv = 0.5 * (ctx.dvdx.abs() + ctx.dvdy.abs());
+        if _dv == 0.0 {
+            0.0005
+        } else {
+            _dv
+        }
+    };
+    shifted_ctx.p = ctx.p + dv * ctx.shading.dpdv;
+    shifted_ctx.uv = ctx.uv + Vector2f::new(0.0, dv);
+    let v_displace = displacement.evaluate(&shifted_ctx);
+
+    let displace = displacement.evaluate(&ctx.texture_eval_context());
+
+    // Compute bump-mapped differential geometry
+    let n = Vector3f::from(ctx.shading.n);
+    let dpdu = ctx.shading.dpdu + ((u_displace - displace) / du) * n + displace * ctx.shading.dndu;
+    let dpdv = ctx.shading.dpdv + ((v_displace - displace) / dv) * n + displace * ctx.shading.dndv;
+
+    return (dpdu, dpdv);
+}
+
 pub trait Material: Send + Sync {
     fn is_mix_material(&self) -> bool {
         return false;
     }
 
+    fn choose_material(&self, _ctx: &MaterialEvalContext) -> Arc<dyn Material> {
+        panic!("choose_material() should be implemented only by MixMaterial");
+    }
+
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return None;
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return None;
+    }
+
     fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF;
     // in pbrt-rust get_bsdf works like get_bxdf in PBRT-v4
+
+    fn get_bssrdf(
+        &self,
+        _ctx: &MaterialEvalContext,
+        _lambda: &mut SampledWavelengths,
+    ) -> Option<TabulatedBSSRDF> {
+        return None;
+    }
+}
+
+pub fn build_roughness_distribution(
+    u_roughness: &Arc<dyn FloatTexture>,
+    v_roughness: &Arc<dyn FloatTexture>,
+    remap_roughness: bool,
+    ctx: &TextureEvalContext,
+) -> TrowbridgeReitzDistribution {
+    let mut u_rough = u_roughness.evaluate(ctx);
+    let mut v_rough = v_roughness.evaluate(ctx);
+
+    if remap_roughness {
+        u_rough = TrowbridgeReitzDistribution::roughness_to_alpha(u_rough);
+        v_rough = TrowbridgeReitzDistribution::roughness_to_alpha(v_rough);
+    }
+
+    return TrowbridgeReitzDistribution::new(u_rough, v_rough);
+}
+
+fn get_roughness_textures(
+    parameter_dict: &ParameterDict,
+    prefix: &str,
+) -> Result<(Arc<dyn FloatTexture>, Arc<dyn FloatTexture>), SceneError> {
+    // `uroughness` and `vroughness` fall back to the isotropic `roughness`
+    let roughness =
+        parameter_dict.get_float_texture_with_default(&format!("{}roughness", prefix), 0.0)?;
+
+    let get_texture = |key: String| {
+        if parameter_dict.has_float_texture(&key) || parameter_dict.has_float(&key) {
+            parameter_dict.get_float_texture_with_default(&key, 0.0)
+        } else {
+            Ok(roughness.clone())
+        }
+    };
+
+    return Ok((
+        get_texture(format!("{}uroughness", prefix))?,
+        get_texture(format!("{}vroughness", prefix))?,
+    ));
+}
+
+fn get_spectrum_texture_or_none(
+    parameter_dict: &ParameterDict,
+    key: &str,
+) -> Result<Option<Arc<dyn SpectrumTexture>>, SceneError> {
+    if parameter_dict.has_texture(key) {
+        return Ok(Some(parameter_dict.get_texture(key)?));
+    }
+
+    if parameter_dict.has_rgb(key) {
+        let spectrum = RGBAlbedoSpectrum::new(
+            parameter_dict.get_color_space(),
+            parameter_dict.get_rgb(key, None)?,
+        );
+        return Ok(Some(Arc::new(SpectrumConstantTexture::new(Arc::new(
+            spectrum,
+        )))));
+    }
+
+    return Ok(None);
+}
+
+fn get_float_texture_or_none(
+    parameter_dict: &ParameterDict,
+    key: &str,
+) -> Result<Option<Arc<dyn FloatTexture>>, SceneError> {
+    if parameter_dict.has_float_texture(key) {
+        return Ok(Some(parameter_dict.get_float_texture(key)?));
+    }
+
+    if parameter_dict.has_float(key) {
+        return Ok(Some(Arc::new(FloatConstantTexture::new(
+            parameter_dict.get_one_float(key, None)?,
+        ))));
+    }
+
+    return Ok(None);
+}
+
+fn get_eta(
+    parameter_dict: &ParameterDict,
+    key: &str,
+    default: f64,
+) -> Result<Arc<dyn Spectrum>, SceneError> {
+    if parameter_dict.has_spectrum(key) {
+        return parameter_dict.get_spectrum(key);
+    }
+
+    return Ok(Arc::new(ConstSpectrum::new(
+        parameter_dict.get_one_float(key, Some(default))?,
+    )));
+}
+
+type ConductorSpectra = (
+    Option<Arc<dyn Spectrum>>,
+    Option<Arc<dyn Spectrum>>,
+    Option<Arc<dyn SpectrumTexture>>,
+);
+
+fn get_conductor_spectra(
+    parameter_dict: &ParameterDict,
+    prefix: &str,
+) -> Result<ConductorSpectra, SceneError> {
+    let eta_key = format!("{}eta", prefix);
+    let k_key = format!("{}k", prefix);
+
+    let reflectance = get_spectrum_texture_or_none(parameter_dict, "reflectance")?;
+    if reflectance.is_some() && !parameter_dict.has_spectrum(&eta_key) {
+        return Ok((None, None, reflectance));
+    }
+
+    // default to copper when neither the complex IOR nor a reflectance is given
+    let get_spectrum = |key: &str, default_name: &str| -> Result<Arc<dyn Spectrum>, SceneError> {
+        if parameter_dict.has_spectrum(key) {
+            parameter_dict.get_spectrum(key)
+        } else {
+            Ok(Arc::new(DenselySampledSpectrum::from_spectrum(
+                get_named_spectrum(default_name).unwrap(),
+            )))
+        }
+    };
+
+    return Ok((
+        Some(get_spectrum(&eta_key, "metal-Cu-eta")?),
+        Some(get_spectrum(&k_key, "metal-Cu-k")?),
+        None,
+    ));
+}
+
+fn get_named_material(
+    named_materials: &HashMap<String, Arc<dyn Material>>,
+    name: &str,
+) -> Result<Arc<dyn Material>, SceneError> {
+    return match named_materials.get(name) {
+        None => Err(SceneError::new(
+            SceneErrorKind::UndefinedNamedMaterial(name.to_string()),
+            None,
+        )),
+        Some(_material) => Ok(_material.clone()),
+    };
 }
 
-pub fn create_material(material_type: &str, parameter_dict: &ParameterDict) -> Arc<dyn Material> {
-    return match material_type {
+// errors without a location are raised by the directive that creates the material
+pub fn create_material(
+    material_type: &str,
+    parameter_dict: &ParameterDict,
+    normal_map: Option<Arc<Image>>,
+    named_materials: &HashMap<String, Arc<dyn Material>>,
+) -> Result<Arc<dyn Material>, SceneError> {
+    let displacement = if parameter_dict.has_float_texture("displacement") {
+        Some(parameter_dict.get_float_texture("displacement")?)
+    } else if parameter_dict.has_float("displacement") {
+        let value = parameter_dict.get_one_float("displacement", None)?;
+        Some(Arc::new(FloatConstantTexture::new(value)) as Arc<dyn FloatTexture>)
+    } else {
+        None
+    };
+
+    let material: Arc<dyn Material> = match material_type {
         "diffuse" => {
             let reflectance = {
                 let key = "reflectance";
                 if parameter_dict.has_texture(key) {
-                    parameter_dict.get_texture(key)
+                    parameter_dict.get_texture(key)?
                 } else if parameter_dict.has_rgb(key) {
-                    let rgb_color = parameter_dict.get_rgb(key, None);
-                    let spectrum = RGBAlbedoSpectrum::new(rgb_color);
+                    let rgb_color = parameter_dict.get_rgb(key, None)?;
+                    let spectrum =
+                        RGBAlbedoSpectrum::new(parameter_dict.get_color_space(), rgb_color);
 
                     Arc::new(SpectrumConstantTexture::new(Arc::new(spectrum)))
                 } else {
-                    panic!("couldn't find `{}` in parameters", key);
+                    return Err(SceneError::new(
+                        SceneErrorKind::MissingParameter(key.to_string()),
+                        None,
+                    ));
                 }
             };
 
-            Arc::new(DiffuseMaterial::new(reflectance))
+            Arc::new(DiffuseMaterial::new(reflectance, displacement, normal_map))
+        }
+
+        "diffusetransmission" => {
+            let get_texture = |key: &str, default: f64| {
+                return Ok::<_, SceneError>(
+                    get_spectrum_texture_or_none(parameter_dict, key)?.unwrap_or(Arc::new(
+                        SpectrumConstantTexture::new(Arc::new(ConstSpectrum::new(default))),
+                    )),
+                );
+            };
+
+            let reflectance = get_texture("reflectance", 0.25)?;
+            let transmittance = get_texture("transmittance", 0.25)?;
+            let scale = parameter_dict.get_one_float("scale", Some(1.0))?;
+
+            Arc::new(DiffuseTransmissionMaterial::new(
+                reflectance,
+                transmittance,
+                scale,
+                displacement,
+                normal_map,
+            ))
         }
 
         "coateddiffuse" => {
             let reflectance = {
                 let key = "reflectance";
                 if parameter_dict.has_texture(key) {
-                    parameter_dict.get_texture(key)
+                    parameter_dict.get_texture(key)?
                 } else if parameter_dict.has_rgb(key) {
-                    let rgb_color = parameter_dict.get_rgb(key, None);
-                    let spectrum = RGBAlbedoSpectrum::new(rgb_color);
+                    let rgb_color = parameter_dict.get_rgb(key, None)?;
+                    let spectrum =
+                        RGBAlbedoSpectrum::new(parameter_dict.get_color_space(), rgb_color);
                     Arc::new(SpectrumConstantTexture::new(Arc::new(spectrum)))
                 } else {
-                    let spectrum = RGBAlbedoSpectrum::new(RGB::new(0.5, 0.5, 0.5));
+                    let spectrum = RGBAlbedoSpectrum::new(
+                        parameter_dict.get_color_space(),
+                        RGB::new(0.5, 0.5, 0.5),
+                    );
                     Arc::new(SpectrumConstantTexture::new(Arc::new(spectrum)))
                 }
             };
 
             let u_roughness = {
-                let roughness = parameter_dict.get_one_float("uroughness", Some(0.0));
+                let roughness = parameter_dict.get_one_float("uroughness", Some(0.0))?;
                 Arc::new(FloatConstantTexture::new(roughness))
             };
 
             let v_roughness = {
-                let roughness = parameter_dict.get_one_float("vroughness", Some(0.0));
+                let roughness = parameter_dict.get_one_float("vroughness", Some(0.0))?;
                 Arc::new(FloatConstantTexture::new(roughness))
             };
 
             let thickness = {
-                let thickness = parameter_dict.get_one_float("vroughness", Some(0.01));
+                let thickness = parameter_dict.get_one_float("thickness", Some(0.01))?;
                 Arc::new(FloatConstantTexture::new(thickness))
             };
 
             let eta = {
-                let eta = parameter_dict.get_one_float("eta", Some(1.5));
+                let eta = parameter_dict.get_one_float("eta", Some(1.5))?;
                 Arc::new(ConstSpectrum::new(eta))
             };
 
-            let max_depth = parameter_dict.get_one_integer("maxdepth", Some(10));
-            let n_samples = parameter_dict.get_one_integer("nsample", Some(1));
+            let max_depth = parameter_dict.get_one_integer("maxdepth", Some(10))?;
+            let n_samples = parameter_dict.get_one_integer("nsample", Some(1))?;
 
             let g = {
-                let g = parameter_dict.get_one_float("g", Some(0.0));
+                let g = parameter_dict.get_one_float("g", Some(0.0))?;
                 Arc::new(FloatConstantTexture::new(g))
             };
 
             let albedo = {
-                let albedo = parameter_dict.get_one_float("albedo", Some(0.0));
+                let albedo = parameter_dict.get_one_float("albedo", Some(0.0))?;
                 Arc::new(SpectrumConstantTexture::new(Arc::new(ConstSpectrum::new(
                     albedo,
                 ))))
             };
 
-            let remap_roughness = parameter_dict.get_one_bool("remaproughness", Some(true));
+            let remap_roughness = parameter_dict.get_one_bool("remaproughness", Some(true))?;
 
             let coated_diffuse = CoatedDiffuseMaterial::new(
                 reflectance,
@@ -120,13 +438,322 @@ pub fn create_material(material_type: &str, parameter_dict: &ParameterDict) -> A
                 remap_roughness,
                 max_depth as usize,
                 n_samples as usize,
+                displacement,
+                normal_map,
             );
 
             Arc::new(coated_diffuse)
         }
 
+        "conductor" => {
+            let (eta, k, reflectance) = get_conductor_spectra(parameter_dict, "")?;
+            let (u_roughness, v_roughness) = get_roughness_textures(parameter_dict, "")?;
+            let remap_roughness = parameter_dict.get_one_bool("remaproughness", Some(true))?;
+
+            Arc::new(ConductorMaterial::new(
+                eta,
+                k,
+                reflectance,
+                u_roughness,
+                v_roughness,
+                remap_roughness,
+                displacement,
+                normal_map,
+            ))
+        }
+
+        "dielectric" => {
+            let eta = get_eta(parameter_dict, "eta", 1.5)?;
+            let (u_roughness, v_roughness) = get_roughness_textures(parameter_dict, "")?;
+            let remap_roughness = parameter_dict.get_one_bool("remaproughness", Some(true))?;
+
+            Arc::new(DielectricMaterial::new(
+                eta,
+                u_roughness,
+                v_roughness,
+                remap_roughness,
+                displacement,
+                normal_map,
+            ))
+        }
+
+        "coatedconductor" => {
+            let (interface_u_roughness, interface_v_roughness) =
+                get_roughness_textures(parameter_dict, "interface.")?;
+            let thickness = parameter_dict.get_float_texture_with_default("thickness", 0.01)?;
+            let interface_eta = get_eta(parameter_dict, "interface.eta", 1.5)?;
+
+            let (conductor_u_roughness, conductor_v_roughness) =
+                get_roughness_textures(parameter_dict, "conductor.")?;
+            let (conductor_eta, k, reflectance) =
+                get_conductor_spectra(parameter_dict, "conductor.")?;
+
+            let max_depth = parameter_dict.get_one_integer("maxdepth", Some(10))?;
+            let n_samples = parameter_dict.get_one_integer("nsamples", Some(1))?;
+
+            let g = parameter_dict.get_float_texture_with_default("g", 0.0)?;
+            let albedo = get_spectrum_texture_or_none(parameter_dict, "albedo")?.unwrap_or(
+                Arc::new(SpectrumConstantTexture::new(Arc::new(ConstSpectrum::new(
+                    parameter_dict.get_one_float("albedo", Some(0.0))?,
+                )))),
+            );
+
+            let remap_roughness = parameter_dict.get_one_bool("remaproughness", Some(true))?;
+
+            Arc::new(CoatedConductorMaterial::new(
+                interface_u_roughness,
+                interface_v_roughness,
+                thickness,
+                interface_eta,
+                g,
+                albedo,
+                conductor_u_roughness,
+                conductor_v_roughness,
+                conductor_eta,
+                k,
+                reflectance,
+                remap_roughness,
+                max_depth as usize,
+                n_samples as usize,
+                displacement,
+                normal_map,
+            ))
+        }
+
+        "hair" => {
+            let sigma_a = if parameter_dict.has_rgb("sigma_a") {
+                // absorption coefficients are not bounded by 1
+                let spectrum = RGBUnboundedSpectrum::new(
+                    parameter_dict.get_color_space(),
+                    parameter_dict.get_rgb("sigma_a", None)?,
+                );
+                Some(Arc::new(SpectrumConstantTexture::new(Arc::new(spectrum)))
+                    as Arc<dyn SpectrumTexture>)
+            } else {
+                get_spectrum_texture_or_none(parameter_dict, "sigma_a")?
+            };
+            let reflectance = get_spectrum_texture_or_none(parameter_dict, "reflectance")?;
+            let mut eumelanin = get_float_texture_or_none(parameter_dict, "eumelanin")?;
+            let mut pheomelanin = get_float_texture_or_none(parameter_dict, "pheomelanin")?;
+
+            if sigma_a.is_some() {
+                if reflectance.is_some() {
+                    println!("hair: ignoring `reflectance` parameter since `sigma_a` was provided");
+                }
+                if eumelanin.is_some() || pheomelanin.is_some() {
+                    println!("hair: ignoring `eumelanin`/`pheomelanin` parameter since `sigma_a` was provided");
+                }
+            } else if reflectance.is_some() {
+                if eumelanin.is_some() || pheomelanin.is_some() {
+                    println!("hair: ignoring `eumelanin`/`pheomelanin` parameter since `reflectance` was provided");
+                }
+            } else if eumelanin.is_none() && pheomelanin.is_none() {
+                // default: brown-ish hair
+                eumelanin = Some(Arc::new(FloatConstantTexture::new(1.3)));
+                pheomelanin = Some(Arc::new(FloatConstantTexture::new(0.0)));
+            }
+
+            let eta = parameter_dict.get_float_texture_with_default("eta", 1.55)?;
+            let beta_m = parameter_dict.get_float_texture_with_default("beta_m", 0.3)?;
+            let beta_n = parameter_dict.get_float_texture_with_default("beta_n", 0.3)?;
+            let alpha = parameter_dict.get_float_texture_with_default("alpha", 2.0)?;
+
+            Arc::new(HairMaterial::new(
+                sigma_a,
+                reflectance,
+                eumelanin,
+                pheomelanin,
+                eta,
+                beta_m,
+                beta_n,
+                alpha,
+                displacement,
+                normal_map,
+            ))
+        }
+
+        "layered" => {
+            let top =
+                get_named_material(named_materials, &parameter_dict.get_string("top", None)?)?;
+            let bottom =
+                get_named_material(named_materials, &parameter_dict.get_string("bottom", None)?)?;
+
+            let thickness = parameter_dict.get_float_texture_with_default("thickness", 0.01)?;
+            let g = parameter_dict.get_float_texture_with_default("g", 0.0)?;
+            let albedo = get_spectrum_texture_or_none(parameter_dict, "albedo")?.unwrap_or(
+                Arc::new(SpectrumConstantTexture::new(Arc::new(ConstSpectrum::new(
+                    parameter_dict.get_one_float("albedo", Some(0.0))?,
+                )))),
+            );
+
+            let two_sided = parameter_dict.get_one_bool("twosided", Some(true))?;
+            let max_depth = parameter_dict.get_one_integer("maxdepth", Some(10))?;
+            let n_samples = parameter_dict.get_one_integer("nsamples", Some(1))?;
+
+            Arc::new(LayeredMaterial::new(
+                top,
+                bottom,
+                thickness,
+                albedo,
+                g,
+                two_sided,
+                max_depth as usize,
+                n_samples as usize,
+                displacement,
+                normal_map,
+            ))
+        }
+
+        "measured" => {
+            let filename = parameter_dict.get_string("filename", None)?;
+
+            let material = MeasuredMaterial::new(&filename, displacement, normal_map);
+            match material {
+                Err(message) => {
+                    return Err(SceneError::new(
+                        SceneErrorKind::Io { filename, message },
+                        None,
+                    ));
+                }
+                Ok(_material) => Arc::new(_material),
+            }
+        }
+
+        "mix" => {
+            let material_names = parameter_dict.get_string_array("materials")?;
+            if material_names.len() != 2 {
+                return Err(parameter_dict.parameter_error(
+                    "materials",
+                    format!("expected two values, {} supplied", material_names.len()),
+                ));
+            }
+
+            let materials = material_names
+                .iter()
+                .map(|name| get_named_material(named_materials, name))
+                .collect::<Result<Vec<Arc<dyn Material>>, SceneError>>()?;
+
+            let amount = parameter_dict.get_float_texture_with_default("amount", 0.5)?;
+
+            Arc::new(MixMaterial::new(
+                [materials[0].clone(), materials[1].clone()],
+                amount,
+            ))
+        }
+
+        "subsurface" => {
+            let to_texture = |spectrum: Arc<dyn Spectrum>| {
+                Some(Arc::new(SpectrumConstantTexture::new(spectrum)) as Arc<dyn SpectrumTexture>)
+            };
+            // scattering coefficients are not bounded by 1
+            let get_unbounded_texture = |key: &str| {
+                if parameter_dict.has_rgb(key) {
+                    let spectrum = RGBUnboundedSpectrum::new(
+                        parameter_dict.get_color_space(),
+                        parameter_dict.get_rgb(key, None)?,
+                    );
+                    Ok(to_texture(Arc::new(spectrum)))
+                } else {
+                    get_spectrum_texture_or_none(parameter_dict, key)
+                }
+            };
+
+            let mut g = parameter_dict.get_one_float("g", Some(0.0))?;
+            let sigma_a;
+            let sigma_s;
+            let mut reflectance = None;
+            let mut mfp = None;
+
+            let name = parameter_dict.get_string("name", Some("".to_string()))?;
+            if !name.is_empty() {
+                // 1. named media: measured coefficients are reduced, so `g` is forced to 0
+                let (sig_a, sig_s) = match get_medium_scattering_properties(&name) {
+                    None => {
+                        return Err(parameter_dict.parameter_error(
+                            "name",
+                            format!("named medium `{}` not found", name),
+                        ));
+                    }
+                    Some(_coefficients) => _coefficients,
+                };
+                if g != 0.0 {
+                    println!("subsurface: non-zero `g` ignored with named scattering coefficients");
+                }
+                g = 0.0;
+                sigma_a = to_texture(sig_a);
+                sigma_s = to_texture(sig_s);
+            } else {
+                // 2. sigma_a and sigma_s directly specified
+                sigma_a = get_unbounded_texture("sigma_a")?;
+                sigma_s = get_unbounded_texture("sigma_s")?;
+                if sigma_a.is_some() != sigma_s.is_some() {
+                    return Err(SceneError::new(
+                        SceneErrorKind::InvalidEntity(
+                            "subsurface: must provide both `sigma_a` and `sigma_s` or neither"
+                                .to_string(),
+                        ),
+                        None,
+                    ));
+                }
+
+                if sigma_a.is_none() {
+                    // 3. reflectance and mean free path, _get_bssrdf()_ needs one or the other
+                    // pair of coefficients
+                    reflectance = get_spectrum_texture_or_none(parameter_dict, "reflectance")?;
+                    if reflectance.is_none() {
+                        return Err(SceneError::new(
+                            SceneErrorKind::MissingParameter("reflectance".to_string()),
+                            None,
+                        ));
+                    }
+
+                    mfp = if parameter_dict.has_float("mfp") {
+                        to_texture(Arc::new(ConstSpectrum::new(
+                            parameter_dict.get_one_float("mfp", None)?,
+                        )))
+                    } else {
+                        get_unbounded_texture("mfp")?
+                    };
+                    if mfp.is_none() {
+                        return Err(SceneError::new(
+                            SceneErrorKind::MissingParameter("mfp".to_string()),
+                            None,
+                        ));
+                    }
+                }
+            }
+
+            let scale = parameter_dict.get_one_float("scale", Some(1.0))?;
+            let eta = parameter_dict.get_one_float("eta", Some(1.33))?;
+            let (u_roughness, v_roughness) = get_roughness_textures(parameter_dict, "")?;
+            let remap_roughness = parameter_dict.get_one_bool("remaproughness", Some(true))?;
+
+            Arc::new(SubsurfaceMaterial::new(
+                scale,
+                sigma_a,
+                sigma_s,
+                reflectance,
+                mfp,
+                g,
+                eta,
+                u_roughness,
+                v_roughness,
+                remap_roughness,
+                displacement,
+                normal_map,
+            ))
+        }
+
         _ => {
-            panic!("unknown material type: `{}`", material_type);
+            return Err(SceneError::new(
+                SceneErrorKind::UnknownType {
+                    category: "material".to_string(),
+                    name: material_type.to_string(),
+                },
+                None,
+            ));
         }
     };
+
+    return Ok(material);
 }
@@ -1,4 +1,5 @@
 pub mod bsdf;
+pub mod bssrdf;
 pub mod bxdf;
 pub mod camera;
 pub mod film;
@@ -4,11 +4,16 @@ use crate::pbrt::*;
 pub struct Ray {
     pub o: Point3f,
     pub d: Vector3f,
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(o: Point3f, d: Vector3f) -> Ray {
-        return Ray { o, d };
+        return Ray { o, d, time: 0.0 };
+    }
+
+    pub fn new_with_time(o: Point3f, d: Vector3f, time: f64) -> Ray {
+        return Ray { o, d, time };
     }
 
     pub fn at(&self, t: f64) -> Point3f {
@@ -44,12 +49,18 @@ fn offset_ray_origin(pi: Point3fi, n: Normal3f, w: Vector3f) -> Point3f {
     return po;
 }
 
-pub fn spawn_ray_to(p_from: Point3fi, n_from: Normal3f, p_to: Point3fi, n_to: Normal3f) -> Ray {
+pub fn spawn_ray_to(
+    p_from: Point3fi,
+    n_from: Normal3f,
+    time: f64,
+    p_to: Point3fi,
+    n_to: Normal3f,
+) -> Ray {
     let pf = offset_ray_origin(p_from, n_from, Point3f::from(p_to) - Point3f::from(p_from));
 
     let pt = offset_ray_origin(p_to, n_to, pf - Point3f::from(p_to));
 
-    return Ray::new(pf, pt - pf);
+    return Ray::new_with_time(pf, pt - pf, time);
 }
 
 #[derive(Clone)]
@@ -64,9 +75,9 @@ pub struct DifferentialRay {
 }
 
 impl DifferentialRay {
-    pub fn new(o: Point3f, d: Vector3f) -> Self {
+    pub fn new(o: Point3f, d: Vector3f, time: f64) -> Self {
         return Self {
-            ray: Ray::new(o, d),
+            ray: Ray::new_with_time(o, d, time),
             has_differentials: false,
             rx_origin: Point3f::nan(),
             ry_origin: Point3f::nan(),
@@ -5,6 +5,8 @@ pub trait Sampler: Send + Sync {
 
     fn samples_per_pixel(&self) -> usize;
 
+    fn get_seed(&self) -> u64;
+
     fn start_pixel_sample(&mut self, p_pixel: Point2i, sample_index: usize);
 
     fn get_1d(&mut self) -> f64;
@@ -18,6 +20,7 @@ pub trait Sampler: Send + Sync {
         return CameraSample::new(
             Point2f::from(p_pixel) + fs.p + Vector2f::new(0.5, 0.5),
             self.get_2d(),
+            self.get_1d(),
             1.0,
         );
     }
@@ -89,11 +89,23 @@ pub const CIE_Z_DENSELY_SAMPLED: DenselySampledSpectrum =
 pub const ILLUM_D65: ConstPieceWiseLinearSpectrum<{ CIE_ILLUM_D6500.len() / 2 }> =
     ConstPieceWiseLinearSpectrum::from_interleaved_full_visible_wavelengths(CIE_ILLUM_D6500, true);
 
-pub fn get_named_spectrum(name: &str) -> &'static dyn Spectrum {
+pub const METAL_CU_ETA_PLS: ConstPieceWiseLinearSpectrum<N_METAL_CU_SAMPLES> =
+    ConstPieceWiseLinearSpectrum::new(METAL_CU_LAMBDA, METAL_CU_ETA);
+
+pub const METAL_CU_K_PLS: ConstPieceWiseLinearSpectrum<N_METAL_CU_SAMPLES> =
+    ConstPieceWiseLinearSpectrum::new(METAL_CU_LAMBDA, METAL_CU_K);
+
+lazy_static! {
+    // the ACES white point is defined as the CIE D illuminant at 6000K
+    pub static ref ILLUM_ACES_D60: DenselySampledSpectrum = DenselySampledSpectrum::cie_d(6000.0);
+}
+
+pub fn get_named_spectrum(name: &str) -> Option<&'static dyn Spectrum> {
     return match name {
-        "stdillum-D65" => &ILLUM_D65,
-        _ => {
-            panic!("unknown spectrum: `{}`", name);
-        }
+        "stdillum-D65" => Some(&ILLUM_D65),
+        "illum-acesD60" => Some(&*ILLUM_ACES_D60),
+        "metal-Cu-eta" => Some(&METAL_CU_ETA_PLS),
+        "metal-Cu-k" => Some(&METAL_CU_K_PLS),
+        _ => None,
     };
 }
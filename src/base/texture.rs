@@ -34,6 +34,20 @@ impl TextureEvalContext {
             dvdy: f64::NAN,
         };
     }
+
+    pub fn from_point(p: Point3f, n: Normal3f, uv: Point2f) -> Self {
+        return Self {
+            p,
+            dpdx: Vector3f::new(0.0, 0.0, 0.0),
+            dpdy: Vector3f::new(0.0, 0.0, 0.0),
+            n,
+            uv,
+            dudx: 0.0,
+            dudy: 0.0,
+            dvdx: 0.0,
+            dvdy: 0.0,
+        };
+    }
 }
 
 pub struct ImageTextureBase {
@@ -52,16 +66,27 @@ impl ImageTextureBase {
         wrap_mode: WrapMode,
         scale: f64,
         invert: bool,
-    ) -> Self {
-        let mipmap = MIPMap::create_from_file(filename, filter_options, wrap_mode);
+    ) -> Result<Self, SceneError> {
+        let mipmap = match MIPMap::create_from_file(filename, filter_options, wrap_mode) {
+            Err(message) => {
+                return Err(SceneError::new(
+                    SceneErrorKind::Io {
+                        filename: filename.to_string(),
+                        message,
+                    },
+                    None,
+                ));
+            }
+            Ok(_mipmap) => _mipmap,
+        };
 
-        return Self {
+        return Ok(Self {
             mapping,
             filename: filename.to_string(),
             scale,
             invert,
             mipmap,
-        };
+        });
     }
 }
 
@@ -73,27 +98,57 @@ pub trait SpectrumTexture: Send + Sync {
     fn evaluate(&self, ctx: &TextureEvalContext, lambda: &SampledWavelengths) -> SampledSpectrum;
 }
 
+pub fn create_float_texture(
+    texture_type: &str,
+    render_from_texture: &Transform,
+    parameters: &ParameterDict,
+) -> Result<Arc<dyn FloatTexture>, SceneError> {
+    return match texture_type {
+        "constant" => Ok(Arc::new(FloatConstantTexture::new(
+            parameters.get_one_float("value", Some(1.0))?,
+        ))),
+        "imagemap" => Ok(Arc::new(FloatImageTexture::new(
+            render_from_texture,
+            parameters,
+        )?)),
+        _ => Err(SceneError::new(
+            SceneErrorKind::UnknownType {
+                category: "float texture".to_string(),
+                name: texture_type.to_string(),
+            },
+            None,
+        )),
+    };
+}
+
 pub fn create_spectrum_texture(
     texture_type: &str,
     render_from_texture: &Transform,
     parameters: &ParameterDict,
     spectrum_type: SpectrumType,
-) -> Arc<dyn SpectrumTexture> {
+) -> Result<Arc<dyn SpectrumTexture>, SceneError> {
     return match texture_type {
-        "imagemap" => Arc::new(SpectrumImageTexture::new(
+        "imagemap" => Ok(Arc::new(SpectrumImageTexture::new(
             render_from_texture,
             parameters,
             spectrum_type,
-        )),
+        )?)),
         "scale" => {
-            let spectrum_texture = parameters.get_texture("tex");
+            let spectrum_texture = parameters.get_texture("tex")?;
 
-            let scale = parameters.get_one_float("scale", Some(1.0));
+            let scale = parameters.get_one_float("scale", Some(1.0))?;
 
-            Arc::new(SpectrumScaledTexture::new(spectrum_texture, scale))
-        }
-        _ => {
-            panic!("unknown SpectrumTexture type: `{}`", texture_type);
+            Ok(Arc::new(SpectrumScaledTexture::new(
+                spectrum_texture,
+                scale,
+            )))
         }
+        _ => Err(SceneError::new(
+            SceneErrorKind::UnknownType {
+                category: "spectrum texture".to_string(),
+                name: texture_type.to_string(),
+            },
+            None,
+        )),
     };
 }
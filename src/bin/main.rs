@@ -8,36 +8,230 @@ use std::path::PathBuf;
 #[derive(Parser)] // requires `derive` feature
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    #[arg(long)]
+    /// samples per pixel, overrides `pixelsamples` of the scene sampler
+    #[arg(long, value_parser = parse_positive::<usize>)]
     spp: Option<usize>,
+    /// seed of the sampler random streams, overrides `seed` of the scene sampler
+    #[arg(long)]
+    seed: Option<u64>,
+    /// write the final image to this file instead of the film `filename`
+    #[arg(long)]
+    outfile: Option<String>,
+    /// number of rendering threads (default: number of cores)
+    #[arg(long)]
+    nthreads: Option<usize>,
+    /// film resolution, overrides `xresolution` and `yresolution`
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], value_parser = parse_positive::<i32>)]
+    resolution: Option<Vec<i32>>,
+    /// scale the film resolution by this factor
+    #[arg(long)]
+    pixelscale: Option<f64>,
+    /// render only this region of the image, in [0, 1] image coordinates
+    #[arg(long, num_args = 4, value_names = ["X0", "X1", "Y0", "Y1"])]
+    cropwindow: Option<Vec<f64>>,
+    /// render only these pixels of the image
+    #[arg(long, num_args = 4, value_names = ["X0", "X1", "Y0", "Y1"], conflicts_with = "cropwindow")]
+    pixelbounds: Option<Vec<i32>>,
+    /// reduce samples per pixel and path depth for a fast preview
+    #[arg(long)]
+    quick: bool,
+    /// stop sampling after this many seconds and write the image rendered so far
+    #[arg(long, value_parser = parse_seconds)]
+    time_limit: Option<f64>,
+    /// print statistics once rendering is done
+    #[arg(long)]
+    stats: bool,
+    /// write the statistics to this file as JSON
+    #[arg(long)]
+    stats_json: Option<String>,
+    /// parse the scene file and exit without rendering
+    #[arg(long)]
+    parse_only: bool,
+    /// adaptive sampling: stop sampling pixels whose relative error is below this
+    #[arg(long)]
+    target_error: Option<f64>,
+    /// adaptive sampling: total number of samples distributed over the image
+    #[arg(long)]
+    sample_budget: Option<usize>,
+    /// file the film is periodically saved to
+    #[arg(long, conflicts_with_all = ["target_error", "sample_budget"])]
+    checkpoint: Option<String>,
+    /// seconds between two checkpoints
+    #[arg(long, default_value_t = 300.0, value_parser = parse_seconds)]
+    checkpoint_interval: f64,
+    /// continue the render saved in the checkpoint file
+    #[arg(long, requires = "checkpoint")]
+    resume: bool,
+    /// also write a denoised image, guided by the albedo and normal of visible surfaces
+    #[arg(long)]
+    denoise: bool,
+    /// print the scene in a normalized layout instead of rendering it
+    #[arg(long)]
+    format: bool,
+    /// print the scene with its pbrt-v3 syntax rewritten for pbrt-v4 (implies --format)
+    #[arg(long)]
+    upgrade: bool,
+    /// write the content of included and imported files in place of the directives
+    #[arg(long)]
+    expand_includes: bool,
+    /// write triangle meshes to binary PLY files in the current directory
+    #[arg(long)]
+    toply: bool,
+    /// write PLY meshes as triangle meshes in the scene
+    #[arg(long, conflicts_with = "toply")]
+    inline_meshes: bool,
     scene_file: PathBuf,
 }
 
-fn render(file_path: &str, samples_per_pixel: usize) {
+// a duration in seconds, rejected by clap when negative or not finite
+fn parse_seconds(value: &str) -> Result<f64, String> {
+    let seconds = value
+        .parse::<f64>()
+        .map_err(|error| format!("`{}` is not a number: {}", value, error))?;
+    if Duration::try_from_secs_f64(seconds).is_err() {
+        return Err(format!("`{}` is not a valid number of seconds", value));
+    }
+
+    return Ok(seconds);
+}
+
+// a count or size, rejected by clap when zero or negative
+fn parse_positive<T>(value: &str) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + Default,
+    T::Err: std::fmt::Display,
+{
+    let number = value
+        .parse::<T>()
+        .map_err(|error| format!("`{}` is not a number: {}", value, error))?;
+    if number <= T::default() {
+        return Err(format!("`{}` is not a positive number", value));
+    }
+
+    return Ok(number);
+}
+
+fn scene_options(args: &Cli) -> SceneOptions {
+    return SceneOptions {
+        samples_per_pixel: args.spp,
+        seed: args.seed,
+        outfile: args.outfile.clone(),
+        resolution: args
+            .resolution
+            .as_ref()
+            .map(|resolution| [resolution[0], resolution[1]]),
+        pixel_scale: args.pixelscale,
+        crop_window: args.cropwindow.as_ref().map(|crop_window| {
+            [
+                crop_window[0],
+                crop_window[1],
+                crop_window[2],
+                crop_window[3],
+            ]
+        }),
+        pixel_bounds: args.pixelbounds.as_ref().map(|pixel_bounds| {
+            [
+                pixel_bounds[0],
+                pixel_bounds[1],
+                pixel_bounds[2],
+                pixel_bounds[3],
+            ]
+        }),
+        quick: args.quick,
+    };
+}
+
+fn format_options(args: &Cli) -> FormatOptions {
+    let mesh_format = if args.toply {
+        MeshFormat::Ply
+    } else if args.inline_meshes {
+        MeshFormat::Inline
+    } else {
+        MeshFormat::Unchanged
+    };
+
+    return FormatOptions {
+        upgrade: args.upgrade,
+        expand_includes: args.expand_includes,
+        mesh_format,
+        ply_directory: ".".to_string(),
+    };
+}
+
+fn render(file_path: &str, args: &Cli) -> Result<(), SceneError> {
     let start = Instant::now();
 
+    // the first builder computes the spectrum tables of the color spaces
     let mut builder = SceneBuilder::default();
-    let mut scene_config = builder.parse_scene(file_path, samples_per_pixel);
+    builder.record_phase("initialization", start.elapsed());
+
+    if args.format || args.upgrade || args.toply || args.inline_meshes || args.expand_includes {
+        print!(
+            "{}",
+            builder.format_scene(file_path, &format_options(args))?
+        );
+        return Ok(());
+    }
+
+    if args.parse_only {
+        builder.parse_scene_file(file_path)?;
+        println!("parsing took: {:.2} seconds", start.elapsed().as_secs_f32());
+        return Ok(());
+    }
+
+    let mut scene_config = builder.parse_scene(file_path, &scene_options(args))?;
 
     println!(
         "preprocessing (spectra computing + BVH building): {:.2} seconds",
         start.elapsed().as_secs_f32(),
     );
 
-    let cpu_num = num_cpus::get();
+    if let Some(checkpoint) = &args.checkpoint {
+        scene_config.set_checkpoint(
+            checkpoint,
+            Duration::from_secs_f64(args.checkpoint_interval),
+            args.resume,
+        );
+    }
+
+    if args.denoise {
+        scene_config.enable_denoiser();
+    }
+
+    if let Some(time_limit) = args.time_limit {
+        scene_config.set_time_limit(Duration::from_secs_f64(time_limit));
+    }
+
+    if args.stats {
+        scene_config.enable_stats();
+    }
+
+    if let Some(stats_json) = &args.stats_json {
+        scene_config.set_stats_json(stats_json);
+    }
+
+    let num_threads = args.nthreads.unwrap_or_else(num_cpus::get).max(1);
+
+    if args.target_error.is_some() || args.sample_budget.is_some() {
+        scene_config.render_adaptive(args.target_error, args.sample_budget, num_threads)?;
+    } else {
+        scene_config.render(num_threads)?;
+    }
 
-    scene_config.render(samples_per_pixel, cpu_num);
+    return Ok(());
 }
 
 fn main() {
     let args = Cli::parse();
     if !args.scene_file.is_file() {
-        panic!("`{}` is not a file", args.scene_file.display().to_string());
+        eprintln!("error: `{}` is not a file", args.scene_file.display());
+        exit(1);
     }
 
-    let absolute_path = fs::canonicalize(args.scene_file).unwrap();
-
-    let spp = args.spp.unwrap_or_else(|| 32);
+    let absolute_path = fs::canonicalize(&args.scene_file).unwrap();
 
-    render(&absolute_path.display().to_string(), spp);
+    if let Err(error) = render(&absolute_path.display().to_string(), &args) {
+        eprintln!("{}", error);
+        exit(1);
+    }
 }
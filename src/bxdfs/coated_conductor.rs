@@ -0,0 +1,65 @@
+use crate::pbrt::*;
+
+#[derive(Clone)]
+pub struct CoatedConductorBxDF {
+    bxdf: LayeredBxDF<DielectricBxDF, ConductorBxDF, true>,
+}
+
+impl BxDF for CoatedConductorBxDF {
+    fn fork(&self) -> Arc<dyn BxDF> {
+        return Arc::new(self.clone());
+    }
+
+    fn flags(&self) -> BxDFFlags {
+        return self.bxdf.flags();
+    }
+
+    fn f(&self, wo: Vector3f, wi: Vector3f, mode: TransportMode) -> SampledSpectrum {
+        return self.bxdf.f(wo, wi, mode);
+    }
+
+    fn sample_f(
+        &self,
+        wo: Vector3f,
+        uc: f64,
+        u: Point2f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> Option<BSDFSample> {
+        return self.bxdf.sample_f(wo, uc, u, mode, sample_flags);
+    }
+
+    fn pdf(
+        &self,
+        wo: Vector3f,
+        wi: Vector3f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> f64 {
+        return self.bxdf.pdf(wo, wi, mode, sample_flags);
+    }
+}
+
+impl CoatedConductorBxDF {
+    pub fn new(
+        top_bxdf: Arc<DielectricBxDF>,
+        bottom_bxdf: Arc<ConductorBxDF>,
+        thickness: f64,
+        albedo: SampledSpectrum,
+        g: f64,
+        max_depth: usize,
+        n_samples: usize,
+    ) -> Self {
+        return Self {
+            bxdf: LayeredBxDF::<DielectricBxDF, ConductorBxDF, true>::new(
+                top_bxdf,
+                bottom_bxdf,
+                thickness,
+                albedo,
+                g,
+                max_depth,
+                n_samples,
+            ),
+        };
+    }
+}
@@ -0,0 +1,156 @@
+use crate::pbrt::*;
+
+#[derive(Clone, Copy)]
+pub struct ConductorBxDF {
+    mf_distribution: TrowbridgeReitzDistribution,
+    eta: SampledSpectrum,
+    k: SampledSpectrum,
+}
+
+impl ConductorBxDF {
+    pub fn new(
+        mf_distribution: TrowbridgeReitzDistribution,
+        eta: SampledSpectrum,
+        k: SampledSpectrum,
+    ) -> Self {
+        return ConductorBxDF {
+            mf_distribution,
+            eta,
+            k,
+        };
+    }
+}
+
+impl BxDF for ConductorBxDF {
+    fn fork(&self) -> Arc<dyn BxDF> {
+        return Arc::new(self.clone());
+    }
+
+    fn flags(&self) -> BxDFFlags {
+        return if self.mf_distribution.effectively_smooth() {
+            BxDFFlags::SpecularReflection
+        } else {
+            BxDFFlags::GlossyReflection
+        };
+    }
+
+    fn f(&self, wo: Vector3f, wi: Vector3f, mode: TransportMode) -> SampledSpectrum {
+        if !wo.same_hemisphere(wi) {
+            return SampledSpectrum::same_value(0.0);
+        }
+        if self.mf_distribution.effectively_smooth() {
+            return SampledSpectrum::same_value(0.0);
+        }
+
+        // Evaluate rough conductor BRDF
+        // Compute cosines and $\wm$ for conductor BRDF
+        let cos_theta_o = wo.abs_cos_theta();
+        let cos_theta_i = wi.abs_cos_theta();
+        if cos_theta_i == 0.0 || cos_theta_o == 0.0 {
+            return SampledSpectrum::same_value(0.0);
+        }
+
+        let wm = wi + wo;
+        if wm.length_squared() == 0.0 {
+            return SampledSpectrum::same_value(0.0);
+        }
+        let wm = wm.normalize();
+
+        // Evaluate Fresnel factor _F_ for conductor BRDF
+        let F = fr_complex_spectrum(wo.abs_dot(wm), self.eta, self.k);
+
+        return self.mf_distribution.d1(wm) * F * self.mf_distribution.g(wo, wi)
+            / (4.0 * cos_theta_i * cos_theta_o);
+    }
+
+    fn sample_f(
+        &self,
+        wo: Vector3f,
+        uc: f64,
+        u: Point2f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> Option<BSDFSample> {
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() {
+            return None;
+        }
+
+        if self.mf_distribution.effectively_smooth() {
+            // Sample perfect specular conductor BRDF
+            let wi = Vector3f::new(-wo.x, -wo.y, wo.z);
+            let f = fr_complex_spectrum(wi.abs_cos_theta(), self.eta, self.k) / wi.abs_cos_theta();
+
+            return Some(BSDFSample {
+                f,
+                wi,
+                pdf: 1.0,
+                flags: BxDFFlags::SpecularReflection,
+                eta: 1.0,
+                pdf_is_proportional: false,
+            });
+        }
+
+        // Sample rough conductor BRDF
+        // Sample microfacet normal $\wm$ and reflected direction $\wi$
+        if wo.z == 0.0 {
+            return None;
+        }
+
+        let wm = self.mf_distribution.sample_wm(wo, u);
+        let wi = reflect(wo, wm);
+        if !wo.same_hemisphere(wi) {
+            return None;
+        }
+
+        // Compute PDF of _wi_ for microfacet reflection
+        let pdf = self.mf_distribution.pdf(wo, wm) / (4.0 * wo.abs_dot(wm));
+
+        let cos_theta_o = wo.abs_cos_theta();
+        let cos_theta_i = wi.abs_cos_theta();
+        if cos_theta_i == 0.0 || cos_theta_o == 0.0 {
+            return None;
+        }
+
+        // Evaluate Fresnel factor _F_ for conductor BRDF
+        let F = fr_complex_spectrum(wo.abs_dot(wm), self.eta, self.k);
+
+        let f = self.mf_distribution.d1(wm) * F * self.mf_distribution.g(wo, wi)
+            / (4.0 * cos_theta_i * cos_theta_o);
+
+        return Some(BSDFSample {
+            f,
+            wi,
+            pdf,
+            flags: BxDFFlags::GlossyReflection,
+            eta: 1.0,
+            pdf_is_proportional: false,
+        });
+    }
+
+    fn pdf(
+        &self,
+        wo: Vector3f,
+        wi: Vector3f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> f64 {
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() {
+            return 0.0;
+        }
+        if !wo.same_hemisphere(wi) {
+            return 0.0;
+        }
+        if self.mf_distribution.effectively_smooth() {
+            return 0.0;
+        }
+
+        // Evaluate sampling PDF of rough conductor BRDF
+        let wm = wo + wi;
+        if wm.length_squared() == 0.0 {
+            return 0.0;
+        }
+        let wm = wm.normalize().face_forward(Vector3f::new(0.0, 0.0, 1.0));
+
+        return self.mf_distribution.pdf(wo, wm) / (4.0 * wo.abs_dot(wm));
+    }
+}
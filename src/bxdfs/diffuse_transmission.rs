@@ -0,0 +1,125 @@
+use crate::pbrt::*;
+
+#[derive(Clone, Copy)]
+pub struct DiffuseTransmissionBxDF {
+    r: SampledSpectrum,
+    t: SampledSpectrum,
+}
+
+impl DiffuseTransmissionBxDF {
+    pub fn new(r: SampledSpectrum, t: SampledSpectrum) -> DiffuseTransmissionBxDF {
+        return DiffuseTransmissionBxDF { r, t };
+    }
+}
+
+impl BxDF for DiffuseTransmissionBxDF {
+    fn fork(&self) -> Arc<dyn BxDF> {
+        return Arc::new(self.clone());
+    }
+
+    fn flags(&self) -> BxDFFlags {
+        let reflection = if self.r.is_positive() {
+            BxDFFlags::DiffuseReflection as isize
+        } else {
+            BxDFFlags::Unset as isize
+        };
+        let transmission = if self.t.is_positive() {
+            BxDFFlags::DiffuseTransmission as isize
+        } else {
+            BxDFFlags::Unset as isize
+        };
+
+        return BxDFFlags::from(reflection | transmission);
+    }
+
+    fn f(&self, wo: Vector3f, wi: Vector3f, _mode: TransportMode) -> SampledSpectrum {
+        return if wo.same_hemisphere(wi) {
+            self.r * INV_PI
+        } else {
+            self.t * INV_PI
+        };
+    }
+
+    fn sample_f(
+        &self,
+        wo: Vector3f,
+        uc: f64,
+        u: Point2f,
+        _mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> Option<BSDFSample> {
+        // Compute reflection and transmission probabilities for diffuse BSDF
+        let mut pr = self.r.max_component_value();
+        let mut pt = self.t.max_component_value();
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() {
+            pr = 0.0;
+        }
+        if !(sample_flags & BxDFReflTransFlags::Transmission).is_set() {
+            pt = 0.0;
+        }
+        if pr == 0.0 && pt == 0.0 {
+            return None;
+        }
+
+        // Randomly sample diffuse BSDF reflection or transmission
+        let mut wi = sample_cosine_hemisphere(u);
+        if uc < pr / (pr + pt) {
+            // Sample diffuse BSDF reflection
+            if wo.z < 0.0 {
+                wi.z *= -1.0;
+            }
+            let pdf = cosine_hemisphere_pdf(wi.abs_cos_theta()) * pr / (pr + pt);
+
+            return Some(BSDFSample {
+                f: self.r * INV_PI,
+                wi,
+                pdf,
+                flags: BxDFFlags::DiffuseReflection,
+                eta: 1.0,
+                pdf_is_proportional: false,
+            });
+        }
+
+        // Sample diffuse BSDF transmission
+        if wo.z > 0.0 {
+            wi.z *= -1.0;
+        }
+        let pdf = cosine_hemisphere_pdf(wi.abs_cos_theta()) * pt / (pr + pt);
+
+        return Some(BSDFSample {
+            f: self.t * INV_PI,
+            wi,
+            pdf,
+            flags: BxDFFlags::DiffuseTransmission,
+            eta: 1.0,
+            pdf_is_proportional: false,
+        });
+    }
+
+    fn pdf(
+        &self,
+        wo: Vector3f,
+        wi: Vector3f,
+        _mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> f64 {
+        // Compute reflection and transmission probabilities for diffuse BSDF
+        let mut pr = self.r.max_component_value();
+        let mut pt = self.t.max_component_value();
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() {
+            pr = 0.0;
+        }
+        if !(sample_flags & BxDFReflTransFlags::Transmission).is_set() {
+            pt = 0.0;
+        }
+        if pr == 0.0 && pt == 0.0 {
+            return 0.0;
+        }
+
+        return if wo.same_hemisphere(wi) {
+            pr / (pr + pt) * cosine_hemisphere_pdf(wi.abs_cos_theta())
+        } else {
+            pt / (pr + pt) * cosine_hemisphere_pdf(wi.abs_cos_theta())
+        };
+    }
+}
@@ -0,0 +1,433 @@
+use crate::pbrt::*;
+
+const P_MAX: usize = 3;
+const SQRT_PI_OVER_8: f64 = 0.626657069;
+
+fn i0(x: f64) -> f64 {
+    let mut val = 0.0;
+    let mut x2i = 1.0;
+    let mut ifact = 1.0;
+    let mut i4 = 1.0;
+    // I0(x) \approx Sum_i x^(2i) / (4^i (i!)^2)
+    for i in 0..10 {
+        if i > 1 {
+            ifact *= i as f64;
+        }
+        val += x2i / (i4 * sqr(ifact));
+        x2i *= x * x;
+        i4 *= 4.0;
+    }
+
+    return val;
+}
+
+fn log_i0(x: f64) -> f64 {
+    if x > 12.0 {
+        return x + 0.5 * (-(2.0 * PI).ln() + (1.0 / x).ln() + 1.0 / (8.0 * x));
+    }
+
+    return i0(x).ln();
+}
+
+fn mp(cos_theta_i: f64, cos_theta_o: f64, sin_theta_i: f64, sin_theta_o: f64, v: f64) -> f64 {
+    let a = cos_theta_i * cos_theta_o / v;
+    let b = sin_theta_i * sin_theta_o / v;
+
+    return if v <= 0.1 {
+        (log_i0(a) - b - 1.0 / v + std::f64::consts::LN_2 + (1.0 / (2.0 * v)).ln()).exp()
+    } else {
+        ((-b).exp() * i0(a)) / ((1.0 / v).sinh() * 2.0 * v)
+    };
+}
+
+fn ap(cos_theta_o: f64, eta: f64, h: f64, t: SampledSpectrum) -> [SampledSpectrum; P_MAX + 1] {
+    let mut ap = [SampledSpectrum::same_value(0.0); P_MAX + 1];
+
+    // Compute $p=0$ attenuation at initial cylinder intersection
+    let cos_gamma_o = safe_sqrt(1.0 - sqr(h));
+    let cos_theta = cos_theta_o * cos_gamma_o;
+    let f = fr_dielectric(cos_theta, eta);
+    ap[0] = SampledSpectrum::same_value(f);
+
+    // Compute $p=1$ attenuation term
+    ap[1] = sqr(1.0 - f) * t;
+
+    // Compute attenuation terms up to $p=_P_MAX_$
+    for p in 2..P_MAX {
+        ap[p] = ap[p - 1] * t * f;
+    }
+
+    // Compute attenuation term accounting for remaining orders of scattering
+    for i in 0..NUM_SPECTRUM_SAMPLES {
+        let one_minus_tf = 1.0 - t[i] * f;
+        if one_minus_tf != 0.0 {
+            ap[P_MAX][i] = ap[P_MAX - 1][i] * f * t[i] / one_minus_tf;
+        }
+    }
+
+    return ap;
+}
+
+fn phi(p: usize, gamma_o: f64, gamma_t: f64) -> f64 {
+    let p = p as f64;
+    return 2.0 * p * gamma_t - 2.0 * gamma_o + p * PI;
+}
+
+fn np(phi_value: f64, p: usize, s: f64, gamma_o: f64, gamma_t: f64) -> f64 {
+    let mut dphi = phi_value - phi(p, gamma_o, gamma_t);
+
+    // Remap _dphi_ to $[-\pi,\pi]$
+    while dphi > PI {
+        dphi -= 2.0 * PI;
+    }
+    while dphi < -PI {
+        dphi += 2.0 * PI;
+    }
+
+    return trimmed_logistic(dphi, s, -PI, PI);
+}
+
+#[derive(Clone, Copy)]
+pub struct HairBxDF {
+    h: f64,
+    eta: f64,
+    sigma_a: SampledSpectrum,
+    v: [f64; P_MAX + 1],
+    s: f64,
+    sin_2k_alpha: [f64; P_MAX],
+    cos_2k_alpha: [f64; P_MAX],
+}
+
+impl HairBxDF {
+    pub fn new(
+        h: f64,
+        eta: f64,
+        sigma_a: SampledSpectrum,
+        beta_m: f64,
+        beta_n: f64,
+        alpha: f64,
+    ) -> Self {
+        // Compute longitudinal variance from $\beta_m$
+        let mut v = [0.0; P_MAX + 1];
+        v[0] = sqr(0.726 * beta_m + 0.812 * sqr(beta_m) + 3.7 * beta_m.powi(20));
+        v[1] = 0.25 * v[0];
+        v[2] = 4.0 * v[0];
+        for p in 3..=P_MAX {
+            v[p] = v[2];
+        }
+
+        // Compute azimuthal logistic scale factor from $\beta_n$
+        let s = SQRT_PI_OVER_8 * (0.265 * beta_n + 1.194 * sqr(beta_n) + 5.372 * beta_n.powi(22));
+
+        // Compute $\alpha$ terms for hair scales
+        let mut sin_2k_alpha = [0.0; P_MAX];
+        let mut cos_2k_alpha = [0.0; P_MAX];
+        sin_2k_alpha[0] = degree_to_radian(alpha).sin();
+        cos_2k_alpha[0] = safe_sqrt(1.0 - sqr(sin_2k_alpha[0]));
+        for i in 1..P_MAX {
+            sin_2k_alpha[i] = 2.0 * cos_2k_alpha[i - 1] * sin_2k_alpha[i - 1];
+            cos_2k_alpha[i] = sqr(cos_2k_alpha[i - 1]) - sqr(sin_2k_alpha[i - 1]);
+        }
+
+        return HairBxDF {
+            h,
+            eta,
+            sigma_a,
+            v,
+            s,
+            sin_2k_alpha,
+            cos_2k_alpha,
+        };
+    }
+
+    pub fn sigma_a_from_concentration(ce: f64, cp: f64) -> RGBUnboundedSpectrum {
+        let eumelanin_sigma_a = RGB::new(0.419, 0.697, 1.37);
+        let pheomelanin_sigma_a = RGB::new(0.187, 0.4, 1.05);
+
+        return RGBUnboundedSpectrum::new(
+            &COLOR_SPACE,
+            eumelanin_sigma_a * ce + pheomelanin_sigma_a * cp,
+        );
+    }
+
+    pub fn sigma_a_from_reflectance(c: &SampledSpectrum, beta_n: f64) -> SampledSpectrum {
+        let mut sigma_a = SampledSpectrum::same_value(0.0);
+        for i in 0..NUM_SPECTRUM_SAMPLES {
+            sigma_a[i] = sqr(c[i].ln()
+                / (5.969 - 0.215 * beta_n + 2.532 * sqr(beta_n) - 10.73 * beta_n.powi(3)
+                    + 5.574 * beta_n.powi(4)
+                    + 0.245 * beta_n.powi(5)));
+        }
+
+        return sigma_a;
+    }
+
+    // $\sin\thetao$ and $\cos\thetao$ terms accounting for scales
+    fn tilted_theta_o(&self, p: usize, sin_theta_o: f64, cos_theta_o: f64) -> (f64, f64) {
+        let (sin_thetap_o, cos_thetap_o) = match p {
+            0 => (
+                sin_theta_o * self.cos_2k_alpha[1] - cos_theta_o * self.sin_2k_alpha[1],
+                cos_theta_o * self.cos_2k_alpha[1] + sin_theta_o * self.sin_2k_alpha[1],
+            ),
+            1 => (
+                sin_theta_o * self.cos_2k_alpha[0] + cos_theta_o * self.sin_2k_alpha[0],
+                cos_theta_o * self.cos_2k_alpha[0] - sin_theta_o * self.sin_2k_alpha[0],
+            ),
+            2 => (
+                sin_theta_o * self.cos_2k_alpha[2] + cos_theta_o * self.sin_2k_alpha[2],
+                cos_theta_o * self.cos_2k_alpha[2] - sin_theta_o * self.sin_2k_alpha[2],
+            ),
+            _ => (sin_theta_o, cos_theta_o),
+        };
+
+        // Handle out-of-range $\cos\thetao$ from scale adjustment
+        return (sin_thetap_o, cos_thetap_o.abs());
+    }
+
+    fn ap_pdf(&self, cos_theta_o: f64) -> [f64; P_MAX + 1] {
+        // Initialize array of $A_p$ values for _cos_theta_o_
+        let sin_theta_o = safe_sqrt(1.0 - sqr(cos_theta_o));
+
+        // Compute $\cos\thetat$ for refracted ray
+        let sin_theta_t = sin_theta_o / self.eta;
+        let cos_theta_t = safe_sqrt(1.0 - sqr(sin_theta_t));
+
+        // Compute $\gammat$ for refracted ray
+        let etap = safe_sqrt(sqr(self.eta) - sqr(sin_theta_o)) / cos_theta_o;
+        let sin_gamma_t = self.h / etap;
+        let cos_gamma_t = safe_sqrt(1.0 - sqr(sin_gamma_t));
+
+        // Compute the transmittance _t_ of a single path through the cylinder
+        let t = (self.sigma_a * (-2.0 * cos_gamma_t / cos_theta_t)).exp();
+        let ap = ap(cos_theta_o, self.eta, self.h, t);
+
+        // Compute $A_p$ PDF from individual $A_p$ terms
+        let sum_y: f64 = ap.iter().map(|a| a.average()).sum();
+
+        let mut ap_pdf = [0.0; P_MAX + 1];
+        for i in 0..=P_MAX {
+            ap_pdf[i] = ap[i].average() / sum_y;
+        }
+
+        return ap_pdf;
+    }
+
+    fn pdf_with_angles(
+        &self,
+        ap_pdf: &[f64; P_MAX + 1],
+        sin_theta_o: f64,
+        cos_theta_o: f64,
+        sin_theta_i: f64,
+        cos_theta_i: f64,
+        dphi: f64,
+        gamma_o: f64,
+        gamma_t: f64,
+    ) -> f64 {
+        let mut pdf = 0.0;
+        for p in 0..P_MAX {
+            let (sin_thetap_o, cos_thetap_o) = self.tilted_theta_o(p, sin_theta_o, cos_theta_o);
+
+            pdf += mp(
+                cos_theta_i,
+                cos_thetap_o,
+                sin_theta_i,
+                sin_thetap_o,
+                self.v[p],
+            ) * ap_pdf[p]
+                * np(dphi, p, self.s, gamma_o, gamma_t);
+        }
+
+        pdf += mp(
+            cos_theta_i,
+            cos_theta_o,
+            sin_theta_i,
+            sin_theta_o,
+            self.v[P_MAX],
+        ) * ap_pdf[P_MAX]
+            * (1.0 / (2.0 * PI));
+
+        return pdf;
+    }
+}
+
+impl BxDF for HairBxDF {
+    fn fork(&self) -> Arc<dyn BxDF> {
+        return Arc::new(self.clone());
+    }
+
+    fn flags(&self) -> BxDFFlags {
+        return BxDFFlags::GlossyReflection;
+    }
+
+    fn f(&self, wo: Vector3f, wi: Vector3f, mode: TransportMode) -> SampledSpectrum {
+        // Compute hair coordinate system terms related to _wo_
+        let sin_theta_o = wo.x;
+        let cos_theta_o = safe_sqrt(1.0 - sqr(sin_theta_o));
+        let phi_o = wo.z.atan2(wo.y);
+        let gamma_o = safe_asin(self.h);
+
+        // Compute hair coordinate system terms related to _wi_
+        let sin_theta_i = wi.x;
+        let cos_theta_i = safe_sqrt(1.0 - sqr(sin_theta_i));
+        let phi_i = wi.z.atan2(wi.y);
+
+        // Compute $\cos\thetat$ for refracted ray
+        let sin_theta_t = sin_theta_o / self.eta;
+        let cos_theta_t = safe_sqrt(1.0 - sqr(sin_theta_t));
+
+        // Compute $\gammat$ for refracted ray
+        let etap = safe_sqrt(sqr(self.eta) - sqr(sin_theta_o)) / cos_theta_o;
+        let sin_gamma_t = self.h / etap;
+        let cos_gamma_t = safe_sqrt(1.0 - sqr(sin_gamma_t));
+        let gamma_t = safe_asin(sin_gamma_t);
+
+        // Compute the transmittance _t_ of a single path through the cylinder
+        let t = (self.sigma_a * (-2.0 * cos_gamma_t / cos_theta_t)).exp();
+
+        // Evaluate hair BSDF
+        let phi = phi_i - phi_o;
+        let ap = ap(cos_theta_o, self.eta, self.h, t);
+        let mut fsum = SampledSpectrum::same_value(0.0);
+        for p in 0..P_MAX {
+            let (sin_thetap_o, cos_thetap_o) = self.tilted_theta_o(p, sin_theta_o, cos_theta_o);
+
+            fsum += mp(
+                cos_theta_i,
+                cos_thetap_o,
+                sin_theta_i,
+                sin_thetap_o,
+                self.v[p],
+            ) * ap[p]
+                * np(phi, p, self.s, gamma_o, gamma_t);
+        }
+
+        // Compute contribution of remaining terms after _P_MAX_
+        fsum += mp(
+            cos_theta_i,
+            cos_theta_o,
+            sin_theta_i,
+            sin_theta_o,
+            self.v[P_MAX],
+        ) * ap[P_MAX]
+            / (2.0 * PI);
+
+        if wi.abs_cos_theta() > 0.0 {
+            fsum /= wi.abs_cos_theta();
+        }
+
+        return fsum;
+    }
+
+    fn sample_f(
+        &self,
+        wo: Vector3f,
+        uc: f64,
+        u: Point2f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> Option<BSDFSample> {
+        // Compute hair coordinate system terms related to _wo_
+        let sin_theta_o = wo.x;
+        let cos_theta_o = safe_sqrt(1.0 - sqr(sin_theta_o));
+        let phi_o = wo.z.atan2(wo.y);
+        let gamma_o = safe_asin(self.h);
+
+        // Determine which term $p$ to sample for hair scattering
+        let ap_pdf = self.ap_pdf(cos_theta_o);
+        let (p, uc) = match sample_discrete(&ap_pdf, uc) {
+            None => {
+                return None;
+            }
+            Some(_sampled) => _sampled,
+        };
+
+        let (sin_thetap_o, cos_thetap_o) = self.tilted_theta_o(p, sin_theta_o, cos_theta_o);
+
+        // Sample $M_p$ to compute $\thetai$
+        let cos_theta =
+            1.0 + self.v[p] * (u.x.max(1e-5) + (1.0 - u.x) * (-2.0 / self.v[p]).exp()).ln();
+        let sin_theta = safe_sqrt(1.0 - sqr(cos_theta));
+        let cos_phi = (2.0 * PI * u.y).cos();
+        let sin_theta_i = -cos_theta * sin_thetap_o + sin_theta * cos_phi * cos_thetap_o;
+        let cos_theta_i = safe_sqrt(1.0 - sqr(sin_theta_i));
+
+        // Sample $N_p$ to compute $\Delta\phi$
+        // Compute $\gammat$ for refracted ray
+        let etap = safe_sqrt(sqr(self.eta) - sqr(sin_theta_o)) / cos_theta_o;
+        let sin_gamma_t = self.h / etap;
+        let gamma_t = safe_asin(sin_gamma_t);
+
+        let dphi = if p < P_MAX {
+            phi(p, gamma_o, gamma_t) + sample_trimmed_logistic(uc, self.s, -PI, PI)
+        } else {
+            2.0 * PI * uc
+        };
+
+        // Compute _wi_ from sampled hair scattering angles
+        let phi_i = phi_o + dphi;
+        let wi = Vector3f::new(
+            sin_theta_i,
+            cos_theta_i * phi_i.cos(),
+            cos_theta_i * phi_i.sin(),
+        );
+
+        // Compute PDF for sampled hair scattering direction _wi_
+        let pdf = self.pdf_with_angles(
+            &ap_pdf,
+            sin_theta_o,
+            cos_theta_o,
+            sin_theta_i,
+            cos_theta_i,
+            dphi,
+            gamma_o,
+            gamma_t,
+        );
+
+        return Some(BSDFSample {
+            f: self.f(wo, wi, mode),
+            wi,
+            pdf,
+            flags: self.flags(),
+            eta: 1.0,
+            pdf_is_proportional: false,
+        });
+    }
+
+    fn pdf(
+        &self,
+        wo: Vector3f,
+        wi: Vector3f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> f64 {
+        // Compute hair coordinate system terms related to _wo_
+        let sin_theta_o = wo.x;
+        let cos_theta_o = safe_sqrt(1.0 - sqr(sin_theta_o));
+        let phi_o = wo.z.atan2(wo.y);
+        let gamma_o = safe_asin(self.h);
+
+        // Compute hair coordinate system terms related to _wi_
+        let sin_theta_i = wi.x;
+        let cos_theta_i = safe_sqrt(1.0 - sqr(sin_theta_i));
+        let phi_i = wi.z.atan2(wi.y);
+
+        // Compute $\gammat$ for refracted ray
+        let etap = safe_sqrt(sqr(self.eta) - sqr(sin_theta_o)) / cos_theta_o;
+        let sin_gamma_t = self.h / etap;
+        let gamma_t = safe_asin(sin_gamma_t);
+
+        // Compute PDF for $A_p$ terms
+        let ap_pdf = self.ap_pdf(cos_theta_o);
+
+        return self.pdf_with_angles(
+            &ap_pdf,
+            sin_theta_o,
+            cos_theta_o,
+            sin_theta_i,
+            cos_theta_i,
+            phi_i - phi_o,
+            gamma_o,
+            gamma_t,
+        );
+    }
+}
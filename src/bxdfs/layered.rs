@@ -1,6 +1,17 @@
 use crate::pbrt::*;
 
-pub struct LayeredBxDF<TypeTopBxDF: BxDF, TypeBottomBxDF: BxDF, const TWO_SIDED: bool> {
+// the random walk through the layers is seeded by the arguments of the query,
+// so that evaluating the same BSDF twice gives the same value
+fn layered_rng(values: &[f64]) -> RNG {
+    let bits = values.iter().map(|v| v.to_bits()).collect::<Vec<u64>>();
+    return RNG::new(calculate_hash(&bits));
+}
+
+pub struct LayeredBxDF<
+    TypeTopBxDF: BxDF + ?Sized,
+    TypeBottomBxDF: BxDF + ?Sized,
+    const TWO_SIDED: bool,
+> {
     top: Arc<TypeTopBxDF>,
     bottom: Arc<TypeBottomBxDF>,
     thickness: f64,
@@ -10,7 +21,7 @@ pub struct LayeredBxDF<TypeTopBxDF: BxDF, TypeBottomBxDF: BxDF, const TWO_SIDED:
     n_samples: usize,
 }
 
-impl<TypeTopBxDF: BxDF, TypeBottomBxDF: BxDF, const TWO_SIDED: bool> Clone
+impl<TypeTopBxDF: BxDF + ?Sized, TypeBottomBxDF: BxDF + ?Sized, const TWO_SIDED: bool> Clone
     for LayeredBxDF<TypeTopBxDF, TypeBottomBxDF, TWO_SIDED>
 {
     fn clone(&self) -> Self {
@@ -26,8 +37,11 @@ impl<TypeTopBxDF: BxDF, TypeBottomBxDF: BxDF, const TWO_SIDED: bool> Clone
     }
 }
 
-impl<TypeTopBxDF: BxDF + 'static, TypeBottomBxDF: BxDF + 'static, const TWO_SIDED: bool> BxDF
-    for LayeredBxDF<TypeTopBxDF, TypeBottomBxDF, TWO_SIDED>
+impl<
+        TypeTopBxDF: BxDF + ?Sized + 'static,
+        TypeBottomBxDF: BxDF + ?Sized + 'static,
+        const TWO_SIDED: bool,
+    > BxDF for LayeredBxDF<TypeTopBxDF, TypeBottomBxDF, TWO_SIDED>
 {
     fn fork(&self) -> Arc<dyn BxDF> {
         let forked_self = self.clone();
@@ -116,10 +130,9 @@ impl<TypeTopBxDF: BxDF + 'static, TypeBottomBxDF: BxDF + 'static, const TWO_SIDE
             f = (self.n_samples as f64) * enter_interface.f(wo, wi, mode);
         }
 
-        // TODO: build a deterministic RNG like PBRT-v4
-        let mut rng = StdRng::from_entropy();
+        let mut rng = layered_rng(&[wo.x, wo.y, wo.z, wi.x, wi.y, wi.z]);
         let mut r = || -> f64 {
-            return rng.gen::<f64>();
+            return rng.uniform_f64();
         };
 
         for _ in 0..self.n_samples {
@@ -343,10 +356,9 @@ impl<TypeTopBxDF: BxDF + 'static, TypeBottomBxDF: BxDF + 'static, const TWO_SIDE
         let mut w = bs.wi;
         let mut specular_path = bs.is_specular();
 
-        // TODO: build a deterministic RNG like PBRT-v4
-        let mut rng = StdRng::from_entropy();
+        let mut rng = layered_rng(&[wo.x, wo.y, wo.z, uc, u.x, u.y]);
         let mut r = || -> f64 {
-            return rng.gen::<f64>();
+            return rng.uniform_f64();
         };
 
         // Declare common variables for layered BSDF sampling
@@ -488,10 +500,9 @@ impl<TypeTopBxDF: BxDF + 'static, TypeBottomBxDF: BxDF + 'static, const TWO_SIDE
             (wo, wi)
         };
 
-        // TODO: build a deterministic RNG like PBRT-v4
-        let mut rng = StdRng::from_entropy();
+        let mut rng = layered_rng(&[wi.x, wi.y, wi.z, wo.x, wo.y, wo.z]);
         let mut r = || -> f64 {
-            return rng.gen::<f64>();
+            return rng.uniform_f64();
         };
 
         let entered_top = TWO_SIDED || wo.z > 0.0;
@@ -679,7 +690,7 @@ impl<TypeTopBxDF: BxDF + 'static, TypeBottomBxDF: BxDF + 'static, const TWO_SIDE
     }
 }
 
-impl<TypeTopBxDF: BxDF, TypeBottomBxDF: BxDF, const TWO_SIDED: bool>
+impl<TypeTopBxDF: BxDF + ?Sized, TypeBottomBxDF: BxDF + ?Sized, const TWO_SIDED: bool>
     LayeredBxDF<TypeTopBxDF, TypeBottomBxDF, TWO_SIDED>
 {
     pub fn new(
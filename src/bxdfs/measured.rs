@@ -0,0 +1,451 @@
+use crate::pbrt::*;
+
+const TENSOR_DTYPE_UINT8: u8 = 1;
+const TENSOR_DTYPE_FLOAT32: u8 = 10;
+
+struct TensorField {
+    dtype: u8,
+    shape: Vec<usize>,
+    data: Vec<u8>,
+}
+
+impl TensorField {
+    fn to_floats(&self, name: &str) -> Result<Vec<f64>, String> {
+        if self.dtype != TENSOR_DTYPE_FLOAT32 {
+            return Err(format!("field `{}` is not of type Float32", name));
+        }
+
+        return Ok(self
+            .data
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64)
+            .collect());
+    }
+}
+
+// simple exchange format for tensor data, as written by the RGL material database
+struct Tensor {
+    fields: HashMap<String, TensorField>,
+}
+
+impl Tensor {
+    // errors are described without _filename_, the caller reports it
+    fn read(filename: &str) -> Result<Tensor, String> {
+        let mut buffer = vec![];
+        match File::open(filename) {
+            Err(_err) => {
+                return Err(_err.to_string());
+            }
+            Ok(mut _file) => {
+                if let Err(_err) = _file.read_to_end(&mut buffer) {
+                    return Err(_err.to_string());
+                }
+            }
+        };
+
+        let mut offset = 0;
+        let mut read_bytes = |len: usize| -> Result<&[u8], String> {
+            if offset + len > buffer.len() {
+                return Err("unexpected end of file".to_string());
+            }
+            let bytes = &buffer[offset..offset + len];
+            offset += len;
+            return Ok(bytes);
+        };
+
+        let header = read_bytes(12)?;
+        if header != b"tensor_file\0" {
+            return Err("invalid tensor file header".to_string());
+        }
+
+        let version = read_bytes(2)?;
+        if version != [1, 0] {
+            return Err(format!(
+                "file is in an incompatible version {}.{}",
+                version[0], version[1]
+            ));
+        }
+
+        let n_fields = u32::from_le_bytes(read_bytes(4)?.try_into().unwrap());
+
+        let mut entries = vec![];
+        for _ in 0..n_fields {
+            let name_length = u16::from_le_bytes(read_bytes(2)?.try_into().unwrap()) as usize;
+            let name = String::from_utf8_lossy(read_bytes(name_length)?).to_string();
+            let ndim = u16::from_le_bytes(read_bytes(2)?.try_into().unwrap()) as usize;
+            let dtype = read_bytes(1)?[0];
+            let data_offset = u64::from_le_bytes(read_bytes(8)?.try_into().unwrap()) as usize;
+
+            let mut shape = vec![];
+            for _ in 0..ndim {
+                shape.push(u64::from_le_bytes(read_bytes(8)?.try_into().unwrap()) as usize);
+            }
+
+            entries.push((name, dtype, data_offset, shape));
+        }
+
+        let mut fields = HashMap::new();
+        for (name, dtype, data_offset, shape) in entries {
+            let dtype_size = match dtype {
+                1 | 2 => 1,
+                3 | 4 | 9 => 2,
+                5 | 6 | 10 => 4,
+                7 | 8 | 11 => 8,
+                _ => {
+                    return Err(format!("field `{}` has unknown data type {}", name, dtype));
+                }
+            };
+
+            let size = shape.iter().product::<usize>() * dtype_size;
+            if data_offset + size > buffer.len() {
+                return Err(format!("field `{}` exceeds file size", name));
+            }
+
+            fields.insert(
+                name,
+                TensorField {
+                    dtype,
+                    shape,
+                    data: buffer[data_offset..data_offset + size].to_vec(),
+                },
+            );
+        }
+
+        return Ok(Tensor { fields });
+    }
+
+    fn field(&self, name: &str) -> Result<&TensorField, String> {
+        return match self.fields.get(name) {
+            None => Err(format!("missing field `{}`", name)),
+            Some(_field) => Ok(_field),
+        };
+    }
+}
+
+pub struct MeasuredBxDFData {
+    ndf: PiecewiseLinear2D,
+    sigma: PiecewiseLinear2D,
+    vndf: PiecewiseLinear2D,
+    luminance: PiecewiseLinear2D,
+    spectra: PiecewiseLinear2D,
+    isotropic: bool,
+}
+
+impl MeasuredBxDFData {
+    pub fn read(filename: &str) -> Result<MeasuredBxDFData, String> {
+        let tensor = Tensor::read(filename)?;
+
+        let theta_i = tensor.field("theta_i")?;
+        let phi_i = tensor.field("phi_i")?;
+        let ndf = tensor.field("ndf")?;
+        let sigma = tensor.field("sigma")?;
+        let vndf = tensor.field("vndf")?;
+        let luminance = tensor.field("luminance")?;
+        let description = tensor.field("description")?;
+        let jacobian = tensor.field("jacobian")?;
+
+        if !(description.shape.len() == 1 && description.dtype == TENSOR_DTYPE_UINT8) {
+            return Err("invalid `description` field".to_string());
+        }
+        if !(jacobian.shape.len() == 1
+            && jacobian.shape[0] == 1
+            && jacobian.dtype == TENSOR_DTYPE_UINT8)
+        {
+            return Err("invalid `jacobian` field".to_string());
+        }
+
+        if !(theta_i.shape.len() == 1 && phi_i.shape.len() == 1) {
+            return Err("invalid `theta_i` or `phi_i` field".to_string());
+        }
+        let n_theta = theta_i.shape[0];
+        let n_phi = phi_i.shape[0];
+
+        if !(ndf.shape.len() == 2 && sigma.shape.len() == 2) {
+            return Err("invalid `ndf` or `sigma` field".to_string());
+        }
+        if !(vndf.shape.len() == 4 && vndf.shape[0] == n_phi && vndf.shape[1] == n_theta) {
+            return Err("invalid `vndf` field".to_string());
+        }
+        if !(luminance.shape.len() == 4
+            && luminance.shape[0] == n_phi
+            && luminance.shape[1] == n_theta
+            && luminance.shape[2] == luminance.shape[3])
+        {
+            return Err("invalid `luminance` field".to_string());
+        }
+
+        if !tensor.fields.contains_key("spectra") {
+            return Err(
+                "only spectral RGL files are supported (missing `spectra` field)".to_string(),
+            );
+        }
+        let wavelengths = tensor.field("wavelengths")?;
+        let spectra = tensor.field("spectra")?;
+        if !(wavelengths.shape.len() == 1
+            && spectra.shape.len() == 5
+            && spectra.shape[0] == n_phi
+            && spectra.shape[1] == n_theta
+            && spectra.shape[2] == wavelengths.shape[0]
+            && spectra.shape[3] == luminance.shape[2]
+            && spectra.shape[4] == luminance.shape[3])
+        {
+            return Err("invalid `wavelengths` or `spectra` field".to_string());
+        }
+
+        let theta_i_values = theta_i.to_floats("theta_i")?;
+        let phi_i_values = phi_i.to_floats("phi_i")?;
+        let wavelength_values = wavelengths.to_floats("wavelengths")?;
+
+        return Ok(MeasuredBxDFData {
+            ndf: PiecewiseLinear2D::new(
+                &ndf.to_floats("ndf")?,
+                ndf.shape[1],
+                ndf.shape[0],
+                vec![],
+                vec![],
+                false,
+                false,
+            ),
+            sigma: PiecewiseLinear2D::new(
+                &sigma.to_floats("sigma")?,
+                sigma.shape[1],
+                sigma.shape[0],
+                vec![],
+                vec![],
+                false,
+                false,
+            ),
+            vndf: PiecewiseLinear2D::new(
+                &vndf.to_floats("vndf")?,
+                vndf.shape[3],
+                vndf.shape[2],
+                vec![n_phi, n_theta],
+                vec![phi_i_values.clone(), theta_i_values.clone()],
+                true,
+                true,
+            ),
+            luminance: PiecewiseLinear2D::new(
+                &luminance.to_floats("luminance")?,
+                luminance.shape[3],
+                luminance.shape[2],
+                vec![n_phi, n_theta],
+                vec![phi_i_values.clone(), theta_i_values.clone()],
+                true,
+                true,
+            ),
+            spectra: PiecewiseLinear2D::new(
+                &spectra.to_floats("spectra")?,
+                spectra.shape[4],
+                spectra.shape[3],
+                vec![n_phi, n_theta, wavelength_values.len()],
+                vec![phi_i_values, theta_i_values, wavelength_values],
+                false,
+                false,
+            ),
+            isotropic: n_phi <= 2,
+        });
+    }
+}
+
+#[derive(Clone)]
+pub struct MeasuredBxDF {
+    brdf: Arc<MeasuredBxDFData>,
+    lambda: SampledWavelengths,
+}
+
+impl MeasuredBxDF {
+    pub fn new(brdf: Arc<MeasuredBxDFData>, lambda: SampledWavelengths) -> Self {
+        return MeasuredBxDF { brdf, lambda };
+    }
+
+    fn theta2u(theta: f64) -> f64 {
+        return (theta * (2.0 / PI)).sqrt();
+    }
+
+    fn phi2u(phi: f64) -> f64 {
+        return (phi + PI) / (2.0 * PI);
+    }
+
+    fn u2theta(u: f64) -> f64 {
+        return sqr(u) * (PI / 2.0);
+    }
+
+    fn u2phi(u: f64) -> f64 {
+        return (2.0 * u - 1.0) * PI;
+    }
+
+    fn evaluate_spectra(&self, sample: Point2f, phi_i: f64, theta_i: f64) -> SampledSpectrum {
+        let mut fr = SampledSpectrum::same_value(0.0);
+        for i in 0..NUM_SPECTRUM_SAMPLES {
+            fr[i] = self
+                .brdf
+                .spectra
+                .evaluate(sample, &[phi_i, theta_i, self.lambda[i]])
+                .max(0.0);
+        }
+
+        return fr;
+    }
+}
+
+impl BxDF for MeasuredBxDF {
+    fn fork(&self) -> Arc<dyn BxDF> {
+        return Arc::new(self.clone());
+    }
+
+    fn flags(&self) -> BxDFFlags {
+        return BxDFFlags::GlossyReflection;
+    }
+
+    fn f(&self, wo: Vector3f, wi: Vector3f, mode: TransportMode) -> SampledSpectrum {
+        // Check that _wo_ and _wi_ are valid
+        if !wo.same_hemisphere(wi) {
+            return SampledSpectrum::same_value(0.0);
+        }
+        let (wo, wi) = if wo.z < 0.0 { (-wo, -wi) } else { (wo, wi) };
+
+        // Determine half-direction vector _wm_
+        let wm = wi + wo;
+        if wm.length_squared() == 0.0 {
+            return SampledSpectrum::same_value(0.0);
+        }
+        let wm = wm.normalize();
+
+        // Map _wi_ and _wm_ to the unit square
+        let theta_i = spherical_theta(wi);
+        let phi_i = wi.y.atan2(wi.x);
+        let theta_m = spherical_theta(wm);
+        let phi_m = wm.y.atan2(wm.x);
+
+        let u_wi = Point2f::new(Self::theta2u(theta_i), Self::phi2u(phi_i));
+        let mut u_wm = Point2f::new(
+            Self::theta2u(theta_m),
+            Self::phi2u(if self.brdf.isotropic {
+                phi_m - phi_i
+            } else {
+                phi_m
+            }),
+        );
+        u_wm.y = u_wm.y - u_wm.y.floor();
+
+        // Evaluate inverse parameterization $R^{-1}$
+        let params = [phi_i, theta_i];
+        let sample = self.brdf.vndf.invert(u_wm, &params).p;
+
+        // Evaluate spectral 5D interpolant
+        let fr = self.evaluate_spectra(sample, phi_i, theta_i);
+
+        // Return measured BRDF value
+        return fr * self.brdf.ndf.evaluate(u_wm, &[])
+            / (4.0 * self.brdf.sigma.evaluate(u_wi, &[]) * wi.abs_cos_theta());
+    }
+
+    fn sample_f(
+        &self,
+        wo: Vector3f,
+        uc: f64,
+        u: Point2f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> Option<BSDFSample> {
+        // Check flags and detect interactions in lower hemisphere
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() {
+            return None;
+        }
+        let flip_wi = wo.z <= 0.0;
+        let wo = if flip_wi { -wo } else { wo };
+
+        // Initialize parameters of conditional distribution
+        let theta_i = spherical_theta(wo);
+        let phi_i = wo.y.atan2(wo.x);
+        let params = [phi_i, theta_i];
+
+        // Warp sample using luminance distribution
+        let luminance_sample = self.brdf.luminance.sample(u, &params);
+        let u = luminance_sample.p;
+        let luminance_pdf = luminance_sample.pdf;
+
+        // Sample visible normal distribution of measured BRDF
+        let vndf_sample = self.brdf.vndf.sample(u, &params);
+        let u_wm = vndf_sample.p;
+        let mut pdf = vndf_sample.pdf;
+
+        // Map from microfacet normal to incident direction
+        let mut phi_m = Self::u2phi(u_wm.y);
+        let theta_m = Self::u2theta(u_wm.x);
+        if self.brdf.isotropic {
+            phi_m += phi_i;
+        }
+        let sin_theta_m = theta_m.sin();
+        let cos_theta_m = theta_m.cos();
+        let wm = spherical_direction(sin_theta_m, cos_theta_m, phi_m);
+        let wi = reflect(wo, wm);
+        if wi.z <= 0.0 {
+            return None;
+        }
+
+        // Interpolate spectral BRDF
+        let u_wo = Point2f::new(Self::theta2u(theta_i), Self::phi2u(phi_i));
+        let fr = self.evaluate_spectra(u, phi_i, theta_i) * self.brdf.ndf.evaluate(u_wm, &[])
+            / (4.0 * self.brdf.sigma.evaluate(u_wo, &[]) * wi.abs_cos_theta());
+
+        pdf /= 4.0 * wo.dot(wm) * (2.0 * sqr(PI) * u_wm.x * sin_theta_m).max(1e-6);
+
+        // Handle interactions in lower hemisphere
+        let wi = if flip_wi { -wi } else { wi };
+
+        return Some(BSDFSample {
+            f: fr,
+            wi,
+            pdf: pdf * luminance_pdf,
+            flags: BxDFFlags::GlossyReflection,
+            eta: 1.0,
+            pdf_is_proportional: false,
+        });
+    }
+
+    fn pdf(
+        &self,
+        wo: Vector3f,
+        wi: Vector3f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> f64 {
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() || !wo.same_hemisphere(wi) {
+            return 0.0;
+        }
+        let (wo, wi) = if wo.z < 0.0 { (-wo, -wi) } else { (wo, wi) };
+
+        let wm = wi + wo;
+        if wm.length_squared() == 0.0 {
+            return 0.0;
+        }
+        let wm = wm.normalize();
+
+        let theta_i = spherical_theta(wo);
+        let phi_i = wo.y.atan2(wo.x);
+        let theta_m = spherical_theta(wm);
+        let phi_m = wm.y.atan2(wm.x);
+
+        let mut u_wm = Point2f::new(
+            Self::theta2u(theta_m),
+            Self::phi2u(if self.brdf.isotropic {
+                phi_m - phi_i
+            } else {
+                phi_m
+            }),
+        );
+        u_wm.y = u_wm.y - u_wm.y.floor();
+
+        let params = [phi_i, theta_i];
+        let inverted = self.brdf.vndf.invert(u_wm, &params);
+        let sample = inverted.p;
+        let vndf_pdf = inverted.pdf;
+
+        let pdf = self.brdf.luminance.evaluate(sample, &params);
+        let sin_theta_m = (sqr(wm.x) + sqr(wm.y)).sqrt();
+        let jacobian = 4.0 * wo.dot(wm) * (2.0 * sqr(PI) * u_wm.x * sin_theta_m).max(1e-6);
+
+        return vndf_pdf * pdf / jacobian;
+    }
+}
@@ -1,5 +1,11 @@
+pub mod coated_conductor;
 pub mod coated_diffuse;
+pub mod conductor;
 pub mod dielectric;
 pub mod diffuse;
+pub mod diffuse_transmission;
+pub mod hair;
 pub mod layered;
+pub mod measured;
+pub mod normalized_fresnel;
 pub mod top_or_bottom;
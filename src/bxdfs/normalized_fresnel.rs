@@ -0,0 +1,85 @@
+use crate::pbrt::*;
+
+// angular distribution of light leaving a subsurface scattering material
+#[derive(Clone, Copy)]
+pub struct NormalizedFresnelBxDF {
+    eta: f64,
+}
+
+impl NormalizedFresnelBxDF {
+    pub fn new(eta: f64) -> Self {
+        return NormalizedFresnelBxDF { eta };
+    }
+}
+
+impl BxDF for NormalizedFresnelBxDF {
+    fn fork(&self) -> Arc<dyn BxDF> {
+        return Arc::new(self.clone());
+    }
+
+    fn flags(&self) -> BxDFFlags {
+        return BxDFFlags::DiffuseReflection;
+    }
+
+    fn f(&self, wo: Vector3f, wi: Vector3f, mode: TransportMode) -> SampledSpectrum {
+        if !wo.same_hemisphere(wi) {
+            return SampledSpectrum::same_value(0.0);
+        }
+
+        // Compute $\Sw$ factor for BSSRDF value
+        let c = 1.0 - 2.0 * fresnel_moment1(1.0 / self.eta);
+        let mut f =
+            SampledSpectrum::same_value((1.0 - fr_dielectric(wi.cos_theta(), self.eta)) / (c * PI));
+
+        // Update BSSRDF transmission term to account for adjoint light transport
+        if mode == TransportMode::Radiance {
+            f *= sqr(self.eta);
+        }
+
+        return f;
+    }
+
+    fn sample_f(
+        &self,
+        wo: Vector3f,
+        uc: f64,
+        u: Point2f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> Option<BSDFSample> {
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() {
+            return None;
+        }
+
+        // Cosine-sample the hemisphere, flipping the direction if necessary
+        let unsigned_wi = sample_cosine_hemisphere(u);
+        let wi = if wo.z < 0.0 {
+            -unsigned_wi
+        } else {
+            unsigned_wi
+        };
+
+        return Some(BSDFSample {
+            f: self.f(wo, wi, mode),
+            wi,
+            pdf: self.pdf(wo, wi, mode, sample_flags),
+            flags: BxDFFlags::DiffuseReflection,
+            eta: 1.0,
+            pdf_is_proportional: false,
+        });
+    }
+
+    fn pdf(
+        &self,
+        wo: Vector3f,
+        wi: Vector3f,
+        mode: TransportMode,
+        sample_flags: BxDFReflTransFlags,
+    ) -> f64 {
+        if !(sample_flags & BxDFReflTransFlags::Reflection).is_set() || !wo.same_hemisphere(wi) {
+            return 0.0;
+        }
+
+        return cosine_hemisphere_pdf(wi.abs_cos_theta());
+    }
+}
@@ -1,11 +1,13 @@
 use crate::pbrt::*;
 
-pub struct TopOrBottomBxDF<TypeTopBxDF: BxDF, TypeBottomBxDF: BxDF> {
+pub struct TopOrBottomBxDF<TypeTopBxDF: BxDF + ?Sized, TypeBottomBxDF: BxDF + ?Sized> {
     pub top_bxdf: Option<Arc<TypeTopBxDF>>,
     pub bottom_bxdf: Option<Arc<TypeBottomBxDF>>,
 }
 
-impl<TypeTopBxDF: BxDF, TypeBottomBxDF: BxDF> TopOrBottomBxDF<TypeTopBxDF, TypeBottomBxDF> {
+impl<TypeTopBxDF: BxDF + ?Sized, TypeBottomBxDF: BxDF + ?Sized>
+    TopOrBottomBxDF<TypeTopBxDF, TypeBottomBxDF>
+{
     pub fn new(
         top_bxdf: Option<Arc<TypeTopBxDF>>,
         bottom_bxdf: Option<Arc<TypeBottomBxDF>>,
@@ -19,8 +19,18 @@ impl PerspectiveCamera {
         camera_transform: CameraTransform,
         parameters: ParameterDict,
         resolution: Point2i,
-    ) -> Self {
-        let _fov = parameters.get_one_float("fov", Some(90.0));
+    ) -> Result<Self, SceneError> {
+        let _fov = parameters.get_one_float("fov", Some(90.0))?;
+
+        let mut shutter_open = parameters.get_one_float("shutteropen", Some(0.0))?;
+        let mut shutter_close = parameters.get_one_float("shutterclose", Some(1.0))?;
+        if shutter_close < shutter_open {
+            println!(
+                "Shutter close time {} < shutter open {}. Swapping them.",
+                shutter_close, shutter_open
+            );
+            (shutter_open, shutter_close) = (shutter_close, shutter_open);
+        }
 
         let frame_aspect_ratio = (resolution.x as f64) / (resolution.y as f64);
 
@@ -61,13 +71,15 @@ impl PerspectiveCamera {
 
         let camera_base = CameraBase {
             camera_transform,
+            shutter_open,
+            shutter_close,
             min_pos_differential_x: Vector3f::nan(),
             min_pos_differential_y: Vector3f::nan(),
             min_dir_differential_x: Vector3f::nan(),
             min_dir_differential_y: Vector3f::nan(),
         };
 
-        return Self {
+        return Ok(Self {
             camera_base,
             raster_from_screen,
             screen_from_raster,
@@ -76,25 +88,25 @@ impl PerspectiveCamera {
             dx_camera,
             dy_camera,
             lens_radius: 0.0,
-        };
+        });
     }
 
     pub fn new(
         camera_transform: CameraTransform,
         parameters: ParameterDict,
         resolution: Point2i,
-    ) -> Self {
+    ) -> Result<Self, SceneError> {
         let mut perspective_camera = PerspectiveCamera::build_camera_without_differential(
             camera_transform,
             parameters,
             resolution,
-        );
+        )?;
 
         let mut camera_base = perspective_camera.camera_base.clone();
         camera_base.find_minimum_differentials(&perspective_camera, resolution);
         perspective_camera.camera_base = camera_base;
 
-        return perspective_camera;
+        return Ok(perspective_camera);
     }
 }
 
@@ -104,9 +116,10 @@ impl Camera for PerspectiveCamera {
         let p_film = Point3f::new(sample.p_film.x, sample.p_film.y, 0.0);
         let p_camera = self.camera_from_raster.on_point3f(p_film);
 
-        let ray = Ray::new(
+        let ray = Ray::new_with_time(
             Point3f::new(0.0, 0.0, 0.0),
             Vector3f::from(p_camera).normalize(),
+            self.camera_base.sample_time(sample.time),
         );
 
         if self.lens_radius == 0.0 {
@@ -131,9 +144,10 @@ impl Camera for PerspectiveCamera {
         let p_film = Point3f::new(sample.p_film.x, sample.p_film.y, 0.0);
         let p_camera = self.camera_from_raster.on_point3f(p_film);
 
-        let ray = Ray::new(
+        let ray = Ray::new_with_time(
             Point3f::new(0.0, 0.0, 0.0),
             Vector3f::from(p_camera).normalize(),
+            self.camera_base.sample_time(sample.time),
         );
 
         if self.lens_radius == 0.0 {
@@ -155,7 +169,7 @@ impl Camera for PerspectiveCamera {
             let (transformed_differential_ray, _) = self
                 .camera_base
                 .camera_transform
-                .render_from_camera
+                .render_from_camera(differential_ray.ray.time)
                 .on_differential_ray(&differential_ray);
 
             return CameraDifferentialRay {
@@ -172,14 +186,12 @@ impl Camera for PerspectiveCamera {
         &self,
         p: Point3f,
         n: Normal3f,
+        time: f64,
         samples_per_pixel: usize,
     ) -> (Vector3f, Vector3f) {
         // Compute tangent plane equation for ray differential intersections
-        let p_camera = self
-            .camera_base
-            .camera_transform
-            .camera_from_render
-            .on_point3f(p);
+        let render_from_camera = self.camera_base.camera_transform.render_from_camera(time);
+        let p_camera = render_from_camera.inverse().on_point3f(p);
         let down_z_from_camera =
             Transform::rotate_from_to(Vector3f::from(p_camera), Vector3f::new(0.0, 0.0, 1.0));
 
@@ -209,18 +221,10 @@ impl Camera for PerspectiveCamera {
         let spp_scale = (0.125 as f64).max(1.0 / (samples_per_pixel as f64).sqrt());
 
         let dpdx = spp_scale
-            * self
-                .camera_base
-                .camera_transform
-                .render_from_camera
-                .on_vector3f(down_z_from_camera.inverse_on_vector3f(px - p_down_z));
+            * render_from_camera.on_vector3f(down_z_from_camera.inverse_on_vector3f(px - p_down_z));
 
         let dpdy = spp_scale
-            * self
-                .camera_base
-                .camera_transform
-                .render_from_camera
-                .on_vector3f(down_z_from_camera.inverse_on_vector3f(py - p_down_z));
+            * render_from_camera.on_vector3f(down_z_from_camera.inverse_on_vector3f(py - p_down_z));
 
         return (dpdx, dpdy);
     }
@@ -0,0 +1,193 @@
+use crate::pbrt::*;
+
+// number of time steps used by motion_bounds() to sweep a bounding box through the motion
+const MOTION_BOUNDS_STEPS: usize = 128;
+
+#[derive(Copy, Clone)]
+pub struct AnimatedTransform {
+    pub start_transform: Transform,
+    pub end_transform: Transform,
+    pub start_time: f64,
+    pub end_time: f64,
+
+    actually_animated: bool,
+    translation: [Vector3f; 2],
+    rotation: [Quaternion; 2],
+    scale: [SquareMatrix<4>; 2],
+}
+
+fn decompose(m: &SquareMatrix<4>) -> (Vector3f, Quaternion, SquareMatrix<4>) {
+    // Extract translation _T_ from transformation matrix
+    let translation = Vector3f::new(m[0][3], m[1][3], m[2][3]);
+
+    // Compute new transformation matrix _M_ without translation
+    let mut matrix = *m;
+    for i in 0..3 {
+        matrix[i][3] = 0.0;
+        matrix[3][i] = 0.0;
+    }
+    matrix[3][3] = 1.0;
+
+    // Extract rotation _R_ from transformation matrix with polar decomposition
+    let mut rotation_matrix = matrix;
+    for _ in 0..100 {
+        // Compute next matrix _Rnext_ in series
+        let r_inv_transpose = rotation_matrix.transpose().inverse();
+        let mut r_next = SquareMatrix::<4>::zero();
+        for i in 0..4 {
+            for j in 0..4 {
+                r_next[i][j] = 0.5 * (rotation_matrix[i][j] + r_inv_transpose[i][j]);
+            }
+        }
+
+        // Compute norm of difference between _R_ and _Rnext_
+        let mut norm: f64 = 0.0;
+        for i in 0..3 {
+            let n = (rotation_matrix[i][0] - r_next[i][0]).abs()
+                + (rotation_matrix[i][1] - r_next[i][1]).abs()
+                + (rotation_matrix[i][2] - r_next[i][2]).abs();
+            norm = norm.max(n);
+        }
+
+        rotation_matrix = r_next;
+        if norm <= 0.0001 {
+            break;
+        }
+    }
+
+    // Compute scale _S_ using rotation and original matrix
+    let scale = rotation_matrix.inverse() * matrix;
+
+    return (
+        translation,
+        Quaternion::from_matrix(&rotation_matrix),
+        scale,
+    );
+}
+
+impl AnimatedTransform {
+    pub fn new(
+        start_transform: Transform,
+        start_time: f64,
+        end_transform: Transform,
+        end_time: f64,
+    ) -> Self {
+        let actually_animated = start_transform != end_transform;
+
+        let (t0, r0, s0) = decompose(&start_transform.get_matrix());
+        let (t1, r1, s1) = decompose(&end_transform.get_matrix());
+
+        // Flip _R[1]_ if needed to select shortest path
+        let r1 = if r0.dot(r1) < 0.0 { -r1 } else { r1 };
+
+        return AnimatedTransform {
+            start_transform,
+            end_transform,
+            start_time,
+            end_time,
+            actually_animated,
+            translation: [t0, t1],
+            rotation: [r0, r1],
+            scale: [s0, s1],
+        };
+    }
+
+    pub fn from_transform(transform: Transform) -> Self {
+        return AnimatedTransform::new(transform, 0.0, transform, 1.0);
+    }
+
+    pub fn is_animated(&self) -> bool {
+        return self.actually_animated;
+    }
+
+    pub fn interpolate(&self, time: f64) -> Transform {
+        // Handle boundary conditions for matrix interpolation
+        if !self.actually_animated || time <= self.start_time {
+            return self.start_transform;
+        }
+        if time >= self.end_time {
+            return self.end_transform;
+        }
+
+        let dt = (time - self.start_time) / (self.end_time - self.start_time);
+
+        // Interpolate translation at _dt_
+        let trans = self.translation[0] * (1.0 - dt) + self.translation[1] * dt;
+
+        // Interpolate rotation at _dt_
+        let rotate = Quaternion::slerp(dt, self.rotation[0], self.rotation[1]);
+
+        // Interpolate scale at _dt_
+        let mut scale = SquareMatrix::<4>::zero();
+        for i in 0..3 {
+            for j in 0..3 {
+                scale[i][j] = lerp(dt, self.scale[0][i][j], self.scale[1][i][j]);
+            }
+        }
+        scale[3][3] = 1.0;
+
+        // Compute interpolated matrix as product of interpolated components
+        return Transform::translate(trans.x, trans.y, trans.z)
+            * rotate.to_transform()
+            * Transform::from_matrix(scale);
+    }
+
+    pub fn on_point3f(&self, p: Point3f, time: f64) -> Point3f {
+        return self.interpolate(time).on_point3f(p);
+    }
+
+    pub fn on_vector3f(&self, v: Vector3f, time: f64) -> Vector3f {
+        return self.interpolate(time).on_vector3f(v);
+    }
+
+    pub fn on_ray(&self, r: &Ray) -> (Ray, f64) {
+        return self.interpolate(r.time).on_ray(r);
+    }
+
+    pub fn motion_bounds(&self, b: Bounds3f) -> Bounds3f {
+        if !self.actually_animated {
+            return self.start_transform.on_bounds(b);
+        }
+
+        // sweep the box through the motion and pad the union by the largest distance
+        // a corner travels between two steps, so that curved (rotational) paths
+        // in between the steps are still covered
+        let corners = (0..8)
+            .map(|idx| {
+                Point3f::new(
+                    if idx & 1 == 0 { b.p_min.x } else { b.p_max.x },
+                    if idx & 2 == 0 { b.p_min.y } else { b.p_max.y },
+                    if idx & 4 == 0 { b.p_min.z } else { b.p_max.z },
+                )
+            })
+            .collect::<Vec<Point3f>>();
+
+        let mut bounds = Bounds3f::empty();
+        let mut max_step: f64 = 0.0;
+        let mut last_corners: Option<Vec<Point3f>> = None;
+
+        for step in 0..=MOTION_BOUNDS_STEPS {
+            let time = lerp(
+                step as f64 / MOTION_BOUNDS_STEPS as f64,
+                self.start_time,
+                self.end_time,
+            );
+            let transform = self.interpolate(time);
+
+            let moved_corners = corners
+                .iter()
+                .map(|p| transform.on_point3f(*p))
+                .collect::<Vec<Point3f>>();
+
+            for idx in 0..moved_corners.len() {
+                bounds = bounds.union(moved_corners[idx]);
+                if let Some(last) = &last_corners {
+                    max_step = max_step.max((moved_corners[idx] - last[idx]).length());
+                }
+            }
+            last_corners = Some(moved_corners);
+        }
+
+        return bounds.expand(max_step);
+    }
+}
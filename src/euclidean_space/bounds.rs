@@ -1,5 +1,6 @@
 use crate::pbrt::*;
 
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Bounds2<T> {
     pub p_min: Point2<T>,
     pub p_max: Point2<T>,
@@ -20,6 +21,42 @@ impl Bounds2<i32> {
             p_max: _max,
         };
     }
+
+    pub fn from_min_max(p_min: Point2i, p_max: Point2i) -> Bounds2<i32> {
+        return Bounds2::<i32> { p_min, p_max };
+    }
+
+    pub fn diagonal(&self) -> Point2i {
+        return Point2i::new(self.p_max.x - self.p_min.x, self.p_max.y - self.p_min.y);
+    }
+
+    pub fn area(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let d = self.diagonal();
+        return (d.x as usize) * (d.y as usize);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.p_min.x >= self.p_max.x || self.p_min.y >= self.p_max.y;
+    }
+
+    pub fn intersect(&self, b: &Bounds2<i32>) -> Bounds2<i32> {
+        return Bounds2::<i32> {
+            p_min: self.p_min.max(&b.p_min),
+            p_max: self.p_max.min(&b.p_max),
+        };
+    }
+
+    // _p_max_ is exclusive: a pixel on the upper edges lies outside
+    pub fn inside_exclusive(&self, p: Point2i) -> bool {
+        return p.x >= self.p_min.x
+            && p.x < self.p_max.x
+            && p.y >= self.p_min.y
+            && p.y < self.p_max.y;
+    }
 }
 
 impl Bounds2<f64> {
@@ -105,6 +142,21 @@ impl Bounds3<f64> {
         };
     }
 
+    pub fn overlaps(&self, b: &Bounds3<f64>) -> bool {
+        let x = self.p_max.x >= b.p_min.x && self.p_min.x <= b.p_max.x;
+        let y = self.p_max.y >= b.p_min.y && self.p_min.y <= b.p_max.y;
+        let z = self.p_max.z >= b.p_min.z && self.p_min.z <= b.p_max.z;
+        return x && y && z;
+    }
+
+    pub fn expand(&self, delta: f64) -> Bounds3<f64> {
+        let offset = Vector3f::new(delta, delta, delta);
+        return Bounds3::<f64> {
+            p_min: self.p_min - offset,
+            p_max: self.p_max + offset,
+        };
+    }
+
     pub fn diagonal(&self) -> Vector3f {
         return self.p_max - self.p_min;
     }
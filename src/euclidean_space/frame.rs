@@ -26,6 +26,18 @@ impl Frame {
         return Self { x, y, z };
     }
 
+    pub fn from_x(x: Vector3f) -> Self {
+        let (y, z) = x.coordinate_system();
+
+        return Self { x, y, z };
+    }
+
+    pub fn from_y(y: Vector3f) -> Self {
+        let (z, x) = y.coordinate_system();
+
+        return Self { x, y, z };
+    }
+
     pub fn from_xz(x: Vector3f, z: Vector3f) -> Self {
         return Self {
             x,
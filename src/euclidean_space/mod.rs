@@ -1,3 +1,4 @@
+pub mod animated_transform;
 pub mod bounds;
 pub mod compensated_float;
 pub mod float;
@@ -6,6 +7,7 @@ pub mod interval;
 pub mod normal;
 pub mod point2;
 pub mod point3;
+pub mod quaternion;
 pub mod square_matrix;
 pub mod transform;
 pub mod vector2;
@@ -0,0 +1,170 @@
+use crate::pbrt::*;
+
+#[derive(Copy, Clone)]
+pub struct Quaternion {
+    pub v: Vector3f,
+    pub w: f64,
+}
+
+impl Quaternion {
+    pub fn identity() -> Self {
+        return Quaternion {
+            v: Vector3f::new(0.0, 0.0, 0.0),
+            w: 1.0,
+        };
+    }
+
+    pub fn from_matrix(m: &SquareMatrix<4>) -> Self {
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        if trace > 0.0 {
+            // Compute w from matrix trace, then xyz
+            // 4w^2 = m[0][0] + m[1][1] + m[2][2] + m[3][3] (but m[3][3] == 1)
+            let s = (trace + 1.0).sqrt();
+            let w = s / 2.0;
+            let s = 0.5 / s;
+
+            return Quaternion {
+                v: Vector3f::new(
+                    (m[2][1] - m[1][2]) * s,
+                    (m[0][2] - m[2][0]) * s,
+                    (m[1][0] - m[0][1]) * s,
+                ),
+                w,
+            };
+        }
+
+        // Compute largest of x, y, or z, then remaining components
+        let next = [1, 2, 0];
+        let mut q = [0.0; 3];
+        let mut i = 0;
+        if m[1][1] > m[0][0] {
+            i = 1;
+        }
+        if m[2][2] > m[i][i] {
+            i = 2;
+        }
+        let j = next[i];
+        let k = next[j];
+
+        let s = ((m[i][i] - (m[j][j] + m[k][k])) + 1.0).sqrt();
+        q[i] = s * 0.5;
+        let s = if s != 0.0 { 0.5 / s } else { s };
+        let w = (m[k][j] - m[j][k]) * s;
+        q[j] = (m[j][i] + m[i][j]) * s;
+        q[k] = (m[k][i] + m[i][k]) * s;
+
+        return Quaternion {
+            v: Vector3f::new(q[0], q[1], q[2]),
+            w,
+        };
+    }
+
+    pub fn dot(&self, q: Quaternion) -> f64 {
+        return self.v.dot(q.v) + self.w * q.w;
+    }
+
+    pub fn length(&self) -> f64 {
+        return self.dot(*self).sqrt();
+    }
+
+    pub fn normalize(&self) -> Quaternion {
+        return *self / self.length();
+    }
+
+    pub fn angle_between(&self, q: Quaternion) -> f64 {
+        if self.dot(q) < 0.0 {
+            return PI - 2.0 * safe_asin((*self + q).length() / 2.0);
+        }
+
+        return 2.0 * safe_asin((q - *self).length() / 2.0);
+    }
+
+    pub fn slerp(t: f64, q1: Quaternion, q2: Quaternion) -> Quaternion {
+        let theta = q1.angle_between(q2);
+        let sin_theta_over_theta = sinx_over_x(theta);
+
+        return q1 * ((1.0 - t) * sinx_over_x((1.0 - t) * theta) / sin_theta_over_theta)
+            + q2 * (t * sinx_over_x(t * theta) / sin_theta_over_theta);
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        let xx = self.v.x * self.v.x;
+        let yy = self.v.y * self.v.y;
+        let zz = self.v.z * self.v.z;
+        let xy = self.v.x * self.v.y;
+        let xz = self.v.x * self.v.z;
+        let yz = self.v.y * self.v.z;
+        let wx = self.v.x * self.w;
+        let wy = self.v.y * self.w;
+        let wz = self.v.z * self.w;
+
+        let mut m = SquareMatrix::<4>::identity();
+        m[0][0] = 1.0 - 2.0 * (yy + zz);
+        m[0][1] = 2.0 * (xy + wz);
+        m[0][2] = 2.0 * (xz - wy);
+        m[1][0] = 2.0 * (xy - wz);
+        m[1][1] = 1.0 - 2.0 * (xx + zz);
+        m[1][2] = 2.0 * (yz + wx);
+        m[2][0] = 2.0 * (xz + wy);
+        m[2][1] = 2.0 * (yz - wx);
+        m[2][2] = 1.0 - 2.0 * (xx + yy);
+
+        // Transpose since we are left-handed
+        return Transform::new_with_inverse(m.transpose(), m);
+    }
+}
+
+impl Add<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    fn add(self, rhs: Quaternion) -> Self::Output {
+        return Quaternion {
+            v: self.v + rhs.v,
+            w: self.w + rhs.w,
+        };
+    }
+}
+
+impl Sub<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    fn sub(self, rhs: Quaternion) -> Self::Output {
+        return Quaternion {
+            v: self.v - rhs.v,
+            w: self.w - rhs.w,
+        };
+    }
+}
+
+impl Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Self::Output {
+        return Quaternion {
+            v: -self.v,
+            w: -self.w,
+        };
+    }
+}
+
+impl Mul<f64> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        return Quaternion {
+            v: self.v * rhs,
+            w: self.w * rhs,
+        };
+    }
+}
+
+impl Div<f64> for Quaternion {
+    type Output = Quaternion;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        return Quaternion {
+            v: self.v / rhs,
+            w: self.w / rhs,
+        };
+    }
+}
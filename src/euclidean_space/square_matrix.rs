@@ -5,6 +5,24 @@ pub struct SquareMatrix<const N: usize> {
     matrix: [[f64; N]; N],
 }
 
+// solve for the matrix M minimizing |A M^T - B| over the rows of _a_ and _b_
+pub fn linear_least_squares(a: &[[f64; 3]], b: &[[f64; 3]]) -> SquareMatrix<3> {
+    assert_eq!(a.len(), b.len());
+
+    let mut at_a = SquareMatrix::<3>::zero();
+    let mut at_b = SquareMatrix::<3>::zero();
+    for i in 0..3 {
+        for j in 0..3 {
+            for r in 0..a.len() {
+                at_a[i][j] += a[r][i] * a[r][j];
+                at_b[i][j] += a[r][i] * b[r][j];
+            }
+        }
+    }
+
+    return (at_a.inverse() * at_b).transpose();
+}
+
 impl<const N: usize> SquareMatrix<N> {
     pub fn zero() -> Self {
         return SquareMatrix {
@@ -16,6 +16,10 @@ impl Transform {
         };
     }
 
+    pub fn get_matrix(&self) -> SquareMatrix<4> {
+        return self.matrix;
+    }
+
     pub fn is_identity(&self) -> bool {
         return self.matrix.is_identity() && self.inverted_matrix.is_identity();
     }
@@ -112,6 +116,46 @@ impl Transform {
         };
     }
 
+    pub fn look_at(pos: Point3f, look: Point3f, up: Vector3f) -> Transform {
+        let mut world_from_camera = SquareMatrix::<4>::zero();
+        world_from_camera[0][3] = pos.x;
+        world_from_camera[1][3] = pos.y;
+        world_from_camera[2][3] = pos.z;
+        world_from_camera[3][3] = 1.0;
+
+        let dir = (look - pos).normalize();
+        if up.normalize().cross(dir).length() == 0.0 {
+            panic!("LookAt: `up` vector and viewing direction are pointing in the same direction");
+        }
+
+        let right = up.normalize().cross(dir).normalize();
+        let new_up = dir.cross(right);
+
+        world_from_camera[0][0] = right.x;
+        world_from_camera[1][0] = right.y;
+        world_from_camera[2][0] = right.z;
+        world_from_camera[3][0] = 0.0;
+        world_from_camera[0][1] = new_up.x;
+        world_from_camera[1][1] = new_up.y;
+        world_from_camera[2][1] = new_up.z;
+        world_from_camera[3][1] = 0.0;
+        world_from_camera[0][2] = dir.x;
+        world_from_camera[1][2] = dir.y;
+        world_from_camera[2][2] = dir.z;
+        world_from_camera[3][2] = 0.0;
+
+        let camera_from_world = world_from_camera.inverse();
+        return Transform::new_with_inverse(camera_from_world, world_from_camera);
+    }
+
+    pub fn swaps_handedness(&self) -> bool {
+        let m = &self.matrix;
+        let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+        return det < 0.0;
+    }
+
     pub fn rotate_from_to(from: Vector3f, to: Vector3f) -> Transform {
         // Compute intermediate vector for vector reflection
         let threshold = 0.72;
@@ -382,7 +426,7 @@ impl Transform {
         let dt = d.abs().dot(o.error()) / length_squared;
         let offset_o = o + Vector3fi::from(d * dt);
 
-        return (Ray::new(offset_o.into(), d), dt);
+        return (Ray::new_with_time(offset_o.into(), d, r.time), dt);
     }
 
     pub fn on_differential_ray(&self, r: &DifferentialRay) -> (DifferentialRay, f64) {
@@ -417,6 +461,7 @@ impl Transform {
             n: self.on_normal3f(interaction.n),
             wo: self.on_vector3f(interaction.wo).normalize(),
             uv: interaction.uv,
+            time: interaction.time,
         };
     }
 
@@ -444,6 +489,20 @@ impl Transform {
     }
 }
 
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        for y in 0..4 {
+            for x in 0..4 {
+                if self.matrix[y][x] != other.matrix[y][x] {
+                    return false;
+                }
+            }
+        }
+
+        return true;
+    }
+}
+
 impl Mul<Transform> for Transform {
     type Output = Transform;
 
@@ -383,3 +383,7 @@ pub fn spherical_direction(sin_theta: f64, cos_theta: f64, phi: f64) -> Vector3f
         z: cos_theta.clamp(-1.0, 1.0),
     };
 }
+
+pub fn spherical_theta(v: Vector3f) -> f64 {
+    return safe_acos(v.z);
+}
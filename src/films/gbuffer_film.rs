@@ -0,0 +1,411 @@
+use crate::pbrt::*;
+
+#[derive(Copy, Clone, Default)]
+struct GBufferPixel {
+    pub rgb_sum: [f64; 3],
+    pub weight_sum: f64,
+    pub g_buffer_weight_sum: f64,
+    pub p_sum: [f64; 3],
+    pub depth_sum: f64,
+    pub dzdx_sum: f64,
+    pub dzdy_sum: f64,
+    pub n_sum: [f64; 3],
+    pub ns_sum: [f64; 3],
+    pub uv_sum: [f64; 2],
+    pub rgb_albedo_sum: [f64; 3],
+    pub rgb_variance: [VarianceEstimator; 3],
+}
+
+impl GBufferPixel {
+    const STATE_SIZE: usize = 31;
+
+    fn write_state(&self, state: &mut Vec<f64>) {
+        state.extend(self.rgb_sum);
+        state.push(self.weight_sum);
+        state.push(self.g_buffer_weight_sum);
+        state.extend(self.p_sum);
+        state.push(self.depth_sum);
+        state.push(self.dzdx_sum);
+        state.push(self.dzdy_sum);
+        state.extend(self.n_sum);
+        state.extend(self.ns_sum);
+        state.extend(self.uv_sum);
+        state.extend(self.rgb_albedo_sum);
+        for variance in &self.rgb_variance {
+            state.extend(variance.to_array());
+        }
+    }
+
+    fn read_state(state: &[f64]) -> Self {
+        let array3 = |offset: usize| [state[offset], state[offset + 1], state[offset + 2]];
+
+        return GBufferPixel {
+            rgb_sum: array3(0),
+            weight_sum: state[3],
+            g_buffer_weight_sum: state[4],
+            p_sum: array3(5),
+            depth_sum: state[8],
+            dzdx_sum: state[9],
+            dzdy_sum: state[10],
+            n_sum: array3(11),
+            ns_sum: array3(14),
+            uv_sum: [state[17], state[18]],
+            rgb_albedo_sum: array3(19),
+            rgb_variance: [0, 1, 2].map(|c| VarianceEstimator::from_array(array3(22 + 3 * c))),
+        };
+    }
+}
+
+#[derive(Clone)]
+pub struct GBufferFilm {
+    resolution: Point2i,
+    pixel_bounds: Bounds2i,
+    filename: String,
+    filter: Arc<dyn Filter>,
+    sensor: Arc<PixelSensor>,
+    color_space: &'static RGBColorSpace,
+    output_rgb_from_sensor_rgb: SquareMatrix<3>,
+    output_from_render: Transform,
+    camera_from_render: Transform,
+    pixels: Vec<Vec<GBufferPixel>>,
+}
+
+impl GBufferFilm {
+    pub fn new(
+        resolution: Point2i,
+        pixel_bounds: Bounds2i,
+        filename: &String,
+        sensor: Arc<PixelSensor>,
+        filter: Arc<dyn Filter>,
+        color_space: &'static RGBColorSpace,
+        output_from_render: Transform,
+        camera_from_render: Transform,
+    ) -> Self {
+        // auxiliary channels only fit into a multi-channel EXR
+        let extension = get_extension(filename);
+        let exr_filename = if extension == "exr" {
+            filename.clone()
+        } else {
+            change_extension(filename, "exr")
+        };
+
+        let output_rgb_from_sensor_rgb = color_space.rgb_from_xyz * sensor.xyz_from_sensor_rgb;
+
+        return GBufferFilm {
+            resolution,
+            pixel_bounds,
+            filename: exr_filename,
+            filter,
+            sensor,
+            color_space,
+            output_rgb_from_sensor_rgb,
+            output_from_render,
+            camera_from_render,
+            pixels: vec![
+                vec![GBufferPixel::default(); pixel_bounds.diagonal().x as usize];
+                pixel_bounds.diagonal().y as usize
+            ],
+        };
+    }
+
+    fn get_pixel(&self, p: Point2i) -> &GBufferPixel {
+        return &self.pixels[(p.y - self.pixel_bounds.p_min.y) as usize]
+            [(p.x - self.pixel_bounds.p_min.x) as usize];
+    }
+
+    fn get_channels(&self) -> Vec<(String, Vec<f32>)> {
+        let channel_names = [
+            "R",
+            "G",
+            "B",
+            "Albedo.R",
+            "Albedo.G",
+            "Albedo.B",
+            "P.X",
+            "P.Y",
+            "P.Z",
+            "Z",
+            "dzdx",
+            "dzdy",
+            "N.X",
+            "N.Y",
+            "N.Z",
+            "Ns.X",
+            "Ns.Y",
+            "Ns.Z",
+            "u",
+            "v",
+            "Variance.R",
+            "Variance.G",
+            "Variance.B",
+            "RelativeVariance.R",
+            "RelativeVariance.G",
+            "RelativeVariance.B",
+        ];
+
+        let num_pixels = self.pixel_bounds.area();
+        let mut values = vec![Vec::with_capacity(num_pixels); channel_names.len()];
+
+        for y in self.pixel_bounds.p_min.y..self.pixel_bounds.p_max.y {
+            for x in self.pixel_bounds.p_min.x..self.pixel_bounds.p_max.x {
+                let pixel = self.get_pixel(Point2i::new(x, y));
+
+                // Normalize pixel values with weight sums
+                let rgb = self.get_pixel_rgb(Point2i::new(x, y));
+
+                let g_buffer_weight = if pixel.g_buffer_weight_sum != 0.0 {
+                    1.0 / pixel.g_buffer_weight_sum
+                } else {
+                    0.0
+                };
+
+                let albedo = RGB::new(
+                    pixel.rgb_albedo_sum[0],
+                    pixel.rgb_albedo_sum[1],
+                    pixel.rgb_albedo_sum[2],
+                ) * g_buffer_weight;
+
+                let mut n = Vector3f::new(pixel.n_sum[0], pixel.n_sum[1], pixel.n_sum[2]);
+                if n.length() > 0.0 {
+                    n = n.normalize();
+                }
+                let mut ns = Vector3f::new(pixel.ns_sum[0], pixel.ns_sum[1], pixel.ns_sum[2]);
+                if ns.length() > 0.0 {
+                    ns = ns.normalize();
+                }
+
+                let pixel_values = [
+                    rgb.r,
+                    rgb.g,
+                    rgb.b,
+                    albedo.r,
+                    albedo.g,
+                    albedo.b,
+                    pixel.p_sum[0] * g_buffer_weight,
+                    pixel.p_sum[1] * g_buffer_weight,
+                    pixel.p_sum[2] * g_buffer_weight,
+                    pixel.depth_sum * g_buffer_weight,
+                    pixel.dzdx_sum * g_buffer_weight,
+                    pixel.dzdy_sum * g_buffer_weight,
+                    n.x,
+                    n.y,
+                    n.z,
+                    ns.x,
+                    ns.y,
+                    ns.z,
+                    pixel.uv_sum[0] * g_buffer_weight,
+                    pixel.uv_sum[1] * g_buffer_weight,
+                    pixel.rgb_variance[0].variance(),
+                    pixel.rgb_variance[1].variance(),
+                    pixel.rgb_variance[2].variance(),
+                    pixel.rgb_variance[0].relative_variance(),
+                    pixel.rgb_variance[1].relative_variance(),
+                    pixel.rgb_variance[2].relative_variance(),
+                ];
+
+                for (channel, value) in pixel_values.iter().enumerate() {
+                    values[channel].push(*value as f32);
+                }
+            }
+        }
+
+        return channel_names
+            .iter()
+            .map(|name| name.to_string())
+            .zip(values)
+            .collect();
+    }
+}
+
+impl Film for GBufferFilm {
+    fn fork_tile(&self, tile: Bounds2i) -> Box<dyn Film> {
+        let tile = tile.intersect(&self.pixel_bounds);
+
+        return Box::new(GBufferFilm {
+            resolution: self.resolution,
+            pixel_bounds: tile,
+            filename: self.filename.clone(),
+            filter: self.filter.clone(),
+            sensor: self.sensor.clone(),
+            color_space: self.color_space,
+            output_rgb_from_sensor_rgb: self.output_rgb_from_sensor_rgb,
+            output_from_render: self.output_from_render,
+            camera_from_render: self.camera_from_render,
+            pixels: crop_pixels(&self.pixels, self.pixel_bounds, tile),
+        });
+    }
+
+    fn merge_tile(&mut self, tile_film: &dyn Film) {
+        let g_buffer_film = tile_film.convert_to_gbuffer_film();
+        assert_eq!(self.resolution, g_buffer_film.get_resolution());
+
+        paste_pixels(
+            &mut self.pixels,
+            self.pixel_bounds,
+            &g_buffer_film.pixels,
+            g_buffer_film.pixel_bounds,
+        );
+    }
+
+    fn convert_to_gbuffer_film(&self) -> GBufferFilm {
+        return self.clone();
+    }
+
+    fn uses_visible_surface(&self) -> bool {
+        return true;
+    }
+
+    fn get_pixel_guides(&self, p: Point2i) -> Option<(RGB, Vector3f)> {
+        let pixel = self.get_pixel(p);
+        if pixel.g_buffer_weight_sum == 0.0 {
+            return Some((RGB::black(), Vector3f::new(0.0, 0.0, 0.0)));
+        }
+
+        // misses count as zero albedo so that partially covered pixels are
+        // demodulated consistently with their neighbors
+        let albedo = RGB::new(
+            pixel.rgb_albedo_sum[0],
+            pixel.rgb_albedo_sum[1],
+            pixel.rgb_albedo_sum[2],
+        ) / pixel.weight_sum;
+
+        let mut ns = Vector3f::new(pixel.ns_sum[0], pixel.ns_sum[1], pixel.ns_sum[2]);
+        if ns.length() > 0.0 {
+            ns = ns.normalize();
+        }
+
+        return Some((albedo, ns));
+    }
+
+    fn get_filename(&self) -> String {
+        return self.filename.clone();
+    }
+
+    fn get_resolution(&self) -> Point2i {
+        return self.resolution;
+    }
+
+    fn get_pixel_bounds(&self) -> Bounds2i {
+        return self.pixel_bounds;
+    }
+
+    fn get_filter(&self) -> Arc<dyn Filter> {
+        return self.filter.clone();
+    }
+
+    fn get_pixel_rgb(&self, p: Point2i) -> RGB {
+        let pixel = self.get_pixel(p);
+        let raw_rgb = RGB::new(pixel.rgb_sum[0], pixel.rgb_sum[1], pixel.rgb_sum[2]);
+
+        // Normalize _rgb_ with weight sum
+        let rgb = if pixel.weight_sum != 0.0 {
+            raw_rgb / pixel.weight_sum
+        } else {
+            raw_rgb
+        };
+
+        return self.output_rgb_from_sensor_rgb * rgb;
+    }
+
+    fn get_state(&self) -> Vec<f64> {
+        let mut state = vec![];
+        for pixel in self.pixels.iter().flatten() {
+            pixel.write_state(&mut state);
+        }
+
+        return state;
+    }
+
+    fn set_state(&mut self, state: &[f64]) -> Result<(), String> {
+        let num_pixels = self.pixel_bounds.area();
+        if state.len() != num_pixels * GBufferPixel::STATE_SIZE {
+            return Err(format!(
+                "the film state has {} values, {} expected",
+                state.len(),
+                num_pixels * GBufferPixel::STATE_SIZE
+            ));
+        }
+
+        for (pixel, values) in self
+            .pixels
+            .iter_mut()
+            .flatten()
+            .zip(state.chunks(GBufferPixel::STATE_SIZE))
+        {
+            *pixel = GBufferPixel::read_state(values);
+        }
+
+        return Ok(());
+    }
+
+    fn get_pixel_error(&self, p: Point2i) -> f64 {
+        let pixel = self.get_pixel(p);
+
+        return pixel
+            .rgb_variance
+            .iter()
+            .map(|variance| variance.relative_error())
+            .fold(0.0, f64::max);
+    }
+
+    fn add_sample(
+        &mut self,
+        point_film: Point2i,
+        l: &SampledSpectrum,
+        lambda: &SampledWavelengths,
+        visible_surface: Option<&VisibleSurface>,
+        weight: f64,
+    ) {
+        // Convert sample radiance to _PixelSensor_ RGB
+        let rgb = self.sensor.to_sensor_rgb(l, lambda);
+
+        let pixel = &mut self.pixels[(point_film.y - self.pixel_bounds.p_min.y) as usize]
+            [(point_film.x - self.pixel_bounds.p_min.x) as usize];
+
+        match visible_surface {
+            Some(_visible_surface) if _visible_surface.set => {
+                // Update auxiliary channels with the first visible surface
+                pixel.g_buffer_weight_sum += weight;
+
+                let p = self.output_from_render.on_point3f(_visible_surface.p);
+                let n = Vector3f::from(self.output_from_render.on_normal3f(_visible_surface.n));
+                let ns = Vector3f::from(self.output_from_render.on_normal3f(_visible_surface.ns));
+                let p_camera = self.camera_from_render.on_point3f(_visible_surface.p);
+                let dpdx = self.camera_from_render.on_vector3f(_visible_surface.dpdx);
+                let dpdy = self.camera_from_render.on_vector3f(_visible_surface.dpdy);
+                let albedo = _visible_surface.albedo.to_rgb(lambda, self.color_space);
+
+                for c in 0..3 {
+                    pixel.p_sum[c] += weight * p[c];
+                    pixel.n_sum[c] += weight * n[c];
+                    pixel.ns_sum[c] += weight * ns[c];
+                    pixel.rgb_albedo_sum[c] += weight * albedo[c];
+                }
+                pixel.depth_sum += weight * p_camera.z;
+                if dpdx.x.is_finite() && dpdy.x.is_finite() {
+                    pixel.dzdx_sum += weight * dpdx.z;
+                    pixel.dzdy_sum += weight * dpdy.z;
+                }
+                pixel.uv_sum[0] += weight * _visible_surface.uv.x;
+                pixel.uv_sum[1] += weight * _visible_surface.uv.y;
+            }
+            _ => {}
+        }
+
+        // Update pixel values with filtered sample contribution
+        for c in 0..3 {
+            pixel.rgb_variance[c].add(rgb[c]);
+            pixel.rgb_sum[c] += weight * rgb[c];
+        }
+        pixel.weight_sum += weight;
+    }
+
+    fn export_image(&self, filename: &str) {
+        export_channels_to_exr(
+            filename,
+            self.resolution,
+            self.pixel_bounds,
+            self.get_channels(),
+        );
+    }
+}
@@ -1,2 +1,4 @@
+pub mod gbuffer_film;
 pub mod pixel_sensor;
 pub mod rgb_film;
+pub mod spectral_film;
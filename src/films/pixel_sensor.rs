@@ -2,6 +2,7 @@ use crate::pbrt::*;
 
 const NUM_SWATCH_REFLECTANCES: usize = 24;
 
+#[allow(clippy::approx_constant)]
 const SWATCH_REFLECTRANCES: [ConstPieceWiseLinearSpectrum<38>; NUM_SWATCH_REFLECTANCES] = [
     ConstPieceWiseLinearSpectrum::from_interleaved_missing_short_and_long_wavelengths(
         [
@@ -269,6 +270,50 @@ const SWATCH_REFLECTRANCES: [ConstPieceWiseLinearSpectrum<38>; NUM_SWATCH_REFLEC
     ),
 ];
 
+// camera sensors measured in pbrt-v4, selectable with the `sensor` film parameter
+const MEASURED_SENSORS: [&str; 17] = [
+    "canon_eos_100d",
+    "canon_eos_1dx_mkii",
+    "canon_eos_200d",
+    "canon_eos_200d_mkii",
+    "canon_eos_5d",
+    "canon_eos_5d_mkii",
+    "canon_eos_5d_mkiii",
+    "canon_eos_5d_mkiv",
+    "canon_eos_5ds",
+    "canon_eos_m",
+    "hasselblad_l1d_20c",
+    "nikon_d810",
+    "nikon_d850",
+    "sony_ilce_6400",
+    "sony_ilce_7m3",
+    "sony_ilce_7rm3",
+    "sony_ilce_9",
+];
+
+fn project_reflectance(
+    reflectance: &dyn Spectrum,
+    illuminant: &dyn Spectrum,
+    b1: &dyn Spectrum,
+    b2: &dyn Spectrum,
+    b3: &dyn Spectrum,
+) -> [f64; 3] {
+    let mut result = [0.0; 3];
+    let mut g_integral = 0.0;
+
+    for lambda in (LAMBDA_MIN as usize)..=(LAMBDA_MAX as usize) {
+        let lambda = lambda as f64;
+        let reflected = reflectance.eval(lambda) * illuminant.eval(lambda);
+
+        result[0] += b1.eval(lambda) * reflected;
+        result[1] += b2.eval(lambda) * reflected;
+        result[2] += b3.eval(lambda) * reflected;
+        g_integral += b2.eval(lambda) * illuminant.eval(lambda);
+    }
+
+    return result.map(|v| v / g_integral);
+}
+
 pub struct PixelSensor {
     r_bar: Arc<dyn Spectrum>,
     g_bar: Arc<dyn Spectrum>,
@@ -278,20 +323,24 @@ pub struct PixelSensor {
 }
 
 impl PixelSensor {
-    pub fn create(parameters: &ParameterDict, exposure_time: f64) -> Self {
+    pub fn create(
+        parameters: &ParameterDict,
+        exposure_time: f64,
+        scene_directory: Option<&str>,
+    ) -> Result<Self, SceneError> {
         // Imaging ratio parameters
         // The defaults here represent a "passthrough" setup such that the imaging
         // ratio will be exactly 1. This is a useful default since scenes that
         // weren't authored with a physical camera in mind will render as expected.
 
-        let iso = parameters.get_one_float("iso", Some(100.0));
-        let sensor_name = parameters.get_string("senseo", Some("cie1931".to_string()));
+        let iso = parameters.get_one_float("iso", Some(100.0))?;
+        let sensor_name = parameters.get_string("sensor", Some("cie1931".to_string()))?;
 
         // Pass through 0 for cie1931 if it's unspecified so that it doesn't do
         // any white balancing. For actual sensors, 6500 is the default...
 
         let white_balance_val = {
-            let wb = parameters.get_one_float("whitebalance", Some(0.0));
+            let wb = parameters.get_one_float("whitebalance", Some(0.0))?;
             if sensor_name != "cie1931" && wb == 0.0 {
                 6500.0
             } else {
@@ -316,11 +365,106 @@ impl PixelSensor {
             None
         };
 
-        return match sensor_name.as_str() {
-            "cie1931" => PixelSensor::cie_1931(&COLOR_SPACE, sensor_illum, imaging_ratio),
-            _ => {
-                panic!("sensor `{}` is not implemented", sensor_name);
+        if sensor_name == "cie1931" {
+            return Ok(PixelSensor::cie_1931(
+                parameters.get_color_space(),
+                sensor_illum,
+                imaging_ratio,
+            ));
+        }
+
+        if !MEASURED_SENSORS.contains(&sensor_name.as_str()) {
+            return Err(parameters.parameter_error(
+                "sensor",
+                format!("sensor `{}` is not implemented", sensor_name),
+            ));
+        }
+
+        // Measured response curves are read from `<sensordata>/<sensor>_{r,g,b}.spd`,
+        // a relative `sensordata` is found next to the scene file
+        let sensor_data = parameters.get_string("sensordata", Some("sensors".to_string()))?;
+        let sensor_data = match scene_directory {
+            Some(_directory) if Path::new(&sensor_data).is_relative() => {
+                format!("{}/{}", _directory, sensor_data)
             }
+            _ => sensor_data,
+        };
+        let read_curve = |channel: &str| -> Result<Arc<dyn Spectrum>, SceneError> {
+            let filename = format!("{}/{}_{}.spd", sensor_data, sensor_name, channel);
+            return match PiecewiseLinearSpectrum::read(&filename) {
+                Err(message) => Err(SceneError::new(
+                    SceneErrorKind::Io { filename, message },
+                    None,
+                )),
+                Ok(_curve) => Ok(Arc::new(DenselySampledSpectrum::from_spectrum(&_curve))),
+            };
+        };
+        let r_bar = read_curve("r")?;
+        let g_bar = read_curve("g")?;
+        let b_bar = read_curve("b")?;
+
+        let sensor_illum = match sensor_illum {
+            None => {
+                panic!(
+                    "measured sensor `{}` requires a white balance illuminant",
+                    sensor_name
+                );
+            }
+            Some(_sensor_illum) => _sensor_illum,
+        };
+
+        return Ok(PixelSensor::new(
+            r_bar,
+            g_bar,
+            b_bar,
+            parameters.get_color_space(),
+            sensor_illum.as_ref(),
+            imaging_ratio,
+        ));
+    }
+
+    pub fn new(
+        r_bar: Arc<dyn Spectrum>,
+        g_bar: Arc<dyn Spectrum>,
+        b_bar: Arc<dyn Spectrum>,
+        output_color_space: &RGBColorSpace,
+        sensor_illum: &dyn Spectrum,
+        imaging_ratio: f64,
+    ) -> Self {
+        // Compute _rgb_camera_ values for training swatches
+        let rgb_camera = SWATCH_REFLECTRANCES.map(|swatch| {
+            project_reflectance(
+                &swatch,
+                sensor_illum,
+                r_bar.as_ref(),
+                g_bar.as_ref(),
+                b_bar.as_ref(),
+            )
+        });
+
+        // Compute _xyz_output_ values for training swatches
+        let sensor_white_g = sensor_illum.inner_product(g_bar.as_ref());
+        let sensor_white_y = sensor_illum.inner_product(&CIE_Y_DENSELY_SAMPLED);
+        let xyz_output = SWATCH_REFLECTRANCES.map(|swatch| {
+            project_reflectance(
+                &swatch,
+                output_color_space.illuminant,
+                &CIE_X_DENSELY_SAMPLED,
+                &CIE_Y_DENSELY_SAMPLED,
+                &CIE_Z_DENSELY_SAMPLED,
+            )
+            .map(|v| v * sensor_white_y / sensor_white_g)
+        });
+
+        // Initialize _xyz_from_sensor_rgb_ using linear least squares
+        let xyz_from_sensor_rgb = linear_least_squares(&rgb_camera, &xyz_output);
+
+        return Self {
+            r_bar,
+            g_bar,
+            b_bar,
+            imaging_ratio,
+            xyz_from_sensor_rgb,
         };
     }
 
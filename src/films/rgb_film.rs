@@ -4,6 +4,10 @@ use crate::pbrt::*;
 struct Pixel {
     pub rgb_sum: [f64; 3],
     pub weight_sum: f64,
+    pub rgb_variance: [VarianceEstimator; 3],
+    pub guide_weight_sum: f64,
+    pub albedo_sum: [f64; 3],
+    pub n_sum: [f64; 3],
 }
 
 impl Default for Pixel {
@@ -11,6 +15,37 @@ impl Default for Pixel {
         return Pixel {
             rgb_sum: [0.0; 3],
             weight_sum: 0.0,
+            rgb_variance: [VarianceEstimator::default(); 3],
+            guide_weight_sum: 0.0,
+            albedo_sum: [0.0; 3],
+            n_sum: [0.0; 3],
+        };
+    }
+}
+
+impl Pixel {
+    const STATE_SIZE: usize = 20;
+
+    fn write_state(&self, state: &mut Vec<f64>) {
+        state.extend(self.rgb_sum);
+        state.push(self.weight_sum);
+        for variance in &self.rgb_variance {
+            state.extend(variance.to_array());
+        }
+        state.push(self.guide_weight_sum);
+        state.extend(self.albedo_sum);
+        state.extend(self.n_sum);
+    }
+
+    fn read_state(state: &[f64]) -> Self {
+        return Pixel {
+            rgb_sum: [state[0], state[1], state[2]],
+            weight_sum: state[3],
+            rgb_variance: [0, 1, 2]
+                .map(|c| VarianceEstimator::from_array([4, 5, 6].map(|i| state[i + 3 * c]))),
+            guide_weight_sum: state[13],
+            albedo_sum: [state[14], state[15], state[16]],
+            n_sum: [state[17], state[18], state[19]],
         };
     }
 }
@@ -18,22 +53,27 @@ impl Default for Pixel {
 #[derive(Clone)]
 pub struct RGBFilm {
     resolution: Point2i,
+    pixel_bounds: Bounds2i,
     filename: String,
     filter: Arc<dyn Filter>,
     sensor: Arc<PixelSensor>,
+    color_space: &'static RGBColorSpace,
     output_rgb_from_sensor_rgb: SquareMatrix<3>,
+    store_guides: bool,
     pixels: Vec<Vec<Pixel>>,
 }
 
 impl RGBFilm {
     pub fn new(
         resolution: Point2i,
+        pixel_bounds: Bounds2i,
         filename: &String,
         sensor: Arc<PixelSensor>,
         filter: Arc<dyn Filter>,
+        color_space: &'static RGBColorSpace,
     ) -> Self {
-        let width = resolution.x;
-        let height = resolution.y;
+        let width = pixel_bounds.diagonal().x;
+        let height = pixel_bounds.diagonal().y;
 
         let extension = get_extension(filename);
         let png_filename = if extension == "png" {
@@ -42,38 +82,94 @@ impl RGBFilm {
             change_extension(filename, "png")
         };
 
-        let output_rgb_from_sensor_rgb = COLOR_SPACE.rgb_from_xyz * sensor.xyz_from_sensor_rgb;
+        let output_rgb_from_sensor_rgb = color_space.rgb_from_xyz * sensor.xyz_from_sensor_rgb;
 
         return RGBFilm {
             resolution: resolution.clone(),
+            pixel_bounds,
             filename: png_filename,
             sensor,
             filter: filter.clone(),
+            color_space,
             output_rgb_from_sensor_rgb,
+            store_guides: false,
             pixels: vec![vec![Pixel::default(); width as usize]; height as usize],
         };
     }
+
+    fn get_pixel(&self, p: Point2i) -> &Pixel {
+        return &self.pixels[(p.y - self.pixel_bounds.p_min.y) as usize]
+            [(p.x - self.pixel_bounds.p_min.x) as usize];
+    }
 }
 
 impl Film for RGBFilm {
-    fn fork(&self) -> Box<dyn Film> {
+    fn fork_tile(&self, tile: Bounds2i) -> Box<dyn Film> {
+        let tile = tile.intersect(&self.pixel_bounds);
+
         return Box::new(RGBFilm {
             resolution: self.resolution,
+            pixel_bounds: tile,
             filename: self.filename.clone(),
             filter: self.filter.clone(),
             sensor: self.sensor.clone(),
+            color_space: self.color_space,
             output_rgb_from_sensor_rgb: self.output_rgb_from_sensor_rgb,
-            pixels: vec![
-                vec![Pixel::default(); self.resolution.x as usize];
-                self.resolution.y as usize
-            ],
+            store_guides: self.store_guides,
+            pixels: crop_pixels(&self.pixels, self.pixel_bounds, tile),
         });
     }
 
+    fn merge_tile(&mut self, tile_film: &dyn Film) {
+        let rgb_film = tile_film.convert_to_rgb_film();
+        assert_eq!(self.resolution, rgb_film.get_resolution());
+
+        paste_pixels(
+            &mut self.pixels,
+            self.pixel_bounds,
+            &rgb_film.pixels,
+            rgb_film.pixel_bounds,
+        );
+    }
+
     fn convert_to_rgb_film(&self) -> RGBFilm {
         return self.clone();
     }
 
+    fn uses_visible_surface(&self) -> bool {
+        return self.store_guides;
+    }
+
+    fn enable_denoising_guides(&mut self) {
+        self.store_guides = true;
+    }
+
+    fn get_pixel_guides(&self, p: Point2i) -> Option<(RGB, Vector3f)> {
+        if !self.store_guides {
+            return None;
+        }
+
+        let pixel = self.get_pixel(p);
+        if pixel.guide_weight_sum == 0.0 {
+            return Some((RGB::black(), Vector3f::new(0.0, 0.0, 0.0)));
+        }
+
+        // misses count as zero albedo so that partially covered pixels are
+        // demodulated consistently with their neighbors
+        let albedo = RGB::new(
+            pixel.albedo_sum[0],
+            pixel.albedo_sum[1],
+            pixel.albedo_sum[2],
+        ) / pixel.weight_sum;
+
+        let mut n = Vector3f::new(pixel.n_sum[0], pixel.n_sum[1], pixel.n_sum[2]);
+        if n.length() > 0.0 {
+            n = n.normalize();
+        }
+
+        return Some((albedo, n));
+    }
+
     fn get_filename(&self) -> String {
         return self.filename.clone();
     }
@@ -82,12 +178,16 @@ impl Film for RGBFilm {
         return self.resolution;
     }
 
+    fn get_pixel_bounds(&self) -> Bounds2i {
+        return self.pixel_bounds;
+    }
+
     fn get_filter(&self) -> Arc<dyn Filter> {
         return self.filter.clone();
     }
 
     fn get_pixel_rgb(&self, p: Point2i) -> RGB {
-        let pixel = self.pixels[p.y as usize][p.x as usize];
+        let pixel = self.get_pixel(p);
         let raw_rgb = RGB::new(
             pixel.rgb_sum[0] as f64,
             pixel.rgb_sum[1] as f64,
@@ -107,13 +207,45 @@ impl Film for RGBFilm {
         return self.output_rgb_from_sensor_rgb * rgb;
     }
 
-    fn merge(&mut self, film: &dyn Film, y_list: Vec<usize>) {
-        let rgb_film = film.convert_to_rgb_film();
-        assert_eq!(self.resolution, rgb_film.get_resolution());
+    fn get_state(&self) -> Vec<f64> {
+        let mut state = vec![];
+        for pixel in self.pixels.iter().flatten() {
+            pixel.write_state(&mut state);
+        }
+
+        return state;
+    }
 
-        for y in y_list {
-            self.pixels[y] = rgb_film.pixels[y].clone();
+    fn set_state(&mut self, state: &[f64]) -> Result<(), String> {
+        let num_pixels = self.pixel_bounds.area();
+        if state.len() != num_pixels * Pixel::STATE_SIZE {
+            return Err(format!(
+                "the film state has {} values, {} expected",
+                state.len(),
+                num_pixels * Pixel::STATE_SIZE
+            ));
         }
+
+        for (pixel, values) in self
+            .pixels
+            .iter_mut()
+            .flatten()
+            .zip(state.chunks(Pixel::STATE_SIZE))
+        {
+            *pixel = Pixel::read_state(values);
+        }
+
+        return Ok(());
+    }
+
+    fn get_pixel_error(&self, p: Point2i) -> f64 {
+        let pixel = self.get_pixel(p);
+
+        return pixel
+            .rgb_variance
+            .iter()
+            .map(|variance| variance.relative_error())
+            .fold(0.0, f64::max);
     }
 
     fn add_sample(
@@ -121,6 +253,7 @@ impl Film for RGBFilm {
         point_film: Point2i,
         l: &SampledSpectrum,
         lambda: &SampledWavelengths,
+        visible_surface: Option<&VisibleSurface>,
         weight: f64,
     ) {
         // Convert sample radiance to _PixelSensor_ RGB
@@ -135,10 +268,28 @@ impl Film for RGBFilm {
         */
 
         // Update pixel values with filtered sample contribution
-        let pixel = &mut self.pixels[point_film.y as usize][point_film.x as usize];
+        let pixel = &mut self.pixels[(point_film.y - self.pixel_bounds.p_min.y) as usize]
+            [(point_film.x - self.pixel_bounds.p_min.x) as usize];
         for c in 0..3 {
+            pixel.rgb_variance[c].add(rgb[c]);
             pixel.rgb_sum[c] += (weight * rgb[c]) as f64;
         }
         pixel.weight_sum += weight as f64;
+
+        if self.store_guides {
+            match visible_surface {
+                Some(_visible_surface) if _visible_surface.set => {
+                    // Update denoising guides with the first visible surface
+                    let albedo = _visible_surface.albedo.to_rgb(lambda, self.color_space);
+                    let n = Vector3f::from(_visible_surface.ns);
+                    pixel.guide_weight_sum += weight;
+                    for c in 0..3 {
+                        pixel.albedo_sum[c] += weight * albedo[c];
+                        pixel.n_sum[c] += weight * n[c];
+                    }
+                }
+                _ => {}
+            }
+        }
     }
 }
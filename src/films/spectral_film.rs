@@ -0,0 +1,325 @@
+use crate::pbrt::*;
+
+#[derive(Clone)]
+struct SpectralPixel {
+    pub rgb_sum: [f64; 3],
+    pub weight_sum: f64,
+    pub bucket_sums: Vec<f64>,
+    pub rgb_variance: [VarianceEstimator; 3],
+}
+
+impl SpectralPixel {
+    fn new(n_buckets: usize) -> Self {
+        return SpectralPixel {
+            rgb_sum: [0.0; 3],
+            weight_sum: 0.0,
+            bucket_sums: vec![0.0; n_buckets],
+            rgb_variance: [VarianceEstimator::default(); 3],
+        };
+    }
+
+    fn state_size(n_buckets: usize) -> usize {
+        return 13 + n_buckets;
+    }
+
+    fn write_state(&self, state: &mut Vec<f64>) {
+        state.extend(self.rgb_sum);
+        state.push(self.weight_sum);
+        for variance in &self.rgb_variance {
+            state.extend(variance.to_array());
+        }
+        state.extend(&self.bucket_sums);
+    }
+
+    fn read_state(state: &[f64]) -> Self {
+        return SpectralPixel {
+            rgb_sum: [state[0], state[1], state[2]],
+            weight_sum: state[3],
+            rgb_variance: [0, 1, 2]
+                .map(|c| VarianceEstimator::from_array([4, 5, 6].map(|i| state[i + 3 * c]))),
+            bucket_sums: state[13..].to_vec(),
+        };
+    }
+}
+
+#[derive(Clone)]
+pub struct SpectralFilm {
+    resolution: Point2i,
+    pixel_bounds: Bounds2i,
+    filename: String,
+    filter: Arc<dyn Filter>,
+    sensor: Arc<PixelSensor>,
+    output_rgb_from_sensor_rgb: SquareMatrix<3>,
+    lambda_min: f64,
+    lambda_max: f64,
+    n_buckets: usize,
+    pixels: Vec<Vec<SpectralPixel>>,
+}
+
+impl SpectralFilm {
+    pub fn new(
+        resolution: Point2i,
+        pixel_bounds: Bounds2i,
+        filename: &String,
+        sensor: Arc<PixelSensor>,
+        filter: Arc<dyn Filter>,
+        color_space: &RGBColorSpace,
+        lambda_min: f64,
+        lambda_max: f64,
+        n_buckets: usize,
+    ) -> Self {
+        if lambda_min < LAMBDA_MIN || lambda_max > LAMBDA_MAX {
+            panic!(
+                "spectral film: wavelength range [{}, {}] exceeds [{}, {}]",
+                lambda_min, lambda_max, LAMBDA_MIN, LAMBDA_MAX
+            );
+        }
+        if lambda_min >= lambda_max {
+            panic!(
+                "spectral film: `lambdamin` ({}) should be smaller than `lambdamax` ({})",
+                lambda_min, lambda_max
+            );
+        }
+        if n_buckets == 0 {
+            panic!("spectral film: `nbuckets` should be positive");
+        }
+
+        // spectral channels only fit into a multi-channel EXR
+        let extension = get_extension(filename);
+        let exr_filename = if extension == "exr" {
+            filename.clone()
+        } else {
+            change_extension(filename, "exr")
+        };
+
+        let output_rgb_from_sensor_rgb = color_space.rgb_from_xyz * sensor.xyz_from_sensor_rgb;
+
+        return SpectralFilm {
+            resolution,
+            pixel_bounds,
+            filename: exr_filename,
+            filter,
+            sensor,
+            output_rgb_from_sensor_rgb,
+            lambda_min,
+            lambda_max,
+            n_buckets,
+            pixels: vec![
+                vec![SpectralPixel::new(n_buckets); pixel_bounds.diagonal().x as usize];
+                pixel_bounds.diagonal().y as usize
+            ],
+        };
+    }
+
+    fn get_pixel(&self, p: Point2i) -> &SpectralPixel {
+        return &self.pixels[(p.y - self.pixel_bounds.p_min.y) as usize]
+            [(p.x - self.pixel_bounds.p_min.x) as usize];
+    }
+
+    fn bucket_width(&self) -> f64 {
+        return (self.lambda_max - self.lambda_min) / self.n_buckets as f64;
+    }
+
+    fn lambda_to_bucket(&self, lambda: f64) -> Option<usize> {
+        if lambda < self.lambda_min || lambda >= self.lambda_max {
+            return None;
+        }
+
+        let bucket = ((lambda - self.lambda_min) / self.bucket_width()) as usize;
+        return Some(bucket.min(self.n_buckets - 1));
+    }
+
+    fn get_channels(&self) -> Vec<(String, Vec<f32>)> {
+        // RGB channels followed by one channel per bucket, named after the bucket center
+        // following the spectral OpenEXR layout: "S0.<wavelength>nm" with a decimal comma
+        let mut channel_names = vec!["R".to_string(), "G".to_string(), "B".to_string()];
+        for b in 0..self.n_buckets {
+            let lambda = self.lambda_min + (b as f64 + 0.5) * self.bucket_width();
+            channel_names.push(format!(
+                "S0.{}nm",
+                format!("{:.6}", lambda).replace('.', ",")
+            ));
+        }
+
+        let num_pixels = self.pixel_bounds.area();
+        let mut values = vec![Vec::with_capacity(num_pixels); channel_names.len()];
+
+        for y in self.pixel_bounds.p_min.y..self.pixel_bounds.p_max.y {
+            for x in self.pixel_bounds.p_min.x..self.pixel_bounds.p_max.x {
+                let rgb = self.get_pixel_rgb(Point2i::new(x, y));
+                values[0].push(rgb.r as f32);
+                values[1].push(rgb.g as f32);
+                values[2].push(rgb.b as f32);
+
+                let pixel = self.get_pixel(Point2i::new(x, y));
+                for b in 0..self.n_buckets {
+                    let value = if pixel.weight_sum != 0.0 {
+                        pixel.bucket_sums[b] / pixel.weight_sum
+                    } else {
+                        0.0
+                    };
+                    values[3 + b].push(value as f32);
+                }
+            }
+        }
+
+        return channel_names.into_iter().zip(values).collect();
+    }
+}
+
+impl Film for SpectralFilm {
+    fn fork_tile(&self, tile: Bounds2i) -> Box<dyn Film> {
+        let tile = tile.intersect(&self.pixel_bounds);
+
+        return Box::new(SpectralFilm {
+            resolution: self.resolution,
+            pixel_bounds: tile,
+            filename: self.filename.clone(),
+            filter: self.filter.clone(),
+            sensor: self.sensor.clone(),
+            output_rgb_from_sensor_rgb: self.output_rgb_from_sensor_rgb,
+            lambda_min: self.lambda_min,
+            lambda_max: self.lambda_max,
+            n_buckets: self.n_buckets,
+            pixels: crop_pixels(&self.pixels, self.pixel_bounds, tile),
+        });
+    }
+
+    fn merge_tile(&mut self, tile_film: &dyn Film) {
+        let spectral_film = tile_film.convert_to_spectral_film();
+        assert_eq!(self.resolution, spectral_film.get_resolution());
+
+        paste_pixels(
+            &mut self.pixels,
+            self.pixel_bounds,
+            &spectral_film.pixels,
+            spectral_film.pixel_bounds,
+        );
+    }
+
+    fn convert_to_spectral_film(&self) -> SpectralFilm {
+        return self.clone();
+    }
+
+    fn get_filename(&self) -> String {
+        return self.filename.clone();
+    }
+
+    fn get_resolution(&self) -> Point2i {
+        return self.resolution;
+    }
+
+    fn get_pixel_bounds(&self) -> Bounds2i {
+        return self.pixel_bounds;
+    }
+
+    fn get_filter(&self) -> Arc<dyn Filter> {
+        return self.filter.clone();
+    }
+
+    fn get_pixel_rgb(&self, p: Point2i) -> RGB {
+        let pixel = self.get_pixel(p);
+        let raw_rgb = RGB::new(pixel.rgb_sum[0], pixel.rgb_sum[1], pixel.rgb_sum[2]);
+
+        // Normalize _rgb_ with weight sum
+        let rgb = if pixel.weight_sum != 0.0 {
+            raw_rgb / pixel.weight_sum
+        } else {
+            raw_rgb
+        };
+
+        return self.output_rgb_from_sensor_rgb * rgb;
+    }
+
+    fn get_state(&self) -> Vec<f64> {
+        let mut state = vec![];
+        for pixel in self.pixels.iter().flatten() {
+            pixel.write_state(&mut state);
+        }
+
+        return state;
+    }
+
+    fn set_state(&mut self, state: &[f64]) -> Result<(), String> {
+        let num_pixels = self.pixel_bounds.area();
+        let state_size = SpectralPixel::state_size(self.n_buckets);
+        if state.len() != num_pixels * state_size {
+            return Err(format!(
+                "the film state has {} values, {} expected",
+                state.len(),
+                num_pixels * state_size
+            ));
+        }
+
+        for (pixel, values) in self
+            .pixels
+            .iter_mut()
+            .flatten()
+            .zip(state.chunks(state_size))
+        {
+            *pixel = SpectralPixel::read_state(values);
+        }
+
+        return Ok(());
+    }
+
+    fn get_pixel_error(&self, p: Point2i) -> f64 {
+        let pixel = self.get_pixel(p);
+
+        return pixel
+            .rgb_variance
+            .iter()
+            .map(|variance| variance.relative_error())
+            .fold(0.0, f64::max);
+    }
+
+    fn add_sample(
+        &mut self,
+        point_film: Point2i,
+        l: &SampledSpectrum,
+        lambda: &SampledWavelengths,
+        _visible_surface: Option<&VisibleSurface>,
+        weight: f64,
+    ) {
+        // Convert sample radiance to _PixelSensor_ RGB
+        let rgb = self.sensor.to_sensor_rgb(l, lambda);
+
+        // Estimate the average spectral radiance of each bucket hit by _lambda_
+        let pdf = lambda.pdf_as_sampled_spectrum();
+        let bucket_width = self.bucket_width();
+        let mut bucket_contributions = vec![];
+        for i in 0..NUM_SPECTRUM_SAMPLES {
+            if pdf[i] == 0.0 {
+                continue;
+            }
+            match self.lambda_to_bucket(lambda[i]) {
+                None => {}
+                Some(b) => {
+                    let contribution = l[i] / (pdf[i] * NUM_SPECTRUM_SAMPLES as f64 * bucket_width);
+                    bucket_contributions.push((b, contribution));
+                }
+            }
+        }
+
+        // Update pixel values with filtered sample contribution
+        let pixel = &mut self.pixels[(point_film.y - self.pixel_bounds.p_min.y) as usize]
+            [(point_film.x - self.pixel_bounds.p_min.x) as usize];
+        for c in 0..3 {
+            pixel.rgb_variance[c].add(rgb[c]);
+            pixel.rgb_sum[c] += weight * rgb[c];
+        }
+        for (b, contribution) in bucket_contributions {
+            pixel.bucket_sums[b] += weight * contribution;
+        }
+        pixel.weight_sum += weight;
+    }
+
+    fn export_image(&self, filename: &str) {
+        export_channels_to_exr(
+            filename,
+            self.resolution,
+            self.pixel_bounds,
+            self.get_channels(),
+        );
+    }
+}
@@ -32,6 +32,7 @@ impl Integrator for AmbientOcclusion {
         ray: &DifferentialRay,
         lambda: &mut SampledWavelengths,
         sampler: &mut dyn Sampler,
+        visible_surface: Option<&mut VisibleSurface>,
     ) -> SampledSpectrum {
         // TODO: this is incomplete, consider BSDF only for now
         let si = match self.base.aggregate.intersect(&ray.ray, f64::INFINITY) {
@@ -41,7 +42,15 @@ impl Integrator for AmbientOcclusion {
             Some(_si) => _si,
         };
 
-        let isect = si.surface_interaction;
+        let mut isect = si.surface_interaction;
+
+        match visible_surface {
+            None => {}
+            Some(_visible_surface) => {
+                let bsdf = isect.get_bsdf(ray, lambda, self.base.camera.as_ref(), sampler);
+                *_visible_surface = VisibleSurface::from_bsdf(&isect, &bsdf);
+            }
+        }
 
         let n = isect.interaction.n.face_forward(-ray.ray.d);
         let u = sampler.get_2d();
@@ -58,6 +67,7 @@ impl Integrator for AmbientOcclusion {
 
         // Divide by pi so that fully visible is one.
         let differential_ray = isect.spawn_ray(wi);
+        add_stat(StatCounter::ShadowRays, 1);
         if !self
             .base
             .aggregate
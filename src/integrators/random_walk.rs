@@ -1,6 +1,7 @@
 use crate::pbrt::*;
 
 pub struct RandomWalkIntegrator {
+    max_depth: usize,
     illuminant_spectrum: &'static dyn Spectrum,
     illuminant_scale: f64,
     base: IntegratorBase,
@@ -12,6 +13,7 @@ impl RandomWalkIntegrator {
         aggregate: Arc<dyn Primitive>,
         camera: Arc<dyn Camera>,
         lights: Vec<Arc<dyn Light>>,
+        max_depth: usize,
     ) -> Self {
         let illuminant_scale = 1.0 / illuminant_spectrum.to_photometric();
 
@@ -23,6 +25,7 @@ impl RandomWalkIntegrator {
         }
 
         return Self {
+            max_depth,
             base: IntegratorBase::new(aggregate, camera, lights),
             illuminant_spectrum,
             illuminant_scale,
@@ -34,12 +37,14 @@ impl RandomWalkIntegrator {
         ray: &DifferentialRay,
         lambda: &mut SampledWavelengths,
         sampler: &mut dyn Sampler,
+        visible_surface: Option<&mut VisibleSurface>,
         depth: usize,
     ) -> SampledSpectrum {
         let mut isect = match self.base.aggregate.intersect(&ray.ray, f64::INFINITY) {
             None => {
                 // Return emitted light from infinite light sources
                 // to be change later to infinite lights
+                record_path_length(depth);
                 return SampledSpectrum::same_value(0.0);
             }
             Some(shape_intersection) => shape_intersection.surface_interaction,
@@ -50,16 +55,25 @@ impl RandomWalkIntegrator {
         let le = isect.le(wo, lambda);
 
         // Terminate random walk if maximum depth has been reached
-        if depth >= 5 {
+        if depth >= self.max_depth {
+            record_path_length(depth + 1);
             return le;
         }
 
         // Compute BSDF at random walk intersection point
         let bsdf = isect.get_bsdf(ray, lambda, self.base.camera.as_ref(), sampler);
         if bsdf.bxdf.is_none() {
+            record_path_length(depth + 1);
             return le;
         }
 
+        match visible_surface {
+            None => {}
+            Some(_visible_surface) => {
+                *_visible_surface = VisibleSurface::from_bsdf(&isect, &bsdf);
+            }
+        }
+
         // Randomly sample direction leaving surface for random walk
         let u = sampler.get_2d();
         let wp = Vector3f::sample_uniform_sphere(u);
@@ -68,12 +82,15 @@ impl RandomWalkIntegrator {
         let fcos = bsdf.f(wo, wp, TransportMode::Radiance) * isect.shading.n.abs_dot(wp);
 
         if !fcos.is_positive() {
+            record_path_length(depth + 1);
             return le;
         }
 
         // Recursively trace ray to estimate incident radiance at surface
         let ray = isect.spawn_ray(wp);
-        return le + fcos * self.random_walk_li(&ray, lambda, sampler, depth + 1) * (4.0 * PI);
+        add_stat(StatCounter::IndirectRays, 1);
+        return le
+            + fcos * self.random_walk_li(&ray, lambda, sampler, None, depth + 1) * (4.0 * PI);
     }
 }
 
@@ -87,7 +104,8 @@ impl Integrator for RandomWalkIntegrator {
         ray: &DifferentialRay,
         lambda: &mut SampledWavelengths,
         sampler: &mut dyn Sampler,
+        visible_surface: Option<&mut VisibleSurface>,
     ) -> SampledSpectrum {
-        return self.random_walk_li(ray, lambda, sampler, 0);
+        return self.random_walk_li(ray, lambda, sampler, visible_surface, 0);
     }
 }
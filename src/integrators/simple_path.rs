@@ -16,6 +16,7 @@ impl Integrator for SimplePath {
         ray: &DifferentialRay,
         lambda: &mut SampledWavelengths,
         sampler: &mut dyn Sampler,
+        mut visible_surface: Option<&mut VisibleSurface>,
     ) -> SampledSpectrum {
         // Estimate radiance along ray using simple path tracing
         let mut L = SampledSpectrum::same_value(0.0);
@@ -60,37 +61,16 @@ impl Integrator for SimplePath {
                 panic!("SimplePathIntegrator: `bsdf.bxdf.is_none()` is not implemented");
             }
 
-            // Sample direct illumination if _sampleLights_ is true
-            match self.light_sampler.sample(sampler.get_1d()) {
+            // Initialize _visible_surface_ at first intersection
+            match visible_surface.take() {
                 None => {}
-                Some(sampled_light) => {
-                    // Sample point on _sampledLight_ to estimate direct illumination
-                    let u_light = sampler.get_2d();
-                    match sampled_light.light.sample_li(
-                        &LightSampleContext::from_surface_interaction(isect),
-                        u_light,
-                        lambda,
-                        false,
-                    ) {
-                        None => {}
-
-                        Some(ls) => {
-                            if ls.l.is_positive() && ls.pdf > 0.0 {
-                                // Evaluate BSDF for light and possibly add scattered radiance
-                                let wi = ls.wi;
-                                let f = bsdf.f(wo, wi, TransportMode::Radiance)
-                                    * wi.abs_dot(Vector3f::from(isect.shading.n));
-
-                                if f.is_positive()
-                                    && self.unoccluded(&isect.interaction, &ls.p_light)
-                                {
-                                    L += beta * f * ls.l / (sampled_light.p * ls.pdf);
-                                }
-                            }
-                        }
-                    };
+                Some(_visible_surface) => {
+                    *_visible_surface = VisibleSurface::from_bsdf(isect, &bsdf);
                 }
-            };
+            }
+
+            // Sample direct illumination if _sampleLights_ is true
+            L += beta * self.sample_direct_lighting(isect, &bsdf, wo, lambda, sampler);
 
             // Sample outgoing direction at intersection to continue path
             // Sample BSDF for new path direction
@@ -112,17 +92,178 @@ impl Integrator for SimplePath {
 
             specular_bounce = bs.is_specular();
             ray = isect.spawn_ray(bs.wi);
+            add_stat(StatCounter::IndirectRays, 1);
+
+            // Account for subsurface scattering, if applicable
+            if !bs.is_transmission() {
+                continue;
+            }
+            let bssrdf = match isect.get_bssrdf(lambda) {
+                None => {
+                    continue;
+                }
+                Some(_bssrdf) => _bssrdf,
+            };
+
+            // Sample BSSRDF probe segment to find exit point
+            let uc = sampler.get_1d();
+            let up = sampler.get_2d();
+            let probe_segment = match bssrdf.sample(uc, up) {
+                None => {
+                    break;
+                }
+                Some(_segment) => _segment,
+            };
+
+            // Sample random intersection along BSSRDF probe segment
+            let candidates = self.probe_intersections(isect, &probe_segment);
+            if candidates.is_empty() {
+                break;
+            }
+            let index =
+                ((sampler.get_1d() * candidates.len() as f64) as usize).min(candidates.len() - 1);
+            let probe_prob = 1.0 / candidates.len() as f64;
+            let mut probe_si = candidates[index].clone();
+
+            // Convert probe intersection to _BSSRDFSample_
+            let bssrdf_sample = bssrdf.probe_intersection_to_sample(&probe_si);
+            if !bssrdf_sample.sp.is_positive() || !bssrdf_sample.pdf.is_positive() {
+                break;
+            }
+
+            // Update path state for subsurface scattering
+            beta *= bssrdf_sample.sp / (probe_prob * bssrdf_sample.pdf[0]);
+            probe_si.interaction.wo = bssrdf_sample.wo;
+            let sw = &bssrdf_sample.sw;
+
+            // Possibly regularize subsurface BSDF and sample direct lighting at exit point
+            L += beta
+                * self.sample_direct_lighting(&probe_si, sw, bssrdf_sample.wo, lambda, sampler);
+
+            // Sample ray for indirect subsurface scattering
+            let u = sampler.get_1d();
+            let bs = match sw.sample_f(
+                bssrdf_sample.wo,
+                u,
+                sampler.get_2d(),
+                TransportMode::Radiance,
+                BxDFReflTransFlags::All,
+            ) {
+                None => {
+                    break;
+                }
+                Some(_bs) => _bs,
+            };
+
+            beta *= bs.f * bs.wi.abs_dot(Vector3f::from(probe_si.shading.n)) / bs.pdf;
+            specular_bounce = bs.is_specular();
+            ray = probe_si.spawn_ray(bs.wi);
+            add_stat(StatCounter::IndirectRays, 1);
         }
 
+        record_path_length(depth);
+
         return L;
     }
 }
 
 impl SimplePath {
+    fn sample_direct_lighting(
+        &self,
+        isect: &SurfaceInteraction,
+        bsdf: &BSDF,
+        wo: Vector3f,
+        lambda: &SampledWavelengths,
+        sampler: &mut dyn Sampler,
+    ) -> SampledSpectrum {
+        let sampled_light = match self.light_sampler.sample(sampler.get_1d()) {
+            None => {
+                return SampledSpectrum::same_value(0.0);
+            }
+            Some(_sampled_light) => _sampled_light,
+        };
+
+        // Sample point on _sampledLight_ to estimate direct illumination
+        add_stat(StatCounter::LightSamples, 1);
+        let u_light = sampler.get_2d();
+        let ls = match sampled_light.light.sample_li(
+            &LightSampleContext::from_surface_interaction(isect),
+            u_light,
+            lambda,
+            false,
+        ) {
+            None => {
+                return SampledSpectrum::same_value(0.0);
+            }
+            Some(_ls) => _ls,
+        };
+
+        if !ls.l.is_positive() || ls.pdf == 0.0 {
+            return SampledSpectrum::same_value(0.0);
+        }
+
+        // Evaluate BSDF for light and possibly add scattered radiance
+        let wi = ls.wi;
+        let f =
+            bsdf.f(wo, wi, TransportMode::Radiance) * wi.abs_dot(Vector3f::from(isect.shading.n));
+        if !f.is_positive() || !self.unoccluded(&isect.interaction, &ls.p_light) {
+            return SampledSpectrum::same_value(0.0);
+        }
+
+        return f * ls.l / (sampled_light.p * ls.pdf);
+    }
+
+    fn probe_intersections(
+        &self,
+        isect: &SurfaceInteraction,
+        probe_segment: &BSSRDFProbeSegment,
+    ) -> Vec<SurfaceInteraction> {
+        // only surfaces with the same material as _isect_ are candidates for the exit point
+        let material = match &isect.material {
+            None => {
+                return vec![];
+            }
+            Some(_material) => _material.clone(),
+        };
+
+        let mut candidates = vec![];
+        let p1 = Point3fi::from(probe_segment.p1);
+        let mut base_p = Point3fi::from(probe_segment.p0);
+        let mut base_n = Normal3f::new(0.0, 0.0, 0.0);
+        let time = isect.interaction.time;
+
+        loop {
+            let r = spawn_ray_to(base_p, base_n, time, p1, Normal3f::new(0.0, 0.0, 0.0));
+            if r.d.x == 0.0 && r.d.y == 0.0 && r.d.z == 0.0 {
+                break;
+            }
+
+            let si = match self.base.aggregate.intersect(&r, 1.0) {
+                None => {
+                    break;
+                }
+                Some(_si) => _si.surface_interaction,
+            };
+
+            base_p = si.interaction.pi;
+            base_n = si.interaction.n;
+
+            match &si.material {
+                Some(_material) if Arc::ptr_eq(_material, &material) => {
+                    candidates.push(si);
+                }
+                _ => {}
+            }
+        }
+
+        return candidates;
+    }
+
     pub fn new(
         aggregate: Arc<dyn Primitive>,
         camera: Arc<dyn Camera>,
         lights: Vec<Arc<dyn Light>>,
+        max_depth: usize,
     ) -> Self {
         let light_sampler = UniformLightSampler {
             lights: lights.clone(),
@@ -130,7 +271,7 @@ impl SimplePath {
 
         return Self {
             base: IntegratorBase::new(aggregate, camera, lights),
-            max_depth: 5,
+            max_depth,
             light_sampler,
         };
     }
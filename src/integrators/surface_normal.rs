@@ -6,10 +6,7 @@ pub struct SurfaceNormal {
 }
 
 impl SurfaceNormal {
-    pub fn new(
-        aggregate: Arc<dyn Primitive>,
-        camera: Arc<dyn Camera>,
-    ) -> Self {
+    pub fn new(aggregate: Arc<dyn Primitive>, camera: Arc<dyn Camera>) -> Self {
         return SurfaceNormal {
             base: IntegratorBase::new(aggregate, camera, vec![]),
             rgb: COLOR_SPACE.generate_albedo_rgb(),
@@ -26,7 +23,8 @@ impl Integrator for SurfaceNormal {
         &self,
         camera_ray: &DifferentialRay,
         lambda: &mut SampledWavelengths,
-        _sampler: &mut dyn Sampler,
+        sampler: &mut dyn Sampler,
+        visible_surface: Option<&mut VisibleSurface>,
     ) -> SampledSpectrum {
         return match self
             .base
@@ -35,11 +33,18 @@ impl Integrator for SurfaceNormal {
         {
             None => SampledSpectrum::same_value(0.0),
             Some(shape_intersection) => {
-                let n = shape_intersection
-                    .surface_interaction
-                    .interaction
-                    .n
-                    .face_forward(-camera_ray.ray.d);
+                let mut isect = shape_intersection.surface_interaction;
+
+                match visible_surface {
+                    None => {}
+                    Some(_visible_surface) => {
+                        let bsdf =
+                            isect.get_bsdf(camera_ray, lambda, self.base.camera.as_ref(), sampler);
+                        *_visible_surface = VisibleSurface::from_bsdf(&isect, &bsdf);
+                    }
+                }
+
+                let n = isect.interaction.n.face_forward(-camera_ray.ray.d);
 
                 let color = Vector3f::from(n).normalize().softmax_color();
 
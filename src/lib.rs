@@ -1,10 +1,5 @@
-#![feature(const_fmt_arguments_new)]
-#![feature(const_fn_floating_point_arithmetic)]
-#![feature(const_float_bits_conv)]
-#![feature(const_float_classify)]
-#![feature(const_trait_impl)]
 #![feature(generic_const_exprs)]
-#![feature(is_sorted)]
+#![feature(const_trait_impl)]
 
 mod accelerator;
 mod base;
@@ -8,6 +8,7 @@ pub struct DiffuseAreaLight {
     two_sided: bool,
     lemit: DenselySampledSpectrum,
     scale: f64,
+    alpha: Option<Arc<dyn FloatTexture>>,
 }
 
 impl Light for DiffuseAreaLight {
@@ -32,6 +33,9 @@ impl Light for DiffuseAreaLight {
         if !self.two_sided && n.dot(w) < 0.0 {
             return SampledSpectrum::same_value(0.0);
         }
+        if self.alpha_masked(p, n, uv) {
+            return SampledSpectrum::same_value(0.0);
+        }
 
         return self.scale * self.lemit.sample(lambda);
     }
@@ -95,25 +99,39 @@ impl DiffuseAreaLight {
         render_from_light: Transform,
         parameters: &ParameterDict,
         shape: Arc<dyn Shape>,
-    ) -> Self {
-        let rgb_l = parameters.get_rgb("L", None);
-        let spectrum_l = RGBIlluminantSpectrum::new(rgb_l);
-        let mut scale = parameters.get_one_float("scale", Some(1.0));
-        let two_sided = parameters.get_one_bool("twosided", Some(false));
+        alpha: Option<Arc<dyn FloatTexture>>,
+    ) -> Result<Self, SceneError> {
+        let spectrum_l: Arc<dyn Spectrum> = if parameters.has_spectrum("L") {
+            parameters.get_spectrum("L")?
+        } else {
+            let rgb_l = parameters.get_rgb("L", None)?;
+            Arc::new(RGBIlluminantSpectrum::new(
+                parameters.get_color_space(),
+                rgb_l,
+            ))
+        };
+        let mut scale = parameters.get_one_float("scale", Some(1.0))?;
+        let two_sided = parameters.get_one_bool("twosided", Some(false))?;
 
         if parameters.has_string("filename") {
-            panic!("this part was not implemented");
+            return Err(parameters.parameter_error(
+                "filename",
+                "image area lights are not supported".to_string(),
+            ));
         }
 
         scale /= spectrum_l.to_photometric();
 
-        let phi_v = parameters.get_one_float("power", Some(-1.0));
+        let phi_v = parameters.get_one_float("power", Some(-1.0))?;
         if phi_v > 0.0 {
-            panic!("this part was not implemented");
+            return Err(parameters.parameter_error(
+                "power",
+                "area lights with a given power are not supported".to_string(),
+            ));
         }
 
         let area = shape.area();
-        return Self {
+        return Ok(Self {
             base: LightBase {
                 light_type: LightType::DeltaDirection,
                 render_from_light,
@@ -121,8 +139,28 @@ impl DiffuseAreaLight {
             shape,
             area,
             two_sided,
-            lemit: DenselySampledSpectrum::from_spectrum(&spectrum_l),
+            lemit: DenselySampledSpectrum::from_spectrum(spectrum_l.as_ref()),
             scale,
+            alpha,
+        });
+    }
+
+    fn alpha_masked(&self, p: Point3f, n: Normal3f, uv: Point2f) -> bool {
+        let alpha = match &self.alpha {
+            None => {
+                return false;
+            }
+            Some(_alpha) => _alpha,
         };
+
+        let a = alpha.evaluate(&TextureEvalContext::from_point(p, n, uv));
+        if a >= 1.0 {
+            return false;
+        }
+        if a <= 0.0 {
+            return true;
+        }
+
+        return hash_float(&Vector3f::new(p.x, p.y, p.z)) > a;
     }
 }
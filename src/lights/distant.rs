@@ -28,21 +28,27 @@ impl Light for DistantLight {
 }
 
 impl DistantLight {
-    pub fn new(render_from_light: &Transform, parameters: &ParameterDict) -> Self {
+    pub fn new(
+        render_from_light: &Transform,
+        parameters: &ParameterDict,
+    ) -> Result<Self, SceneError> {
         let l = if parameters.has_rgb("L") {
-            let rgb = parameters.get_rgb("L", None);
-            RGBIlluminantSpectrum::new(rgb)
+            let rgb = parameters.get_rgb("L", None)?;
+            RGBIlluminantSpectrum::new(parameters.get_color_space(), rgb)
         } else {
-            panic!("this part is not implemented");
+            return Err(SceneError::new(
+                SceneErrorKind::Unsupported("distant light without `L`".to_string()),
+                None,
+            ));
         };
 
         let scale = {
             // Scale the light spectrum to be equivalent to 1 nit
-            let _scale = parameters.get_one_float("scale", Some(1.0)) / l.to_photometric();
+            let _scale = parameters.get_one_float("scale", Some(1.0))? / l.to_photometric();
             // Adjust scale to meet target illuminance value
             // Like for IBLs we measure illuminance as incident on an upward-facing
             // patch.
-            let e_v = parameters.get_one_float("illuminance", Some(-1.0));
+            let e_v = parameters.get_one_float("illuminance", Some(-1.0))?;
 
             if e_v > 0.0 {
                 _scale * e_v
@@ -51,8 +57,8 @@ impl DistantLight {
             }
         };
 
-        let from = parameters.get_one_point3("from", Some(Point3f::new(0.0, 0.0, 0.0)));
-        let to = parameters.get_one_point3("to", Some(Point3f::new(0.0, 0.0, 1.0)));
+        let from = parameters.get_one_point3("from", Some(Point3f::new(0.0, 0.0, 0.0)))?;
+        let to = parameters.get_one_point3("to", Some(Point3f::new(0.0, 0.0, 1.0)))?;
 
         let w = (from - to).normalize();
         let (v1, v2) = w.coordinate_system();
@@ -66,13 +72,13 @@ impl DistantLight {
 
         let final_render_from_light = *render_from_light * t;
 
-        return Self {
+        return Ok(Self {
             lemit: Arc::new(l),
             scale,
             light_base: LightBase {
                 light_type: LightType::DeltaDirection,
                 render_from_light: final_render_from_light,
             },
-        };
+        });
     }
 }
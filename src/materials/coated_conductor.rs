@@ -0,0 +1,128 @@
+use crate::pbrt::*;
+
+pub struct CoatedConductorMaterial {
+    interface_u_roughness: Arc<dyn FloatTexture>,
+    interface_v_roughness: Arc<dyn FloatTexture>,
+    thickness: Arc<dyn FloatTexture>,
+    interface_eta: Arc<dyn Spectrum>,
+    g: Arc<dyn FloatTexture>,
+    albedo: Arc<dyn SpectrumTexture>,
+    conductor_u_roughness: Arc<dyn FloatTexture>,
+    conductor_v_roughness: Arc<dyn FloatTexture>,
+    conductor_eta: Option<Arc<dyn Spectrum>>,
+    k: Option<Arc<dyn Spectrum>>,
+    reflectance: Option<Arc<dyn SpectrumTexture>>,
+    remap_roughness: bool,
+    max_depth: usize,
+    n_samples: usize,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+impl Material for CoatedConductorMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
+        // Initialize interface _DielectricBxDF_
+        let interface_distribution = build_roughness_distribution(
+            &self.interface_u_roughness,
+            &self.interface_v_roughness,
+            self.remap_roughness,
+            &ctx.texture_eval_context,
+        );
+
+        let thick = self.thickness.evaluate(&ctx.texture_eval_context);
+
+        let mut interface_eta = self.interface_eta.eval(lambda[0]);
+        if !self.interface_eta.is_constant_spectrum() {
+            lambda.terminate_secondary();
+        }
+        if interface_eta == 0.0 {
+            interface_eta = 1.0;
+        }
+
+        // Initialize conductor _ConductorBxDF_
+        let (ce, ck) = conductor_eta_k(
+            &self.conductor_eta,
+            &self.k,
+            &self.reflectance,
+            &ctx.texture_eval_context,
+            lambda,
+        );
+        // the conductor sits below the coating, so its IOR is relative to the interface
+        let ce = ce / interface_eta;
+        let ck = ck / interface_eta;
+
+        let conductor_distribution = build_roughness_distribution(
+            &self.conductor_u_roughness,
+            &self.conductor_v_roughness,
+            self.remap_roughness,
+            &ctx.texture_eval_context,
+        );
+
+        let a = self
+            .albedo
+            .evaluate(&ctx.texture_eval_context, lambda)
+            .clamp(0.0, 1.0);
+
+        let gg = self.g.evaluate(&ctx.texture_eval_context).clamp(-1.0, 1.0);
+
+        let coated_conductor_bxdf = CoatedConductorBxDF::new(
+            Arc::new(DielectricBxDF::new(interface_eta, interface_distribution)),
+            Arc::new(ConductorBxDF::new(conductor_distribution, ce, ck)),
+            thick,
+            a,
+            gg,
+            self.max_depth,
+            self.n_samples,
+        );
+
+        return BSDF::new(ctx.ns, ctx.dpdus, Some(Arc::new(coated_conductor_bxdf)));
+    }
+}
+
+impl CoatedConductorMaterial {
+    pub fn new(
+        interface_u_roughness: Arc<dyn FloatTexture>,
+        interface_v_roughness: Arc<dyn FloatTexture>,
+        thickness: Arc<dyn FloatTexture>,
+        interface_eta: Arc<dyn Spectrum>,
+        g: Arc<dyn FloatTexture>,
+        albedo: Arc<dyn SpectrumTexture>,
+        conductor_u_roughness: Arc<dyn FloatTexture>,
+        conductor_v_roughness: Arc<dyn FloatTexture>,
+        conductor_eta: Option<Arc<dyn Spectrum>>,
+        k: Option<Arc<dyn Spectrum>>,
+        reflectance: Option<Arc<dyn SpectrumTexture>>,
+        remap_roughness: bool,
+        max_depth: usize,
+        n_samples: usize,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        return Self {
+            interface_u_roughness,
+            interface_v_roughness,
+            thickness,
+            interface_eta,
+            g,
+            albedo,
+            conductor_u_roughness,
+            conductor_v_roughness,
+            conductor_eta,
+            k,
+            reflectance,
+            remap_roughness,
+            max_depth,
+            n_samples,
+            displacement,
+            normal_map,
+        };
+    }
+}
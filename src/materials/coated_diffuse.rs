@@ -11,24 +11,31 @@ pub struct CoatedDiffuseMaterial {
     remap_roughness: bool,
     max_depth: usize,
     n_samples: usize,
-    // TODO: displacement and normal_map not implemented
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
 }
 
 impl Material for CoatedDiffuseMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
     fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
         let r = self
             .reflectance
             .evaluate(&ctx.texture_eval_context, lambda)
             .clamp(0.0, 1.0);
 
-        let u_rough = self.u_roughness.evaluate(&ctx.texture_eval_context);
-        let v_rough = self.v_roughness.evaluate(&ctx.texture_eval_context);
-
-        if self.remap_roughness {
-            panic!("this part is not implemented");
-        }
-
-        let distribution = TrowbridgeReitzDistribution::new(u_rough, v_rough);
+        let distribution = build_roughness_distribution(
+            &self.u_roughness,
+            &self.v_roughness,
+            self.remap_roughness,
+            &ctx.texture_eval_context,
+        );
 
         let thick = self.thickness.evaluate(&ctx.texture_eval_context);
 
@@ -75,6 +82,8 @@ impl CoatedDiffuseMaterial {
         remap_roughness: bool,
         max_depth: usize,
         n_samples: usize,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
     ) -> Self {
         return Self {
             reflectance,
@@ -87,6 +96,8 @@ impl CoatedDiffuseMaterial {
             remap_roughness,
             max_depth,
             n_samples,
+            displacement,
+            normal_map,
         };
     }
 }
@@ -0,0 +1,94 @@
+use crate::pbrt::*;
+
+pub struct ConductorMaterial {
+    eta: Option<Arc<dyn Spectrum>>,
+    k: Option<Arc<dyn Spectrum>>,
+    reflectance: Option<Arc<dyn SpectrumTexture>>,
+    u_roughness: Arc<dyn FloatTexture>,
+    v_roughness: Arc<dyn FloatTexture>,
+    remap_roughness: bool,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+impl Material for ConductorMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
+        let distribution = build_roughness_distribution(
+            &self.u_roughness,
+            &self.v_roughness,
+            self.remap_roughness,
+            &ctx.texture_eval_context,
+        );
+
+        let (eta, k) = conductor_eta_k(
+            &self.eta,
+            &self.k,
+            &self.reflectance,
+            &ctx.texture_eval_context,
+            lambda,
+        );
+
+        return BSDF::new(
+            ctx.ns,
+            ctx.dpdus,
+            Some(Arc::new(ConductorBxDF::new(distribution, eta, k))),
+        );
+    }
+}
+
+impl ConductorMaterial {
+    pub fn new(
+        eta: Option<Arc<dyn Spectrum>>,
+        k: Option<Arc<dyn Spectrum>>,
+        reflectance: Option<Arc<dyn SpectrumTexture>>,
+        u_roughness: Arc<dyn FloatTexture>,
+        v_roughness: Arc<dyn FloatTexture>,
+        remap_roughness: bool,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        return Self {
+            eta,
+            k,
+            reflectance,
+            u_roughness,
+            v_roughness,
+            remap_roughness,
+            displacement,
+            normal_map,
+        };
+    }
+}
+
+pub fn conductor_eta_k(
+    eta: &Option<Arc<dyn Spectrum>>,
+    k: &Option<Arc<dyn Spectrum>>,
+    reflectance: &Option<Arc<dyn SpectrumTexture>>,
+    ctx: &TextureEvalContext,
+    lambda: &SampledWavelengths,
+) -> (SampledSpectrum, SampledSpectrum) {
+    return match (eta, k, reflectance) {
+        (Some(_eta), Some(_k), _) => (_eta.sample(lambda), _k.sample(lambda)),
+        (_, _, Some(_reflectance)) => {
+            // Map reflectance to an artist-friendly conductor with $\eta = 1$
+            let r = _reflectance.evaluate(ctx, lambda).clamp(0.0, 0.9999);
+            let mut k = SampledSpectrum::same_value(0.0);
+            for i in 0..NUM_SPECTRUM_SAMPLES {
+                k[i] = 2.0 * r[i].sqrt() / (1.0 - r[i]).sqrt();
+            }
+
+            (SampledSpectrum::same_value(1.0), k)
+        }
+        _ => {
+            panic!("conductor needs either `eta` and `k` or `reflectance`");
+        }
+    };
+}
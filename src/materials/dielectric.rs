@@ -0,0 +1,65 @@
+use crate::pbrt::*;
+
+pub struct DielectricMaterial {
+    eta: Arc<dyn Spectrum>,
+    u_roughness: Arc<dyn FloatTexture>,
+    v_roughness: Arc<dyn FloatTexture>,
+    remap_roughness: bool,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+impl Material for DielectricMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
+        // Compute index of refraction for dielectric material
+        let mut sampled_eta = self.eta.eval(lambda[0]);
+        if !self.eta.is_constant_spectrum() {
+            lambda.terminate_secondary();
+        }
+        // Handle edge case in case lambda[0] is beyond the wavelengths stored by the Spectrum.
+        if sampled_eta == 0.0 {
+            sampled_eta = 1.0;
+        }
+
+        let distribution = build_roughness_distribution(
+            &self.u_roughness,
+            &self.v_roughness,
+            self.remap_roughness,
+            &ctx.texture_eval_context,
+        );
+
+        return BSDF::new(
+            ctx.ns,
+            ctx.dpdus,
+            Some(Arc::new(DielectricBxDF::new(sampled_eta, distribution))),
+        );
+    }
+}
+
+impl DielectricMaterial {
+    pub fn new(
+        eta: Arc<dyn Spectrum>,
+        u_roughness: Arc<dyn FloatTexture>,
+        v_roughness: Arc<dyn FloatTexture>,
+        remap_roughness: bool,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        return Self {
+            eta,
+            u_roughness,
+            v_roughness,
+            remap_roughness,
+            displacement,
+            normal_map,
+        };
+    }
+}
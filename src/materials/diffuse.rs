@@ -2,9 +2,19 @@ use crate::pbrt::*;
 
 pub struct DiffuseMaterial {
     reflectance: Arc<dyn SpectrumTexture>,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
 }
 
 impl Material for DiffuseMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
     fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
         let r = self
             .reflectance
@@ -16,7 +26,15 @@ impl Material for DiffuseMaterial {
 }
 
 impl DiffuseMaterial {
-    pub fn new(reflectance: Arc<dyn SpectrumTexture>) -> Self {
-        return Self { reflectance };
+    pub fn new(
+        reflectance: Arc<dyn SpectrumTexture>,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        return Self {
+            reflectance,
+            displacement,
+            normal_map,
+        };
     }
 }
@@ -0,0 +1,53 @@
+use crate::pbrt::*;
+
+pub struct DiffuseTransmissionMaterial {
+    reflectance: Arc<dyn SpectrumTexture>,
+    transmittance: Arc<dyn SpectrumTexture>,
+    scale: f64,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+impl Material for DiffuseTransmissionMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
+        let r = (self.scale * self.reflectance.evaluate(&ctx.texture_eval_context, lambda))
+            .clamp(0.0, 1.0);
+        let t = (self.scale
+            * self
+                .transmittance
+                .evaluate(&ctx.texture_eval_context, lambda))
+        .clamp(0.0, 1.0);
+
+        return BSDF::new(
+            ctx.ns,
+            ctx.dpdus,
+            Some(Arc::new(DiffuseTransmissionBxDF::new(r, t))),
+        );
+    }
+}
+
+impl DiffuseTransmissionMaterial {
+    pub fn new(
+        reflectance: Arc<dyn SpectrumTexture>,
+        transmittance: Arc<dyn SpectrumTexture>,
+        scale: f64,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        return Self {
+            reflectance,
+            transmittance,
+            scale,
+            displacement,
+            normal_map,
+        };
+    }
+}
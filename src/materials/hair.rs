@@ -0,0 +1,92 @@
+use crate::pbrt::*;
+
+pub struct HairMaterial {
+    sigma_a: Option<Arc<dyn SpectrumTexture>>,
+    reflectance: Option<Arc<dyn SpectrumTexture>>,
+    eumelanin: Option<Arc<dyn FloatTexture>>,
+    pheomelanin: Option<Arc<dyn FloatTexture>>,
+    eta: Arc<dyn FloatTexture>,
+    beta_m: Arc<dyn FloatTexture>,
+    beta_n: Arc<dyn FloatTexture>,
+    alpha: Arc<dyn FloatTexture>,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+impl Material for HairMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
+        let texture_ctx = &ctx.texture_eval_context;
+
+        let bm = self.beta_m.evaluate(texture_ctx).max(1e-2);
+        let bn = self.beta_n.evaluate(texture_ctx).max(1e-2);
+        let a = self.alpha.evaluate(texture_ctx);
+        let e = self.eta.evaluate(texture_ctx);
+
+        let sigma_a = match (&self.sigma_a, &self.reflectance) {
+            (Some(_sigma_a), _) => _sigma_a
+                .evaluate(texture_ctx, lambda)
+                .clamp(0.0, f64::INFINITY),
+            (None, Some(_reflectance)) => {
+                let c = _reflectance.evaluate(texture_ctx, lambda).clamp(0.0, 1.0);
+                HairBxDF::sigma_a_from_reflectance(&c, bn)
+            }
+            (None, None) => {
+                let ce = match &self.eumelanin {
+                    None => 0.0,
+                    Some(_eumelanin) => _eumelanin.evaluate(texture_ctx).max(0.0),
+                };
+                let cp = match &self.pheomelanin {
+                    None => 0.0,
+                    Some(_pheomelanin) => _pheomelanin.evaluate(texture_ctx).max(0.0),
+                };
+
+                HairBxDF::sigma_a_from_concentration(ce, cp).sample(lambda)
+            }
+        };
+
+        // Offset along width
+        let h = -1.0 + 2.0 * texture_ctx.uv.y;
+
+        return BSDF::new(
+            ctx.ns,
+            ctx.dpdus,
+            Some(Arc::new(HairBxDF::new(h, e, sigma_a, bm, bn, a))),
+        );
+    }
+}
+
+impl HairMaterial {
+    pub fn new(
+        sigma_a: Option<Arc<dyn SpectrumTexture>>,
+        reflectance: Option<Arc<dyn SpectrumTexture>>,
+        eumelanin: Option<Arc<dyn FloatTexture>>,
+        pheomelanin: Option<Arc<dyn FloatTexture>>,
+        eta: Arc<dyn FloatTexture>,
+        beta_m: Arc<dyn FloatTexture>,
+        beta_n: Arc<dyn FloatTexture>,
+        alpha: Arc<dyn FloatTexture>,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        return Self {
+            sigma_a,
+            reflectance,
+            eumelanin,
+            pheomelanin,
+            eta,
+            beta_m,
+            beta_n,
+            alpha,
+            displacement,
+            normal_map,
+        };
+    }
+}
@@ -0,0 +1,109 @@
+use crate::pbrt::*;
+
+// a two-layer stack of arbitrary materials, evaluated with the generic _LayeredBxDF_
+pub struct LayeredMaterial {
+    top: Arc<dyn Material>,
+    bottom: Arc<dyn Material>,
+    thickness: Arc<dyn FloatTexture>,
+    albedo: Arc<dyn SpectrumTexture>,
+    g: Arc<dyn FloatTexture>,
+    two_sided: bool,
+    max_depth: usize,
+    n_samples: usize,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+fn get_layer_bxdf(
+    material: &Arc<dyn Material>,
+    ctx: &MaterialEvalContext,
+    lambda: &mut SampledWavelengths,
+) -> Arc<dyn BxDF> {
+    let mut material = material.clone();
+    while material.is_mix_material() {
+        material = material.choose_material(ctx);
+    }
+
+    return match material.get_bsdf(ctx, lambda).bxdf {
+        None => {
+            panic!("layered material: a layer evaluated to an empty BSDF");
+        }
+        Some(_bxdf) => _bxdf,
+    };
+}
+
+impl Material for LayeredMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
+        let top = get_layer_bxdf(&self.top, ctx, lambda);
+        let bottom = get_layer_bxdf(&self.bottom, ctx, lambda);
+
+        let thick = self.thickness.evaluate(&ctx.texture_eval_context);
+
+        let a = self
+            .albedo
+            .evaluate(&ctx.texture_eval_context, lambda)
+            .clamp(0.0, 1.0);
+
+        let gg = self.g.evaluate(&ctx.texture_eval_context).clamp(-1.0, 1.0);
+
+        let bxdf: Arc<dyn BxDF> = if self.two_sided {
+            Arc::new(LayeredBxDF::<dyn BxDF, dyn BxDF, true>::new(
+                top,
+                bottom,
+                thick,
+                a,
+                gg,
+                self.max_depth,
+                self.n_samples,
+            ))
+        } else {
+            Arc::new(LayeredBxDF::<dyn BxDF, dyn BxDF, false>::new(
+                top,
+                bottom,
+                thick,
+                a,
+                gg,
+                self.max_depth,
+                self.n_samples,
+            ))
+        };
+
+        return BSDF::new(ctx.ns, ctx.dpdus, Some(bxdf));
+    }
+}
+
+impl LayeredMaterial {
+    pub fn new(
+        top: Arc<dyn Material>,
+        bottom: Arc<dyn Material>,
+        thickness: Arc<dyn FloatTexture>,
+        albedo: Arc<dyn SpectrumTexture>,
+        g: Arc<dyn FloatTexture>,
+        two_sided: bool,
+        max_depth: usize,
+        n_samples: usize,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        return Self {
+            top,
+            bottom,
+            thickness,
+            albedo,
+            g,
+            two_sided,
+            max_depth,
+            n_samples,
+            displacement,
+            normal_map,
+        };
+    }
+}
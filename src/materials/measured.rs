@@ -0,0 +1,57 @@
+use crate::pbrt::*;
+
+lazy_static! {
+    // loaded BRDFs are shared between all materials referring to the same file
+    static ref MEASURED_BXDF_DATA_CACHE: Mutex<HashMap<String, Arc<MeasuredBxDFData>>> =
+        Mutex::new(HashMap::new());
+}
+
+pub struct MeasuredMaterial {
+    brdf: Arc<MeasuredBxDFData>,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+impl Material for MeasuredMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, lambda: &mut SampledWavelengths) -> BSDF {
+        return BSDF::new(
+            ctx.ns,
+            ctx.dpdus,
+            Some(Arc::new(MeasuredBxDF::new(self.brdf.clone(), *lambda))),
+        );
+    }
+}
+
+impl MeasuredMaterial {
+    pub fn new(
+        filename: &str,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Result<Self, String> {
+        let brdf = {
+            let mut cache = MEASURED_BXDF_DATA_CACHE.lock().unwrap();
+            match cache.get(filename) {
+                Some(_brdf) => _brdf.clone(),
+                None => {
+                    let brdf = Arc::new(MeasuredBxDFData::read(filename)?);
+                    cache.insert(filename.to_string(), brdf.clone());
+                    brdf
+                }
+            }
+        };
+
+        return Ok(Self {
+            brdf,
+            displacement,
+            normal_map,
+        });
+    }
+}
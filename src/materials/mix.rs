@@ -0,0 +1,43 @@
+use crate::pbrt::*;
+
+pub struct MixMaterial {
+    materials: [Arc<dyn Material>; 2],
+    amount: Arc<dyn FloatTexture>,
+}
+
+impl Material for MixMaterial {
+    fn is_mix_material(&self) -> bool {
+        return true;
+    }
+
+    fn choose_material(&self, ctx: &MaterialEvalContext) -> Arc<dyn Material> {
+        let amount = self.amount.evaluate(&ctx.texture_eval_context);
+        if amount <= 0.0 {
+            return self.materials[0].clone();
+        }
+        if amount >= 1.0 {
+            return self.materials[1].clone();
+        }
+
+        // the choice only depends on the shading point and the outgoing direction,
+        // so every integrator resolves the same material for the same intersection
+        let p = ctx.texture_eval_context.p;
+        let u = hash_float(&(Vector3f::new(p.x, p.y, p.z), ctx.wo));
+
+        return if amount < u {
+            self.materials[0].clone()
+        } else {
+            self.materials[1].clone()
+        };
+    }
+
+    fn get_bsdf(&self, _ctx: &MaterialEvalContext, _lambda: &mut SampledWavelengths) -> BSDF {
+        panic!("MixMaterial::get_bsdf() shouldn't be called, resolve it with choose_material()");
+    }
+}
+
+impl MixMaterial {
+    pub fn new(materials: [Arc<dyn Material>; 2], amount: Arc<dyn FloatTexture>) -> Self {
+        return Self { materials, amount };
+    }
+}
@@ -0,0 +1,126 @@
+use crate::pbrt::*;
+
+pub struct SubsurfaceMaterial {
+    scale: f64,
+    sigma_a: Option<Arc<dyn SpectrumTexture>>,
+    sigma_s: Option<Arc<dyn SpectrumTexture>>,
+    reflectance: Option<Arc<dyn SpectrumTexture>>,
+    mfp: Option<Arc<dyn SpectrumTexture>>,
+    eta: f64,
+    u_roughness: Arc<dyn FloatTexture>,
+    v_roughness: Arc<dyn FloatTexture>,
+    remap_roughness: bool,
+    table: Arc<BSSRDFTable>,
+    displacement: Option<Arc<dyn FloatTexture>>,
+    normal_map: Option<Arc<Image>>,
+}
+
+impl Material for SubsurfaceMaterial {
+    fn get_displacement(&self) -> Option<Arc<dyn FloatTexture>> {
+        return self.displacement.clone();
+    }
+
+    fn get_normal_map(&self) -> Option<Arc<Image>> {
+        return self.normal_map.clone();
+    }
+
+    fn get_bsdf(&self, ctx: &MaterialEvalContext, _lambda: &mut SampledWavelengths) -> BSDF {
+        // Initialize BSDF for _SubsurfaceMaterial_
+        let distribution = build_roughness_distribution(
+            &self.u_roughness,
+            &self.v_roughness,
+            self.remap_roughness,
+            &ctx.texture_eval_context,
+        );
+
+        // Initialize _bsdf_ for smooth or rough dielectric
+        return BSDF::new(
+            ctx.ns,
+            ctx.dpdus,
+            Some(Arc::new(DielectricBxDF::new(self.eta, distribution))),
+        );
+    }
+
+    fn get_bssrdf(
+        &self,
+        ctx: &MaterialEvalContext,
+        lambda: &mut SampledWavelengths,
+    ) -> Option<TabulatedBSSRDF> {
+        let texture_ctx = &ctx.texture_eval_context;
+
+        let (sigma_a, sigma_s) = match (&self.sigma_a, &self.sigma_s, &self.reflectance) {
+            (Some(_sigma_a), Some(_sigma_s), _) => {
+                // Evaluate textures for $\sigma_\roman{a}$ and $\sigma_\roman{s}$
+                let sig_a =
+                    (self.scale * _sigma_a.evaluate(texture_ctx, lambda)).clamp(0.0, f64::INFINITY);
+                let sig_s =
+                    (self.scale * _sigma_s.evaluate(texture_ctx, lambda)).clamp(0.0, f64::INFINITY);
+
+                (sig_a, sig_s)
+            }
+            (_, _, Some(_reflectance)) => {
+                // Compute _sig_a_ and _sig_s_ from reflectance and mfp
+                let mfree = match &self.mfp {
+                    None => {
+                        panic!("SubsurfaceMaterial: `mfp` is required with `reflectance`");
+                    }
+                    Some(_mfp) => {
+                        (self.scale * _mfp.evaluate(texture_ctx, lambda)).clamp(0.0, f64::INFINITY)
+                    }
+                };
+                let r = _reflectance.evaluate(texture_ctx, lambda).clamp(0.0, 1.0);
+
+                subsurface_from_diffuse(&self.table, &r, &mfree)
+            }
+            _ => {
+                panic!(
+                    "SubsurfaceMaterial: either `sigma_a`/`sigma_s` or `reflectance` is required"
+                );
+            }
+        };
+
+        return Some(TabulatedBSSRDF::new(
+            texture_ctx.p,
+            ctx.ns,
+            self.eta,
+            sigma_a,
+            sigma_s,
+            self.table.clone(),
+        ));
+    }
+}
+
+impl SubsurfaceMaterial {
+    pub fn new(
+        scale: f64,
+        sigma_a: Option<Arc<dyn SpectrumTexture>>,
+        sigma_s: Option<Arc<dyn SpectrumTexture>>,
+        reflectance: Option<Arc<dyn SpectrumTexture>>,
+        mfp: Option<Arc<dyn SpectrumTexture>>,
+        g: f64,
+        eta: f64,
+        u_roughness: Arc<dyn FloatTexture>,
+        v_roughness: Arc<dyn FloatTexture>,
+        remap_roughness: bool,
+        displacement: Option<Arc<dyn FloatTexture>>,
+        normal_map: Option<Arc<Image>>,
+    ) -> Self {
+        let mut table = BSSRDFTable::new(100, 64);
+        compute_beam_diffusion_bssrdf(g, eta, &mut table);
+
+        return Self {
+            scale,
+            sigma_a,
+            sigma_s,
+            reflectance,
+            mfp,
+            eta,
+            u_roughness,
+            v_roughness,
+            remap_roughness,
+            table: Arc::new(table),
+            displacement,
+            normal_map,
+        };
+    }
+}
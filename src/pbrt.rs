@@ -6,10 +6,31 @@ lazy_static! {
             Point2f::new(0.64, 0.33),
             Point2f::new(0.3, 0.6),
             Point2f::new(0.15, 0.06),
-            get_named_spectrum("stdillum-D65"),
+            get_named_spectrum("stdillum-D65").unwrap(),
             srgb_to_spectrum_table,
         )
     };
+    pub static ref DCI_P3_COLOR_SPACE: RGBColorSpace = RGBColorSpace::new(
+        Point2f::new(0.68, 0.32),
+        Point2f::new(0.265, 0.69),
+        Point2f::new(0.15, 0.06),
+        get_named_spectrum("stdillum-D65").unwrap(),
+        RGBtoSpectrumTable::new("DCI-P3"),
+    );
+    pub static ref REC2020_COLOR_SPACE: RGBColorSpace = RGBColorSpace::new(
+        Point2f::new(0.708, 0.292),
+        Point2f::new(0.170, 0.797),
+        Point2f::new(0.131, 0.046),
+        get_named_spectrum("stdillum-D65").unwrap(),
+        RGBtoSpectrumTable::new("Rec2020"),
+    );
+    pub static ref ACES2065_1_COLOR_SPACE: RGBColorSpace = RGBColorSpace::new(
+        Point2f::new(0.7347, 0.2653),
+        Point2f::new(0.0, 1.0),
+        Point2f::new(0.0001, -0.077),
+        get_named_spectrum("illum-acesD60").unwrap(),
+        RGBtoSpectrumTable::new("ACES2065-1"),
+    );
     pub static ref COLOR_ENCODING: SRGBColorEncoding = SRGBColorEncoding {};
 }
 
@@ -20,13 +41,13 @@ pub const ONE_MINUS_EPSILON: f64 = {
 
 pub use fma::fma;
 pub use lazy_static::lazy_static;
-pub use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+pub use rand::{thread_rng, Rng, SeedableRng};
 pub use rand_distr::num_traits::Float;
 pub use rayon::prelude::*;
 pub use std::{
     any::{type_name, Any, TypeId},
     cmp::Ordering,
-    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    collections::{HashMap, HashSet},
     fmt::{Debug, Display, Formatter},
     fs::{read_to_string, File},
     hash::{Hash, Hasher},
@@ -35,13 +56,14 @@ pub use std::{
     ops::{
         Add, AddAssign, BitAnd, BitOr, BitOrAssign, Div, Index, IndexMut, Mul, MulAssign, Neg, Sub,
     },
+    path::Path,
     process::exit,
     str::FromStr,
     sync::{
-        atomic::AtomicUsize,
+        atomic::{AtomicBool, AtomicUsize},
         {Arc, Mutex},
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 pub use strum::IntoEnumIterator;
 pub use strum_macros::EnumIter;
@@ -50,40 +72,54 @@ pub use crate::base::bsdf::*;
 pub use crate::{
     accelerator::bvh::*,
     base::{
-        bxdf::*, camera::*, film::*, filter::*, integrator::*, interaction::*, light::*,
+        bssrdf::*, bxdf::*, camera::*, film::*, filter::*, integrator::*, interaction::*, light::*,
         light_sampler::*, material::*, medium::*, primitive::*, ray::*, sampler::*, shape::*,
         spectrum::*, texture::*,
     },
-    bxdfs::{coated_diffuse::*, dielectric::*, diffuse::*, layered::*, top_or_bottom::*},
+    bxdfs::{
+        coated_conductor::*, coated_diffuse::*, conductor::*, dielectric::*, diffuse::*,
+        diffuse_transmission::*, hair::*, layered::*, measured::*, normalized_fresnel::*,
+        top_or_bottom::*,
+    },
     cameras::perspective::*,
     euclidean_space::{
-        bounds::*, compensated_float::*, float::*, frame::*, interval::*, interval::*, normal::*,
-        point2::*, point3::*, square_matrix::*, transform::*, vector2::*, vector3::*,
+        animated_transform::*, bounds::*, compensated_float::*, float::*, frame::*, interval::*,
+        normal::*, point2::*, point3::*, quaternion::*, square_matrix::*, transform::*, vector2::*,
+        vector3::*,
     },
-    films::{pixel_sensor::*, rgb_film::*},
+    films::{gbuffer_film::*, pixel_sensor::*, rgb_film::*, spectral_film::*},
     filters::box_filter::*,
     integrators::{ambient_occlusion::*, random_walk::*, simple_path::*, surface_normal::*},
     light_samplers::uniform_light_sampler::*,
     lights::{diffuse_area::*, distant::*},
-    materials::{coated_diffuse::*, diffuse::*},
-    primitives::{geometric_primitive::*, simple_primitive::*},
+    materials::{
+        coated_conductor::*, coated_diffuse::*, conductor::*, dielectric::*, diffuse::*,
+        diffuse_transmission::*, hair::*, layered::*, measured::*, mix::*, subsurface::*,
+    },
+    primitives::{animated_primitive::*, geometric_primitive::*, simple_primitive::*},
     samplers::independent::*,
-    scene::{lexer::*, parameter_dict::*, renderer::*, scene_builder::*, util::*},
-    shapes::{loop_subdivision::*, sphere::*, tri_quad_mesh::*, triangle::*, triangle_mesh::*},
+    scene::{
+        checkpoint::*, lexer::*, parameter_dict::*, renderer::*, scene_api::*, scene_builder::*,
+        scene_error::*, scene_formatter::*, scene_options::*, util::*,
+    },
+    shapes::{
+        curve::*, loop_subdivision::*, sphere::*, tri_quad_mesh::*, triangle::*, triangle_mesh::*,
+    },
     spectra::{
         black_body_spectrum::*, cie_xyz::*, const_piecewise_linear_spectrum::*, const_spectrum::*,
         densely_sampled_spectrum::*, measured_spectra_data::*, piecewise_linear_spectrum::*,
         rgb::*, rgb_albedo_spectrum::*, rgb_illuminant_spectrum::*, rgb_sigmoid_polynomial::*,
-        rgb_to_spectrum_data::*, rgb_to_spectrum_table::*, sampled_spectrum::*,
-        sampled_wavelengths::*,
+        rgb_to_spectrum_data::*, rgb_to_spectrum_table::*, rgb_unbounded_spectrum::*,
+        sampled_spectrum::*, sampled_wavelengths::*,
     },
     textures::{
-        float_constant_texture::*, mipmap::*, spectrum_constant_texture::*,
+        float_constant_texture::*, float_image_texture::*, mipmap::*, spectrum_constant_texture::*,
         spectrum_image_texture::*, spectrum_scaled_texture::*, texture_mapping_2d::*,
         uv_mapping::*,
     },
     util::{
-        color::*, color_encoding::*, colorspace::*, image::*, math::*, sampling::*, scattering::*,
+        color::*, color_encoding::*, colorspace::*, complex::*, denoiser::*, image::*, math::*,
+        rng::*, sampling::*, scattering::*, splines::*, statistics::*,
     },
 };
 
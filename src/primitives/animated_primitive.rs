@@ -0,0 +1,52 @@
+use crate::pbrt::*;
+
+pub struct AnimatedPrimitive {
+    primitive: Arc<dyn Primitive>,
+    render_from_primitive: AnimatedTransform,
+}
+
+impl Primitive for AnimatedPrimitive {
+    fn intersect(&self, ray: &Ray, t_max: f64) -> Option<ShapeIntersection> {
+        // Compute _ray_ after transformation by _renderFromPrimitive_
+        let interpolated_render_from_primitive = self.render_from_primitive.interpolate(ray.time);
+        let (primitive_ray, dt) = interpolated_render_from_primitive.inverse().on_ray(ray);
+
+        let mut si = match self.primitive.intersect(&primitive_ray, t_max - dt) {
+            None => {
+                return None;
+            }
+            Some(_si) => _si,
+        };
+
+        // Transform instance's intersection data to render space
+        si.surface_interaction =
+            interpolated_render_from_primitive.on_surface_interaction(si.surface_interaction);
+
+        return Some(si);
+    }
+
+    fn fast_intersect(&self, ray: &Ray, t_max: f64) -> bool {
+        let (primitive_ray, dt) = self
+            .render_from_primitive
+            .interpolate(ray.time)
+            .inverse()
+            .on_ray(ray);
+
+        return self.primitive.fast_intersect(&primitive_ray, t_max - dt);
+    }
+
+    fn bounds(&self) -> Bounds3f {
+        return self
+            .render_from_primitive
+            .motion_bounds(self.primitive.bounds());
+    }
+}
+
+impl AnimatedPrimitive {
+    pub fn new(primitive: Arc<dyn Primitive>, render_from_primitive: AnimatedTransform) -> Self {
+        return Self {
+            primitive,
+            render_from_primitive,
+        };
+    }
+}
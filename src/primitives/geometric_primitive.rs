@@ -3,7 +3,8 @@ use crate::pbrt::*;
 pub struct GeometricPrimitive {
     shape: Arc<dyn Shape>,
     material: Arc<dyn Material>,
-    area_light: Arc<dyn Light>,
+    area_light: Option<Arc<dyn Light>>,
+    alpha: Option<Arc<dyn FloatTexture>>,
 }
 
 impl Primitive for GeometricPrimitive {
@@ -15,13 +16,45 @@ impl Primitive for GeometricPrimitive {
             Some(_si) => _si,
         };
 
+        // Test intersection against alpha texture, if present
+        if let Some(alpha) = &self.alpha {
+            let a = alpha.evaluate(&TextureEvalContext::new(&si.surface_interaction));
+            if a < 1.0 {
+                // Possibly ignore intersection based on stochastic alpha test
+                let u = if a <= 0.0 {
+                    1.0
+                } else {
+                    hash_float(&(Vector3f::new(ray.o.x, ray.o.y, ray.o.z), ray.d))
+                };
+
+                if u > a {
+                    // Ignore this intersection and trace a new ray
+                    let next_ray = si.surface_interaction.spawn_ray(ray.d).ray;
+                    let mut next_si = match self.intersect(&next_ray, t_max - si.t_hit) {
+                        None => {
+                            return None;
+                        }
+                        Some(_si) => _si,
+                    };
+                    next_si.t_hit += si.t_hit;
+
+                    return Some(next_si);
+                }
+            }
+        }
+
         si.surface_interaction
-            .set_intersection_properties(self.material.clone(), Some(self.area_light.clone()));
+            .set_intersection_properties(self.material.clone(), self.area_light.clone());
 
         return Some(si);
     }
 
     fn fast_intersect(&self, ray: &Ray, t_max: f64) -> bool {
+        if self.alpha.is_some() {
+            // shadow rays have to go through the same stochastic alpha test
+            return self.intersect(ray, t_max).is_some();
+        }
+
         return self.shape.fast_intersect(ray, t_max);
     }
 
@@ -34,12 +67,14 @@ impl GeometricPrimitive {
     pub fn new(
         shape: Arc<dyn Shape>,
         material: Arc<dyn Material>,
-        area_light: Arc<dyn Light>,
+        area_light: Option<Arc<dyn Light>>,
+        alpha: Option<Arc<dyn FloatTexture>>,
     ) -> Self {
         return Self {
             shape,
             material,
             area_light,
+            alpha,
         };
     }
 }
@@ -1,2 +1,3 @@
+pub mod animated_primitive;
 pub mod geometric_primitive;
 pub mod simple_primitive;
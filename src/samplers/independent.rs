@@ -2,43 +2,54 @@ use crate::pbrt::*;
 
 pub struct IndependentSampler {
     samples_per_pixel: usize,
-    pub rng: StdRng,
+    seed: u64,
+    pub rng: RNG,
 }
 
 impl IndependentSampler {
     pub fn new(samples_per_pixel: usize) -> Self {
-        return Self {
-            samples_per_pixel,
-            rng: StdRng::from_entropy(),
-        };
+        return IndependentSampler::new_from_seed(0, samples_per_pixel);
     }
 
     pub fn new_from_seed(seed: u64, samples_per_pixel: usize) -> Self {
         return Self {
             samples_per_pixel,
-            rng: StdRng::seed_from_u64(seed),
+            seed,
+            rng: RNG::new(seed),
         };
     }
 }
 
 impl Sampler for IndependentSampler {
     fn fork(&self) -> Box<dyn Sampler> {
-        return Box::new(IndependentSampler::new(self.samples_per_pixel));
+        return Box::new(IndependentSampler::new_from_seed(
+            self.seed,
+            self.samples_per_pixel,
+        ));
     }
 
     fn samples_per_pixel(&self) -> usize {
         return self.samples_per_pixel;
     }
+
+    fn get_seed(&self) -> u64 {
+        return self.seed;
+    }
+
     fn start_pixel_sample(&mut self, p_pixel: Point2i, sample_index: usize) {
-        // do nothing
+        // the random stream of a sample only depends on the seed, the pixel and
+        // the sample index, so a sample can be re-taken in any order
+        self.rng
+            .set_sequence(calculate_hash(&(p_pixel.x, p_pixel.y, self.seed)));
+        self.rng.advance(sample_index as u64 * 65536);
     }
 
     fn get_1d(&mut self) -> f64 {
-        return self.rng.gen::<f64>();
+        return self.rng.uniform_f64();
     }
 
     fn get_2d(&mut self) -> Point2f {
-        return Point2f::new(self.rng.gen::<f64>(), self.rng.gen::<f64>());
+        return Point2f::new(self.rng.uniform_f64(), self.rng.uniform_f64());
     }
 
     fn get_pixel_2d(&mut self) -> Point2f {
@@ -0,0 +1,210 @@
+use crate::pbrt::*;
+use std::io::{BufWriter, Write};
+
+const CHECKPOINT_MAGIC: &[u8; 8] = b"PBRTCKPT";
+const CHECKPOINT_VERSION: u32 = 1;
+
+pub struct Checkpoint {
+    pub resolution: Point2i,
+    pub pixel_bounds: Bounds2i,
+    pub completed_samples: usize,
+    pub seed: u64,
+    pub film_state: Vec<f64>,
+}
+
+// Errors are described without the checkpoint filename, the caller reports it.
+impl Checkpoint {
+    pub fn write(&self, filename: &str) -> Result<(), String> {
+        // write to a temporary file first so that an interrupted write
+        // never corrupts the previous checkpoint
+        let temporary_filename = format!("{}.tmp", filename);
+
+        let file = match File::create(&temporary_filename) {
+            Ok(_file) => _file,
+            Err(error) => {
+                return Err(format!(
+                    "couldn't create `{}`: {}",
+                    temporary_filename, error
+                ));
+            }
+        };
+
+        let mut bytes = Vec::with_capacity(64 + self.film_state.len() * 8);
+        bytes.extend(CHECKPOINT_MAGIC);
+        bytes.extend(CHECKPOINT_VERSION.to_le_bytes());
+        bytes.extend(self.resolution.x.to_le_bytes());
+        bytes.extend(self.resolution.y.to_le_bytes());
+        for value in [
+            self.pixel_bounds.p_min.x,
+            self.pixel_bounds.p_min.y,
+            self.pixel_bounds.p_max.x,
+            self.pixel_bounds.p_max.y,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend((self.completed_samples as u64).to_le_bytes());
+        bytes.extend(self.seed.to_le_bytes());
+        bytes.extend((self.film_state.len() as u64).to_le_bytes());
+        for value in &self.film_state {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        let mut writer = BufWriter::new(file);
+        match writer.write_all(&bytes).and_then(|_| writer.flush()) {
+            Ok(_) => {}
+            Err(error) => {
+                return Err(format!(
+                    "couldn't write `{}`: {}",
+                    temporary_filename, error
+                ));
+            }
+        }
+        drop(writer);
+
+        return match std::fs::rename(&temporary_filename, filename) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!(
+                "couldn't rename `{}`: {}",
+                temporary_filename, error
+            )),
+        };
+    }
+
+    pub fn read(filename: &str) -> Result<Self, String> {
+        let mut bytes = vec![];
+        if let Err(error) = File::open(filename).and_then(|mut file| file.read_to_end(&mut bytes)) {
+            return Err(error.to_string());
+        }
+
+        let mut offset = 0;
+        let mut take = |size: usize| -> Result<&[u8], String> {
+            if offset + size > bytes.len() {
+                return Err("the file is truncated".to_string());
+            }
+            offset += size;
+            return Ok(&bytes[offset - size..offset]);
+        };
+
+        if take(8)? != CHECKPOINT_MAGIC {
+            return Err("not a checkpoint".to_string());
+        }
+        let version = u32::from_le_bytes(take(4)?.try_into().unwrap());
+        if version != CHECKPOINT_VERSION {
+            return Err(format!(
+                "version {} not supported (expected {})",
+                version, CHECKPOINT_VERSION
+            ));
+        }
+
+        let x = i32::from_le_bytes(take(4)?.try_into().unwrap());
+        let y = i32::from_le_bytes(take(4)?.try_into().unwrap());
+        let mut bounds = [0; 4];
+        for bound in &mut bounds {
+            *bound = i32::from_le_bytes(take(4)?.try_into().unwrap());
+        }
+        let completed_samples = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+        let seed = u64::from_le_bytes(take(8)?.try_into().unwrap());
+        let state_size = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+
+        // the size isn't trusted for an allocation, a corrupted one ends as truncated
+        let mut film_state = vec![];
+        for _ in 0..state_size {
+            film_state.push(f64::from_le_bytes(take(8)?.try_into().unwrap()));
+        }
+
+        return Ok(Checkpoint {
+            resolution: Point2i::new(x, y),
+            pixel_bounds: Bounds2i::from_min_max(
+                Point2i::new(bounds[0], bounds[1]),
+                Point2i::new(bounds[2], bounds[3]),
+            ),
+            completed_samples,
+            seed,
+            film_state,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "pbrt-rust-checkpoint-{}-{}.ckpt",
+            std::process::id(),
+            name
+        ));
+
+        return path.to_str().unwrap().to_string();
+    }
+
+    fn checkpoint() -> Checkpoint {
+        return Checkpoint {
+            resolution: Point2i::new(4, 2),
+            pixel_bounds: Bounds2i::from_min_max(Point2i::new(1, 0), Point2i::new(3, 2)),
+            completed_samples: 16,
+            seed: 7,
+            film_state: vec![0.5, 1.0, -2.0],
+        };
+    }
+
+    #[test]
+    fn checkpoints_are_read_back() {
+        let filename = checkpoint_path("roundtrip");
+        checkpoint().write(&filename).unwrap();
+        let read = Checkpoint::read(&filename);
+        std::fs::remove_file(&filename).unwrap();
+
+        let read = read.unwrap();
+        assert_eq!(read.resolution, Point2i::new(4, 2));
+        assert_eq!(read.pixel_bounds, checkpoint().pixel_bounds);
+        assert_eq!(read.completed_samples, 16);
+        assert_eq!(read.seed, 7);
+        assert_eq!(read.film_state, vec![0.5, 1.0, -2.0]);
+    }
+
+    #[test]
+    fn invalid_checkpoints_are_errors() {
+        let filename = checkpoint_path("invalid");
+        checkpoint().write(&filename).unwrap();
+        let bytes = std::fs::read(&filename).unwrap();
+
+        let read_bytes = |bytes: &[u8]| {
+            std::fs::write(&filename, bytes).unwrap();
+            return Checkpoint::read(&filename).err();
+        };
+
+        assert_eq!(
+            read_bytes(&bytes[..bytes.len() - 1]),
+            Some("the file is truncated".to_string())
+        );
+
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+        assert_eq!(
+            read_bytes(&wrong_magic),
+            Some("not a checkpoint".to_string())
+        );
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[8] = 9;
+        assert_eq!(
+            read_bytes(&wrong_version),
+            Some("version 9 not supported (expected 1)".to_string())
+        );
+
+        // a corrupted state size doesn't allocate the state up front
+        let mut huge_state = bytes.clone();
+        let state_size_offset = bytes.len() - 3 * 8 - 8;
+        huge_state[state_size_offset..state_size_offset + 8]
+            .copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            read_bytes(&huge_state),
+            Some("the file is truncated".to_string())
+        );
+
+        std::fs::remove_file(&filename).unwrap();
+        assert!(Checkpoint::read(&filename).is_err());
+    }
+}
@@ -1,8 +1,8 @@
 use crate::pbrt::*;
+use flate2::bufread::GzDecoder;
 
 #[derive(Clone, Debug, PartialEq)]
 enum ParserToken {
-    Illegal,
     EOF,
     RightBracket,
     WorldBegin,
@@ -30,27 +30,57 @@ pub enum Token {
     List(Vec<String>),
 }
 
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        return match self {
+            Token::AttributeBegin => write!(f, "`AttributeBegin`"),
+            Token::AttributeEnd => write!(f, "`AttributeEnd`"),
+            Token::WorldBegin => write!(f, "`WorldBegin`"),
+            Token::Keyword(kw) => write!(f, "`{}`", kw),
+            Token::Number(num) => write!(f, "number `{}`", num),
+            Token::String(str) => write!(f, "string \"{}\"", str),
+            Token::Bool(val) => write!(f, "bool `{}`", val),
+            Token::Variable((_type, _name)) => write!(f, "parameter \"{} {}\"", _type, _name),
+            Token::List(_) => write!(f, "a list"),
+        };
+    }
+}
+
 impl Token {
-    pub fn convert_to_float(&self) -> f64 {
+    // name of a directive token, as written in the scene file
+    pub fn directive_name(&self) -> String {
+        return match self {
+            Token::AttributeBegin => "AttributeBegin".to_string(),
+            Token::AttributeEnd => "AttributeEnd".to_string(),
+            Token::WorldBegin => "WorldBegin".to_string(),
+            Token::Keyword(kw) => kw.clone(),
+            _ => self.to_string(),
+        };
+    }
+
+    pub fn convert_to_float(&self) -> Result<f64, SceneErrorKind> {
         return match self {
             Token::Number(num) => match num.parse::<f64>() {
-                Ok(x) => x,
-                Err(_) => {
-                    panic!("fail to convert {:?} into f64", self);
-                }
+                Ok(x) => Ok(x),
+                Err(_) => Err(SceneErrorKind::UnexpectedToken {
+                    expected: "a number".to_string(),
+                    found: format!("`{}`", num),
+                }),
             },
-            _ => {
-                panic!("expect Token::String, get {:?}", self);
-            }
+            _ => Err(SceneErrorKind::UnexpectedToken {
+                expected: "a number".to_string(),
+                found: self.to_string(),
+            }),
         };
     }
 
-    pub fn convert_to_string(&self) -> String {
+    pub fn convert_to_string(&self) -> Result<String, SceneErrorKind> {
         return match self {
-            Token::String(str) => str.clone(),
-            _ => {
-                panic!("expect Token::String, get {:?}", self);
-            }
+            Token::String(str) => Ok(str.clone()),
+            _ => Err(SceneErrorKind::UnexpectedToken {
+                expected: "a quoted string".to_string(),
+                found: self.to_string(),
+            }),
         };
     }
 }
@@ -85,47 +115,73 @@ fn parse_identifier(identifier: &str) -> ParserToken {
     };
 }
 
-pub fn parse_pbrt_into_token(file_path: &str) -> Vec<Token> {
-    let mut lexer = {
-        let binding = match read_to_string(file_path) {
-            Ok(content) => content,
-            Err(msg) => {
-                panic!("fail to read file `{}`:\n`{}`", file_path, msg)
-            }
-        };
-        let input = binding.as_str();
-        Lexer::new(input)
+// scene files ending with `.gz` are gzip compressed
+pub fn read_scene_file(file_path: &str) -> std::io::Result<String> {
+    if file_path.ends_with(".gz") {
+        let mut content = String::new();
+        GzDecoder::new(BufReader::new(File::open(file_path)?)).read_to_string(&mut content)?;
+
+        return Ok(content);
+    }
+
+    return read_to_string(file_path);
+}
+
+// tokens of the file and where each of them starts
+pub fn parse_pbrt_into_token(file_path: &str) -> Result<(Vec<Token>, Vec<FileLoc>), SceneError> {
+    let content = match read_scene_file(file_path) {
+        Ok(_content) => _content,
+        Err(msg) => {
+            return Err(SceneError::new(
+                SceneErrorKind::Io {
+                    filename: file_path.to_string(),
+                    message: msg.to_string(),
+                },
+                None,
+            ));
+        }
     };
+    let mut lexer = Lexer::new(&content, file_path);
 
     let mut tokens = vec![];
+    let mut locs = vec![];
 
     lexer.read_char();
     loop {
-        let token = lexer.next_token();
-        if token == ParserToken::EOF {
-            break;
-        }
-        if token == ParserToken::Illegal {
-            panic!(
-                "\nparsing {} fails at line {}",
-                file_path, lexer.line_number
-            );
-        }
+        lexer.skip_space_and_comment();
+        let loc = lexer.loc();
+
+        let token = match lexer.next_token()? {
+            ParserToken::EOF => {
+                break;
+            }
+            ParserToken::RightBracket => {
+                return Err(SceneError::at(
+                    SceneErrorKind::UnexpectedToken {
+                        expected: "a directive".to_string(),
+                        found: "`]`".to_string(),
+                    },
+                    &loc,
+                ));
+            }
+            _token => Token::from(_token),
+        };
 
         tokens.push(token);
+        locs.push(loc);
     }
 
-    return tokens
-        .iter()
-        .map(|t| Token::from(t.clone()))
-        .collect::<Vec<Token>>();
+    return Ok((tokens, locs));
 }
 
 pub struct Lexer {
     input: Vec<char>,
+    filename: String,
     pub position: usize,
     pub current_char: Option<char>,
-    pub line_number: usize,
+    pub line_number: u32,
+    // position of the first character of the current line
+    line_start: usize,
 }
 
 fn is_letter(ch: char) -> bool {
@@ -137,12 +193,14 @@ fn is_digit(ch: char) -> bool {
 }
 
 impl Lexer {
-    pub fn new(input: &str) -> Self {
+    pub fn new(input: &str, filename: &str) -> Self {
         Self {
             input: input.chars().collect(),
+            filename: filename.to_string(),
             position: 0,
             current_char: None,
             line_number: 1,
+            line_start: 0,
         }
     }
 
@@ -156,6 +214,15 @@ impl Lexer {
         self.position += 1;
     }
 
+    // location of _current_char_
+    pub fn loc(&self) -> FileLoc {
+        return FileLoc {
+            filename: self.filename.clone(),
+            line: self.line_number,
+            column: (self.position - self.line_start) as u32,
+        };
+    }
+
     fn skip_space(&mut self) {
         loop {
             match self.current_char {
@@ -166,6 +233,7 @@ impl Lexer {
                     if ch == ' ' || ch == '\t' || ch == '\n' || ch == '\r' {
                         if ch == '\n' {
                             self.line_number += 1;
+                            self.line_start = self.position;
                         }
                         self.read_char();
                         continue;
@@ -177,12 +245,17 @@ impl Lexer {
     }
 
     fn skip_comment(&mut self) {
-        loop {
+        // stop on the line break, _skip_space()_ counts it
+        while self.current_char.is_some() && self.current_char != Some('\n') {
             self.read_char();
-            if self.current_char == Some('\n') {
-                self.line_number += 1;
-                return;
-            }
+        }
+    }
+
+    fn skip_space_and_comment(&mut self) {
+        self.skip_space();
+        while self.current_char == Some('#') {
+            self.skip_comment();
+            self.skip_space();
         }
     }
 
@@ -220,12 +293,23 @@ impl Lexer {
             .collect();
     }
 
-    fn read_quoted_string(&mut self) -> ParserToken {
+    fn read_quoted_string(&mut self) -> Result<ParserToken, SceneError> {
+        let loc = self.loc();
         let last_position = self.position - 1;
 
         self.read_char(); // consume the first quote
-        while self.current_char != Some('"') {
-            self.read_char();
+        loop {
+            match self.current_char {
+                None | Some('\n') => {
+                    return Err(SceneError::at(SceneErrorKind::UnterminatedString, &loc));
+                }
+                Some('"') => {
+                    break;
+                }
+                Some(_) => {
+                    self.read_char();
+                }
+            }
         }
         self.read_char(); // consume the last quote
 
@@ -234,82 +318,195 @@ impl Lexer {
             .collect();
 
         if !string_without_quote.contains(' ') {
-            return ParserToken::String(string_without_quote);
+            return Ok(ParserToken::String(string_without_quote));
         }
 
-        let split_char = string_without_quote.split(' ');
-        let mut parts = vec![];
-        for p in split_char {
-            parts.push(p.to_string());
+        let parts = string_without_quote
+            .split_whitespace()
+            .collect::<Vec<&str>>();
+        if parts.len() != 2 {
+            return Err(SceneError::at(
+                SceneErrorKind::UnexpectedToken {
+                    expected: "a parameter declaration \"type name\"".to_string(),
+                    found: format!("\"{}\"", string_without_quote),
+                },
+                &loc,
+            ));
         }
-        assert_eq!(parts.len(), 2);
-        return ParserToken::Variable((parts[0].clone(), parts[1].clone()));
+
+        return Ok(ParserToken::Variable((
+            parts[0].to_string(),
+            parts[1].to_string(),
+        )));
     }
 
-    fn read_list(&mut self) -> Vec<String> {
+    fn read_list(&mut self) -> Result<Vec<String>, SceneError> {
+        let list_loc = self.loc();
         self.read_char(); // consume `[`
 
         let mut list = vec![];
         loop {
-            let token = self.next_token();
-            let val = match token {
+            self.skip_space_and_comment();
+            let loc = self.loc();
+
+            let val = match self.next_token()? {
                 ParserToken::RightBracket => {
                     break;
                 }
                 ParserToken::Number(num) => num,
                 ParserToken::String(str) => str,
-
-                _ => {
-                    panic!("line {}: get {:?}", self.line_number, token);
+                ParserToken::EOF => {
+                    return Err(SceneError::at(SceneErrorKind::UnterminatedList, &list_loc));
+                }
+                token => {
+                    return Err(SceneError::at(
+                        SceneErrorKind::UnexpectedToken {
+                            expected: "a number or a string inside `[ ]`".to_string(),
+                            found: match token {
+                                ParserToken::List(_) => "a nested list".to_string(),
+                                _ => Token::from(token).to_string(),
+                            },
+                        },
+                        &loc,
+                    ));
                 }
             };
 
             list.push(val);
         }
 
-        return list;
+        return Ok(list);
     }
 
-    fn next_token(&mut self) -> ParserToken {
-        self.skip_space();
-        while self.current_char == Some('#') {
-            self.skip_comment();
-            self.skip_space();
-        }
+    fn next_token(&mut self) -> Result<ParserToken, SceneError> {
+        self.skip_space_and_comment();
 
         return match self.current_char {
-            None => ParserToken::EOF,
+            None => Ok(ParserToken::EOF),
             Some(current_char) => match current_char {
-                '[' => {
-                    let token = ParserToken::List(self.read_list());
-                    self.read_char();
-                    token
-                }
+                // _read_list()_ consumes the closing `]`
+                '[' => Ok(ParserToken::List(self.read_list()?)),
                 ']' => {
                     let token = ParserToken::RightBracket;
                     self.read_char();
-                    token
+                    Ok(token)
                 }
 
                 '"' => self.read_quoted_string(),
 
                 _ => {
                     if is_letter(current_char) {
-                        return parse_identifier(&self.read_identifier());
+                        return Ok(parse_identifier(&self.read_identifier()));
                     }
 
                     if current_char == '-' || current_char == '.' || is_digit(current_char) {
-                        return ParserToken::Number(self.read_number());
+                        return Ok(ParserToken::Number(self.read_number()));
                     }
 
-                    println!(
-                        "line {}: illegal char: `{}`",
-                        self.line_number, current_char
-                    );
-
-                    ParserToken::Illegal
+                    Err(SceneError::at(
+                        SceneErrorKind::IllegalCharacter(current_char),
+                        &self.loc(),
+                    ))
                 }
             },
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // _content_ written to a scene file of its own, removed by the caller
+    fn write_scene(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "pbrt-rust-lexer-{}-{}.pbrt",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, content).unwrap();
+
+        return path.to_str().unwrap().to_string();
+    }
+
+    fn loc(filename: &str, line: u32, column: u32) -> FileLoc {
+        return FileLoc {
+            filename: filename.to_string(),
+            line,
+            column,
+        };
+    }
+
+    #[test]
+    fn tokens_are_located() {
+        let path = write_scene("tokens", "Shape \"sphere\"\n  \"float radius\" 2 # size\n");
+        let result = parse_pbrt_into_token(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let (tokens, locs) = result.unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Keyword("Shape".to_string()),
+                Token::String("sphere".to_string()),
+                Token::Variable(("float".to_string(), "radius".to_string())),
+                Token::Number("2".to_string()),
+            ]
+        );
+        assert_eq!(
+            locs,
+            vec![
+                loc(&path, 1, 1),
+                loc(&path, 1, 7),
+                loc(&path, 2, 3),
+                loc(&path, 2, 18),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_points_to_its_opening_quote() {
+        let path = write_scene("string", "WorldBegin\n# comment\nShape \"sphere\n");
+        let result = parse_pbrt_into_token(&path);
+        let message = result.as_ref().err().map(|err| err.to_string());
+        std::fs::remove_file(&path).unwrap();
+
+        let error = result.err().unwrap();
+        assert_eq!(error.kind, SceneErrorKind::UnterminatedString);
+        assert_eq!(error.loc, Some(loc(&path, 3, 7)));
+        assert_eq!(
+            message.unwrap(),
+            format!(
+                "{}:3:7: error: missing closing `\"` before the end of the line\n    Shape \"sphere\n          ^",
+                path
+            )
+        );
+    }
+
+    #[test]
+    fn illegal_character_keeps_tabs_in_the_excerpt() {
+        let path = write_scene("character", "AttributeBegin\n\tShape @\n");
+        let result = parse_pbrt_into_token(&path);
+        let message = result.as_ref().err().map(|err| err.to_string());
+        std::fs::remove_file(&path).unwrap();
+
+        let error = result.err().unwrap();
+        assert_eq!(error.kind, SceneErrorKind::IllegalCharacter('@'));
+        assert_eq!(error.loc, Some(loc(&path, 2, 8)));
+        assert_eq!(
+            message.unwrap(),
+            format!(
+                "{}:2:8: error: illegal character `@`\n    \tShape @\n    \t      ^",
+                path
+            )
+        );
+    }
+
+    #[test]
+    fn missing_file_is_not_located() {
+        let error = parse_pbrt_into_token("/nonexistent/scene.pbrt").err().unwrap();
+
+        assert!(matches!(error.kind, SceneErrorKind::Io { .. }));
+        assert_eq!(error.loc, None);
+    }
+}
@@ -1,5 +1,10 @@
+pub mod checkpoint;
 pub mod lexer;
 pub mod parameter_dict;
 pub mod renderer;
+pub mod scene_api;
 pub mod scene_builder;
+pub mod scene_error;
+pub mod scene_formatter;
+pub mod scene_options;
 pub mod util;
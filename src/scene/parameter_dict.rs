@@ -1,15 +1,35 @@
 use crate::pbrt::*;
 
+#[derive(Clone)]
+pub struct NamedTextures {
+    pub spectrum_textures: HashMap<String, Arc<dyn SpectrumTexture>>,
+    pub float_textures: HashMap<String, Arc<dyn FloatTexture>>,
+}
+
+impl Default for NamedTextures {
+    fn default() -> Self {
+        return NamedTextures {
+            spectrum_textures: HashMap::new(),
+            float_textures: HashMap::new(),
+        };
+    }
+}
+
 pub struct ParameterDict {
     integers: HashMap<String, Vec<i32>>,
     floats: HashMap<String, Vec<f64>>,
-    strings: HashMap<String, String>,
+    strings: HashMap<String, Vec<String>>,
     point2s: HashMap<String, Vec<Point2f>>,
     point3s: HashMap<String, Vec<Point3f>>,
     normal3s: HashMap<String, Vec<Normal3f>>,
     rgbs: HashMap<String, RGB>,
+    spectra: HashMap<String, Arc<dyn Spectrum>>,
     textures: HashMap<String, Arc<dyn SpectrumTexture>>,
+    float_textures: HashMap<String, Arc<dyn FloatTexture>>,
     bools: HashMap<String, Vec<bool>>,
+    color_space: &'static RGBColorSpace,
+    // where each parameter is declared, errors about its values point there
+    locs: HashMap<String, FileLoc>,
 }
 
 impl Default for ParameterDict {
@@ -22,8 +42,12 @@ impl Default for ParameterDict {
             point3s: HashMap::new(),
             normal3s: HashMap::new(),
             rgbs: HashMap::new(),
+            spectra: HashMap::new(),
             textures: HashMap::new(),
+            float_textures: HashMap::new(),
             bools: HashMap::new(),
+            color_space: &*COLOR_SPACE,
+            locs: HashMap::new(),
         };
     }
 }
@@ -38,8 +62,12 @@ impl Clone for ParameterDict {
             point3s: self.point3s.clone(),
             normal3s: self.normal3s.clone(),
             rgbs: self.rgbs.clone(),
+            spectra: self.spectra.clone(),
             textures: self.textures.clone(),
+            float_textures: self.float_textures.clone(),
             bools: self.bools.clone(),
+            color_space: self.color_space,
+            locs: self.locs.clone(),
         };
     }
 }
@@ -63,67 +91,89 @@ fn _print<T: Display>(hashmap: &HashMap<String, Vec<T>>) {
     println!();
 }
 
-pub fn convert_string<T: FromStr>(string_vec: &[String]) -> Vec<T>
-where
-    <T as FromStr>::Err: Debug,
-{
+// the first value that can't be parsed is returned as error
+pub fn convert_string<T: FromStr>(string_vec: &[String]) -> Result<Vec<T>, String> {
     return string_vec
         .into_iter()
-        .map(|x| x.parse::<T>().unwrap())
-        .collect::<Vec<T>>();
+        .map(|x| x.parse::<T>().map_err(|_| x.clone()))
+        .collect::<Result<Vec<T>, String>>();
 }
 
-fn get_one_val<T: Copy>(key: &str, default: Option<T>, dict: &HashMap<String, Vec<T>>) -> T {
-    match (dict.get(key), default) {
-        (Some(val), _) => {
-            if val.len() != 1 {
-                panic!("array length is larger than 1");
-            }
-            return val[0];
-        }
-        (_, Some(val)) => {
-            return val;
-        }
-        _ => {
-            panic!("found no key with name `{}`", key);
-        }
-    }
+// not located, the caller knows which directive needed the parameter
+fn missing_parameter(key: &str) -> SceneError {
+    return SceneError::new(SceneErrorKind::MissingParameter(key.to_string()), None);
 }
 
-fn get_array<T: Copy>(key: &str, dict: &HashMap<String, Vec<T>>) -> Vec<T> {
+fn get_array<T: Clone>(key: &str, dict: &HashMap<String, Vec<T>>) -> Result<Vec<T>, SceneError> {
     return match dict.get(key) {
-        None => {
-            panic!("found no key with name `{}`", key);
-        }
-        Some(val) => val.clone(),
+        None => Err(missing_parameter(key)),
+        Some(val) => Ok(val.clone()),
     };
 }
 
 impl ParameterDict {
+    // _locs_ gives the location of each token of _array_
     pub fn build_parameter_dict(
         array: &[Token],
-        named_texture: &HashMap<String, Arc<dyn SpectrumTexture>>,
+        locs: &[FileLoc],
+        named_textures: &NamedTextures,
         dir_path: Option<String>,
-    ) -> ParameterDict {
+        color_space: &'static RGBColorSpace,
+    ) -> Result<ParameterDict, SceneError> {
         let mut integers = HashMap::<String, Vec<i32>>::new();
         let mut floats = HashMap::<String, Vec<f64>>::new();
-        let mut strings = HashMap::<String, String>::new();
+        let mut strings = HashMap::<String, Vec<String>>::new();
         let mut point2s = HashMap::<String, Vec<Point2f>>::new();
         let mut point3s = HashMap::<String, Vec<Point3f>>::new();
         let mut normal3s = HashMap::<String, Vec<Normal3f>>::new();
         let mut rgbs = HashMap::<String, RGB>::new();
+        let mut spectra = HashMap::<String, Arc<dyn Spectrum>>::new();
         let mut textures = HashMap::<String, Arc<dyn SpectrumTexture>>::new();
+        let mut float_textures = HashMap::<String, Arc<dyn FloatTexture>>::new();
         let mut bools = HashMap::<String, Vec<bool>>::new();
+        let mut parameter_locs = HashMap::<String, FileLoc>::new();
 
         for idx in (0..array.len()).step_by(2) {
             let token = array[idx].clone();
             let (variable_type, variable_name) = match token {
                 Token::Variable((_type, _name)) => (_type, _name),
                 _ => {
-                    panic!("expect Token::Variable here, get {:?}", token);
+                    return Err(SceneError::at(
+                        SceneErrorKind::UnexpectedToken {
+                            expected: "a parameter declaration \"type name\"".to_string(),
+                            found: token.to_string(),
+                        },
+                        &locs[idx],
+                    ));
                 }
             };
 
+            let invalid_parameter = |loc: &FileLoc, message: String| {
+                return SceneError::at(
+                    SceneErrorKind::InvalidParameter {
+                        name: variable_name.clone(),
+                        message,
+                    },
+                    loc,
+                );
+            };
+
+            if parameter_locs.contains_key(&variable_name) {
+                return Err(invalid_parameter(
+                    &locs[idx],
+                    "defined more than once".to_string(),
+                ));
+            }
+            parameter_locs.insert(variable_name.clone(), locs[idx].clone());
+
+            if idx + 1 >= array.len() {
+                return Err(invalid_parameter(
+                    &locs[idx],
+                    "no value supplied".to_string(),
+                ));
+            }
+            let values_loc = &locs[idx + 1];
+
             let variable_values = {
                 let t = array[idx + 1].clone();
                 match t {
@@ -137,37 +187,89 @@ impl ParameterDict {
 
                     Token::List(ls) => ls,
                     _ => {
-                        panic!("fail to parse {:?} into variable values", t);
+                        return Err(invalid_parameter(
+                            values_loc,
+                            format!("expected values, found {}", t),
+                        ));
                     }
                 }
             };
+            if variable_values.is_empty() {
+                return Err(invalid_parameter(
+                    values_loc,
+                    "no value supplied".to_string(),
+                ));
+            }
+
+            let parse_values = |type_name: &str| {
+                return convert_string::<f64>(&variable_values).map_err(|value| {
+                    invalid_parameter(
+                        values_loc,
+                        format!("`{}` is not a valid {}", value, type_name),
+                    )
+                });
+            };
+            let check_multiple_of = |values: &Vec<f64>, n: usize| {
+                if values.len() % n != 0 {
+                    return Err(invalid_parameter(
+                        values_loc,
+                        format!(
+                            "expected a multiple of {} values, {} supplied",
+                            n,
+                            values.len()
+                        ),
+                    ));
+                }
+                return Ok(());
+            };
 
-            //let variable_values = fetch_variable_value(&array[idx + 1]);
             match variable_type.as_str() {
                 "string" => {
                     match (variable_name.as_str(), &dir_path) {
-                        ("filename", Some(dir)) => {
-                            strings
-                                .insert(variable_name, format!("{}/{}", dir, variable_values[0]));
+                        ("filename" | "normalmap", Some(dir)) => {
+                            strings.insert(
+                                variable_name,
+                                variable_values
+                                    .iter()
+                                    .map(|v| format!("{}/{}", dir, v))
+                                    .collect(),
+                            );
+                        }
+                        ("colorspace", _) => {
+                            if RGBColorSpace::get_named(&variable_values[0]).is_none() {
+                                return Err(invalid_parameter(
+                                    values_loc,
+                                    format!("unknown color space `{}`", variable_values[0]),
+                                ));
+                            }
+                            strings.insert(variable_name, variable_values.clone());
                         }
                         (_, _) => {
-                            strings.insert(variable_name, variable_values[0].clone());
+                            strings.insert(variable_name, variable_values.clone());
                         }
                     };
                 }
                 "integer" => {
-                    integers.insert(variable_name, convert_string::<i32>(&variable_values));
+                    let values = convert_string::<i32>(&variable_values).map_err(|value| {
+                        invalid_parameter(values_loc, format!("`{}` is not a valid integer", value))
+                    })?;
+                    integers.insert(variable_name, values);
                 }
                 "float" => {
-                    floats.insert(variable_name, convert_string::<f64>(&variable_values));
+                    let values = parse_values("float")?;
+                    floats.insert(variable_name, values);
                 }
 
                 "bool" => {
-                    bools.insert(variable_name, convert_string::<bool>(&variable_values));
+                    let values = convert_string::<bool>(&variable_values).map_err(|value| {
+                        invalid_parameter(values_loc, format!("`{}` is not a valid bool", value))
+                    })?;
+                    bools.insert(variable_name, values);
                 }
 
                 "point2" => {
-                    let float_numbers = convert_string::<f64>(&variable_values);
+                    let float_numbers = parse_values("point2")?;
+                    check_multiple_of(&float_numbers, 2)?;
 
                     let mut point_set = vec![];
                     for idx in (0..float_numbers.len()).step_by(2) {
@@ -176,7 +278,8 @@ impl ParameterDict {
                     point2s.insert(variable_name, point_set);
                 }
                 "point3" => {
-                    let float_numbers = convert_string::<f64>(&variable_values);
+                    let float_numbers = parse_values("point3")?;
+                    check_multiple_of(&float_numbers, 3)?;
 
                     let mut point_set = vec![];
                     for idx in (0..float_numbers.len()).step_by(3) {
@@ -190,7 +293,8 @@ impl ParameterDict {
                 }
 
                 "normal" => {
-                    let float_numbers = convert_string::<f64>(&variable_values);
+                    let float_numbers = parse_values("normal")?;
+                    check_multiple_of(&float_numbers, 3)?;
 
                     let mut normal_set = vec![];
                     for idx in (0..float_numbers.len()).step_by(3) {
@@ -204,8 +308,13 @@ impl ParameterDict {
                 }
 
                 "rgb" => {
-                    let float_numbers = convert_string::<f64>(&variable_values);
-                    assert_eq!(float_numbers.len(), 3);
+                    let float_numbers = parse_values("rgb")?;
+                    if float_numbers.len() != 3 {
+                        return Err(invalid_parameter(
+                            values_loc,
+                            format!("expected 3 values, {} supplied", float_numbers.len()),
+                        ));
+                    }
 
                     rgbs.insert(
                         variable_name,
@@ -213,26 +322,86 @@ impl ParameterDict {
                     );
                 }
 
+                "spectrum" => {
+                    // either a named spectrum or interleaved (lambda, value) pairs
+                    let spectrum: Arc<dyn Spectrum> = if variable_values[0].parse::<f64>().is_ok() {
+                        let samples = parse_values("spectrum")?;
+                        check_multiple_of(&samples, 2)?;
+                        Arc::new(PiecewiseLinearSpectrum::from_interleaved(samples, false))
+                    } else {
+                        if variable_values.len() != 1 {
+                            return Err(invalid_parameter(
+                                values_loc,
+                                "expected a single spectrum name".to_string(),
+                            ));
+                        }
+                        match get_named_spectrum(&variable_values[0]) {
+                            None => {
+                                return Err(invalid_parameter(
+                                    values_loc,
+                                    format!("unknown spectrum `{}`", variable_values[0]),
+                                ));
+                            }
+                            Some(_spectrum) => {
+                                Arc::new(DenselySampledSpectrum::from_spectrum(_spectrum))
+                            }
+                        }
+                    };
+
+                    spectra.insert(variable_name, spectrum);
+                }
+
+                "blackbody" => {
+                    let values = parse_values("blackbody")?;
+                    if values.len() != 1 || values[0] <= 0.0 || values[0].is_nan() {
+                        return Err(invalid_parameter(
+                            values_loc,
+                            "expected a single positive temperature".to_string(),
+                        ));
+                    }
+
+                    // the normalized emission of a blackbody at this temperature in Kelvin
+                    spectra.insert(
+                        variable_name,
+                        Arc::new(DenselySampledSpectrum::from_spectrum(
+                            &BlackBodySpectrum::new(values[0]),
+                        )),
+                    );
+                }
+
                 "texture" => {
                     let texture_id = variable_values[0].clone();
 
-                    let texture = match named_texture.get(&texture_id) {
-                        None => {
-                            panic!("texture not found: `{}`", texture_id);
-                        }
-                        Some(_texture) => _texture.clone(),
-                    };
+                    // float and spectrum textures live in separate namespaces,
+                    // the consumer of the parameter decides which one it needs
+                    let spectrum_texture = named_textures.spectrum_textures.get(&texture_id);
+                    let float_texture = named_textures.float_textures.get(&texture_id);
 
-                    textures.insert(variable_name, texture);
+                    if spectrum_texture.is_none() && float_texture.is_none() {
+                        return Err(SceneError::at(
+                            SceneErrorKind::UndefinedTexture(texture_id),
+                            values_loc,
+                        ));
+                    }
+
+                    if let Some(_texture) = spectrum_texture {
+                        textures.insert(variable_name.clone(), _texture.clone());
+                    }
+                    if let Some(_texture) = float_texture {
+                        float_textures.insert(variable_name, _texture.clone());
+                    }
                 }
 
                 _ => {
-                    panic!("unknown variable type: `{}`", variable_type);
+                    return Err(SceneError::at(
+                        SceneErrorKind::UnknownParameterType(variable_type),
+                        &locs[idx],
+                    ));
                 }
             }
         }
 
-        return Self {
+        return Ok(Self {
             integers,
             floats,
             strings,
@@ -240,11 +409,63 @@ impl ParameterDict {
             point3s,
             normal3s,
             rgbs,
+            spectra,
             textures,
+            float_textures,
             bools,
+            color_space,
+            locs: parameter_locs,
+        });
+    }
+
+    // an error about the values of parameter _key_, located at its declaration
+    pub fn parameter_error(&self, key: &str, message: String) -> SceneError {
+        return SceneError::new(
+            SceneErrorKind::InvalidParameter {
+                name: key.to_string(),
+                message,
+            },
+            self.locs.get(key).cloned(),
+        );
+    }
+
+    fn get_one_val<T: Copy>(
+        &self,
+        key: &str,
+        default: Option<T>,
+        dict: &HashMap<String, Vec<T>>,
+    ) -> Result<T, SceneError> {
+        return match (dict.get(key), default) {
+            (Some(val), _) => {
+                if val.len() != 1 {
+                    return Err(self.parameter_error(
+                        key,
+                        format!("expected a single value, {} supplied", val.len()),
+                    ));
+                }
+                Ok(val[0])
+            }
+            (None, Some(val)) => Ok(val),
+            (None, None) => Err(missing_parameter(key)),
         };
     }
 
+    fn check_not_defined<T>(
+        &self,
+        name: &str,
+        dict: &HashMap<String, T>,
+    ) -> Result<(), SceneError> {
+        if dict.contains_key(name) {
+            return Err(self.parameter_error(name, "defined more than once".to_string()));
+        }
+
+        return Ok(());
+    }
+
+    pub fn has_integer(&self, name: &str) -> bool {
+        return self.integers.get(name).is_some();
+    }
+
     pub fn has_float(&self, name: &str) -> bool {
         return self.floats.get(name).is_some();
     }
@@ -261,99 +482,136 @@ impl ParameterDict {
         return self.rgbs.get(name).is_some();
     }
 
+    pub fn has_spectrum(&self, name: &str) -> bool {
+        return self.spectra.get(name).is_some();
+    }
+
     pub fn has_texture(&self, name: &str) -> bool {
         return self.textures.get(name).is_some();
     }
 
-    pub fn insert_integer(&mut self, name: String, value: Vec<i32>) {
-        if self.integers.contains_key(&name) {
-            panic!("duplicate key: `{}`", name);
-        }
+    pub fn has_float_texture(&self, name: &str) -> bool {
+        return self.float_textures.get(name).is_some();
+    }
 
+    pub fn insert_integer(&mut self, name: String, value: Vec<i32>) -> Result<(), SceneError> {
+        self.check_not_defined(&name, &self.integers)?;
         self.integers.insert(name, value);
-    }
 
-    pub fn insert_float(&mut self, name: String, value: Vec<f64>) {
-        if self.floats.contains_key(&name) {
-            panic!("duplicate key: `{}`", name);
-        }
+        return Ok(());
+    }
 
+    pub fn insert_float(&mut self, name: String, value: Vec<f64>) -> Result<(), SceneError> {
+        self.check_not_defined(&name, &self.floats)?;
         self.floats.insert(name, value);
+
+        return Ok(());
     }
 
-    pub fn insert_string(&mut self, name: String, value: String) {
-        if self.strings.contains_key(&name) {
-            panic!("duplicate key: `{}`", name);
-        }
+    pub fn insert_string(&mut self, name: String, value: String) -> Result<(), SceneError> {
+        self.check_not_defined(&name, &self.strings)?;
+        self.strings.insert(name, vec![value]);
 
-        self.strings.insert(name, value);
+        return Ok(());
     }
 
-    pub fn get_rgb(&self, key: &str, default: Option<RGB>) -> RGB {
+    pub fn get_rgb(&self, key: &str, default: Option<RGB>) -> Result<RGB, SceneError> {
         return match (self.rgbs.get(key), default) {
-            (None, Some(val)) => val,
-            (Some(val), _) => val.clone(),
-            _ => {
-                panic!("get_rgb(): found no key with name `{}`", key);
-            }
+            (None, Some(val)) => Ok(val),
+            (Some(val), _) => Ok(val.clone()),
+            (None, None) => Err(missing_parameter(key)),
         };
     }
 
-    pub fn get_string(&self, key: &str, default: Option<String>) -> String {
+    pub fn get_color_space(&self) -> &'static RGBColorSpace {
+        // an explicit `colorspace` parameter overrides the one of the graphics state,
+        // its name is checked by _build_parameter_dict()_
+        return match self.strings.get("colorspace") {
+            None => self.color_space,
+            Some(_name) => RGBColorSpace::get_named(&_name[0]).unwrap(),
+        };
+    }
+
+    pub fn get_string(&self, key: &str, default: Option<String>) -> Result<String, SceneError> {
         return match (self.strings.get(key), default) {
-            (None, Some(val)) => val,
-            (Some(val), _) => val.clone(),
-            _ => {
-                panic!("get_string(): found no key with name `{}`", key);
-            }
+            (None, Some(val)) => Ok(val),
+            (Some(val), _) => Ok(val[0].clone()),
+            (None, None) => Err(missing_parameter(key)),
         };
     }
 
-    pub fn get_texture(&self, key: &str) -> Arc<dyn SpectrumTexture> {
+    pub fn get_string_array(&self, key: &str) -> Result<Vec<String>, SceneError> {
+        return get_array(key, &self.strings);
+    }
+
+    pub fn get_spectrum(&self, key: &str) -> Result<Arc<dyn Spectrum>, SceneError> {
+        return match self.spectra.get(key) {
+            None => Err(missing_parameter(key)),
+            Some(val) => Ok(val.clone()),
+        };
+    }
+
+    pub fn get_texture(&self, key: &str) -> Result<Arc<dyn SpectrumTexture>, SceneError> {
         return match self.textures.get(key) {
-            Some(val) => val.clone(),
-            _ => {
-                panic!("get_texture(): found no key with name `{}`", key);
-            }
+            None => Err(missing_parameter(key)),
+            Some(val) => Ok(val.clone()),
         };
     }
-    pub fn get_one_float(&self, key: &str, default: Option<f64>) -> f64 {
-        return get_one_val(key, default, &self.floats);
+
+    pub fn get_float_texture(&self, key: &str) -> Result<Arc<dyn FloatTexture>, SceneError> {
+        return match self.float_textures.get(key) {
+            None => Err(missing_parameter(key)),
+            Some(val) => Ok(val.clone()),
+        };
+    }
+
+    pub fn get_float_texture_with_default(
+        &self,
+        key: &str,
+        default: f64,
+    ) -> Result<Arc<dyn FloatTexture>, SceneError> {
+        if self.has_float_texture(key) {
+            return self.get_float_texture(key);
+        }
+
+        return Ok(Arc::new(FloatConstantTexture::new(
+            self.get_one_float(key, Some(default))?,
+        )));
+    }
+
+    pub fn get_one_float(&self, key: &str, default: Option<f64>) -> Result<f64, SceneError> {
+        return self.get_one_val(key, default, &self.floats);
     }
 
-    pub fn get_one_integer(&self, key: &str, default: Option<i32>) -> i32 {
-        return get_one_val(key, default, &self.integers);
+    pub fn get_one_integer(&self, key: &str, default: Option<i32>) -> Result<i32, SceneError> {
+        return self.get_one_val(key, default, &self.integers);
     }
 
-    pub fn get_one_bool(&self, key: &str, default: Option<bool>) -> bool {
-        return get_one_val(key, default, &self.bools);
+    pub fn get_one_bool(&self, key: &str, default: Option<bool>) -> Result<bool, SceneError> {
+        return self.get_one_val(key, default, &self.bools);
     }
 
-    pub fn get_integer_array(&self, key: &str) -> Vec<i32> {
+    pub fn get_integer_array(&self, key: &str) -> Result<Vec<i32>, SceneError> {
         return get_array(key, &self.integers);
     }
 
-    pub fn get_point2_array(&self, key: &str) -> Vec<Point2f> {
+    pub fn get_float_array(&self, key: &str) -> Result<Vec<f64>, SceneError> {
+        return get_array(key, &self.floats);
+    }
+
+    pub fn get_point2_array(&self, key: &str) -> Result<Vec<Point2f>, SceneError> {
         return get_array(key, &self.point2s);
     }
 
-    pub fn get_one_point3(&self, key: &str, default: Option<Point3f>) -> Point3f {
-        return match (self.point3s.get(key), default) {
-            (Some(val), _) => {
-                if val.len() == 1 {
-                    val[0]
-                } else {
-                    unreachable!();
-                }
-            }
-            (None, Some(val)) => val,
-            _ => {
-                unreachable!();
-            }
-        };
+    pub fn get_one_point3(
+        &self,
+        key: &str,
+        default: Option<Point3f>,
+    ) -> Result<Point3f, SceneError> {
+        return self.get_one_val(key, default, &self.point3s);
     }
 
-    pub fn get_point3_array(&self, key: &str) -> Vec<Point3f> {
+    pub fn get_point3_array(&self, key: &str) -> Result<Vec<Point3f>, SceneError> {
         return get_array(key, &self.point3s);
     }
 
@@ -386,7 +644,7 @@ fn display_multi_value_dict<T: Display>(dict: &HashMap<String, Vec<T>>) {
 impl Display for ParameterDict {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(f, "strings: {}\n", self.strings.len()).unwrap();
-        display_single_value_dict(&self.strings);
+        display_multi_value_dict(&self.strings);
 
         write!(f, "integers: {}\n", self.integers.len()).unwrap();
         display_multi_value_dict(&self.integers);
@@ -406,11 +664,21 @@ impl Display for ParameterDict {
         write!(f, "rgbs: {}\n", self.rgbs.len()).unwrap();
         display_single_value_dict(&self.rgbs);
 
+        write!(f, "spectra: {}\n", self.spectra.len()).unwrap();
+        for key in self.spectra.keys() {
+            println!("    {}", key);
+        }
+
         write!(f, "textures: {}\n", self.textures.len()).unwrap();
         for key in self.textures.keys() {
             println!("    {}", key);
         }
 
+        write!(f, "float textures: {}\n", self.float_textures.len()).unwrap();
+        for key in self.float_textures.keys() {
+            println!("    {}", key);
+        }
+
         write!(f, "bools: {}\n", self.bools.len()).unwrap();
         display_multi_value_dict(&self.bools);
 
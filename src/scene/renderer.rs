@@ -1,63 +1,115 @@
 use crate::pbrt::*;
-use std::thread;
-use std::thread::JoinHandle;
+use std::sync::atomic;
+
+// samples every pixel takes before adaptive sampling estimates its error
+const ADAPTIVE_INITIAL_SAMPLES: usize = 16;
+// average samples an unconverged pixel receives in each adaptive pass
+const ADAPTIVE_PASS_SAMPLES: usize = 16;
+// samples per pixel taken between two chances to write a checkpoint or to stop
+// at the time limit
+const PASS_SAMPLES: usize = 8;
+// width and height of the tiles handed out to rendering threads
+const TILE_SIZE: i32 = 16;
+
+// samples taken so far in the current render, reported to the progress callback
+#[derive(Clone, Copy, Debug)]
+pub struct RenderProgress {
+    pub completed_samples: usize,
+    pub total_samples: usize,
+}
+
+impl RenderProgress {
+    pub fn fraction(&self) -> f64 {
+        if self.total_samples == 0 {
+            return 1.0;
+        }
+
+        return (self.completed_samples as f64 / self.total_samples as f64).min(1.0);
+    }
+}
 
 pub struct Renderer {
     integrator: Arc<dyn Integrator>,
     sampler: Arc<dyn Sampler>,
     camera: Arc<dyn Camera>,
     film: Arc<Mutex<dyn Film>>,
+
+    checkpoint_filename: Option<String>,
+    checkpoint_interval: Duration,
+    resume: bool,
+
+    denoise: bool,
+    time_limit: Option<Duration>,
+    print_stats: bool,
+    stats_json_filename: Option<String>,
+    scene_statistics: SceneStatistics,
+    // the statistics of the scene completed with the timings of the current render
+    render_statistics: SceneStatistics,
+    // moved there by the rendering threads each time a tile is done
+    render_counters: Mutex<RenderCounters>,
+    // progress messages go to stdout only for renders writing the film file,
+    // an in-memory render leaves reporting to its caller
+    print_messages: bool,
+
+    // called by the rendering threads each time a tile is done
+    progress_callback: Option<Box<dyn Fn(RenderProgress) + Send + Sync>>,
+    completed_samples: AtomicUsize,
+    total_samples: usize,
+    // set from another thread to stop rendering, the tiles in progress are finished
+    cancel_flag: Option<Arc<AtomicBool>>,
 }
 
-fn single_thread_render(
-    film: &mut Arc<Mutex<dyn Film>>,
-    job_list: &mut Arc<Mutex<Vec<i32>>>,
-    num_samples: usize,
-    integrator: Arc<dyn Integrator>,
-    sampler: Arc<dyn Sampler>,
-    camera: Arc<dyn Camera>,
-) {
-    let mut forked_sampler = sampler.fork();
-    let mutated_sampler = forked_sampler.as_mut();
-    let filter = film.lock().unwrap().get_filter().clone();
-    let resolution = film.lock().unwrap().get_resolution();
-
-    let mut forked_film = film.lock().unwrap().fork();
-    let local_film = forked_film.as_mut();
-
-    let mut rendered_y = vec![];
-    loop {
-        let mut locked_job_list = job_list.lock().unwrap();
-        let maybe_job = locked_job_list.pop();
-        drop(locked_job_list);
-
-        match maybe_job {
-            None => {
-                break;
-            }
-            Some(y) => {
-                for x in 0..resolution.x {
-                    let pixel = Point2i::new(x, y);
+// split _pixel_bounds_ into tiles ordered in a spiral around the center of the image
+fn generate_tiles(pixel_bounds: Bounds2i) -> Vec<Bounds2i> {
+    let extent = pixel_bounds.diagonal();
+    let n_tiles = Point2i::new(
+        (extent.x + TILE_SIZE - 1) / TILE_SIZE,
+        (extent.y + TILE_SIZE - 1) / TILE_SIZE,
+    );
+    let center_x = (n_tiles.x - 1) as f64 / 2.0;
+    let center_y = (n_tiles.y - 1) as f64 / 2.0;
 
-                    for sample_index in 0..num_samples {
-                        mutated_sampler.start_pixel_sample(pixel, sample_index);
+    let mut tiles = vec![];
+    for ty in 0..n_tiles.y {
+        for tx in 0..n_tiles.x {
+            let p_min = Point2i::new(
+                pixel_bounds.p_min.x + tx * TILE_SIZE,
+                pixel_bounds.p_min.y + ty * TILE_SIZE,
+            );
+            let p_max =
+                Point2i::new(p_min.x + TILE_SIZE, p_min.y + TILE_SIZE).min(&pixel_bounds.p_max);
 
-                        integrator.evaluate_pixel_sample(
-                            pixel,
-                            mutated_sampler,
-                            camera.clone(),
-                            filter.clone(),
-                            local_film,
-                        );
-                    }
-                }
+            // tiles on the same square ring around the center are sorted by angle
+            let dx = tx as f64 - center_x;
+            let dy = ty as f64 - center_y;
+            let ring = dx.abs().max(dy.abs());
+            let angle = dy.atan2(dx);
 
-                rendered_y.push(y as usize);
-            }
+            tiles.push((ring, angle, Bounds2i::from_min_max(p_min, p_max)));
         }
     }
 
-    film.lock().unwrap().merge(local_film, rendered_y);
+    tiles.sort_by(|a, b| {
+        a.0.partial_cmp(&b.0)
+            .unwrap()
+            .then(a.1.partial_cmp(&b.1).unwrap())
+    });
+
+    return tiles.into_iter().map(|(_, _, tile)| tile).collect();
+}
+
+// the rendering threads, built once for all the passes of a render
+fn build_thread_pool(num_cores: usize) -> Result<rayon::ThreadPool, SceneError> {
+    return match rayon::ThreadPoolBuilder::new()
+        .num_threads(num_cores)
+        .build()
+    {
+        Ok(_thread_pool) => Ok(_thread_pool),
+        Err(error) => Err(SceneError::new(
+            SceneErrorKind::ThreadPool(error.to_string()),
+            None,
+        )),
+    };
 }
 
 impl Renderer {
@@ -66,64 +118,607 @@ impl Renderer {
         sampler: Arc<dyn Sampler>,
         camera: Arc<dyn Camera>,
         film: Arc<Mutex<dyn Film>>,
+        scene_statistics: SceneStatistics,
     ) -> Self {
         return Renderer {
             integrator,
             sampler,
             camera,
             film,
+            checkpoint_filename: None,
+            checkpoint_interval: Duration::from_secs(300),
+            resume: false,
+            denoise: false,
+            time_limit: None,
+            print_stats: false,
+            stats_json_filename: None,
+            render_statistics: scene_statistics.clone(),
+            scene_statistics,
+            render_counters: Mutex::new(RenderCounters::default()),
+            print_messages: true,
+            progress_callback: None,
+            completed_samples: AtomicUsize::new(0),
+            total_samples: 0,
+            cancel_flag: None,
+        };
+    }
+
+    // also write a denoised image next to the noisy one
+    pub fn enable_denoiser(&mut self) {
+        self.denoise = true;
+        self.film.lock().unwrap().enable_denoising_guides();
+    }
+
+    // stop taking samples once _time_limit_ is exceeded and write what was rendered
+    pub fn set_time_limit(&mut self, time_limit: Duration) {
+        self.time_limit = Some(time_limit);
+    }
+
+    pub fn enable_stats(&mut self) {
+        self.print_stats = true;
+    }
+
+    // write the statistics of the render to _filename_ as JSON
+    pub fn set_stats_json(&mut self, filename: &str) {
+        self.stats_json_filename = Some(filename.to_string());
+    }
+
+    pub fn set_checkpoint(&mut self, filename: &str, interval: Duration, resume: bool) {
+        self.checkpoint_filename = Some(filename.to_string());
+        self.checkpoint_interval = interval;
+        self.resume = resume;
+    }
+
+    pub fn set_progress_callback(
+        &mut self,
+        callback: impl Fn(RenderProgress) + Send + Sync + 'static,
+    ) {
+        self.progress_callback = Some(Box::new(callback));
+    }
+
+    // rendering stops soon after _cancel_flag_ is set, leaving the image partially sampled
+    pub fn set_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>) {
+        self.cancel_flag = Some(cancel_flag);
+    }
+
+    pub fn render(&mut self, num_cores: usize) -> Result<(), SceneError> {
+        self.print_messages = true;
+        let (samples_taken, rendering_time) = self.render_samples(num_cores)?;
+        self.export_image();
+        self.report_statistics(samples_taken, rendering_time, num_cores);
+
+        return Ok(());
+    }
+
+    // render without writing the film file, the image covers the film pixel bounds
+    pub fn render_to_image(&mut self, num_cores: usize) -> Result<Image, SceneError> {
+        self.print_messages = false;
+        let (samples_taken, rendering_time) = self.render_samples(num_cores)?;
+
+        let film = self.film.lock().unwrap();
+        let image = if self.denoise {
+            denoise(&*film)
+        } else {
+            film.get_image()
         };
+        drop(film);
+
+        self.report_statistics(samples_taken, rendering_time, num_cores);
+
+        return Ok(image);
     }
 
-    pub fn render(&mut self, num_samples: usize, num_cores: usize) {
+    fn start_progress(&mut self, total_samples: usize) {
+        self.render_statistics = self.scene_statistics.clone();
+        *self.render_counters.lock().unwrap() = RenderCounters::default();
+        self.completed_samples.store(0, atomic::Ordering::Relaxed);
+        self.total_samples = total_samples;
+    }
+
+    // camera samples taken and the time it took
+    fn render_samples(&mut self, num_cores: usize) -> Result<(usize, Duration), SceneError> {
         let start = Instant::now();
+        let thread_pool = build_thread_pool(num_cores)?;
+
+        let num_samples = self.sampler.samples_per_pixel();
 
-        let resolution = self.film.lock().unwrap().get_resolution();
+        let extent = self.film.lock().unwrap().get_pixel_bounds().diagonal();
 
-        let job_list = Arc::new(Mutex::new((0..resolution.y).collect::<Vec<i32>>()));
+        let mut completed_samples = if self.resume {
+            self.restore_checkpoint()?
+        } else {
+            0
+        };
+        let resumed_samples = completed_samples;
+        if completed_samples > num_samples {
+            self.log(&format!(
+                "checkpoint already holds {} spp, more than the {} spp requested",
+                completed_samples, num_samples
+            ));
+        }
 
-        let mut handles: Vec<JoinHandle<()>> = vec![];
+        let num_pixels = (extent.x * extent.y) as usize;
+        self.start_progress(num_samples.saturating_sub(resumed_samples) * num_pixels);
 
-        for _ in 0..num_cores {
-            let mut shared_film = self.film.clone();
-            let mut shared_job_list = job_list.clone();
+        // with checkpoints, a time limit or cancellation, samples are taken in passes
+        let pass_samples = if self.checkpoint_filename.is_some()
+            || self.time_limit.is_some()
+            || self.cancel_flag.is_some()
+        {
+            PASS_SAMPLES
+        } else {
+            num_samples
+        };
 
-            let integrator = self.integrator.clone();
-            let sampler = self.sampler.clone();
-            let camera = self.camera.clone();
+        let mut last_checkpoint = Instant::now();
+        while completed_samples < num_samples {
+            let n = pass_samples.min(num_samples - completed_samples);
+            let sample_ranges =
+                vec![vec![(completed_samples, n); extent.x as usize]; extent.y as usize];
 
-            let handle = thread::spawn(move || {
-                single_thread_render(
-                    &mut shared_film,
-                    &mut shared_job_list,
-                    num_samples,
-                    integrator,
-                    sampler,
-                    camera,
-                )
-            });
-            handles.push(handle);
+            self.render_pass(sample_ranges, &thread_pool);
+            if self.is_cancelled() {
+                // the pass was interrupted, a checkpoint would hold an unknown sample count
+                self.log(&format!(
+                    "rendering cancelled after {} spp",
+                    completed_samples
+                ));
+                break;
+            }
+            completed_samples += n;
+
+            let out_of_time = self.is_out_of_time(start);
+
+            if self.checkpoint_filename.is_some()
+                && (last_checkpoint.elapsed() >= self.checkpoint_interval
+                    || completed_samples == num_samples
+                    || out_of_time)
+            {
+                self.write_checkpoint(completed_samples)?;
+                last_checkpoint = Instant::now();
+            }
+
+            if out_of_time && completed_samples < num_samples {
+                self.log(&format!(
+                    "time limit reached after {} of {} spp",
+                    completed_samples, num_samples
+                ));
+                break;
+            }
         }
 
-        for handle in handles {
-            handle.join().unwrap();
+        self.log(&format!(
+            "rendering took: {:.2} seconds ({} spp with {} cores)",
+            start.elapsed().as_secs_f32(),
+            completed_samples,
+            num_cores,
+        ));
+
+        self.render_statistics
+            .record_phase("rendering", start.elapsed());
+
+        return Ok((
+            completed_samples.saturating_sub(resumed_samples) * num_pixels,
+            start.elapsed(),
+        ));
+    }
+
+    pub fn render_adaptive(
+        &mut self,
+        target_error: Option<f64>,
+        sample_budget: Option<usize>,
+        num_cores: usize,
+    ) -> Result<(), SceneError> {
+        // Pixels stop receiving samples once their relative error falls below
+        // _target_error_; the budget left is spent on the noisiest pixels.
+        self.print_messages = true;
+        if self.checkpoint_filename.is_some() {
+            return Err(SceneError::new(
+                SceneErrorKind::Unsupported("checkpoints with adaptive sampling".to_string()),
+                None,
+            ));
         }
 
-        let filename = self.film.lock().unwrap().get_filename();
-        let resolution = self.film.lock().unwrap().get_resolution();
+        let start = Instant::now();
+        let thread_pool = build_thread_pool(num_cores)?;
 
-        self.film
-            .lock()
-            .unwrap()
-            .export_image(&filename, resolution);
+        let pixel_bounds = self.film.lock().unwrap().get_pixel_bounds();
+        let width = pixel_bounds.diagonal().x as usize;
+        let height = pixel_bounds.diagonal().y as usize;
+        let num_pixels = width * height;
+        if num_pixels == 0 {
+            self.log("adaptive sampling: no pixel to render");
+            return Ok(());
+        }
 
-        println!(
-            "rendering took: {:.2} seconds ({} spp with {} cores)",
+        // without an explicit budget, spend what uniform sampling would
+        let sample_budget = sample_budget.unwrap_or(self.sampler.samples_per_pixel() * num_pixels);
+        self.start_progress(sample_budget);
+
+        // Take the initial samples required to estimate the error of every pixel
+        let initial_samples = (sample_budget / num_pixels).clamp(2, ADAPTIVE_INITIAL_SAMPLES);
+        let mut pixel_samples = vec![vec![initial_samples; width]; height];
+        let mut samples_taken = initial_samples * num_pixels;
+        self.render_pass(
+            vec![vec![(0, initial_samples); width]; height],
+            &thread_pool,
+        );
+
+        let mut num_converged;
+        loop {
+            // Find pixels that haven't converged yet
+            let mut unconverged = vec![];
+            let film = self.film.lock().unwrap();
+            for y in 0..height {
+                for x in 0..width {
+                    let error = film.get_pixel_error(Point2i::new(
+                        pixel_bounds.p_min.x + x as i32,
+                        pixel_bounds.p_min.y + y as i32,
+                    ));
+                    let converged = match target_error {
+                        None => error == 0.0,
+                        Some(_target_error) => error <= _target_error,
+                    };
+
+                    if !converged {
+                        unconverged.push((x, y, error));
+                    }
+                }
+            }
+            drop(film);
+            num_converged = num_pixels - unconverged.len();
+
+            let remaining_budget = sample_budget.saturating_sub(samples_taken);
+            if unconverged.is_empty() || remaining_budget == 0 {
+                break;
+            }
+            if self.is_out_of_time(start) {
+                self.log("time limit reached");
+                break;
+            }
+            if self.is_cancelled() {
+                self.log("rendering cancelled");
+                break;
+            }
+
+            // NaN and infinite errors (from NaN or infinite radiance samples) count as
+            // the largest finite error, so that they can't take the whole pass
+            let max_finite_error = unconverged
+                .iter()
+                .map(|(_, _, error)| *error)
+                .filter(|error| error.is_finite())
+                .fold(0.0, f64::max);
+            for (_, _, error) in unconverged.iter_mut() {
+                if !error.is_finite() {
+                    *error = if max_finite_error > 0.0 {
+                        max_finite_error
+                    } else {
+                        1.0
+                    };
+                }
+            }
+
+            // Distribute the samples of this pass proportionally to pixel errors
+            let pass_budget = remaining_budget.min(unconverged.len() * ADAPTIVE_PASS_SAMPLES);
+            let error_sum: f64 = unconverged.iter().map(|(_, _, error)| error).sum();
+            unconverged.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+            let mut sample_ranges = vec![vec![(0, 0); width]; height];
+            let mut samples_left = pass_budget;
+            for (x, y, error) in unconverged {
+                if samples_left == 0 {
+                    break;
+                }
+
+                // every selected pixel gets a sample, even when _error_sum_ is 0
+                let n = ((pass_budget as f64 * error / error_sum).ceil() as usize)
+                    .clamp(1, samples_left);
+                sample_ranges[y][x] = (pixel_samples[y][x], n);
+                pixel_samples[y][x] += n;
+                samples_left -= n;
+            }
+            if samples_left == pass_budget {
+                break;
+            }
+            samples_taken += pass_budget - samples_left;
+
+            self.render_pass(sample_ranges, &thread_pool);
+        }
+
+        self.log(&format!(
+            "rendering took: {:.2} seconds ({:.1} spp on average with {} cores)",
             start.elapsed().as_secs_f32(),
-            num_samples,
+            samples_taken as f64 / num_pixels as f64,
             num_cores,
+        ));
+        self.log(&format!(
+            "adaptive sampling: {} of {} pixels converged",
+            num_converged, num_pixels
+        ));
+
+        let rendering_time = start.elapsed();
+        self.render_statistics
+            .record_phase("rendering", rendering_time);
+
+        self.export_image();
+        self.report_statistics(samples_taken, rendering_time, num_cores);
+
+        return Ok(());
+    }
+
+    fn render_pass(
+        &mut self,
+        sample_ranges: Vec<Vec<(usize, usize)>>,
+        thread_pool: &rayon::ThreadPool,
+    ) {
+        // _sample_ranges_ holds (index of the first sample, number of samples)
+        // of every pixel in the film pixel bounds for this pass
+        let pixel_bounds = self.film.lock().unwrap().get_pixel_bounds();
+        let filter = self.film.lock().unwrap().get_filter();
+
+        // only tiles with samples to take in this pass are scheduled
+        let tiles = generate_tiles(pixel_bounds)
+            .into_iter()
+            .filter(|tile| {
+                (tile.p_min.y..tile.p_max.y).any(|y| {
+                    (tile.p_min.x..tile.p_max.x).any(|x| {
+                        sample_ranges[(y - pixel_bounds.p_min.y) as usize]
+                            [(x - pixel_bounds.p_min.x) as usize]
+                            .1
+                            > 0
+                    })
+                })
+            })
+            .collect::<Vec<Bounds2i>>();
+
+        // every thread takes the next tile in spiral order until none is left
+        let next_tile = AtomicUsize::new(0);
+        thread_pool.scope(|scope| {
+            for _ in 0..thread_pool.current_num_threads() {
+                scope.spawn(|_| {
+                    let mut sampler = self.sampler.fork();
+
+                    loop {
+                        let tile_index = next_tile.fetch_add(1, atomic::Ordering::Relaxed);
+                        if tile_index >= tiles.len() || self.is_cancelled() {
+                            break;
+                        }
+
+                        self.render_tile(
+                            tiles[tile_index],
+                            pixel_bounds,
+                            &sample_ranges,
+                            sampler.as_mut(),
+                            filter.clone(),
+                        );
+                    }
+                });
+            }
+        });
+    }
+
+    fn render_tile(
+        &self,
+        tile: Bounds2i,
+        pixel_bounds: Bounds2i,
+        sample_ranges: &Vec<Vec<(usize, usize)>>,
+        sampler: &mut dyn Sampler,
+        filter: Arc<dyn Filter>,
+    ) {
+        let mut tile_film = self.film.lock().unwrap().fork_tile(tile);
+        let mut tile_samples = 0;
+
+        for y in tile.p_min.y..tile.p_max.y {
+            for x in tile.p_min.x..tile.p_max.x {
+                let pixel = Point2i::new(x, y);
+                let (first_sample, num_samples) = sample_ranges
+                    [(y - pixel_bounds.p_min.y) as usize][(x - pixel_bounds.p_min.x) as usize];
+                tile_samples += num_samples;
+
+                for sample_index in first_sample..(first_sample + num_samples) {
+                    sampler.start_pixel_sample(pixel, sample_index);
+
+                    self.integrator.evaluate_pixel_sample(
+                        pixel,
+                        sampler,
+                        self.camera.clone(),
+                        filter.clone(),
+                        tile_film.as_mut(),
+                    );
+                }
+            }
+        }
+
+        self.film.lock().unwrap().merge_tile(tile_film.as_ref());
+        self.render_counters.lock().unwrap().add_thread_counts();
+
+        let completed_samples = self
+            .completed_samples
+            .fetch_add(tile_samples, atomic::Ordering::Relaxed)
+            + tile_samples;
+        if let Some(_callback) = &self.progress_callback {
+            _callback(RenderProgress {
+                completed_samples,
+                total_samples: self.total_samples,
+            });
+        }
+    }
+
+    fn log(&self, message: &str) {
+        if self.print_messages {
+            println!("{}", message);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        return match &self.cancel_flag {
+            None => false,
+            Some(_cancel_flag) => _cancel_flag.load(atomic::Ordering::Relaxed),
+        };
+    }
+
+    fn is_out_of_time(&self, start: Instant) -> bool {
+        return match self.time_limit {
+            None => false,
+            Some(_time_limit) => start.elapsed() >= _time_limit,
+        };
+    }
+
+    fn report_statistics(&self, samples_taken: usize, rendering_time: Duration, num_cores: usize) {
+        if !self.print_stats && self.stats_json_filename.is_none() {
+            return;
+        }
+
+        let film = self.film.lock().unwrap();
+        let statistics = RenderStatistics::collect(
+            film.get_resolution(),
+            film.get_pixel_bounds(),
+            num_cores,
+            samples_taken,
+            rendering_time,
+            &self.render_counters.lock().unwrap(),
+            &self.render_statistics,
+        );
+        drop(film);
+
+        if self.print_stats {
+            statistics.print();
+        }
+
+        if let Some(_filename) = &self.stats_json_filename {
+            // the image is already written, a failure here shouldn't lose it
+            match std::fs::write(_filename, statistics.to_json()) {
+                Ok(_) => {
+                    self.log(&format!("statistics saved to `{}`", _filename));
+                }
+                Err(error) => {
+                    self.log(&format!(
+                        "couldn't write statistics to `{}`: {}",
+                        _filename, error
+                    ));
+                }
+            }
+        }
+    }
+
+    fn checkpoint_error(&self, message: String) -> SceneError {
+        return SceneError::new(
+            SceneErrorKind::Checkpoint {
+                filename: self.checkpoint_filename.clone().unwrap(),
+                message,
+            },
+            None,
         );
+    }
+
+    fn write_checkpoint(&self, completed_samples: usize) -> Result<(), SceneError> {
+        let filename = self.checkpoint_filename.as_ref().unwrap();
+
+        let film = self.film.lock().unwrap();
+        let checkpoint = Checkpoint {
+            resolution: film.get_resolution(),
+            pixel_bounds: film.get_pixel_bounds(),
+            completed_samples,
+            seed: self.sampler.get_seed(),
+            film_state: film.get_state(),
+        };
+        drop(film);
+
+        checkpoint
+            .write(filename)
+            .map_err(|message| self.checkpoint_error(message))?;
+        self.log(&format!(
+            "checkpoint saved to `{}` ({} spp)",
+            filename, completed_samples
+        ));
+
+        return Ok(());
+    }
+
+    fn restore_checkpoint(&mut self) -> Result<usize, SceneError> {
+        let filename = self.checkpoint_filename.clone().unwrap();
+        let checkpoint =
+            Checkpoint::read(&filename).map_err(|message| self.checkpoint_error(message))?;
+
+        let mut film = self.film.lock().unwrap();
+        if checkpoint.resolution != film.get_resolution() {
+            return Err(self.checkpoint_error("rendered at a different resolution".to_string()));
+        }
+        if checkpoint.pixel_bounds != film.get_pixel_bounds() {
+            return Err(self.checkpoint_error("rendered with different pixel bounds".to_string()));
+        }
+        if checkpoint.seed != self.sampler.get_seed() {
+            return Err(self.checkpoint_error(format!(
+                "rendered with seed {}, not {}",
+                checkpoint.seed,
+                self.sampler.get_seed()
+            )));
+        }
+        film.set_state(&checkpoint.film_state)
+            .map_err(|message| self.checkpoint_error(message))?;
+        drop(film);
+
+        self.log(&format!(
+            "resume from `{}` ({} spp)",
+            filename, checkpoint.completed_samples
+        ));
+
+        return Ok(checkpoint.completed_samples);
+    }
+
+    fn export_image(&mut self) {
+        let export_start = Instant::now();
+        let film = self.film.lock().unwrap();
+        let filename = film.get_filename();
+        film.export_image(&filename);
+        self.render_statistics
+            .record_phase("image output", export_start.elapsed());
+
+        self.log(&format!("image saved to `{}`", filename));
+
+        if !self.denoise {
+            return;
+        }
+
+        let start = Instant::now();
+        let image = denoise(&*film);
+
+        let extension = get_extension(&filename);
+        let denoised_filename = change_extension(&filename, &format!("denoised.{}", extension));
+        match extension.as_str() {
+            "exr" => {
+                let mut channels = vec![
+                    ("R".to_string(), vec![]),
+                    ("G".to_string(), vec![]),
+                    ("B".to_string(), vec![]),
+                ];
+                for y in 0..image.resolution.y as usize {
+                    for x in 0..image.resolution.x as usize {
+                        for c in 0..3 {
+                            channels[c].1.push(image[y][x][c] as f32);
+                        }
+                    }
+                }
+
+                export_channels_to_exr(
+                    &denoised_filename,
+                    film.get_resolution(),
+                    film.get_pixel_bounds(),
+                    channels,
+                );
+            }
+            _ => {
+                image.export_to_png(&denoised_filename);
+            }
+        }
+
+        self.render_statistics
+            .record_phase("denoising", start.elapsed());
 
-        println!("image saved to `{}`", filename);
+        self.log(&format!(
+            "denoised image saved to `{}` (denoising took {:.2} seconds)",
+            denoised_filename,
+            start.elapsed().as_secs_f32()
+        ));
     }
 }
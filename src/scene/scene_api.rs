@@ -0,0 +1,464 @@
+use crate::pbrt::*;
+
+// Typed parameters of a scene API call, the equivalent of `"float fov" [ 45 ]` and
+// friends in a scene file:
+//
+//     ParameterList::new().float("fov", 45.0).string("filename", "out.exr")
+#[derive(Clone, Debug, Default)]
+pub struct ParameterList {
+    tokens: Vec<Token>,
+}
+
+fn number_tokens(values: &[f64]) -> Vec<Token> {
+    return values
+        .iter()
+        .map(|x| Token::Number(x.to_string()))
+        .collect();
+}
+
+impl ParameterList {
+    pub fn new() -> Self {
+        return ParameterList::default();
+    }
+
+    fn add(mut self, type_name: &str, name: &str, values: Vec<String>) -> Self {
+        self.tokens
+            .push(Token::Variable((type_name.to_string(), name.to_string())));
+        self.tokens.push(Token::List(values));
+
+        return self;
+    }
+
+    fn add_floats(self, type_name: &str, name: &str, values: &[f64]) -> Self {
+        return self.add(
+            type_name,
+            name,
+            values.iter().map(|x| x.to_string()).collect(),
+        );
+    }
+
+    pub fn float(self, name: &str, value: f64) -> Self {
+        return self.add_floats("float", name, &[value]);
+    }
+
+    pub fn floats(self, name: &str, values: &[f64]) -> Self {
+        return self.add_floats("float", name, values);
+    }
+
+    pub fn integer(self, name: &str, value: i32) -> Self {
+        return self.integers(name, &[value]);
+    }
+
+    pub fn integers(self, name: &str, values: &[i32]) -> Self {
+        return self.add(
+            "integer",
+            name,
+            values.iter().map(|x| x.to_string()).collect(),
+        );
+    }
+
+    pub fn bool(self, name: &str, value: bool) -> Self {
+        return self.add("bool", name, vec![value.to_string()]);
+    }
+
+    pub fn string(self, name: &str, value: &str) -> Self {
+        return self.strings(name, &[value]);
+    }
+
+    pub fn strings(self, name: &str, values: &[&str]) -> Self {
+        return self.add(
+            "string",
+            name,
+            values.iter().map(|x| x.to_string()).collect(),
+        );
+    }
+
+    // a texture defined by _SceneBuilder::texture()_ bound to the parameter
+    pub fn texture(self, name: &str, texture_name: &str) -> Self {
+        return self.add("texture", name, vec![texture_name.to_string()]);
+    }
+
+    pub fn rgb(self, name: &str, rgb: RGB) -> Self {
+        return self.add_floats("rgb", name, &[rgb.r, rgb.g, rgb.b]);
+    }
+
+    pub fn blackbody(self, name: &str, temperature: f64) -> Self {
+        return self.add_floats("blackbody", name, &[temperature]);
+    }
+
+    // a named spectrum such as "metal-Cu-eta"
+    pub fn spectrum(self, name: &str, spectrum_name: &str) -> Self {
+        return self.add("spectrum", name, vec![spectrum_name.to_string()]);
+    }
+
+    pub fn point3(self, name: &str, p: Point3f) -> Self {
+        return self.point3s(name, &[p]);
+    }
+
+    pub fn point3s(self, name: &str, points: &[Point3f]) -> Self {
+        let values = points
+            .iter()
+            .flat_map(|p| [p.x, p.y, p.z])
+            .collect::<Vec<f64>>();
+
+        return self.add_floats("point3", name, &values);
+    }
+
+    pub fn point2s(self, name: &str, points: &[Point2f]) -> Self {
+        let values = points.iter().flat_map(|p| [p.x, p.y]).collect::<Vec<f64>>();
+
+        return self.add_floats("point2", name, &values);
+    }
+
+    pub fn normals(self, name: &str, normals: &[Normal3f]) -> Self {
+        let values = normals
+            .iter()
+            .flat_map(|n| [n.x, n.y, n.z])
+            .collect::<Vec<f64>>();
+
+        return self.add_floats("normal", name, &values);
+    }
+}
+
+// Scene construction from Rust code: every method is the directive of the same name,
+// checked and executed as if it was read from a scene file. Errors are located at
+// "<scene API>:N" where N counts the calls made so far.
+impl SceneBuilder {
+    fn api_directive(
+        &mut self,
+        directive: &str,
+        arguments: Vec<Token>,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        let mut tokens = vec![Token::Keyword(directive.to_string())];
+        tokens.extend(arguments);
+        tokens.extend(parameters.tokens.iter().cloned());
+
+        return self.execute_statement(&tokens);
+    }
+
+    fn api_typed_directive(
+        &mut self,
+        directive: &str,
+        type_name: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_directive(
+            directive,
+            vec![Token::String(type_name.to_string())],
+            parameters,
+        );
+    }
+
+    pub fn look_at(&mut self, eye: Point3f, look: Point3f, up: Vector3f) -> Result<(), SceneError> {
+        let values = [
+            eye.x, eye.y, eye.z, look.x, look.y, look.z, up.x, up.y, up.z,
+        ];
+
+        return self.api_directive("LookAt", number_tokens(&values), &ParameterList::new());
+    }
+
+    pub fn translate(&mut self, delta: Vector3f) -> Result<(), SceneError> {
+        return self.api_directive(
+            "Translate",
+            number_tokens(&[delta.x, delta.y, delta.z]),
+            &ParameterList::new(),
+        );
+    }
+
+    pub fn scale(&mut self, x: f64, y: f64, z: f64) -> Result<(), SceneError> {
+        return self.api_directive("Scale", number_tokens(&[x, y, z]), &ParameterList::new());
+    }
+
+    // _angle_ in degrees
+    pub fn rotate(&mut self, angle: f64, axis: Vector3f) -> Result<(), SceneError> {
+        return self.api_directive(
+            "Rotate",
+            number_tokens(&[angle, axis.x, axis.y, axis.z]),
+            &ParameterList::new(),
+        );
+    }
+
+    // _matrix_ in the column major order of the `Transform` directive
+    pub fn transform(&mut self, matrix: [f64; 16]) -> Result<(), SceneError> {
+        return self.api_directive(
+            "Transform",
+            vec![Token::List(matrix.iter().map(|x| x.to_string()).collect())],
+            &ParameterList::new(),
+        );
+    }
+
+    pub fn reverse_orientation(&mut self) -> Result<(), SceneError> {
+        return self.api_directive("ReverseOrientation", vec![], &ParameterList::new());
+    }
+
+    pub fn color_space(&mut self, name: &str) -> Result<(), SceneError> {
+        return self.api_directive(
+            "ColorSpace",
+            vec![Token::String(name.to_string())],
+            &ParameterList::new(),
+        );
+    }
+
+    pub fn attribute_begin(&mut self) -> Result<(), SceneError> {
+        return self.execute_statement(&[Token::AttributeBegin]);
+    }
+
+    pub fn attribute_end(&mut self) -> Result<(), SceneError> {
+        return self.execute_statement(&[Token::AttributeEnd]);
+    }
+
+    pub fn world_begin(&mut self) -> Result<(), SceneError> {
+        return self.execute_statement(&[Token::WorldBegin]);
+    }
+
+    pub fn camera(
+        &mut self,
+        camera_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("Camera", camera_type, parameters);
+    }
+
+    pub fn film(&mut self, film_type: &str, parameters: &ParameterList) -> Result<(), SceneError> {
+        return self.api_typed_directive("Film", film_type, parameters);
+    }
+
+    pub fn sampler(
+        &mut self,
+        sampler_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("Sampler", sampler_type, parameters);
+    }
+
+    pub fn integrator(
+        &mut self,
+        integrator_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("Integrator", integrator_type, parameters);
+    }
+
+    pub fn light_source(
+        &mut self,
+        light_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("LightSource", light_type, parameters);
+    }
+
+    // emission of the shapes added until the end of the current attribute block
+    pub fn area_light_source(
+        &mut self,
+        light_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("AreaLightSource", light_type, parameters);
+    }
+
+    pub fn shape(
+        &mut self,
+        shape_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("Shape", shape_type, parameters);
+    }
+
+    pub fn material(
+        &mut self,
+        material_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("Material", material_type, parameters);
+    }
+
+    // the material type is given by the "type" string parameter, as in scene files
+    pub fn make_named_material(
+        &mut self,
+        name: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_typed_directive("MakeNamedMaterial", name, parameters);
+    }
+
+    pub fn named_material(&mut self, name: &str) -> Result<(), SceneError> {
+        return self.api_typed_directive("NamedMaterial", name, &ParameterList::new());
+    }
+
+    // _class_ is "float" or "spectrum"
+    pub fn texture(
+        &mut self,
+        name: &str,
+        class: &str,
+        texture_type: &str,
+        parameters: &ParameterList,
+    ) -> Result<(), SceneError> {
+        return self.api_directive(
+            "Texture",
+            vec![
+                Token::String(name.to_string()),
+                Token::String(class.to_string()),
+                Token::String(texture_type.to_string()),
+            ],
+            parameters,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_helper_reaches_the_parameter_dict() {
+        let mut named_textures = NamedTextures::default();
+        named_textures
+            .float_textures
+            .insert("half".to_string(), Arc::new(FloatConstantTexture::new(0.5)));
+
+        let parameters = ParameterList::new()
+            .float("fov", 45.0)
+            .floats("cropwindow", &[0.0, 1.0, 0.25, 0.75])
+            .integer("maxdepth", 5)
+            .integers("indices", &[0, 1, 2])
+            .bool("twosided", true)
+            .string("filename", "out.exr")
+            .strings("materials", &["a", "b"])
+            .texture("roughness", "half")
+            .rgb("reflectance", RGB::new(0.1, 0.2, 0.3))
+            .blackbody("L", 6500.0)
+            .spectrum("eta", "metal-Cu-eta")
+            .point3("from", Point3f::new(1.0, 2.0, 3.0))
+            .point3s(
+                "P",
+                &[Point3f::new(0.0, 0.0, 0.0), Point3f::new(1.0, 0.0, 0.0)],
+            )
+            .point2s("uv", &[Point2f::new(0.0, 1.0)])
+            .normals("N", &[Normal3f::new(0.0, 0.0, 1.0)]);
+        let locs = vec![
+            FileLoc {
+                filename: "<scene API>".to_string(),
+                line: 1,
+                column: 1,
+            };
+            parameters.tokens.len()
+        ];
+
+        let dict = ParameterDict::build_parameter_dict(
+            &parameters.tokens,
+            &locs,
+            &named_textures,
+            None,
+            &COLOR_SPACE,
+        )
+        .unwrap();
+
+        assert_eq!(dict.get_one_float("fov", None), Ok(45.0));
+        assert_eq!(
+            dict.get_float_array("cropwindow"),
+            Ok(vec![0.0, 1.0, 0.25, 0.75])
+        );
+        assert_eq!(dict.get_one_integer("maxdepth", None), Ok(5));
+        assert_eq!(dict.get_integer_array("indices"), Ok(vec![0, 1, 2]));
+        assert_eq!(dict.get_one_bool("twosided", None), Ok(true));
+        assert_eq!(dict.get_string("filename", None), Ok("out.exr".to_string()));
+        assert_eq!(
+            dict.get_string_array("materials"),
+            Ok(vec!["a".to_string(), "b".to_string()])
+        );
+        assert!(dict.get_float_texture("roughness").is_ok());
+
+        let rgb = dict.get_rgb("reflectance", None).unwrap();
+        assert_eq!((rgb.r, rgb.g, rgb.b), (0.1, 0.2, 0.3));
+
+        // a normalized blackbody peaks at 1, at 446 nm for 6500 K
+        let blackbody = dict.get_spectrum("L").unwrap();
+        assert!((blackbody.eval(446.0) - 1.0).abs() < 0.01);
+        assert!(blackbody.eval(700.0) < 1.0);
+        assert!(dict.get_spectrum("eta").unwrap().eval(500.0) > 0.0);
+
+        let from = dict.get_one_point3("from", None).unwrap();
+        assert_eq!((from.x, from.y, from.z), (1.0, 2.0, 3.0));
+        let points = dict.get_point3_array("P").unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[1].x, points[1].y, points[1].z), (1.0, 0.0, 0.0));
+        let uv = dict.get_point2_array("uv").unwrap();
+        assert_eq!((uv[0].x, uv[0].y), (0.0, 1.0));
+        let normals = dict.get_normal3_array("N");
+        assert_eq!((normals[0].x, normals[0].y, normals[0].z), (0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn scene_built_with_the_api_renders() {
+        let quad = ParameterList::new()
+            .integers("indices", &[0, 1, 2, 0, 2, 3])
+            .point3s(
+                "P",
+                &[
+                    Point3f::new(-1.0, -1.0, 0.0),
+                    Point3f::new(1.0, -1.0, 0.0),
+                    Point3f::new(1.0, 1.0, 0.0),
+                    Point3f::new(-1.0, 1.0, 0.0),
+                ],
+            );
+
+        let mut builder = SceneBuilder::default();
+        builder
+            .look_at(
+                Point3f::new(0.0, 0.0, 5.0),
+                Point3f::new(0.0, 0.0, 0.0),
+                Vector3f::new(0.0, 1.0, 0.0),
+            )
+            .unwrap();
+        builder
+            .camera("perspective", &ParameterList::new().float("fov", 45.0))
+            .unwrap();
+        builder
+            .film(
+                "rgb",
+                &ParameterList::new()
+                    .integer("xresolution", 16)
+                    .integer("yresolution", 16)
+                    .string("filename", "api.exr"),
+            )
+            .unwrap();
+        builder
+            .sampler(
+                "independent",
+                &ParameterList::new().integer("pixelsamples", 4),
+            )
+            .unwrap();
+        builder
+            .integrator("simplepath", &ParameterList::new())
+            .unwrap();
+        builder.world_begin().unwrap();
+
+        builder.attribute_begin().unwrap();
+        builder
+            .area_light_source(
+                "diffuse",
+                &ParameterList::new()
+                    .blackbody("L", 6500.0)
+                    .bool("twosided", true),
+            )
+            .unwrap();
+        builder.shape("trianglemesh", &quad).unwrap();
+        builder.attribute_end().unwrap();
+
+        // errors are located by the number of calls made so far
+        let error = builder.named_material("missing").unwrap_err();
+        assert_eq!(
+            error.to_string().lines().next().unwrap(),
+            "<scene API>:11:1: error: named material `missing` not defined"
+        );
+
+        let mut renderer = builder.build(&SceneOptions::default()).unwrap();
+        let image = renderer.render_to_image(1).unwrap();
+
+        assert_eq!((image.resolution.x, image.resolution.y), (16, 16));
+        // the quad covers the center of the image, nothing is behind it
+        assert!(image[8][8].g > 0.0);
+        assert_eq!(image[0][0].g, 0.0);
+    }
+}
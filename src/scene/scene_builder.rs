@@ -1,93 +1,473 @@
 use crate::pbrt::*;
-
-fn build_look_at_transform(pos: Point3f, look: Point3f, up: Vector3f) -> Transform {
-    let mut world_from_camera = SquareMatrix::<4>::zero();
-    world_from_camera[0][3] = pos.x;
-    world_from_camera[1][3] = pos.y;
-    world_from_camera[2][3] = pos.z;
-    world_from_camera[3][3] = 1.0;
-
-    let dir = (look - pos).normalize();
-    if up.normalize().cross(dir).length() == 0.0 {
-        panic!("LookAt: `up` vector and viewing direction are pointing in the same direction");
-    }
-
-    let right = up.normalize().cross(dir).normalize();
-    let new_up = dir.cross(right);
-
-    world_from_camera[0][0] = right.x;
-    world_from_camera[1][0] = right.y;
-    world_from_camera[2][0] = right.z;
-    world_from_camera[3][0] = 0.0;
-    world_from_camera[0][1] = new_up.x;
-    world_from_camera[1][1] = new_up.y;
-    world_from_camera[2][1] = new_up.z;
-    world_from_camera[3][1] = 0.0;
-    world_from_camera[0][2] = dir.x;
-    world_from_camera[1][2] = dir.y;
-    world_from_camera[2][2] = dir.z;
-    world_from_camera[3][2] = 0.0;
-
-    let camera_from_world = world_from_camera.inverse();
-    return Transform::new_with_inverse(camera_from_world, world_from_camera);
-}
+use std::mem::size_of;
+use std::sync::Condvar;
 
 struct SceneEntity {
     pub name: String,
     pub parameters: ParameterDict,
+    pub loc: FileLoc,
 }
 
 struct CameraEntity {
     pub name: String,
     pub parameters: ParameterDict,
     pub camera_transform: CameraTransform,
+    pub loc: FileLoc,
 }
 
 struct LightEntity {
     pub name: String,
     pub parameters: ParameterDict,
     pub render_from_object: Transform,
+    pub loc: FileLoc,
+}
+
+// A file of an `Import` directive, parsed by its own _SceneBuilder_ on the rayon thread
+// pool while the importing file is read. The builder waiting for the result parses the
+// file itself if no thread has started to, so nested imports can't exhaust the pool.
+struct ImportJob {
+    filename: String,
+    builder: Mutex<Option<SceneBuilder>>,
+    result: Mutex<Option<Result<SceneBuilder, SceneError>>>,
+    done: Condvar,
+}
+
+impl ImportJob {
+    fn run(&self) {
+        let mut builder = match self.builder.lock().unwrap().take() {
+            None => {
+                return;
+            }
+            Some(_builder) => _builder,
+        };
+
+        let result = builder
+            .parse_file(&self.filename)
+            .and_then(|_| builder.merge_imported_files())
+            .map(|_| builder);
+
+        *self.result.lock().unwrap() = Some(result);
+        self.done.notify_all();
+    }
+
+    fn wait(&self) -> Result<SceneBuilder, SceneError> {
+        self.run();
+
+        let mut result = self.result.lock().unwrap();
+        loop {
+            match result.take() {
+                None => {
+                    result = self.done.wait(result).unwrap();
+                }
+                Some(_result) => {
+                    return _result;
+                }
+            }
+        }
+    }
+}
+
+struct ImportedFile {
+    loc: FileLoc,
+    filename_loc: FileLoc,
+    job: Arc<ImportJob>,
 }
 
-fn build_film(film_entity: &SceneEntity, _filter: Arc<BoxFilter>) -> Arc<Mutex<dyn Film>> {
-    let xresolution = film_entity.parameters.get_one_integer("xresolution", None);
-    let yresolution = film_entity.parameters.get_one_integer("yresolution", None);
+// an error raised in a file read by the directive at _directive_loc_
+fn nested_file_error(
+    mut error: SceneError,
+    directive_loc: &FileLoc,
+    filename_loc: &FileLoc,
+) -> SceneError {
+    match error.loc {
+        // the file couldn't be read
+        None => {
+            error = error.or_at(filename_loc);
+        }
+        Some(_) => {
+            error.include_chain.push(directive_loc.clone());
+        }
+    }
+
+    return error;
+}
+
+fn build_pixel_bounds(
+    parameters: &ParameterDict,
+    resolution: Point2i,
+    options: &SceneOptions,
+    loc: &FileLoc,
+) -> Result<Bounds2i, SceneError> {
+    let full_bounds = Bounds2i::from_min_max(Point2i::new(0, 0), resolution);
+
+    // bounds given on the command line replace both film parameters
+    let (pb, cr) = match (options.pixel_bounds, options.crop_window) {
+        (Some(_pixel_bounds), _) => (_pixel_bounds.to_vec(), vec![]),
+        (None, Some(_crop_window)) => (vec![], _crop_window.to_vec()),
+        (None, None) => (
+            if parameters.has_integer("pixelbounds") {
+                parameters.get_integer_array("pixelbounds")?
+            } else {
+                vec![]
+            },
+            if parameters.has_float("cropwindow") {
+                parameters.get_float_array("cropwindow")?
+            } else {
+                vec![]
+            },
+        ),
+    };
+
+    if !pb.is_empty() {
+        if pb.len() != 4 {
+            return Err(SceneError::at(
+                SceneErrorKind::InvalidParameter {
+                    name: "pixelbounds".to_string(),
+                    message: format!("expected 4 values, {} supplied", pb.len()),
+                },
+                loc,
+            ));
+        }
+        if !cr.is_empty() {
+            println!("film: both `pixelbounds` and `cropwindow` specified, using `pixelbounds`");
+        }
+
+        let pixel_bounds = Bounds2i::new(&[Point2i::new(pb[0], pb[2]), Point2i::new(pb[1], pb[3])])
+            .intersect(&full_bounds);
+        if pixel_bounds.is_empty() {
+            return Err(SceneError::at(
+                SceneErrorKind::InvalidParameter {
+                    name: "pixelbounds".to_string(),
+                    message: format!("degenerate bounds {:?}", pb),
+                },
+                loc,
+            ));
+        }
+
+        return Ok(pixel_bounds);
+    }
+
+    if !cr.is_empty() {
+        if cr.len() != 4 {
+            return Err(SceneError::at(
+                SceneErrorKind::InvalidParameter {
+                    name: "cropwindow".to_string(),
+                    message: format!("expected 4 values, {} supplied", cr.len()),
+                },
+                loc,
+            ));
+        }
+
+        // Compute pixel bounds covering the crop window given in NDC
+        let crop_min = Point2f::new(
+            cr[0].min(cr[1]).clamp(0.0, 1.0),
+            cr[2].min(cr[3]).clamp(0.0, 1.0),
+        );
+        let crop_max = Point2f::new(
+            cr[0].max(cr[1]).clamp(0.0, 1.0),
+            cr[2].max(cr[3]).clamp(0.0, 1.0),
+        );
+        // NaN coordinates make an empty window as well
+        if !(crop_min.x < crop_max.x && crop_min.y < crop_max.y) {
+            return Err(SceneError::at(
+                SceneErrorKind::InvalidParameter {
+                    name: "cropwindow".to_string(),
+                    message: format!("empty window {:?}", cr),
+                },
+                loc,
+            ));
+        }
+
+        let pixel_bounds = Bounds2i::from_min_max(
+            Point2i::new(
+                (resolution.x as f64 * crop_min.x).ceil() as i32,
+                (resolution.y as f64 * crop_min.y).ceil() as i32,
+            ),
+            Point2i::new(
+                (resolution.x as f64 * crop_max.x).ceil() as i32,
+                (resolution.y as f64 * crop_max.y).ceil() as i32,
+            ),
+        );
+        if pixel_bounds.is_empty() {
+            return Err(SceneError::at(
+                SceneErrorKind::InvalidParameter {
+                    name: "cropwindow".to_string(),
+                    message: format!("degenerate window {:?}", cr),
+                },
+                loc,
+            ));
+        }
+
+        return Ok(pixel_bounds);
+    }
 
-    let resolution = Point2i::new(xresolution, yresolution);
-    let filename = film_entity.parameters.get_string("filename", None);
+    return Ok(full_bounds);
+}
+
+fn build_film(
+    film_entity: &SceneEntity,
+    _filter: Arc<BoxFilter>,
+    camera_transform: &CameraTransform,
+    options: &SceneOptions,
+    scene_directory: Option<&str>,
+) -> Result<Arc<Mutex<dyn Film>>, SceneError> {
+    let (xresolution, yresolution) = match options.resolution {
+        None => {
+            let parameters = &film_entity.parameters;
+            let mut resolution = [0; 2];
+            for (idx, key) in ["xresolution", "yresolution"].into_iter().enumerate() {
+                resolution[idx] = parameters.get_one_integer(key, None)?;
+                if resolution[idx] <= 0 {
+                    return Err(parameters.parameter_error(
+                        key,
+                        format!("expected a positive resolution, got {}", resolution[idx]),
+                    ));
+                }
+            }
+            (resolution[0], resolution[1])
+        }
+        Some(_resolution) => {
+            if _resolution[0] <= 0 || _resolution[1] <= 0 {
+                return Err(SceneError::new(
+                    SceneErrorKind::InvalidParameter {
+                        name: "resolution".to_string(),
+                        message: format!(
+                            "expected a positive resolution, got {} x {}",
+                            _resolution[0], _resolution[1]
+                        ),
+                    },
+                    None,
+                ));
+            }
+            (_resolution[0], _resolution[1])
+        }
+    };
+
+    let resolution = match options.pixel_scale {
+        None => Point2i::new(xresolution, yresolution),
+        Some(_scale) => Point2i::new(
+            ((xresolution as f64 * _scale).round() as i32).max(1),
+            ((yresolution as f64 * _scale).round() as i32).max(1),
+        ),
+    };
+    let pixel_bounds = build_pixel_bounds(
+        &film_entity.parameters,
+        resolution,
+        options,
+        &film_entity.loc,
+    )?;
+
+    let filename = match &options.outfile {
+        None => film_entity.parameters.get_string("filename", None)?,
+        Some(_outfile) => _outfile.clone(),
+    };
 
     match film_entity.name.as_str() {
         "rgb" => {
             let exposure_time = 1.0;
-            let sensor = PixelSensor::create(&film_entity.parameters, exposure_time);
+            let sensor =
+                PixelSensor::create(&film_entity.parameters, exposure_time, scene_directory)?;
 
-            return Arc::new(Mutex::new(RGBFilm::new(
+            return Ok(Arc::new(Mutex::new(RGBFilm::new(
                 resolution,
+                pixel_bounds,
                 &filename,
                 Arc::new(sensor),
                 _filter,
-            )));
+                film_entity.parameters.get_color_space(),
+            ))));
+        }
+        "gbuffer" => {
+            let exposure_time = 1.0;
+            let sensor =
+                PixelSensor::create(&film_entity.parameters, exposure_time, scene_directory)?;
+
+            let camera_from_render = camera_transform.camera_from_render(0.0);
+            let coordinate_system = film_entity
+                .parameters
+                .get_string("coordinatesystem", Some("camera".to_string()))?;
+            let output_from_render = match coordinate_system.as_str() {
+                "camera" => camera_from_render,
+                "world" => camera_transform.world_from_render,
+                _ => {
+                    return Err(SceneError::at(
+                        SceneErrorKind::InvalidParameter {
+                            name: "coordinatesystem".to_string(),
+                            message: format!(
+                                "expected `camera` or `world`, found `{}`",
+                                coordinate_system
+                            ),
+                        },
+                        &film_entity.loc,
+                    ));
+                }
+            };
+
+            return Ok(Arc::new(Mutex::new(GBufferFilm::new(
+                resolution,
+                pixel_bounds,
+                &filename,
+                Arc::new(sensor),
+                _filter,
+                film_entity.parameters.get_color_space(),
+                output_from_render,
+                camera_from_render,
+            ))));
+        }
+        "spectral" => {
+            let exposure_time = 1.0;
+            let sensor =
+                PixelSensor::create(&film_entity.parameters, exposure_time, scene_directory)?;
+
+            let lambda_min = film_entity
+                .parameters
+                .get_one_float("lambdamin", Some(LAMBDA_MIN))?;
+            let lambda_max = film_entity
+                .parameters
+                .get_one_float("lambdamax", Some(LAMBDA_MAX))?;
+            let n_buckets = film_entity
+                .parameters
+                .get_one_integer("nbuckets", Some(16))?;
+            if lambda_min < LAMBDA_MIN || lambda_max > LAMBDA_MAX || lambda_min >= lambda_max {
+                // the defaults are valid, so at least one bound is given
+                let name = if film_entity.parameters.has_float("lambdamin") {
+                    "lambdamin"
+                } else {
+                    "lambdamax"
+                };
+                return Err(film_entity.parameters.parameter_error(
+                    name,
+                    format!(
+                        "invalid wavelength range [{}, {}], expected a range inside [{}, {}]",
+                        lambda_min, lambda_max, LAMBDA_MIN, LAMBDA_MAX
+                    ),
+                ));
+            }
+            if n_buckets <= 0 {
+                return Err(film_entity
+                    .parameters
+                    .parameter_error("nbuckets", "should be positive".to_string()));
+            }
+
+            return Ok(Arc::new(Mutex::new(SpectralFilm::new(
+                resolution,
+                pixel_bounds,
+                &filename,
+                Arc::new(sensor),
+                _filter,
+                film_entity.parameters.get_color_space(),
+                lambda_min,
+                lambda_max,
+                n_buckets as usize,
+            ))));
         }
         _ => {
-            panic!("film `{}` not implemented", film_entity.name);
+            return Err(SceneError::at(
+                SceneErrorKind::UnknownType {
+                    category: "film".to_string(),
+                    name: film_entity.name.clone(),
+                },
+                &film_entity.loc,
+            ));
         }
     };
 }
 
-fn build_camera(camera_entity: &CameraEntity, resolution: Point2i) -> Arc<dyn Camera> {
-    return Arc::new(match camera_entity.name.as_str() {
+fn build_sampler(
+    sampler_entity: &Option<SceneEntity>,
+    options: &SceneOptions,
+) -> Result<Arc<dyn Sampler>, SceneError> {
+    // values given on the command line override the scene file
+    let (sampler_type, parameter_samples, parameter_seed) = match sampler_entity {
+        None => ("independent", None, None),
+        Some(_sampler_entity) => {
+            let parameters = &_sampler_entity.parameters;
+            // the value of _key_ if it is given, at least _min_
+            let get_integer = |key: &str, min: i32, message: &str| {
+                if !parameters.has_integer(key) {
+                    return Ok(None);
+                }
+                return match parameters.get_one_integer(key, None) {
+                    Err(err) => Err(err.or_at(&_sampler_entity.loc)),
+                    Ok(_value) => {
+                        if _value < min {
+                            return Err(parameters
+                                .parameter_error(key, format!("{}, got {}", message, _value))
+                                .or_at(&_sampler_entity.loc));
+                        }
+                        Ok(Some(_value))
+                    }
+                };
+            };
+            (
+                _sampler_entity.name.as_str(),
+                get_integer("pixelsamples", 1, "expected a positive sample count")?
+                    .map(|samples| samples as usize),
+                get_integer("seed", 0, "expected a non-negative seed")?.map(|seed| seed as u64),
+            )
+        }
+    };
+
+    if options.samples_per_pixel == Some(0) {
+        return Err(SceneError::new(
+            SceneErrorKind::InvalidParameter {
+                name: "pixelsamples".to_string(),
+                message: "expected a positive sample count, got 0".to_string(),
+            },
+            None,
+        ));
+    }
+    let mut samples_per_pixel = options
+        .samples_per_pixel
+        .or(parameter_samples)
+        .unwrap_or(32);
+    if options.quick {
+        samples_per_pixel = SceneOptions::quick_samples_per_pixel(samples_per_pixel);
+    }
+    let seed = options.seed.or(parameter_seed).unwrap_or(0);
+
+    return match sampler_type {
+        "independent" => Ok(Arc::new(IndependentSampler::new_from_seed(
+            seed,
+            samples_per_pixel,
+        ))),
+        _ => {
+            println!(
+                "sampler `{}` not implemented, use `independent` instead",
+                sampler_type
+            );
+            Ok(Arc::new(IndependentSampler::new_from_seed(
+                seed,
+                samples_per_pixel,
+            )))
+        }
+    };
+}
+
+fn build_camera(
+    camera_entity: &CameraEntity,
+    resolution: Point2i,
+) -> Result<Arc<dyn Camera>, SceneError> {
+    return Ok(Arc::new(match camera_entity.name.as_str() {
         "perspective" => PerspectiveCamera::new(
             camera_entity.camera_transform,
             camera_entity.parameters.clone(),
             resolution,
-        ),
+        )
+        .map_err(|err| err.or_at(&camera_entity.loc))?,
         _ => {
-            panic!("unknown camera type: `{}`", camera_entity.name);
+            return Err(SceneError::at(
+                SceneErrorKind::UnknownType {
+                    category: "camera".to_string(),
+                    name: camera_entity.name.clone(),
+                },
+                &camera_entity.loc,
+            ));
         }
-    });
+    }));
 }
 
-fn build_lights(light_entities: &Vec<LightEntity>) -> Vec<Arc<dyn Light>> {
+fn build_lights(light_entities: &Vec<LightEntity>) -> Result<Vec<Arc<dyn Light>>, SceneError> {
     let mut lights: Vec<Arc<dyn Light>> = vec![];
 
     for light_entity in light_entities {
@@ -95,7 +475,8 @@ fn build_lights(light_entities: &Vec<LightEntity>) -> Vec<Arc<dyn Light>> {
         match light_type {
             "distant" => {
                 let light =
-                    DistantLight::new(&light_entity.render_from_object, &light_entity.parameters);
+                    DistantLight::new(&light_entity.render_from_object, &light_entity.parameters)
+                        .map_err(|err| err.or_at(&light_entity.loc))?;
 
                 lights.push(Arc::new(light));
             }
@@ -105,37 +486,61 @@ fn build_lights(light_entities: &Vec<LightEntity>) -> Vec<Arc<dyn Light>> {
             }
 
             _ => {
-                panic!("`{}` not implemented", light_type)
+                return Err(SceneError::at(
+                    SceneErrorKind::UnknownType {
+                        category: "light".to_string(),
+                        name: light_type.to_string(),
+                    },
+                    &light_entity.loc,
+                ));
             }
         }
     }
 
-    return lights;
+    return Ok(lights);
 }
 
 fn build_integrator(
-    name: &str,
+    integrator_entity: &Option<SceneEntity>,
     aggregate: Arc<dyn Primitive>,
     camera: Arc<dyn Camera>,
     lights: Vec<Arc<dyn Light>>,
-) -> Arc<dyn Integrator> {
+    color_space: &RGBColorSpace,
+    options: &SceneOptions,
+) -> Result<Arc<dyn Integrator>, SceneError> {
+    let (name, mut max_depth) = match integrator_entity {
+        None => ("ambientocclusion", 5),
+        Some(_integrator_entity) => (
+            _integrator_entity.name.as_str(),
+            _integrator_entity
+                .parameters
+                .get_one_integer("maxdepth", Some(5))
+                .map_err(|err| err.or_at(&_integrator_entity.loc))?
+                .max(1) as usize,
+        ),
+    };
+    if options.quick {
+        max_depth = SceneOptions::quick_max_depth(max_depth);
+    }
+
     println!("Integrator: `{}`", name);
 
-    return match name {
+    let integrator: Arc<dyn Integrator> = match name {
         "ambientocclusion" => Arc::new(AmbientOcclusion::new(
-            COLOR_SPACE.illuminant,
+            color_space.illuminant,
             aggregate,
             camera,
         )),
 
         "randomwalk" => Arc::new(RandomWalkIntegrator::new(
-            COLOR_SPACE.illuminant,
+            color_space.illuminant,
             aggregate,
             camera,
             lights,
+            max_depth,
         )),
 
-        "simplepath" => Arc::new(SimplePath::new(aggregate, camera, lights)),
+        "simplepath" => Arc::new(SimplePath::new(aggregate, camera, lights, max_depth)),
 
         "surfacenormal" => Arc::new(SurfaceNormal::new(aggregate, camera)),
 
@@ -146,19 +551,63 @@ fn build_integrator(
             );
 
             Arc::new(AmbientOcclusion::new(
-                COLOR_SPACE.illuminant,
+                color_space.illuminant,
                 aggregate,
                 camera,
             ))
         }
     };
+
+    return Ok(integrator);
+}
+
+fn get_alpha_texture(
+    parameters: &ParameterDict,
+) -> Result<Option<Arc<dyn FloatTexture>>, SceneError> {
+    if parameters.has_float_texture("alpha") {
+        return Ok(Some(parameters.get_float_texture("alpha")?));
+    }
+
+    let alpha = parameters.get_one_float("alpha", Some(1.0))?;
+    if alpha < 1.0 {
+        return Ok(Some(Arc::new(FloatConstantTexture::new(alpha))));
+    }
+
+    return Ok(None);
+}
+
+// `indices` of a mesh with _n_vertices_ vertices
+fn get_vertex_indices(
+    parameters: &ParameterDict,
+    n_vertices: usize,
+) -> Result<Vec<usize>, SceneError> {
+    let indices = parameters.get_integer_array("indices")?;
+    if let Some(_idx) = indices
+        .iter()
+        .find(|idx| **idx < 0 || **idx as usize >= n_vertices)
+    {
+        return Err(parameters.parameter_error(
+            "indices",
+            format!(
+                "vertex index {} out of range, there are {} vertices",
+                _idx, n_vertices
+            ),
+        ));
+    }
+
+    return Ok(indices.into_iter().map(|idx| idx as usize).collect());
 }
 
 fn split_tokens_into_statements(tokens: &[Token]) -> Vec<usize> {
-    let mut keyword_range = vec![];
+    // tokens before the first directive form a statement of their own, rejected later
+    let mut keyword_range = if tokens.is_empty() { vec![] } else { vec![0] };
     for idx in 0..tokens.len() {
         match tokens[idx].clone() {
             Token::WorldBegin | Token::AttributeEnd | Token::AttributeBegin | Token::Keyword(_) => {
+                // the argument of `ActiveTransform` (StartTime/EndTime/All) is lexed as a keyword
+                if idx == 0 || tokens[idx - 1] == Token::Keyword("ActiveTransform".to_string()) {
+                    continue;
+                }
                 keyword_range.push(idx);
             }
             _ => {}
@@ -169,14 +618,22 @@ fn split_tokens_into_statements(tokens: &[Token]) -> Vec<usize> {
     return keyword_range;
 }
 
+const MAX_TRANSFORMS: usize = 2;
+const START_TRANSFORM_BITS: usize = 1 << 0;
+const END_TRANSFORM_BITS: usize = 1 << 1;
+const ALL_TRANSFORMS_BITS: usize = (1 << MAX_TRANSFORMS) - 1;
+
 #[derive(Clone)]
 struct GraphicsState {
-    current_transform: Transform,
+    current_transform: [Transform; MAX_TRANSFORMS],
+    active_transform_bits: usize,
     current_material: Arc<dyn Material>,
     reverse_orientation: bool,
 
     area_light_name: String,
     area_light_parameter: ParameterDict,
+
+    color_space: &'static RGBColorSpace,
 }
 
 impl GraphicsState {
@@ -184,11 +641,17 @@ impl GraphicsState {
         let constant_texture = SpectrumConstantTexture::new(Arc::new(ConstSpectrum::new(0.5)));
 
         return GraphicsState {
-            current_transform: Transform::identity(),
-            current_material: Arc::new(DiffuseMaterial::new(Arc::new(constant_texture))),
+            current_transform: [Transform::identity(); MAX_TRANSFORMS],
+            active_transform_bits: ALL_TRANSFORMS_BITS,
+            current_material: Arc::new(DiffuseMaterial::new(
+                Arc::new(constant_texture),
+                None,
+                None,
+            )),
             reverse_orientation: false,
             area_light_name: "".to_string(),
             area_light_parameter: ParameterDict::default(),
+            color_space: &*COLOR_SPACE,
         };
     }
 }
@@ -197,19 +660,39 @@ pub struct SceneBuilder {
     graphics_state: GraphicsState,
     pushed_graphics_state: Vec<GraphicsState>,
 
-    named_coordinate_systems: HashMap<String, Transform>,
-    named_texture: HashMap<String, Arc<dyn SpectrumTexture>>,
+    named_coordinate_systems: HashMap<String, [Transform; MAX_TRANSFORMS]>,
+    named_textures: NamedTextures,
+    normal_maps: HashMap<String, Arc<Image>>,
+    named_materials: HashMap<String, Arc<dyn Material>>,
 
     render_from_world: Transform,
+    transform_start_time: f64,
+    transform_end_time: f64,
     primitives: Vec<Arc<dyn Primitive>>,
 
-    integrator_name: String,
+    integrator_entity: Option<SceneEntity>,
+    integrator_color_space: &'static RGBColorSpace,
     film_entity: Option<SceneEntity>,
+    sampler_entity: Option<SceneEntity>,
     camera_entity: Option<CameraEntity>,
     light_entities: Vec<LightEntity>,
     area_lights: Vec<Arc<dyn Light>>,
 
     root: Option<String>,
+    // location of each token of the statement being parsed
+    statement_locs: Vec<FileLoc>,
+
+    imported_files: Vec<ImportedFile>,
+    is_imported: bool,
+
+    // statements are written back by the formatter instead of being executed
+    formatter: Option<SceneFormatter>,
+
+    // number of statements added through the scene API, located by their index
+    api_statements: u32,
+
+    // memory of what the statements created, timings of the parsing and building
+    statistics: SceneStatistics,
 }
 
 impl Default for SceneBuilder {
@@ -219,145 +702,329 @@ impl Default for SceneBuilder {
             pushed_graphics_state: Vec::new(),
 
             named_coordinate_systems: HashMap::new(),
-            named_texture: HashMap::new(),
+            named_textures: NamedTextures::default(),
+            normal_maps: HashMap::new(),
+            named_materials: HashMap::new(),
 
             render_from_world: Transform::identity(),
+            transform_start_time: 0.0,
+            transform_end_time: 1.0,
             primitives: vec![],
 
-            integrator_name: "ambientocclusion".to_string(),
+            integrator_entity: None,
+            integrator_color_space: &*COLOR_SPACE,
             film_entity: None,
+            sampler_entity: None,
             camera_entity: None,
             light_entities: vec![],
             area_lights: vec![],
 
             root: None,
+            statement_locs: vec![],
+
+            imported_files: vec![],
+            is_imported: false,
+
+            formatter: None,
+
+            api_statements: 0,
+
+            statistics: SceneStatistics::default(),
         };
     }
 }
 
 impl SceneBuilder {
-    fn world_area_light_source(&mut self, tokens: &[Token]) {
+    fn error_at(&self, token_idx: usize, kind: SceneErrorKind) -> SceneError {
+        return SceneError::at(kind, &self.statement_locs[token_idx]);
+    }
+
+    // _tokens_ is the directive followed by exactly _count_ arguments
+    fn check_argument_count(&self, tokens: &[Token], count: usize) -> Result<(), SceneError> {
+        if tokens.len() != count + 1 {
+            return Err(self.error_at(
+                0,
+                SceneErrorKind::WrongArgumentCount {
+                    directive: tokens[0].directive_name(),
+                    expected: count,
+                    found: tokens.len() - 1,
+                },
+            ));
+        }
+
+        return Ok(());
+    }
+
+    fn string_at(&self, tokens: &[Token], idx: usize) -> Result<String, SceneError> {
+        return match tokens.get(idx) {
+            None => Err(self.error_at(
+                tokens.len() - 1,
+                SceneErrorKind::UnexpectedToken {
+                    expected: format!("a quoted string after {}", tokens[tokens.len() - 1]),
+                    found: "nothing".to_string(),
+                },
+            )),
+            Some(_token) => _token
+                .convert_to_string()
+                .map_err(|kind| self.error_at(idx, kind)),
+        };
+    }
+
+    // the _count_ numbers following the directive
+    fn floats_at(&self, tokens: &[Token], count: usize) -> Result<Vec<f64>, SceneError> {
+        self.check_argument_count(tokens, count)?;
+
+        let mut floats = vec![];
+        for idx in 1..tokens.len() {
+            floats.push(
+                tokens[idx]
+                    .convert_to_float()
+                    .map_err(|kind| self.error_at(idx, kind))?,
+            );
+        }
+
+        return Ok(floats);
+    }
+
+    // parameter list starting at _tokens[start]_
+    fn parameters_from(
+        &self,
+        tokens: &[Token],
+        start: usize,
+        dir_path: Option<String>,
+    ) -> Result<ParameterDict, SceneError> {
+        let start = start.min(tokens.len());
+
+        return ParameterDict::build_parameter_dict(
+            &tokens[start..],
+            &self.statement_locs[start..],
+            &self.named_textures,
+            dir_path,
+            self.graphics_state.color_space,
+        );
+    }
+
+    fn world_active_transform(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0].clone() == Token::Keyword("ActiveTransform".to_string()));
+        self.check_argument_count(tokens, 1)?;
+
+        self.graphics_state.active_transform_bits = match &tokens[1] {
+            Token::Keyword(kw) if kw == "StartTime" => START_TRANSFORM_BITS,
+            Token::Keyword(kw) if kw == "EndTime" => END_TRANSFORM_BITS,
+            Token::Keyword(kw) if kw == "All" => ALL_TRANSFORMS_BITS,
+            token => {
+                return Err(self.error_at(
+                    1,
+                    SceneErrorKind::UnexpectedToken {
+                        expected: "`StartTime`, `EndTime` or `All`".to_string(),
+                        found: token.to_string(),
+                    },
+                ));
+            }
+        };
+
+        return Ok(());
+    }
+
+    fn world_area_light_source(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("AreaLightSource".to_string()));
 
-        let name = tokens[1].convert_to_string();
+        let name = self.string_at(tokens, 1)?;
         if name != "diffuse" {
-            panic!("only `diffuse` Area Light is supported");
+            return Err(self.error_at(
+                1,
+                SceneErrorKind::UnknownType {
+                    category: "area light".to_string(),
+                    name,
+                },
+            ));
         }
 
         self.graphics_state.area_light_name = name;
-        self.graphics_state.area_light_parameter = ParameterDict::build_parameter_dict(
-            &tokens[2..],
-            &self.named_texture,
-            self.root.clone(),
-        );
+        self.graphics_state.area_light_parameter =
+            self.parameters_from(tokens, 2, self.root.clone())?;
+
+        return Ok(());
     }
 
-    fn world_coord_sys_transform(&mut self, tokens: &[Token]) {
+    fn world_coord_sys_transform(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("CoordSysTransform".to_string()));
-        debug_assert!(tokens.len() == 2);
+        self.check_argument_count(tokens, 1)?;
 
-        let coord_sys_name = {
-            let token = tokens[1].clone();
-            match token {
-                Token::String(kw) => kw,
-                _ => {
-                    panic!("expect Token::String, get `{:?}`", token);
-                }
-            }
-        };
+        let coord_sys_name = self.string_at(tokens, 1)?;
 
         self.graphics_state.current_transform =
             match self.named_coordinate_systems.get(&coord_sys_name) {
                 None => {
-                    panic!("couldn't find key {}", coord_sys_name);
+                    return Err(
+                        self.error_at(1, SceneErrorKind::UndefinedCoordinateSystem(coord_sys_name))
+                    );
                 }
-                Some(transform) => *transform,
+                Some(transforms) => *transforms,
             };
+
+        return Ok(());
     }
 
-    fn world_light_source(&mut self, tokens: &[Token]) {
+    fn world_light_source(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("LightSource".to_string()));
 
-        let light_source_type = tokens[1].convert_to_string();
+        let light_source_type = self.string_at(tokens, 1)?;
+
+        if self.ctm_is_animated() {
+            println!("Animated lights aren't supported. Using the start transform.");
+        }
 
         let light_entity = LightEntity {
             name: light_source_type,
-            parameters: ParameterDict::build_parameter_dict(
-                &tokens[2..],
-                &self.named_texture,
-                None,
-            ),
+            parameters: self.parameters_from(tokens, 2, None)?,
             render_from_object: self.render_from_object(),
+            loc: self.statement_locs[0].clone(),
         };
 
         self.light_entities.push(light_entity);
+
+        return Ok(());
+    }
+
+    fn get_normal_map(
+        &mut self,
+        parameters: &ParameterDict,
+    ) -> Result<Option<Arc<Image>>, SceneError> {
+        let filename = parameters.get_string("normalmap", Some("".to_string()))?;
+        if filename == "" {
+            return Ok(None);
+        }
+
+        // normal maps are shared by all materials referring to the same file
+        if let Some(_normal_map) = self.normal_maps.get(&filename) {
+            return Ok(Some(_normal_map.clone()));
+        }
+
+        let image = match Image::read_from_file_with_encoding(&filename, &LinearColorEncoding {}) {
+            Err(message) => {
+                return Err(parameters.parameter_error(
+                    "normalmap",
+                    format!("couldn't read `{}`: {}", filename, message),
+                ));
+            }
+            Ok(_image) => _image,
+        };
+        add_memory(MemoryCategory::Textures, image_memory(&image));
+
+        let normal_map = Arc::new(image);
+        self.normal_maps.insert(filename, normal_map.clone());
+
+        return Ok(Some(normal_map));
     }
-    fn world_material(&mut self, tokens: &[Token]) {
+
+    fn world_material(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0] == Token::Keyword("Material".to_string()));
 
-        let parameter_dict = ParameterDict::build_parameter_dict(
-            &tokens[2..],
-            &self.named_texture,
-            self.root.clone(),
-        );
+        let material_type = self.string_at(tokens, 1)?;
+        let parameter_dict = self.parameters_from(tokens, 2, self.root.clone())?;
+
+        let normal_map = self.get_normal_map(&parameter_dict)?;
+        self.graphics_state.current_material = create_material(
+            &material_type,
+            &parameter_dict,
+            normal_map,
+            &self.named_materials,
+        )?;
+
+        return Ok(());
+    }
+
+    fn world_make_named_material(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0] == Token::Keyword("MakeNamedMaterial".to_string()));
+
+        let material_name = self.string_at(tokens, 1)?;
+        if self.named_materials.contains_key(&material_name) {
+            return Err(self.error_at(1, SceneErrorKind::RedefinedNamedMaterial(material_name)));
+        }
+
+        let parameter_dict = self.parameters_from(tokens, 2, self.root.clone())?;
+        if !parameter_dict.has_string("type") {
+            return Err(self.error_at(0, SceneErrorKind::MissingParameter("type".to_string())));
+        }
+
+        let material_type = parameter_dict.get_string("type", None)?;
+        let normal_map = self.get_normal_map(&parameter_dict)?;
+        let material = create_material(
+            &material_type,
+            &parameter_dict,
+            normal_map,
+            &self.named_materials,
+        )?;
 
-        let material_type = tokens[1].convert_to_string();
-        self.graphics_state.current_material = create_material(&material_type, &parameter_dict);
+        self.named_materials.insert(material_name, material);
+
+        return Ok(());
     }
 
-    fn world_rotate(&mut self, tokens: &[Token]) {
-        debug_assert!(tokens.len() == 5);
+    fn world_named_material(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0] == Token::Keyword("NamedMaterial".to_string()));
+        self.check_argument_count(tokens, 1)?;
+
+        let material_name = self.string_at(tokens, 1)?;
+        self.graphics_state.current_material = match self.named_materials.get(&material_name) {
+            None => {
+                return Err(self.error_at(1, SceneErrorKind::UndefinedNamedMaterial(material_name)));
+            }
+            Some(_material) => _material.clone(),
+        };
+
+        return Ok(());
+    }
+
+    fn world_rotate(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Rotate".to_string()));
 
-        let floats = tokens[1..]
-            .into_iter()
-            .map(|t| t.convert_to_float())
-            .collect::<Vec<f64>>();
+        let floats = self.floats_at(tokens, 4)?;
 
-        debug_assert!(floats.len() == 4);
+        self.apply_transform(Transform::rotate(
+            floats[0], floats[1], floats[2], floats[3],
+        ));
 
-        self.graphics_state.current_transform = self.graphics_state.current_transform
-            * Transform::rotate(floats[0], floats[1], floats[2], floats[3]);
+        return Ok(());
     }
 
-    fn world_scale(&mut self, tokens: &[Token]) {
-        debug_assert!(tokens.len() == 4);
+    fn world_scale(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Scale".to_string()));
 
-        let floats = tokens[1..]
-            .into_iter()
-            .map(|t| t.convert_to_float())
-            .collect::<Vec<f64>>();
-        debug_assert!(floats.len() == 4);
+        let floats = self.floats_at(tokens, 3)?;
 
-        self.graphics_state.current_transform = self.graphics_state.current_transform
-            * Transform::scale(floats[0], floats[1], floats[2]);
+        self.apply_transform(Transform::scale(floats[0], floats[1], floats[2]));
+
+        return Ok(());
     }
 
-    fn world_shape(&mut self, tokens: &[Token]) {
+    fn world_shape(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Shape".to_string()));
         let material = self.graphics_state.current_material.clone();
 
-        let parameters = ParameterDict::build_parameter_dict(
-            &tokens[2..],
-            &self.named_texture,
-            self.root.clone(),
-        );
+        let shape_name = self.string_at(tokens, 1)?;
+        let parameters = self.parameters_from(tokens, 2, self.root.clone())?;
 
-        let render_from_object = self.render_from_object();
+        // animated shapes are built in object space and moved by an _AnimatedPrimitive_
+        let is_animated = self.ctm_is_animated();
+        let render_from_object = if is_animated {
+            Transform::identity()
+        } else {
+            self.render_from_object()
+        };
         let object_from_render = render_from_object.inverse();
 
         let reverse_orientation = self.graphics_state.reverse_orientation;
 
-        let shape_name = tokens[1].convert_to_string();
+        // `face_indices` of a `plymesh`, one per shape
+        let mut face_indices: Vec<usize> = vec![];
 
         let shapes: Vec<Arc<dyn Shape>> = match shape_name.as_str() {
             "loopsubdiv" => {
-                let levels = parameters.get_one_integer("levels", Some(3)) as usize;
-                let indices_i32 = parameters.get_integer_array("indices");
-                let indices = indices_i32.into_par_iter().map(|x| x as usize).collect();
-
-                let points = parameters.get_point3_array("P");
+                let levels = parameters.get_one_integer("levels", Some(3))?.max(0) as usize;
+                let points = parameters.get_point3_array("P")?;
+                let indices = get_vertex_indices(&parameters, points.len())?;
 
                 loop_subdivide(
                     &render_from_object,
@@ -369,10 +1036,10 @@ impl SceneBuilder {
             }
 
             "sphere" => {
-                let radius = parameters.get_one_float("radius", Some(1.0));
-                let zmin = parameters.get_one_float("zmin", Some(-radius));
-                let zmax = parameters.get_one_float("zmax", Some(radius));
-                let phimax = parameters.get_one_float("phimax", Some(360.0));
+                let radius = parameters.get_one_float("radius", Some(1.0))?;
+                let zmin = parameters.get_one_float("zmin", Some(-radius))?;
+                let zmax = parameters.get_one_float("zmax", Some(radius))?;
+                let phimax = parameters.get_one_float("phimax", Some(360.0))?;
 
                 vec![Arc::new(Sphere::new(
                     render_from_object,
@@ -386,11 +1053,17 @@ impl SceneBuilder {
             }
 
             "trianglemesh" => {
-                let indices = parameters.get_integer_array("indices");
-                let points = parameters.get_point3_array("P");
+                let points = parameters.get_point3_array("P")?;
+                let indices = get_vertex_indices(&parameters, points.len())?;
+                if indices.len() % 3 != 0 {
+                    return Err(parameters.parameter_error(
+                        "indices",
+                        format!("{} values supplied, not a multiple of 3", indices.len()),
+                    ));
+                }
                 let normals = parameters.get_normal3_array("N");
                 let uv = if parameters.has_point2("uv") {
-                    parameters.get_point2_array("uv")
+                    parameters.get_point2_array("uv")?
                 } else {
                     vec![]
                 };
@@ -399,7 +1072,7 @@ impl SceneBuilder {
                     &render_from_object,
                     reverse_orientation,
                     points,
-                    indices.into_par_iter().map(|x| x as usize).collect(),
+                    indices,
                     normals,
                     uv,
                 );
@@ -408,8 +1081,23 @@ impl SceneBuilder {
             }
 
             "plymesh" => {
-                let file_path = &parameters.get_string("filename", None);
-                let tri_quad_mesh = read_ply(file_path);
+                let file_path = parameters.get_string("filename", None)?;
+                let mut tri_quad_mesh = match read_ply(&file_path) {
+                    Err(message) => {
+                        return Err(self.error_at(
+                            0,
+                            SceneErrorKind::Io {
+                                filename: file_path,
+                                message,
+                            },
+                        ));
+                    }
+                    Ok(_mesh) => _mesh,
+                };
+                tri_quad_mesh.convert_to_only_triangles();
+                if parameters.has_string("materials") || parameters.has_float("alphas") {
+                    face_indices = std::mem::take(&mut tri_quad_mesh.face_indices);
+                }
 
                 if tri_quad_mesh.tri_indices.len() > 0 {
                     let triangle_mesh = TriangleMesh::new(
@@ -426,53 +1114,236 @@ impl SceneBuilder {
                 }
             }
 
+            "curve" => {
+                // lights can't sample points on curves
+                if self.graphics_state.area_light_name != "" {
+                    return Err(self.error_at(
+                        1,
+                        SceneErrorKind::Unsupported("curves as area lights".to_string()),
+                    ));
+                }
+
+                create_curves(
+                    &render_from_object,
+                    &object_from_render,
+                    reverse_orientation,
+                    &parameters,
+                )?
+            }
+
             "disk" => {
                 println!("ignore `{}` for the moment", shape_name);
                 vec![]
             }
 
             _ => {
-                panic!("unknown Shape: `{}`", shape_name);
+                return Err(self.error_at(
+                    1,
+                    SceneErrorKind::UnknownType {
+                        category: "shape".to_string(),
+                        name: shape_name,
+                    },
+                ));
             }
         };
 
+        let alpha = get_alpha_texture(&parameters)?;
+        let (face_materials, face_alphas) = self.get_face_attributes(&parameters, &face_indices)?;
+
+        let shape_material = |shape_idx: usize| -> Arc<dyn Material> {
+            if face_materials.is_empty() {
+                return material.clone();
+            }
+            return face_materials[face_indices[shape_idx]].clone();
+        };
+        let shape_alpha = |shape_idx: usize| -> Option<Arc<dyn FloatTexture>> {
+            if face_alphas.is_empty() {
+                return alpha.clone();
+            }
+            return face_alphas[face_indices[shape_idx]].clone();
+        };
+
+        if is_animated {
+            if self.graphics_state.area_light_name != "" {
+                return Err(self.error_at(
+                    0,
+                    SceneErrorKind::Unsupported(
+                        "area lights with animated transformations".to_string(),
+                    ),
+                ));
+            }
+
+            let mut shape_primitives: Vec<Arc<dyn Primitive>> = vec![];
+            for (shape_idx, shape) in shapes.into_iter().enumerate() {
+                let primitive: Arc<dyn Primitive> = match shape_alpha(shape_idx) {
+                    None => Arc::new(SimplePrimitive::new(shape, shape_material(shape_idx))),
+                    Some(_alpha) => Arc::new(GeometricPrimitive::new(
+                        shape,
+                        shape_material(shape_idx),
+                        None,
+                        Some(_alpha),
+                    )),
+                };
+                shape_primitives.push(primitive);
+            }
+
+            let primitive: Arc<dyn Primitive> = match shape_primitives.len() {
+                0 => {
+                    return Ok(());
+                }
+                1 => shape_primitives[0].clone(),
+                _ => Arc::new(BVHAggregate::new(shape_primitives)),
+            };
+
+            self.primitives.push(Arc::new(AnimatedPrimitive::new(
+                primitive,
+                self.animated_render_from_object(),
+            )));
+
+            return Ok(());
+        }
+
         if self.graphics_state.area_light_name == "" {
-            for shape in shapes {
-                self.primitives
-                    .push(Arc::new(SimplePrimitive::new(shape, material.clone())));
+            for (shape_idx, shape) in shapes.into_iter().enumerate() {
+                let shape_alpha = shape_alpha(shape_idx);
+                if shape_alpha.is_some() {
+                    self.primitives.push(Arc::new(GeometricPrimitive::new(
+                        shape,
+                        shape_material(shape_idx),
+                        None,
+                        shape_alpha,
+                    )));
+                } else {
+                    self.primitives.push(Arc::new(SimplePrimitive::new(
+                        shape,
+                        shape_material(shape_idx),
+                    )));
+                }
             }
         } else {
-            for shape in shapes {
-                let area_light = Arc::new(DiffuseAreaLight::new(
-                    self.render_from_object(),
-                    &self.graphics_state.area_light_parameter,
-                    shape.clone(),
-                ));
+            for (shape_idx, shape) in shapes.into_iter().enumerate() {
+                let shape_alpha = shape_alpha(shape_idx);
+                let area_light = Arc::new(
+                    DiffuseAreaLight::new(
+                        self.render_from_object(),
+                        &self.graphics_state.area_light_parameter,
+                        shape.clone(),
+                        shape_alpha.clone(),
+                    )
+                    .map_err(|err| err.or_at(&self.statement_locs[0]))?,
+                );
 
                 self.area_lights.push(area_light.clone());
                 self.primitives.push(Arc::new(GeometricPrimitive::new(
                     shape,
-                    material.clone(),
-                    area_light,
+                    shape_material(shape_idx),
+                    Some(area_light),
+                    shape_alpha,
                 )));
             }
         }
+
+        return Ok(());
+    }
+
+    // Materials and alpha of each face of a `plymesh`, given by the named materials of
+    // "string materials" and the values of "float alphas", indexed by `face_indices`.
+    // Both are empty when the shape uses the current material and "alpha". These two
+    // parameters are extensions of the scene format (see README.md), pbrt-v4 has no
+    // per-face attributes.
+    fn get_face_attributes(
+        &self,
+        parameters: &ParameterDict,
+        face_indices: &[usize],
+    ) -> Result<(Vec<Arc<dyn Material>>, Vec<Option<Arc<dyn FloatTexture>>>), SceneError> {
+        let mut face_materials: Vec<Arc<dyn Material>> = vec![];
+        if parameters.has_string("materials") {
+            for material_name in parameters.get_string_array("materials")? {
+                match self.named_materials.get(&material_name) {
+                    None => {
+                        return Err(
+                            self.error_at(0, SceneErrorKind::UndefinedNamedMaterial(material_name))
+                        );
+                    }
+                    Some(_material) => face_materials.push(_material.clone()),
+                }
+            }
+        }
+
+        let face_alphas: Vec<Option<Arc<dyn FloatTexture>>> = if parameters.has_float("alphas") {
+            parameters
+                .get_float_array("alphas")?
+                .into_iter()
+                .map(|alpha| -> Option<Arc<dyn FloatTexture>> {
+                    if alpha < 1.0 {
+                        return Some(Arc::new(FloatConstantTexture::new(alpha)));
+                    }
+                    return None;
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let max_face_index = face_indices.iter().max().copied();
+        for (name, count) in [
+            ("materials", face_materials.len()),
+            ("alphas", face_alphas.len()),
+        ] {
+            if count == 0 {
+                continue;
+            }
+
+            let message = match max_face_index {
+                None => "the shape is not a PLY mesh with `face_indices`".to_string(),
+                Some(_max_face_index) => {
+                    if _max_face_index < count {
+                        continue;
+                    }
+                    format!(
+                        "face index {} out of range, {} values supplied",
+                        _max_face_index, count
+                    )
+                }
+            };
+
+            return Err(self.error_at(
+                0,
+                SceneErrorKind::InvalidParameter {
+                    name: name.to_string(),
+                    message,
+                },
+            ));
+        }
+
+        return Ok((face_materials, face_alphas));
     }
 
-    fn world_texture(&mut self, tokens: &[Token]) {
+    fn world_texture(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Texture".to_string()));
 
-        let texture_name = tokens[1].convert_to_string();
-        let color_type = tokens[2].convert_to_string();
-        let texture_type = tokens[3].convert_to_string();
+        let texture_name = self.string_at(tokens, 1)?;
+        let color_type = self.string_at(tokens, 2)?;
+        let texture_type = self.string_at(tokens, 3)?;
+
+        let redefined = match color_type.as_str() {
+            "spectrum" => self
+                .named_textures
+                .spectrum_textures
+                .contains_key(&texture_name),
+            "float" => self
+                .named_textures
+                .float_textures
+                .contains_key(&texture_name),
+            _ => false,
+        };
+        if redefined {
+            return Err(self.error_at(1, SceneErrorKind::RedefinedTexture(texture_name)));
+        }
 
         match color_type.as_str() {
             "spectrum" => {
-                let parameter_dict = ParameterDict::build_parameter_dict(
-                    &tokens[4..],
-                    &self.named_texture,
-                    self.root.clone(),
-                );
+                let parameter_dict = self.parameters_from(tokens, 4, self.root.clone())?;
 
                 //TODO: SpectrumType is missing in creating SpectrumTexture
                 let texture = create_spectrum_texture(
@@ -480,73 +1351,155 @@ impl SceneBuilder {
                     &self.render_from_object(),
                     &parameter_dict,
                     SpectrumType::Albedo,
-                );
+                )?;
                 // TODO: hardcode all SpectrumType as Albedo for the moment
 
-                self.named_texture.insert(texture_name, texture);
+                self.named_textures
+                    .spectrum_textures
+                    .insert(texture_name, texture);
+            }
+            "float" => {
+                let parameter_dict = self.parameters_from(tokens, 4, self.root.clone())?;
+
+                let texture = create_float_texture(
+                    &texture_type,
+                    &self.render_from_object(),
+                    &parameter_dict,
+                )?;
+
+                self.named_textures
+                    .float_textures
+                    .insert(texture_name, texture);
             }
             _ => {
-                panic!(
-                    "unknown color type and texture type: ({}, {})",
-                    color_type, texture_type
-                );
+                return Err(self.error_at(
+                    2,
+                    SceneErrorKind::UnexpectedToken {
+                        expected: "\"spectrum\" or \"float\"".to_string(),
+                        found: tokens[2].to_string(),
+                    },
+                ));
             }
         };
+
+        return Ok(());
     }
 
-    fn world_transform(&mut self, tokens: &[Token]) {
-        debug_assert!(tokens.len() == 2);
+    fn world_transform(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Transform".to_string()));
 
-        let floats = match tokens[1].clone() {
-            Token::List(numbers) => {
-                assert_eq!(tokens.len(), 2);
-
-                numbers
-                    .into_iter()
-                    .map(|x| x.parse::<f64>().unwrap())
-                    .collect::<Vec<f64>>()
+        let floats = match tokens.get(1) {
+            Some(Token::List(numbers)) => {
+                self.check_argument_count(tokens, 1)?;
+
+                convert_string::<f64>(numbers).map_err(|value| {
+                    self.error_at(
+                        1,
+                        SceneErrorKind::UnexpectedToken {
+                            expected: "a number".to_string(),
+                            found: format!("`{}`", value),
+                        },
+                    )
+                })?
             }
-            _ => tokens[1..]
-                .into_iter()
-                .map(|t| t.convert_to_float())
-                .collect::<Vec<f64>>(),
+            _ => self.floats_at(tokens, 16)?,
         };
 
-        debug_assert!(floats.len() == 16);
+        if floats.len() != 16 {
+            return Err(self.error_at(
+                0,
+                SceneErrorKind::WrongArgumentCount {
+                    directive: "Transform".to_string(),
+                    expected: 16,
+                    found: floats.len(),
+                },
+            ));
+        }
 
-        self.graphics_state.current_transform =
-            Transform::from_matrix(SquareMatrix::<4>::from_array(&floats)).transpose();
+        self.set_transform(
+            Transform::from_matrix(SquareMatrix::<4>::from_array(&floats)).transpose(),
+        );
+
+        return Ok(());
+    }
+
+    fn world_transform_times(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0].clone() == Token::Keyword("TransformTimes".to_string()));
+
+        let floats = self.floats_at(tokens, 2)?;
+
+        self.transform_start_time = floats[0];
+        self.transform_end_time = floats[1];
+
+        return Ok(());
     }
 
-    fn world_translate(&mut self, tokens: &[Token]) {
+    fn world_translate(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Translate".to_string()));
-        debug_assert!(tokens.len() == 4);
 
-        let floats = tokens[1..]
-            .into_iter()
-            .map(|t| t.convert_to_float())
-            .collect::<Vec<f64>>();
+        let floats = self.floats_at(tokens, 3)?;
+
+        self.apply_transform(Transform::translate(floats[0], floats[1], floats[2]));
 
-        self.graphics_state.current_transform = self.graphics_state.current_transform
-            * Transform::translate(floats[0], floats[1], floats[2]);
+        return Ok(());
     }
 
-    fn parse_statement(&mut self, tokens: &[Token]) {
+    fn parse_statement(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        let result = self.measured_directive(tokens);
+
+        // names defined by an imported file are known once the file is merged
+        return match result {
+            Err(SceneError {
+                kind:
+                    SceneErrorKind::UndefinedTexture(_) | SceneErrorKind::UndefinedNamedMaterial(_),
+                ..
+            }) if !self.imported_files.is_empty() => {
+                self.merge_imported_files()?;
+                self.measured_directive(tokens)
+            }
+            _ => result,
+        };
+    }
+
+    // the memory of what a failed directive created isn't kept
+    fn measured_directive(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        let (result, memory) = measure_memory(|| self.parse_directive(tokens));
+        if result.is_ok() {
+            self.statistics.add_measured_memory(&memory);
+        }
+
+        return result;
+    }
+
+    fn parse_directive(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         let first_token = tokens[0].clone();
 
+        // an imported file only adds to the world
+        if self.is_imported {
+            let name = first_token.directive_name();
+            match name.as_str() {
+                "Camera" | "Film" | "Integrator" | "Sampler" | "WorldBegin" => {
+                    return Err(self.error_at(
+                        0,
+                        SceneErrorKind::Unsupported(format!("`{}` in an imported file", name)),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
         match first_token {
             Token::AttributeBegin => {
-                assert_eq!(tokens.len(), 1);
+                self.check_argument_count(tokens, 0)?;
                 self.pushed_graphics_state.push(self.graphics_state.clone());
             }
 
             Token::AttributeEnd => {
-                assert_eq!(tokens.len(), 1);
+                self.check_argument_count(tokens, 0)?;
 
                 match self.pushed_graphics_state.pop() {
                     None => {
-                        panic!("unmatched `AttributeEnd` encountered.");
+                        return Err(self.error_at(0, SceneErrorKind::UnmatchedAttributeEnd));
                     }
                     Some(_graphics_state) => {
                         self.graphics_state = _graphics_state;
@@ -556,116 +1509,254 @@ impl SceneBuilder {
 
             Token::WorldBegin => {
                 // WorldBegin
-                self.graphics_state.current_transform = Transform::identity();
+                self.check_argument_count(tokens, 0)?;
+                self.graphics_state.current_transform = [Transform::identity(); MAX_TRANSFORMS];
+                self.graphics_state.active_transform_bits = ALL_TRANSFORMS_BITS;
                 self.named_coordinate_systems
                     .insert("world".to_string(), self.graphics_state.current_transform);
             }
 
             Token::Keyword(keyword) => {
-                match keyword.as_str() {
-                    "AreaLightSource" => {
-                        self.world_area_light_source(tokens);
+                return match keyword.as_str() {
+                    "ActiveTransform" => self.world_active_transform(tokens),
+
+                    "AreaLightSource" => self.world_area_light_source(tokens),
+
+                    "Camera" => self.option_camera(tokens),
+
+                    "ColorSpace" => {
+                        self.check_argument_count(tokens, 1)?;
+                        let color_space_name = self.string_at(tokens, 1)?;
+                        match RGBColorSpace::get_named(&color_space_name) {
+                            None => Err(self.error_at(
+                                1,
+                                SceneErrorKind::UnknownType {
+                                    category: "color space".to_string(),
+                                    name: color_space_name,
+                                },
+                            )),
+                            Some(_color_space) => {
+                                self.graphics_state.color_space = _color_space;
+                                Ok(())
+                            }
+                        }
                     }
 
-                    "Camera" => {
-                        self.option_camera(tokens);
-                    }
+                    "CoordSysTransform" => self.world_coord_sys_transform(tokens),
 
-                    "CoordSysTransform" => {
-                        self.world_coord_sys_transform(tokens);
-                    }
+                    "Film" => self.option_film(tokens),
 
-                    "Film" => {
-                        self.option_film(tokens);
-                    }
+                    "Import" => self.import(tokens),
 
-                    "Include" => {
-                        assert_eq!(tokens.len(), 2);
-                        let included_filename = tokens[1].convert_to_string();
-                        self.parse_file(&included_filename);
-                    }
+                    "Include" => self.include(tokens),
 
-                    "Integrator" => {
-                        self.option_integrator(tokens);
-                    }
+                    "Integrator" => self.option_integrator(tokens),
 
-                    "LightSource" => {
-                        self.world_light_source(tokens);
-                    }
+                    "LightSource" => self.world_light_source(tokens),
 
-                    "LookAt" => {
-                        self.option_look_at(tokens);
-                    }
+                    "LookAt" => self.option_look_at(tokens),
 
-                    "Material" => {
-                        self.world_material(tokens);
-                    }
+                    "MakeNamedMaterial" => self.world_make_named_material(tokens),
+
+                    "Material" => self.world_material(tokens),
+
+                    "NamedMaterial" => self.world_named_material(tokens),
 
                     "ReverseOrientation" => {
+                        self.check_argument_count(tokens, 0)?;
                         self.graphics_state.reverse_orientation =
                             !self.graphics_state.reverse_orientation;
+                        Ok(())
                     }
 
-                    "Rotate" => {
-                        self.world_rotate(tokens);
-                    }
+                    "Rotate" => self.world_rotate(tokens),
 
-                    "Scale" => {
-                        self.world_scale(tokens);
-                    }
+                    "Scale" => self.world_scale(tokens),
 
-                    "Shape" => {
-                        self.world_shape(tokens);
-                    }
+                    "Shape" => self.world_shape(tokens),
 
-                    "Texture" => {
-                        self.world_texture(tokens);
-                    }
+                    "Texture" => self.world_texture(tokens),
 
-                    "Transform" => {
-                        self.world_transform(tokens);
-                    }
+                    "Transform" => self.world_transform(tokens),
 
-                    "Translate" => {
-                        self.world_translate(tokens);
-                    }
+                    "TransformTimes" => self.world_transform_times(tokens),
+
+                    "Translate" => self.world_translate(tokens),
+
+                    "Sampler" => self.option_sampler(tokens),
 
-                    "MakeNamedMaterial" | "NamedMaterial" | "Sampler" | "PixelFilter" => {
+                    "PixelFilter" => {
                         println!("`{}` not implemented", keyword);
+                        Ok(())
                     }
 
-                    _ => {
-                        panic!("unrecognized keyword: `{:?}`", keyword);
-                    }
+                    _ => Err(self.error_at(0, SceneErrorKind::UnknownDirective(keyword.clone()))),
                 };
             }
             _ => {
-                panic!("unrecognized Token: `{:?}`", first_token);
+                return Err(self.error_at(
+                    0,
+                    SceneErrorKind::UnexpectedToken {
+                        expected: "a directive".to_string(),
+                        found: first_token.to_string(),
+                    },
+                ));
             }
         }
+
+        return Ok(());
     }
 
-    fn option_camera(&mut self, tokens: &[Token]) {
-        debug_assert!(tokens[0].clone() == Token::Keyword("Camera".to_string()));
+    // parse the file named by the argument of `Include` or `Import` with this builder
+    fn parse_nested_file(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        self.check_argument_count(tokens, 1)?;
 
-        let camera_type = match tokens[1].clone() {
-            Token::String(str) => str,
-            _ => {
-                panic!("expect Token::String");
-            }
+        let nested_filename = self.string_at(tokens, 1)?;
+
+        // parsing the nested file replaces _statement_locs_
+        let directive_loc = self.statement_locs[0].clone();
+        let filename_loc = self.statement_locs[1].clone();
+
+        return self
+            .parse_file(&nested_filename)
+            .map_err(|error| nested_file_error(error, &directive_loc, &filename_loc));
+    }
+
+    fn include(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0].clone() == Token::Keyword("Include".to_string()));
+
+        return self.parse_nested_file(tokens);
+    }
+
+    // builder parsing an imported file: it starts from the current graphics state and
+    // named objects but shares nothing with this builder until merged back
+    fn copy_for_import(&self) -> SceneBuilder {
+        return SceneBuilder {
+            graphics_state: self.graphics_state.clone(),
+            named_coordinate_systems: self.named_coordinate_systems.clone(),
+            named_textures: self.named_textures.clone(),
+            normal_maps: self.normal_maps.clone(),
+            named_materials: self.named_materials.clone(),
+            render_from_world: self.render_from_world,
+            transform_start_time: self.transform_start_time,
+            transform_end_time: self.transform_end_time,
+            root: self.root.clone(),
+            is_imported: true,
+            ..SceneBuilder::default()
         };
+    }
 
-        let parameter_dict = ParameterDict::build_parameter_dict(
-            &tokens[2..],
-            &self.named_texture,
-            self.root.clone(),
-        );
+    fn import(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0].clone() == Token::Keyword("Import".to_string()));
+        self.check_argument_count(tokens, 1)?;
+
+        let imported_filename = self.string_at(tokens, 1)?;
+
+        let job = Arc::new(ImportJob {
+            filename: imported_filename,
+            builder: Mutex::new(Some(self.copy_for_import())),
+            result: Mutex::new(None),
+            done: Condvar::new(),
+        });
+        let spawned_job = job.clone();
+        rayon::spawn(move || spawned_job.run());
+
+        self.imported_files.push(ImportedFile {
+            loc: self.statement_locs[0].clone(),
+            filename_loc: self.statement_locs[1].clone(),
+            job,
+        });
+
+        return Ok(());
+    }
+
+    // wait for the imported files and take over their shapes, lights, named materials
+    // and textures, in the order of the `Import` directives
+    fn merge_imported_files(&mut self) -> Result<(), SceneError> {
+        for imported_file in std::mem::take(&mut self.imported_files) {
+            let import_loc = imported_file.loc;
+            let builder = imported_file.job.wait().map_err(|error| {
+                nested_file_error(error, &import_loc, &imported_file.filename_loc)
+            })?;
+
+            self.statistics.merge(&builder.statistics);
+            self.primitives.extend(builder.primitives);
+            self.area_lights.extend(builder.area_lights);
+            self.light_entities.extend(builder.light_entities);
+
+            for (name, material) in builder.named_materials {
+                match self.named_materials.get(&name) {
+                    None => {
+                        self.named_materials.insert(name, material);
+                    }
+                    Some(_material) => {
+                        // the copy made for the import is the same material
+                        if !Arc::ptr_eq(_material, &material) {
+                            return Err(SceneError::at(
+                                SceneErrorKind::RedefinedNamedMaterial(name),
+                                &import_loc,
+                            ));
+                        }
+                    }
+                }
+            }
+
+            for (name, texture) in builder.named_textures.spectrum_textures {
+                match self.named_textures.spectrum_textures.get(&name) {
+                    None => {
+                        self.named_textures.spectrum_textures.insert(name, texture);
+                    }
+                    Some(_texture) => {
+                        if !Arc::ptr_eq(_texture, &texture) {
+                            return Err(SceneError::at(
+                                SceneErrorKind::RedefinedTexture(name),
+                                &import_loc,
+                            ));
+                        }
+                    }
+                }
+            }
+            for (name, texture) in builder.named_textures.float_textures {
+                match self.named_textures.float_textures.get(&name) {
+                    None => {
+                        self.named_textures.float_textures.insert(name, texture);
+                    }
+                    Some(_texture) => {
+                        if !Arc::ptr_eq(_texture, &texture) {
+                            return Err(SceneError::at(
+                                SceneErrorKind::RedefinedTexture(name),
+                                &import_loc,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn option_camera(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0].clone() == Token::Keyword("Camera".to_string()));
+
+        let camera_type = self.string_at(tokens, 1)?;
+        let parameter_dict = self.parameters_from(tokens, 2, self.root.clone())?;
 
         let camera_from_world = self.graphics_state.current_transform;
-        let world_from_camera = camera_from_world.inverse();
+        let world_from_camera = AnimatedTransform::new(
+            camera_from_world[0].inverse(),
+            self.transform_start_time,
+            camera_from_world[1].inverse(),
+            self.transform_end_time,
+        );
 
-        self.named_coordinate_systems
-            .insert("camera".to_string(), camera_from_world.inverse());
+        self.named_coordinate_systems.insert(
+            "camera".to_string(),
+            [
+                camera_from_world[0].inverse(),
+                camera_from_world[1].inverse(),
+            ],
+        );
 
         let camera_transform =
             CameraTransform::new(world_from_camera, RenderingCoordinateSystem::CameraWorld);
@@ -673,65 +1764,78 @@ impl SceneBuilder {
         self.render_from_world = camera_transform.render_from_world();
 
         self.camera_entity = Some(CameraEntity {
-            name: camera_type.to_string(),
+            name: camera_type,
             parameters: parameter_dict,
             camera_transform,
+            loc: self.statement_locs[0].clone(),
         });
+
+        return Ok(());
     }
 
-    fn option_film(&mut self, tokens: &[Token]) {
+    fn option_film(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Film".to_string()));
 
-        let film_type = match tokens[1].clone() {
-            Token::String(str) => {
-                if str != "rgb" {
-                    println!("warning: only `rgb` film is supported for the moment.");
-                }
-                "rgb".to_string()
-            }
-            _ => {
-                panic!("expect Token::String");
-            }
-        };
-
         self.film_entity = Some(SceneEntity {
-            name: film_type,
-            parameters: ParameterDict::build_parameter_dict(
-                &tokens[2..],
-                &self.named_texture,
-                None,
-            ),
+            name: self.string_at(tokens, 1)?,
+            parameters: self.parameters_from(tokens, 2, None)?,
+            loc: self.statement_locs[0].clone(),
         });
+
+        return Ok(());
     }
 
-    fn option_integrator(&mut self, tokens: &[Token]) {
+    fn option_sampler(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        debug_assert!(tokens[0].clone() == Token::Keyword("Sampler".to_string()));
+
+        self.sampler_entity = Some(SceneEntity {
+            name: self.string_at(tokens, 1)?,
+            parameters: self.parameters_from(tokens, 2, None)?,
+            loc: self.statement_locs[0].clone(),
+        });
+
+        return Ok(());
+    }
+
+    fn option_integrator(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("Integrator".to_string()));
 
-        self.integrator_name = tokens[1].convert_to_string();
-        //TODO: parse Integrator: "integer pixelsamples" [ 32 ]
+        self.integrator_entity = Some(SceneEntity {
+            name: self.string_at(tokens, 1)?,
+            parameters: self.parameters_from(tokens, 2, None)?,
+            loc: self.statement_locs[0].clone(),
+        });
+        self.integrator_color_space = self.graphics_state.color_space;
+
+        return Ok(());
     }
 
-    fn option_look_at(&mut self, tokens: &[Token]) {
+    fn option_look_at(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
         debug_assert!(tokens[0].clone() == Token::Keyword("LookAt".to_string()));
 
-        let data: Vec<f64> = tokens[1..]
-            .into_iter()
-            .map(|token| match token {
-                Token::Number(num) => num.parse::<f64>().unwrap(),
-                _ => {
-                    panic!("expect Token::Number here");
-                }
-            })
-            .collect();
+        let data = self.floats_at(tokens, 9)?;
 
         let position = Point3f::new(data[0], data[1], data[2]);
         let look = Point3f::new(data[3], data[4], data[5]);
         let up = Vector3f::new(data[6], data[7], data[8]);
 
-        let transform_look_at = build_look_at_transform(position, look, up);
+        // NaN when _position_ and _look_ coincide or _up_ is zero
+        let dir = (look - position).normalize();
+        let side = up.normalize().cross(dir).length();
+        if side == 0.0 || side.is_nan() {
+            return Err(self.error_at(
+                0,
+                SceneErrorKind::InvalidEntity(
+                    "`LookAt`: the `up` vector and the viewing direction are parallel".to_string(),
+                ),
+            ));
+        }
+
+        let transform_look_at = Transform::look_at(position, look, up);
 
-        self.graphics_state.current_transform =
-            self.graphics_state.current_transform * transform_look_at;
+        self.apply_transform(transform_look_at);
+
+        return Ok(());
     }
 
     fn get_filepath(&self, file_basename: &str) -> String {
@@ -741,57 +1845,383 @@ impl SceneBuilder {
         };
     }
 
+    fn apply_transform(&mut self, transform: Transform) {
+        for idx in 0..MAX_TRANSFORMS {
+            if self.graphics_state.active_transform_bits & (1 << idx) > 0 {
+                self.graphics_state.current_transform[idx] =
+                    self.graphics_state.current_transform[idx] * transform;
+            }
+        }
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        for idx in 0..MAX_TRANSFORMS {
+            if self.graphics_state.active_transform_bits & (1 << idx) > 0 {
+                self.graphics_state.current_transform[idx] = transform;
+            }
+        }
+    }
+
+    fn ctm_is_animated(&self) -> bool {
+        return self.graphics_state.current_transform[0]
+            != self.graphics_state.current_transform[1];
+    }
+
     fn render_from_object(&self) -> Transform {
-        return self.render_from_world * self.graphics_state.current_transform;
+        return self.render_from_world * self.graphics_state.current_transform[0];
     }
 
-    fn parse_file(&mut self, filename: &str) {
-        let tokens = parse_pbrt_into_token(&self.get_filepath(filename));
+    fn animated_render_from_object(&self) -> AnimatedTransform {
+        return AnimatedTransform::new(
+            self.render_from_world * self.graphics_state.current_transform[0],
+            self.transform_start_time,
+            self.render_from_world * self.graphics_state.current_transform[1],
+            self.transform_end_time,
+        );
+    }
+
+    fn parse_file(&mut self, filename: &str) -> Result<(), SceneError> {
+        let (tokens, locs) = parse_pbrt_into_token(&self.get_filepath(filename))?;
 
         let range_of_statement = split_tokens_into_statements(&tokens);
 
         for range_idx in 0..(range_of_statement.len() - 1) {
-            let statement =
-                &tokens[range_of_statement[range_idx]..range_of_statement[range_idx + 1]];
+            let range = range_of_statement[range_idx]..range_of_statement[range_idx + 1];
+            let statement = &tokens[range.clone()];
+            self.statement_locs = locs[range.clone()].to_vec();
+
+            // objects created by the statement report errors without a location
+            match self.formatter {
+                None => self.parse_statement(statement),
+                Some(_) => self.format_statement(statement),
+            }
+            .map_err(|err| err.or_at(&locs[range.start]))?;
+        }
+
+        return Ok(());
+    }
 
-            self.parse_statement(statement);
+    fn format_statement(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        let formatter = self.formatter.as_mut().unwrap();
+
+        match tokens[0].directive_name().as_str() {
+            "Include" if formatter.expands_includes() => {
+                return self.parse_nested_file(tokens);
+            }
+            "Import" if formatter.expands_includes() => {
+                // an imported file can't change the graphics state of the importing one
+                let loc = self.statement_locs[0].clone();
+                formatter.write_directive("AttributeBegin", &loc);
+                self.parse_nested_file(tokens)?;
+                self.formatter
+                    .as_mut()
+                    .unwrap()
+                    .write_directive("AttributeEnd", &loc);
+
+                return Ok(());
+            }
+            _ => {
+                return formatter.write_statement(tokens, &self.statement_locs);
+            }
         }
     }
 
-    pub fn parse_scene(&mut self, file_path: &str, samples_per_pixel: usize) -> Renderer {
+    // a statement built from Rust code rather than read from a file, see _scene_api_
+    pub fn execute_statement(&mut self, tokens: &[Token]) -> Result<(), SceneError> {
+        self.api_statements += 1;
+        let loc = FileLoc {
+            filename: "<scene API>".to_string(),
+            line: self.api_statements,
+            column: 1,
+        };
+        self.statement_locs = vec![loc.clone(); tokens.len()];
+
+        return self.parse_statement(tokens).map_err(|err| err.or_at(&loc));
+    }
+
+    // directory relative filenames (textures, PLY meshes...) are looked up in _directory_,
+    // otherwise in the current directory
+    pub fn set_search_directory(&mut self, directory: &str) {
+        self.root = Some(directory.to_string());
+    }
+
+    // read the scene description without building the renderer
+    pub fn parse_scene_file(&mut self, file_path: &str) -> Result<(), SceneError> {
+        self.root = Some(get_dirname(file_path));
+        self.parse_file(&get_basename(file_path))?;
+
+        return self.merge_imported_files();
+    }
+
+    // the scene file rewritten by a _SceneFormatter_, nothing is built
+    pub fn format_scene(
+        &mut self,
+        file_path: &str,
+        options: &FormatOptions,
+    ) -> Result<String, SceneError> {
         self.root = Some(get_dirname(file_path));
-        self.parse_file(&get_basename(file_path));
+        self.formatter = Some(SceneFormatter::new(options.clone(), self.root.clone()));
+        self.parse_file(&get_basename(file_path))?;
+
+        return Ok(self.formatter.take().unwrap().finish());
+    }
+
+    // a phase that came before the scene, reported with the statistics of its render
+    pub fn record_phase(&mut self, name: &str, duration: Duration) {
+        self.statistics.record_phase(name, duration);
+    }
+
+    pub fn parse_scene(
+        &mut self,
+        file_path: &str,
+        options: &SceneOptions,
+    ) -> Result<Renderer, SceneError> {
+        let start = Instant::now();
+        self.parse_scene_file(file_path)?;
+        self.statistics.record_phase("parsing", start.elapsed());
+
+        return self.build(options);
+    }
+
+    // the renderer of the scene described so far, from a file or through the scene API
+    pub fn build(&mut self, options: &SceneOptions) -> Result<Renderer, SceneError> {
+        let start = Instant::now();
+        self.merge_imported_files()?;
 
         let filter = Arc::new(BoxFilter::new(0.5));
 
-        let film = match &self.film_entity {
+        let camera_entity = match &self.camera_entity {
             None => {
-                panic!("default Film not implemented");
+                return Err(SceneError::new(
+                    SceneErrorKind::MissingDirective("Camera".to_string()),
+                    None,
+                ));
             }
-            Some(film_entity) => build_film(&film_entity, filter.clone()),
+            Some(_camera_entity) => _camera_entity,
         };
 
-        let camera = match &self.camera_entity {
+        let film = match &self.film_entity {
             None => {
-                panic!("default Camera not implemented");
-            }
-            Some(camera_entity) => {
-                build_camera(&camera_entity, film.lock().unwrap().get_resolution())
+                return Err(SceneError::new(
+                    SceneErrorKind::MissingDirective("Film".to_string()),
+                    None,
+                ));
             }
+            Some(film_entity) => build_film(
+                &film_entity,
+                filter.clone(),
+                &camera_entity.camera_transform,
+                options,
+                self.root.as_deref(),
+            )
+            .map_err(|err| err.or_at(&film_entity.loc))?,
         };
 
-        let sampler = Arc::new(IndependentSampler::new(samples_per_pixel));
-        let bvh_aggregate = Arc::new(BVHAggregate::new(self.primitives.clone()));
+        let resolution = film.lock().unwrap().get_resolution();
+        let camera = build_camera(&camera_entity, resolution)?;
 
-        let mut lights = build_lights(&self.light_entities);
+        let sampler = build_sampler(&self.sampler_entity, options)?;
+        let bvh_start = Instant::now();
+        let (bvh_aggregate, memory) =
+            measure_memory(|| Arc::new(BVHAggregate::new(self.primitives.clone())));
+        self.statistics.add_measured_memory(&memory);
+        self.statistics
+            .record_phase("BVH building", bvh_start.elapsed());
+
+        let (lights, memory) = measure_memory(|| build_lights(&self.light_entities));
+        self.statistics.add_measured_memory(&memory);
+        let mut lights = lights?;
 
         for area_light in &self.area_lights {
             lights.push(area_light.clone());
         }
 
-        let integrator =
-            build_integrator(&self.integrator_name, bvh_aggregate, camera.clone(), lights);
+        let integrator = build_integrator(
+            &self.integrator_entity,
+            bvh_aggregate,
+            camera.clone(),
+            lights,
+            self.integrator_color_space,
+            options,
+        )?;
 
-        return Renderer::new(integrator, sampler, camera, film);
+        let film_state_size = film.lock().unwrap().get_state().len();
+        self.statistics
+            .add_memory(MemoryCategory::Film, film_state_size * size_of::<f64>());
+
+        // BVH building included
+        self.statistics
+            .record_phase("scene building", start.elapsed());
+
+        return Ok(Renderer::new(
+            integrator,
+            sampler,
+            camera,
+            film,
+            self.statistics.clone(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a directory of its own holding _files_, removed by the caller
+    fn write_scene_directory(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("pbrt-rust-import-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&directory).unwrap();
+        for (filename, content) in files {
+            std::fs::write(directory.join(filename), content).unwrap();
+        }
+
+        return directory;
+    }
+
+    fn parse_scene_directory(directory: &std::path::Path) -> Result<SceneBuilder, SceneError> {
+        let mut builder = SceneBuilder::default();
+        let result = builder.parse_scene_file(directory.join("main.pbrt").to_str().unwrap());
+        std::fs::remove_dir_all(directory).unwrap();
+
+        return result.map(|_| builder);
+    }
+
+    #[test]
+    fn names_defined_by_imports_can_be_used() {
+        let directory = write_scene_directory(
+            "names",
+            &[
+                (
+                    "main.pbrt",
+                    "WorldBegin\n\
+                     Import \"materials.pbrt\"\n\
+                     NamedMaterial \"red\"\n\
+                     Shape \"sphere\"\n\
+                     Material \"conductor\" \"texture roughness\" \"rough\"\n\
+                     Shape \"sphere\"\n",
+                ),
+                (
+                    "materials.pbrt",
+                    "Import \"textures.pbrt\"\n\
+                     MakeNamedMaterial \"red\" \"string type\" \"diffuse\" \
+                     \"rgb reflectance\" [0.8 0.1 0.1]\n",
+                ),
+                (
+                    "textures.pbrt",
+                    "Texture \"rough\" \"float\" \"constant\" \"float value\" 0.2\n",
+                ),
+            ],
+        );
+
+        let builder = parse_scene_directory(&directory).unwrap();
+        assert_eq!(builder.primitives.len(), 2);
+        assert!(builder.named_materials.contains_key("red"));
+        assert!(builder.named_textures.float_textures.contains_key("rough"));
+    }
+
+    #[test]
+    fn names_redefined_by_imports_are_rejected() {
+        let directory = write_scene_directory(
+            "redefined",
+            &[
+                (
+                    "main.pbrt",
+                    "WorldBegin\n\
+                     Import \"textures.pbrt\"\n\
+                     Texture \"rough\" \"float\" \"constant\" \"float value\" 0.5\n",
+                ),
+                (
+                    "textures.pbrt",
+                    "Texture \"rough\" \"float\" \"constant\" \"float value\" 0.2\n",
+                ),
+            ],
+        );
+
+        let error = parse_scene_directory(&directory).err().unwrap();
+        assert_eq!(
+            error.kind,
+            SceneErrorKind::RedefinedTexture("rough".to_string())
+        );
+        assert_eq!(error.loc.map(|loc| (loc.line, loc.column)), Some((2, 1)));
+    }
+
+    #[test]
+    fn sample_counts_and_seeds_are_checked() {
+        for (parameter, value, message) in [
+            ("pixelsamples", 0, "expected a positive sample count, got 0"),
+            ("seed", -1, "expected a non-negative seed, got -1"),
+        ] {
+            let scene = format!(
+                "Sampler \"independent\" \"integer {}\" {}\nWorldBegin\n",
+                parameter, value
+            );
+            let directory = write_scene_directory(parameter, &[("main.pbrt", scene.as_str())]);
+            let builder = parse_scene_directory(&directory).unwrap();
+
+            let error = build_sampler(&builder.sampler_entity, &SceneOptions::default())
+                .err()
+                .unwrap();
+            assert_eq!(
+                error.kind,
+                SceneErrorKind::InvalidParameter {
+                    name: parameter.to_string(),
+                    message: message.to_string(),
+                }
+            );
+            assert_eq!(error.loc.map(|loc| loc.line), Some(1));
+        }
+    }
+
+    #[test]
+    fn film_resolutions_and_crop_windows_are_checked() {
+        for (film, options, name, message) in [
+            (
+                "\"integer xresolution\" 0 \"integer yresolution\" 8",
+                SceneOptions::default(),
+                "xresolution",
+                "expected a positive resolution, got 0",
+            ),
+            (
+                "\"integer xresolution\" 8 \"integer yresolution\" 8",
+                SceneOptions {
+                    resolution: Some([8, -2]),
+                    ..SceneOptions::default()
+                },
+                "resolution",
+                "expected a positive resolution, got 8 x -2",
+            ),
+            (
+                "\"integer xresolution\" 8 \"integer yresolution\" 8 \
+                 \"float cropwindow\" [0.5 0.5 0 1]",
+                SceneOptions::default(),
+                "cropwindow",
+                "empty window [0.5, 0.5, 0.0, 1.0]",
+            ),
+        ] {
+            let scene = format!(
+                "Camera \"perspective\"\nFilm \"rgb\" {}\nWorldBegin\n",
+                film
+            );
+            let directory = write_scene_directory(name, &[("main.pbrt", scene.as_str())]);
+            let builder = parse_scene_directory(&directory).unwrap();
+
+            let error = build_film(
+                builder.film_entity.as_ref().unwrap(),
+                Arc::new(BoxFilter::new(0.5)),
+                &builder.camera_entity.as_ref().unwrap().camera_transform,
+                &options,
+                None,
+            )
+            .err()
+            .unwrap();
+            assert_eq!(
+                error.kind,
+                SceneErrorKind::InvalidParameter {
+                    name: name.to_string(),
+                    message: message.to_string(),
+                }
+            );
+        }
     }
 }
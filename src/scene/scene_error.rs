@@ -0,0 +1,282 @@
+use crate::pbrt::*;
+
+// position of a token in a scene file, line and column start at 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileLoc {
+    pub filename: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Display for FileLoc {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        return write!(f, "{}:{}:{}", self.filename, self.line, self.column);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneErrorKind {
+    Io {
+        filename: String,
+        message: String,
+    },
+    IllegalCharacter(char),
+    UnterminatedString,
+    UnterminatedList,
+    UnexpectedToken {
+        expected: String,
+        found: String,
+    },
+    UnknownDirective(String),
+    UnmatchedAttributeEnd,
+    WrongArgumentCount {
+        directive: String,
+        expected: usize,
+        found: usize,
+    },
+    InvalidParameter {
+        name: String,
+        message: String,
+    },
+    UnknownParameterType(String),
+    MissingParameter(String),
+    UndefinedTexture(String),
+    RedefinedTexture(String),
+    UndefinedNamedMaterial(String),
+    RedefinedNamedMaterial(String),
+    UndefinedCoordinateSystem(String),
+    UnknownType {
+        category: String,
+        name: String,
+    },
+    MissingDirective(String),
+    Unsupported(String),
+    // raised while creating the object described by a directive
+    InvalidEntity(String),
+    Checkpoint {
+        filename: String,
+        message: String,
+    },
+    ThreadPool(String),
+}
+
+impl Display for SceneErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        return match self {
+            SceneErrorKind::Io { filename, message } => {
+                write!(f, "couldn't read `{}`: {}", filename, message)
+            }
+            SceneErrorKind::IllegalCharacter(ch) => write!(f, "illegal character `{}`", ch),
+            SceneErrorKind::UnterminatedString => {
+                write!(f, "missing closing `\"` before the end of the line")
+            }
+            SceneErrorKind::UnterminatedList => {
+                write!(f, "missing closing `]` before the end of the file")
+            }
+            SceneErrorKind::UnexpectedToken { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            SceneErrorKind::UnknownDirective(name) => write!(f, "unknown directive `{}`", name),
+            SceneErrorKind::UnmatchedAttributeEnd => {
+                write!(f, "`AttributeEnd` without matching `AttributeBegin`")
+            }
+            SceneErrorKind::WrongArgumentCount {
+                directive,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{}` expects {} argument{}, {} supplied",
+                directive,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            ),
+            SceneErrorKind::InvalidParameter { name, message } => {
+                write!(f, "parameter `{}`: {}", name, message)
+            }
+            SceneErrorKind::UnknownParameterType(name) => {
+                write!(f, "unknown parameter type `{}`", name)
+            }
+            SceneErrorKind::MissingParameter(name) => {
+                write!(f, "missing parameter `{}`", name)
+            }
+            SceneErrorKind::UndefinedTexture(name) => write!(f, "texture `{}` not defined", name),
+            SceneErrorKind::RedefinedTexture(name) => write!(f, "texture `{}` redefined", name),
+            SceneErrorKind::UndefinedNamedMaterial(name) => {
+                write!(f, "named material `{}` not defined", name)
+            }
+            SceneErrorKind::RedefinedNamedMaterial(name) => {
+                write!(f, "named material `{}` redefined", name)
+            }
+            SceneErrorKind::UndefinedCoordinateSystem(name) => {
+                write!(f, "coordinate system `{}` not defined", name)
+            }
+            SceneErrorKind::UnknownType { category, name } => {
+                write!(f, "unknown {} type `{}`", category, name)
+            }
+            SceneErrorKind::MissingDirective(name) => {
+                write!(f, "the scene has no `{}` directive", name)
+            }
+            SceneErrorKind::Unsupported(message) => write!(f, "not supported: {}", message),
+            SceneErrorKind::InvalidEntity(message) => write!(f, "{}", message),
+            SceneErrorKind::Checkpoint { filename, message } => {
+                write!(f, "checkpoint `{}`: {}", filename, message)
+            }
+            SceneErrorKind::ThreadPool(message) => {
+                write!(f, "couldn't start the rendering threads: {}", message)
+            }
+        };
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneError {
+    pub kind: SceneErrorKind,
+    pub loc: Option<FileLoc>,
+    // the source line _loc_ points to, read when the error is located as the file may
+    // change or disappear before the error is displayed, boxed to keep _SceneError_ small
+    pub excerpt: Option<Box<String>>,
+    // `Include` directives that led to _loc_, innermost first
+    pub include_chain: Vec<FileLoc>,
+}
+
+impl SceneError {
+    pub fn new(kind: SceneErrorKind, loc: Option<FileLoc>) -> Self {
+        let excerpt = match &loc {
+            None => None,
+            Some(_loc) => source_excerpt(_loc),
+        };
+
+        return SceneError {
+            kind,
+            loc,
+            excerpt,
+            include_chain: vec![],
+        };
+    }
+
+    pub fn at(kind: SceneErrorKind, loc: &FileLoc) -> Self {
+        return SceneError::new(kind, Some(loc.clone()));
+    }
+
+    // errors raised while creating an object (a missing parameter...) point to
+    // the directive that describes it unless they know a more precise location
+    pub fn or_at(mut self, loc: &FileLoc) -> Self {
+        if self.loc.is_none() {
+            self.loc = Some(loc.clone());
+            self.excerpt = source_excerpt(loc);
+        }
+
+        return self;
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+// the source line _loc_ points to and a caret under its column
+fn source_excerpt(loc: &FileLoc) -> Option<Box<String>> {
+    let content = read_scene_file(&loc.filename).ok()?;
+    let line = content.lines().nth(loc.line.checked_sub(1)? as usize)?;
+
+    // keep tabs in the padding so that the caret lines up with the source
+    let padding: String = line
+        .chars()
+        .take(loc.column.saturating_sub(1) as usize)
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    return Some(Box::new(format!("    {}\n    {}^", line, padding)));
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match &self.loc {
+            None => {
+                write!(f, "error: {}", self.kind)?;
+            }
+            Some(_loc) => {
+                write!(f, "{}: error: {}", _loc, self.kind)?;
+                if let Some(_excerpt) = &self.excerpt {
+                    write!(f, "\n{}", _excerpt)?;
+                }
+            }
+        }
+
+        for include_loc in &self.include_chain {
+            write!(f, "\n  included from {}", include_loc)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loc(line: u32, column: u32) -> FileLoc {
+        return FileLoc {
+            filename: "/nonexistent/scene.pbrt".to_string(),
+            line,
+            column,
+        };
+    }
+
+    #[test]
+    fn or_at_keeps_a_more_precise_location() {
+        let missing = SceneError::new(SceneErrorKind::MissingParameter("P".to_string()), None);
+        assert_eq!(missing.or_at(&loc(3, 1)).loc, Some(loc(3, 1)));
+
+        let located = SceneError::at(
+            SceneErrorKind::InvalidParameter {
+                name: "radius".to_string(),
+                message: "expected a single value, 2 supplied".to_string(),
+            },
+            &loc(3, 15),
+        );
+        assert_eq!(located.or_at(&loc(3, 1)).loc, Some(loc(3, 15)));
+    }
+
+    #[test]
+    fn display_lists_the_include_chain() {
+        let mut error = SceneError::at(SceneErrorKind::UnmatchedAttributeEnd, &loc(4, 2));
+        error.include_chain = vec![loc(10, 1), loc(1, 1)];
+
+        // the excerpt is skipped as the file can't be read
+        assert_eq!(
+            error.to_string(),
+            "/nonexistent/scene.pbrt:4:2: error: `AttributeEnd` without matching `AttributeBegin`\n  included from /nonexistent/scene.pbrt:10:1\n  included from /nonexistent/scene.pbrt:1:1"
+        );
+        assert_eq!(
+            SceneError::new(SceneErrorKind::MissingDirective("Camera".to_string()), None)
+                .to_string(),
+            "error: the scene has no `Camera` directive"
+        );
+    }
+
+    #[test]
+    fn excerpt_is_read_when_the_error_is_located() {
+        let filename = std::env::temp_dir()
+            .join(format!("pbrt-rust-excerpt-{}.pbrt", std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string();
+        std::fs::write(&filename, "WorldBegin\nAttributeEnd\n").unwrap();
+        let loc = FileLoc {
+            filename: filename.clone(),
+            line: 2,
+            column: 1,
+        };
+        let error = SceneError::new(SceneErrorKind::UnmatchedAttributeEnd, None).or_at(&loc);
+        std::fs::remove_file(&filename).unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}:2:1: error: `AttributeEnd` without matching `AttributeBegin`\n    AttributeEnd\n    ^",
+                filename
+            )
+        );
+    }
+}
@@ -0,0 +1,658 @@
+use crate::pbrt::*;
+
+// lists of values longer than this are wrapped
+const MAX_LINE_WIDTH: usize = 80;
+const INDENT_WIDTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MeshFormat {
+    // meshes are written as they appear in the scene
+    Unchanged,
+    // `plymesh` shapes are replaced by `trianglemesh` shapes
+    Inline,
+    // `trianglemesh` shapes are written to binary PLY files
+    Ply,
+}
+
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    // rewrite the pbrt-v3 directives, materials and parameters in pbrt-v4 syntax
+    pub upgrade: bool,
+    // write the content of included and imported files in place of the directive
+    pub expand_includes: bool,
+    pub mesh_format: MeshFormat,
+    // directory the PLY files of `MeshFormat::Ply` are written to
+    pub ply_directory: String,
+}
+
+struct Parameter {
+    type_name: String,
+    name: String,
+    values: Vec<String>,
+}
+
+struct Statement {
+    directive: String,
+    arguments: Vec<Token>,
+    parameters: Vec<Parameter>,
+    loc: FileLoc,
+}
+
+fn warn(loc: &FileLoc, message: &str) {
+    // the formatted scene may go to stdout, warnings must not end up in it
+    eprintln!("{}: warning: {}", loc, message);
+}
+
+fn format_argument(token: &Token) -> String {
+    return match token {
+        Token::String(str) => format!("\"{}\"", str),
+        Token::Number(num) => num.clone(),
+        Token::Bool(val) => val.to_string(),
+        Token::List(ls) => format!("[ {} ]", ls.join(" ")),
+        _ => token.directive_name(),
+    };
+}
+
+fn format_value(type_name: &str, value: &str) -> String {
+    return match type_name {
+        // the lexer only accepts quoted booleans inside lists
+        "string" | "texture" | "bool" => format!("\"{}\"", value),
+        // a spectrum is either a named spectrum or (lambda, value) pairs
+        "spectrum" if value.parse::<f64>().is_err() => format!("\"{}\"", value),
+        _ => value.to_string(),
+    };
+}
+
+// number of values making up one element of a parameter
+fn value_arity(type_name: &str) -> usize {
+    return match type_name {
+        "point3" | "vector3" | "normal" | "rgb" => 3,
+        "point2" => 2,
+        _ => 1,
+    };
+}
+
+impl Statement {
+    fn new(tokens: &[Token], locs: &[FileLoc]) -> Result<Self, SceneError> {
+        let mut arguments = vec![];
+        let mut idx = 1;
+        while idx < tokens.len() {
+            if let Token::Variable(_) = tokens[idx] {
+                break;
+            }
+            arguments.push(tokens[idx].clone());
+            idx += 1;
+        }
+
+        let mut parameters = vec![];
+        while idx < tokens.len() {
+            let (type_name, name) = match &tokens[idx] {
+                Token::Variable((_type, _name)) => (_type.clone(), _name.clone()),
+                token => {
+                    return Err(SceneError::at(
+                        SceneErrorKind::UnexpectedToken {
+                            expected: "a parameter declaration \"type name\"".to_string(),
+                            found: token.to_string(),
+                        },
+                        &locs[idx],
+                    ));
+                }
+            };
+
+            let values = match tokens.get(idx + 1) {
+                Some(Token::Number(num)) => vec![num.clone()],
+                Some(Token::String(str)) => vec![str.clone()],
+                Some(Token::Bool(val)) => vec![val.to_string()],
+                Some(Token::List(ls)) => ls.clone(),
+                _ => {
+                    return Err(SceneError::at(
+                        SceneErrorKind::InvalidParameter {
+                            name,
+                            message: "no value supplied".to_string(),
+                        },
+                        &locs[idx],
+                    ));
+                }
+            };
+
+            parameters.push(Parameter {
+                type_name,
+                name,
+                values,
+            });
+            idx += 2;
+        }
+
+        return Ok(Statement {
+            directive: tokens[0].directive_name(),
+            arguments,
+            parameters,
+            loc: locs[0].clone(),
+        });
+    }
+
+    fn argument(&self, idx: usize) -> Option<String> {
+        return match self.arguments.get(idx) {
+            Some(Token::String(str)) => Some(str.clone()),
+            _ => None,
+        };
+    }
+
+    fn set_argument(&mut self, idx: usize, value: &str) {
+        self.arguments[idx] = Token::String(value.to_string());
+    }
+
+    fn find_parameter(&self, name: &str) -> Option<&Parameter> {
+        return self.parameters.iter().find(|p| p.name == name);
+    }
+
+    fn remove_parameter(&mut self, name: &str) -> Option<Parameter> {
+        let idx = self.parameters.iter().position(|p| p.name == name)?;
+        return Some(self.parameters.remove(idx));
+    }
+
+    fn rename_parameter(&mut self, name: &str, new_name: &str) {
+        for parameter in &mut self.parameters {
+            if parameter.name == name {
+                parameter.name = new_name.to_string();
+            }
+        }
+    }
+
+    fn drop_parameter(&mut self, name: &str, context: &str) {
+        if self.remove_parameter(name).is_some() {
+            warn(
+                &self.loc,
+                &format!(
+                    "`{}` of {} has no pbrt-v4 equivalent, removed",
+                    name, context
+                ),
+            );
+        }
+    }
+
+    fn add_parameter(&mut self, type_name: &str, name: &str, values: Vec<String>) {
+        self.parameters.push(Parameter {
+            type_name: type_name.to_string(),
+            name: name.to_string(),
+            values,
+        });
+    }
+
+    // floats of the parameter _name_, an error if they are not numbers
+    fn get_floats(&self, name: &str) -> Result<Vec<f64>, SceneError> {
+        return match self.find_parameter(name) {
+            None => Ok(vec![]),
+            Some(_parameter) => convert_string::<f64>(&_parameter.values).map_err(|value| {
+                SceneError::at(
+                    SceneErrorKind::InvalidParameter {
+                        name: name.to_string(),
+                        message: format!("`{}` is not a number", value),
+                    },
+                    &self.loc,
+                )
+            }),
+        };
+    }
+}
+
+// pbrt-v3 material type and the material replacing it, with its parameters renamed
+fn upgrade_material(material_type: &str, statement: &mut Statement) -> String {
+    let context = format!("material `{}`", material_type);
+
+    let upgraded_type = match material_type {
+        "matte" => {
+            statement.rename_parameter("Kd", "reflectance");
+            statement.drop_parameter("sigma", &context);
+            "diffuse"
+        }
+        "plastic" | "substrate" | "uber" => {
+            statement.rename_parameter("Kd", "reflectance");
+            statement.rename_parameter("index", "eta");
+            for name in ["Ks", "Kr", "Kt", "opacity"] {
+                statement.drop_parameter(name, &context);
+            }
+            "coateddiffuse"
+        }
+        "translucent" => {
+            statement.rename_parameter("Kd", "reflectance");
+            statement.rename_parameter("Kt", "transmittance");
+            statement.drop_parameter("Ks", &context);
+            statement.drop_parameter("roughness", &context);
+            "diffusetransmission"
+        }
+        "kdsubsurface" => {
+            statement.rename_parameter("Kd", "reflectance");
+            statement.drop_parameter("Kr", &context);
+            statement.drop_parameter("Kt", &context);
+            "subsurface"
+        }
+        "metal" => "conductor",
+        "mirror" => {
+            statement.rename_parameter("Kr", "reflectance");
+            statement.add_parameter("float", "roughness", vec!["0".to_string()]);
+            "conductor"
+        }
+        "glass" => {
+            statement.rename_parameter("index", "eta");
+            statement.drop_parameter("Kr", &context);
+            statement.drop_parameter("Kt", &context);
+            "dielectric"
+        }
+        "mix" => {
+            let materials = ["namedmaterial1", "namedmaterial2"]
+                .iter()
+                .filter_map(|name| statement.remove_parameter(name))
+                .map(|parameter| parameter.values[0].clone())
+                .collect::<Vec<String>>();
+            if !materials.is_empty() {
+                statement.add_parameter("string", "materials", materials);
+            }
+            "mix"
+        }
+        "fourier" => {
+            warn(
+                &statement.loc,
+                "material `fourier` has no pbrt-v4 equivalent",
+            );
+            "fourier"
+        }
+        _ => material_type,
+    };
+
+    statement.rename_parameter("bumpmap", "displacement");
+
+    return upgraded_type.to_string();
+}
+
+fn upgrade_statement(statement: &mut Statement) {
+    // parameter types pbrt-v4 doesn't accept anymore
+    for parameter in &mut statement.parameters {
+        let type_name = match parameter.type_name.as_str() {
+            "color" => "rgb",
+            "point" => "point3",
+            "vector" => "vector3",
+            "normal3" => "normal",
+            _type => _type,
+        };
+        parameter.type_name = type_name.to_string();
+    }
+
+    match statement.directive.as_str() {
+        "TransformBegin" => {
+            statement.directive = "AttributeBegin".to_string();
+        }
+        "TransformEnd" => {
+            statement.directive = "AttributeEnd".to_string();
+        }
+
+        "Material" => {
+            if let Some(_type) = statement.argument(0) {
+                let upgraded_type = upgrade_material(&_type, statement);
+                statement.set_argument(0, &upgraded_type);
+            }
+        }
+
+        "MakeNamedMaterial" => {
+            if let Some(_type) = statement
+                .find_parameter("type")
+                .map(|p| p.values[0].clone())
+            {
+                let upgraded_type = upgrade_material(&_type, statement);
+                if let Some(_parameter) = statement.parameters.iter_mut().find(|p| p.name == "type")
+                {
+                    _parameter.values = vec![upgraded_type];
+                }
+            }
+        }
+
+        "Texture" => {
+            if statement.argument(1) == Some("color".to_string()) {
+                statement.set_argument(1, "spectrum");
+            }
+
+            match statement.argument(2).as_deref() {
+                Some("imagemap") => {
+                    if let Some(_gamma) = statement.remove_parameter("gamma") {
+                        let encoding = if _gamma.values[0] == "true" {
+                            "sRGB"
+                        } else {
+                            "linear"
+                        };
+                        statement.add_parameter("string", "encoding", vec![encoding.to_string()]);
+                    }
+                }
+                Some("scale") => {
+                    statement.rename_parameter("tex1", "tex");
+                    statement.rename_parameter("tex2", "scale");
+                }
+                _ => {}
+            }
+        }
+
+        "Shape" => {
+            for parameter in &mut statement.parameters {
+                if parameter.name == "st" || parameter.name == "uv" {
+                    parameter.name = "uv".to_string();
+                    parameter.type_name = "point2".to_string();
+                }
+            }
+            statement.rename_parameter("nlevels", "levels");
+        }
+
+        "LightSource" => {
+            statement.drop_parameter("nsamples", "lights");
+            statement.rename_parameter("mapname", "filename");
+        }
+
+        "AreaLightSource" => {
+            statement.drop_parameter("nsamples", "area lights");
+        }
+
+        "Film" => {
+            if statement.argument(0) == Some("image".to_string()) {
+                statement.set_argument(0, "rgb");
+            }
+            statement.rename_parameter("maxsampleluminance", "maxcomponentvalue");
+        }
+
+        "Sampler" => {
+            let upgraded_type = match statement.argument(0).as_deref() {
+                Some("lowdiscrepancy" | "02sequence") => Some("paddedsobol"),
+                Some("maxmindist") => Some("pmj02bn"),
+                Some("random") => Some("independent"),
+                _ => None,
+            };
+            if let Some(_type) = upgraded_type {
+                statement.set_argument(0, _type);
+            }
+        }
+
+        _ => {}
+    }
+}
+
+// writes scene statements back in a normalized layout: one directive per line with
+// its parameters below it, indented by the attribute nesting
+pub struct SceneFormatter {
+    options: FormatOptions,
+    root: Option<String>,
+    output: String,
+    indent: usize,
+    ply_count: usize,
+}
+
+impl SceneFormatter {
+    pub fn new(options: FormatOptions, root: Option<String>) -> Self {
+        return SceneFormatter {
+            options,
+            root,
+            output: String::new(),
+            indent: 0,
+            ply_count: 0,
+        };
+    }
+
+    pub fn expands_includes(&self) -> bool {
+        return self.options.expand_includes;
+    }
+
+    pub fn finish(self) -> String {
+        return self.output;
+    }
+
+    fn write_line(&mut self, line: &str) {
+        self.output.push_str(&" ".repeat(self.indent));
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    fn format_parameter(&self, parameter: &Parameter) -> String {
+        let indent = " ".repeat(self.indent + INDENT_WIDTH);
+        let header = format!("\"{} {}\" [", parameter.type_name, parameter.name);
+        let values = parameter
+            .values
+            .iter()
+            .map(|v| format_value(&parameter.type_name, v))
+            .collect::<Vec<String>>();
+
+        let line = format!("{}{} {} ]", indent, header, values.join(" "));
+        if line.len() <= MAX_LINE_WIDTH {
+            return line;
+        }
+
+        // one or more elements per line, elements are never split
+        let value_indent = " ".repeat(self.indent + 2 * INDENT_WIDTH);
+        let mut lines = vec![format!("{}{}", indent, header)];
+        let mut current = value_indent.clone();
+        for element in values.chunks(value_arity(&parameter.type_name)) {
+            let element = element.join(" ");
+            if current.len() > value_indent.len()
+                && current.len() + 1 + element.len() > MAX_LINE_WIDTH
+            {
+                lines.push(current);
+                current = value_indent.clone();
+            }
+            if current.len() > value_indent.len() {
+                current.push(' ');
+            }
+            current.push_str(&element);
+        }
+        lines.push(current);
+        lines.push(format!("{}]", indent));
+
+        return lines.join("\n");
+    }
+
+    fn write_statement_lines(&mut self, statement: &Statement) {
+        match statement.directive.as_str() {
+            "AttributeEnd" | "ObjectEnd" | "TransformEnd" => {
+                self.indent = self.indent.saturating_sub(INDENT_WIDTH);
+            }
+            "WorldBegin" => {
+                self.output.push('\n');
+            }
+            _ => {}
+        }
+
+        let mut line = statement.directive.clone();
+        for argument in &statement.arguments {
+            line.push(' ');
+            line.push_str(&format_argument(argument));
+        }
+        self.write_line(&line);
+
+        for parameter in &statement.parameters {
+            let formatted = self.format_parameter(parameter);
+            self.output.push_str(&formatted);
+            self.output.push('\n');
+        }
+
+        match statement.directive.as_str() {
+            "AttributeBegin" | "ObjectBegin" | "TransformBegin" => {
+                self.indent += INDENT_WIDTH;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn write_directive(&mut self, directive: &str, loc: &FileLoc) {
+        self.write_statement_lines(&Statement {
+            directive: directive.to_string(),
+            arguments: vec![],
+            parameters: vec![],
+            loc: loc.clone(),
+        });
+    }
+
+    // `trianglemesh` written to a PLY file and replaced by a `plymesh`
+    fn mesh_to_ply(&mut self, statement: &mut Statement) -> Result<(), SceneError> {
+        let to_points = |values: Vec<f64>| {
+            values
+                .chunks(3)
+                .map(|v| Point3f::new(v[0], v[1], v[2]))
+                .collect::<Vec<Point3f>>()
+        };
+
+        let mesh = TriQuadMesh {
+            p: to_points(statement.get_floats("P")?),
+            n: statement
+                .get_floats("N")?
+                .chunks(3)
+                .map(|v| Normal3f::new(v[0], v[1], v[2]))
+                .collect(),
+            uv: statement
+                .get_floats("uv")?
+                .chunks(2)
+                .map(|v| Point2f::new(v[0], v[1]))
+                .collect(),
+            tri_indices: statement
+                .get_floats("indices")?
+                .into_iter()
+                .map(|x| x as usize)
+                .collect(),
+            quad_indices: vec![],
+            face_indices: vec![],
+            quad_face_indices: vec![],
+        };
+
+        self.ply_count += 1;
+        let ply_filename = format!("mesh_{:05}.ply", self.ply_count);
+        let ply_path = format!("{}/{}", self.options.ply_directory, ply_filename);
+        if let Err(msg) = write_ply(&ply_path, &mesh) {
+            return Err(SceneError::at(
+                SceneErrorKind::Io {
+                    filename: ply_path,
+                    message: msg.to_string(),
+                },
+                &statement.loc,
+            ));
+        }
+
+        for name in ["P", "N", "uv", "indices"] {
+            statement.remove_parameter(name);
+        }
+        statement.set_argument(0, "plymesh");
+        statement.parameters.insert(
+            0,
+            Parameter {
+                type_name: "string".to_string(),
+                name: "filename".to_string(),
+                values: vec![ply_filename],
+            },
+        );
+
+        return Ok(());
+    }
+
+    // `plymesh` read and replaced by a `trianglemesh`
+    fn mesh_to_inline(&mut self, statement: &mut Statement) -> Result<(), SceneError> {
+        let filename = match statement.remove_parameter("filename") {
+            None => {
+                return Err(SceneError::at(
+                    SceneErrorKind::MissingParameter("filename".to_string()),
+                    &statement.loc,
+                ));
+            }
+            Some(_parameter) => _parameter.values[0].clone(),
+        };
+        let file_path = match &self.root {
+            None => filename,
+            Some(_root) => format!("{}/{}", _root, filename),
+        };
+
+        let mut mesh = match read_ply(&file_path) {
+            Err(message) => {
+                return Err(SceneError::at(
+                    SceneErrorKind::Io {
+                        filename: file_path,
+                        message,
+                    },
+                    &statement.loc,
+                ));
+            }
+            Ok(_mesh) => _mesh,
+        };
+        mesh.convert_to_only_triangles();
+        if !mesh.face_indices.is_empty() {
+            warn(
+                &statement.loc,
+                &format!("`face_indices` of `{}` are dropped", file_path),
+            );
+        }
+
+        // PLY files store single precision values, printed back without noise digits
+        let format_f32 = |x: f64| (x as f32).to_string();
+
+        statement.set_argument(0, "trianglemesh");
+        statement.add_parameter(
+            "point3",
+            "P",
+            mesh.p
+                .iter()
+                .flat_map(|p| [p.x, p.y, p.z])
+                .map(format_f32)
+                .collect(),
+        );
+        if !mesh.n.is_empty() {
+            statement.add_parameter(
+                "normal",
+                "N",
+                mesh.n
+                    .iter()
+                    .flat_map(|n| [n.x, n.y, n.z])
+                    .map(format_f32)
+                    .collect(),
+            );
+        }
+        if !mesh.uv.is_empty() {
+            statement.add_parameter(
+                "point2",
+                "uv",
+                mesh.uv
+                    .iter()
+                    .flat_map(|uv| [uv.x, uv.y])
+                    .map(format_f32)
+                    .collect(),
+            );
+        }
+        statement.add_parameter(
+            "integer",
+            "indices",
+            mesh.tri_indices.iter().map(|idx| idx.to_string()).collect(),
+        );
+
+        return Ok(());
+    }
+
+    pub fn write_statement(
+        &mut self,
+        tokens: &[Token],
+        locs: &[FileLoc],
+    ) -> Result<(), SceneError> {
+        let mut statement = Statement::new(tokens, locs)?;
+
+        if self.options.upgrade {
+            if statement.directive == "WorldEnd" {
+                return Ok(());
+            }
+            upgrade_statement(&mut statement);
+        }
+
+        if statement.directive == "Shape" {
+            match (self.options.mesh_format, statement.argument(0).as_deref()) {
+                (MeshFormat::Ply, Some("trianglemesh")) => {
+                    self.mesh_to_ply(&mut statement)?;
+                }
+                (MeshFormat::Inline, Some("plymesh")) => {
+                    self.mesh_to_inline(&mut statement)?;
+                }
+                _ => {}
+            }
+        }
+
+        self.write_statement_lines(&statement);
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,25 @@
+// command line settings that take precedence over the scene description
+#[derive(Clone, Default)]
+pub struct SceneOptions {
+    pub samples_per_pixel: Option<usize>,
+    pub seed: Option<u64>,
+    pub outfile: Option<String>,
+    pub resolution: Option<[i32; 2]>,
+    // scale applied to the film resolution, after `resolution`
+    pub pixel_scale: Option<f64>,
+    // [x0, x1, y0, y1], as the film parameters of the same name
+    pub crop_window: Option<[f64; 4]>,
+    pub pixel_bounds: Option<[i32; 4]>,
+    // fewer samples per pixel and shorter paths, for previews
+    pub quick: bool,
+}
+
+impl SceneOptions {
+    pub fn quick_samples_per_pixel(samples_per_pixel: usize) -> usize {
+        return (samples_per_pixel / 4).max(1);
+    }
+
+    pub fn quick_max_depth(max_depth: usize) -> usize {
+        return (max_depth / 2).max(1);
+    }
+}
@@ -1,46 +1,49 @@
 use crate::pbrt::*;
+use std::path::Path;
 
 pub fn get_basename(path: &str) -> String {
     return match path.rfind("/") {
-        None => {
-            panic!("couldn't find `/` from {}", path);
-        }
+        None => path.to_string(),
         Some(pos) => path.chars().skip(pos + 1).collect(),
     };
 }
 
+// a path without `/` is in the current directory
 pub fn get_dirname(path: &str) -> String {
     return match path.rfind("/") {
-        None => {
-            panic!("couldn't find `/` from {}", path);
-        }
+        None => ".".to_string(),
         Some(pos) => path.chars().take(pos).collect(),
     };
 }
 
+// empty when _path_ has no extension, the dots of directory names don't count
 pub fn get_extension(path: &str) -> String {
-    match path.rfind(".") {
-        None => {
-            panic!("couldn't find `.` from {}", path);
-        }
-        Some(pos) => {
-            return path.chars().skip(pos + 1).take(usize::MAX).collect();
-        }
+    return match Path::new(path).extension() {
+        None => "".to_string(),
+        Some(_extension) => _extension.to_string_lossy().to_string(),
     };
 }
 
+// a path without extension gets one
 pub fn change_extension(path: &str, extension: &str) -> String {
-    match path.rfind(".") {
-        None => {
-            panic!("couldn't find `.` from {}", path);
-        }
-        Some(pos) => {
-            let mut filename: String = path.chars().take(pos).collect();
+    return Path::new(path)
+        .with_extension(extension)
+        .to_string_lossy()
+        .to_string();
+}
 
-            filename.push('.');
-            filename.push_str(extension);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            return filename;
-        }
-    };
+    #[test]
+    fn extensions_ignore_directory_dots() {
+        assert_eq!(get_extension("./render"), "");
+        assert_eq!(get_extension("../scenes.v2/out.exr"), "exr");
+        assert_eq!(change_extension("./render", "png"), "./render.png");
+        assert_eq!(
+            change_extension("../scenes.v2/out.exr", "denoised.exr"),
+            "../scenes.v2/out.denoised.exr"
+        );
+    }
 }
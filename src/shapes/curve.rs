@@ -0,0 +1,546 @@
+use crate::pbrt::*;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CurveType {
+    Flat,
+    Cylinder,
+    Ribbon,
+}
+
+pub struct CurveCommon {
+    curve_type: CurveType,
+    cp_obj: [Point3f; 4],
+    width: [f64; 2],
+    n: [Normal3f; 2],
+    normal_angle: f64,
+    inv_sin_normal_angle: f64,
+    render_from_object: Transform,
+    object_from_render: Transform,
+    reverse_orientation: bool,
+    transform_swaps_handedness: bool,
+}
+
+impl CurveCommon {
+    pub fn new(
+        cp: [Point3f; 4],
+        width0: f64,
+        width1: f64,
+        curve_type: CurveType,
+        norm: &[Normal3f],
+        render_from_object: Transform,
+        object_from_render: Transform,
+        reverse_orientation: bool,
+    ) -> Self {
+        let (n, normal_angle, inv_sin_normal_angle) = if norm.len() == 2 {
+            let n = [norm[0].normalize(), norm[1].normalize()];
+            let normal_angle = Vector3f::from(n[0]).angle_between(Vector3f::from(n[1]));
+            (n, normal_angle, 1.0 / normal_angle.sin())
+        } else {
+            (
+                [Normal3f::new(0.0, 0.0, 0.0), Normal3f::new(0.0, 0.0, 0.0)],
+                0.0,
+                0.0,
+            )
+        };
+
+        return CurveCommon {
+            curve_type,
+            cp_obj: cp,
+            width: [width0, width1],
+            n,
+            normal_angle,
+            inv_sin_normal_angle,
+            render_from_object,
+            object_from_render,
+            reverse_orientation,
+            transform_swaps_handedness: render_from_object.swaps_handedness(),
+        };
+    }
+}
+
+pub struct Curve {
+    common: Arc<CurveCommon>,
+    u_min: f64,
+    u_max: f64,
+}
+
+impl Curve {
+    pub fn new(common: Arc<CurveCommon>, u_min: f64, u_max: f64) -> Self {
+        return Curve {
+            common,
+            u_min,
+            u_max,
+        };
+    }
+
+    fn width_at(&self, u: f64) -> f64 {
+        return lerp(u, self.common.width[0], self.common.width[1]);
+    }
+
+    // _si_ is only filled in when _want_interaction_ is set, shadow rays just
+    // need to know whether there is any hit
+    fn intersect_ray(
+        &self,
+        r: &Ray,
+        t_max: f64,
+        want_interaction: bool,
+        si: &mut Option<ShapeIntersection>,
+    ) -> bool {
+        // Transform _Ray_ to curve's object space
+        let (ray, _) = self.common.object_from_render.on_ray(r);
+
+        // Get object-space control points for curve segment, _cp_obj_
+        let cp_obj = cubic_bezier_control_points(&self.common.cp_obj, self.u_min, self.u_max);
+
+        // Project curve control points to plane perpendicular to ray
+        let mut dx = ray.d.cross(cp_obj[3] - cp_obj[0]);
+        if dx.length_squared() == 0.0 {
+            (dx, _) = ray.d.coordinate_system();
+        }
+
+        let ray_from_object = Transform::look_at(ray.o, ray.o + ray.d, dx);
+        let cp = cp_obj.map(|p| ray_from_object.on_point3f(p));
+
+        // Test ray against bound of projected control points
+        let max_width = self.width_at(self.u_min).max(self.width_at(self.u_max));
+        let curve_bounds = bound_cubic_bezier(&cp).expand(0.5 * max_width);
+        let ray_bounds = Bounds3f::from_multiple_points(&[
+            Point3f::new(0.0, 0.0, 0.0),
+            Point3f::new(0.0, 0.0, ray.d.length() * t_max),
+        ]);
+        if !ray_bounds.overlaps(&curve_bounds) {
+            return false;
+        }
+
+        // Compute refinement depth for curve, _max_depth_
+        let mut l0: f64 = 0.0;
+        for i in 0..2 {
+            l0 = l0
+                .max((cp[i].x - 2.0 * cp[i + 1].x + cp[i + 2].x).abs())
+                .max((cp[i].y - 2.0 * cp[i + 1].y + cp[i + 2].y).abs())
+                .max((cp[i].z - 2.0 * cp[i + 1].z + cp[i + 2].z).abs());
+        }
+
+        // width / 20
+        let eps = self.common.width[0].max(self.common.width[1]) * 0.05;
+        // Compute log base 4 by dividing log2 in half
+        let r0 = ((std::f64::consts::SQRT_2 * 6.0 * l0 / (8.0 * eps))
+            .log2()
+            .floor() as i32)
+            / 2;
+        let max_depth = r0.clamp(0, 10);
+
+        return self.recursive_intersect(
+            &ray,
+            t_max,
+            &cp,
+            &ray_from_object.inverse(),
+            self.u_min,
+            self.u_max,
+            max_depth,
+            want_interaction,
+            si,
+        );
+    }
+
+    fn recursive_intersect(
+        &self,
+        ray: &Ray,
+        t_max: f64,
+        cp: &[Point3f],
+        object_from_ray: &Transform,
+        u0: f64,
+        u1: f64,
+        depth: i32,
+        want_interaction: bool,
+        si: &mut Option<ShapeIntersection>,
+    ) -> bool {
+        let ray_length = ray.d.length();
+
+        if depth > 0 {
+            // Split curve segment into sub-segments and test for intersection
+            let cp_split = subdivide_cubic_bezier(cp);
+            let u = [u0, (u0 + u1) / 2.0, u1];
+
+            for seg in 0..2 {
+                // Check ray against curve segment's bounding box
+                let max_width = self.width_at(u[seg]).max(self.width_at(u[seg + 1]));
+                let cps = &cp_split[3 * seg..3 * seg + 4];
+                let curve_bounds = bound_cubic_bezier(cps).expand(0.5 * max_width);
+                let ray_bounds = Bounds3f::from_multiple_points(&[
+                    Point3f::new(0.0, 0.0, 0.0),
+                    Point3f::new(0.0, 0.0, ray_length * t_max),
+                ]);
+                if !ray_bounds.overlaps(&curve_bounds) {
+                    continue;
+                }
+
+                // Recursively test ray-segment intersection
+                let hit = self.recursive_intersect(
+                    ray,
+                    t_max,
+                    cps,
+                    object_from_ray,
+                    u[seg],
+                    u[seg + 1],
+                    depth - 1,
+                    want_interaction,
+                    si,
+                );
+                if hit && !want_interaction {
+                    return true;
+                }
+            }
+
+            return si.is_some();
+        }
+
+        // Intersect ray with curve segment
+        // Test ray against segment endpoint boundaries
+        // Test sample point against tangent perpendicular at curve start
+        let edge = (cp[1].y - cp[0].y) * -cp[0].y + cp[0].x * (cp[0].x - cp[1].x);
+        if edge < 0.0 {
+            return false;
+        }
+
+        // Test sample point against tangent perpendicular at curve end
+        let edge = (cp[2].y - cp[3].y) * -cp[3].y + cp[3].x * (cp[3].x - cp[2].x);
+        if edge < 0.0 {
+            return false;
+        }
+
+        // Find line $w$ that gives minimum distance to sample point
+        let segment_dir = Vector2f::new(cp[3].x - cp[0].x, cp[3].y - cp[0].y);
+        let denom = segment_dir.x * segment_dir.x + segment_dir.y * segment_dir.y;
+        if denom == 0.0 {
+            return false;
+        }
+        let w = (-cp[0].x * segment_dir.x - cp[0].y * segment_dir.y) / denom;
+
+        // Compute $u$ coordinate of curve intersection point and _hit_width_
+        let u = lerp(w, u0, u1).clamp(u0, u1);
+        let mut hit_width = self.width_at(u);
+        let mut n_hit = Normal3f::new(0.0, 0.0, 0.0);
+        if self.common.curve_type == CurveType::Ribbon {
+            // Scale _hit_width_ based on ribbon orientation
+            n_hit = if self.common.normal_angle == 0.0 {
+                self.common.n[0]
+            } else {
+                let sin0 =
+                    ((1.0 - u) * self.common.normal_angle).sin() * self.common.inv_sin_normal_angle;
+                let sin1 = (u * self.common.normal_angle).sin() * self.common.inv_sin_normal_angle;
+                sin0 * self.common.n[0] + sin1 * self.common.n[1]
+            };
+            hit_width *= n_hit.abs_dot(ray.d) / ray_length;
+        }
+
+        // Test intersection point against curve width
+        let (pc, dpcdw) = evaluate_cubic_bezier(cp, w.clamp(0.0, 1.0));
+        let pt_curve_dist2 = sqr(pc.x) + sqr(pc.y);
+        if pt_curve_dist2 > sqr(hit_width) * 0.25 {
+            return false;
+        }
+        if pc.z < 0.0 || pc.z > ray_length * t_max {
+            return false;
+        }
+
+        // Compute _t_hit_ for curve intersection
+        let t_hit = pc.z / ray_length;
+        if let Some(_si) = si {
+            if t_hit > _si.t_hit {
+                return false;
+            }
+        }
+
+        if !want_interaction {
+            return true;
+        }
+
+        // Compute $v$ coordinate of curve intersection point
+        let pt_curve_dist = pt_curve_dist2.sqrt();
+        let edge_func = dpcdw.x * -pc.y + pc.x * dpcdw.y;
+        let v = if edge_func > 0.0 {
+            0.5 + pt_curve_dist / hit_width
+        } else {
+            0.5 - pt_curve_dist / hit_width
+        };
+
+        // Compute $\dpdu$ and $\dpdv$ for curve intersection
+        let (_, dpdu) = evaluate_cubic_bezier(&self.common.cp_obj, u);
+        let dpdv = if self.common.curve_type == CurveType::Ribbon {
+            Vector3f::from(n_hit).cross(dpdu).normalize() * hit_width
+        } else {
+            // Compute curve $\dpdv$ for flat and cylinder curves
+            let dpdu_plane = object_from_ray.inverse_on_vector3f(dpdu);
+            let mut dpdv_plane =
+                Vector3f::new(-dpdu_plane.y, dpdu_plane.x, 0.0).normalize() * hit_width;
+            if self.common.curve_type == CurveType::Cylinder {
+                // Rotate _dpdv_plane_ to give cylindrical appearance
+                let theta = lerp(v, -90.0, 90.0);
+                let rot = Transform::rotate(-theta, dpdu_plane.x, dpdu_plane.y, dpdu_plane.z);
+                dpdv_plane = rot.on_vector3f(dpdv_plane);
+            }
+            object_from_ray.on_vector3f(dpdv_plane)
+        };
+
+        // Compute error bounds for curve intersection
+        let p_error = Vector3f::new(hit_width, hit_width, hit_width);
+
+        let mut interaction = SurfaceInteraction::new(
+            Point3fi::from_value_and_error(ray.at(t_hit), p_error),
+            Point2f::new(u, v),
+            -ray.d,
+            dpdu,
+            dpdv,
+            Normal3f::new(0.0, 0.0, 0.0),
+            Normal3f::new(0.0, 0.0, 0.0),
+            ray.time,
+        );
+
+        if self.common.reverse_orientation ^ self.common.transform_swaps_handedness {
+            interaction.interaction.n = -interaction.interaction.n;
+            interaction.shading.n = -interaction.shading.n;
+        }
+
+        *si = Some(ShapeIntersection {
+            t_hit,
+            surface_interaction: self
+                .common
+                .render_from_object
+                .on_surface_interaction(interaction),
+        });
+
+        return true;
+    }
+}
+
+impl Shape for Curve {
+    fn intersect(&self, ray: &Ray, t_max: f64) -> Option<ShapeIntersection> {
+        let mut si = None;
+        self.intersect_ray(ray, t_max, true, &mut si);
+        return si;
+    }
+
+    fn fast_intersect(&self, ray: &Ray, t_max: f64) -> bool {
+        return self.intersect_ray(ray, t_max, false, &mut None);
+    }
+
+    fn bounds(&self) -> Bounds3f {
+        let cp_span = cubic_bezier_control_points(&self.common.cp_obj, self.u_min, self.u_max);
+        let obj_bounds = bound_cubic_bezier(&cp_span);
+
+        // Expand _obj_bounds_ by maximum curve width over $u$ range
+        let max_width = self.width_at(self.u_min).max(self.width_at(self.u_max));
+        let obj_bounds = obj_bounds.expand(max_width * 0.5);
+
+        return self.common.render_from_object.on_bounds(obj_bounds);
+    }
+
+    fn area(&self) -> f64 {
+        let cp_obj = cubic_bezier_control_points(&self.common.cp_obj, self.u_min, self.u_max);
+        let avg_width = (self.width_at(self.u_min) + self.width_at(self.u_max)) * 0.5;
+
+        let mut approx_length = 0.0;
+        for i in 0..3 {
+            approx_length += (cp_obj[i] - cp_obj[i + 1]).length();
+        }
+
+        return approx_length * avg_width;
+    }
+
+    fn sample(&self, u: Point2f) -> Option<ShapeSample> {
+        panic!("Curve::sample() not implemented");
+    }
+
+    fn sample_with_context(&self, ctx: &ShapeSampleContext, u: Point2f) -> Option<ShapeSample> {
+        panic!("Curve::sample_with_context() not implemented");
+    }
+}
+
+fn create_curve(
+    render_from_object: &Transform,
+    object_from_render: &Transform,
+    reverse_orientation: bool,
+    cp: [Point3f; 4],
+    w0: f64,
+    w1: f64,
+    curve_type: CurveType,
+    norm: &[Normal3f],
+    split_depth: usize,
+) -> Vec<Arc<dyn Shape>> {
+    let common = Arc::new(CurveCommon::new(
+        cp,
+        w0,
+        w1,
+        curve_type,
+        norm,
+        *render_from_object,
+        *object_from_render,
+        reverse_orientation,
+    ));
+
+    let n_segments = 1 << split_depth;
+    return (0..n_segments)
+        .map(|i| {
+            let u_min = i as f64 / n_segments as f64;
+            let u_max = (i + 1) as f64 / n_segments as f64;
+            Arc::new(Curve::new(common.clone(), u_min, u_max)) as Arc<dyn Shape>
+        })
+        .collect();
+}
+
+pub fn create_curves(
+    render_from_object: &Transform,
+    object_from_render: &Transform,
+    reverse_orientation: bool,
+    parameters: &ParameterDict,
+) -> Result<Vec<Arc<dyn Shape>>, SceneError> {
+    let width = parameters.get_one_float("width", Some(1.0))?;
+    let width0 = parameters.get_one_float("width0", Some(width))?;
+    let width1 = parameters.get_one_float("width1", Some(width))?;
+
+    let degree = parameters.get_one_integer("degree", Some(3))?;
+    if degree != 2 && degree != 3 {
+        return Err(parameters.parameter_error(
+            "degree",
+            format!(
+                "invalid degree {}: only degree 2 and 3 curves are supported",
+                degree
+            ),
+        ));
+    }
+    let degree = degree as usize;
+
+    let basis = parameters.get_string("basis", Some("bezier".to_string()))?;
+    if basis != "bezier" && basis != "bspline" {
+        return Err(parameters.parameter_error(
+            "basis",
+            format!(
+                "invalid basis `{}`: only `bezier` and `bspline` are supported",
+                basis
+            ),
+        ));
+    }
+
+    let cp = parameters.get_point3_array("P")?;
+    let bezier_basis = basis == "bezier";
+    let n_segments = if bezier_basis {
+        // After the first segment, which uses degree+1 control points,
+        // subsequent segments reuse the last control point of the previous
+        // one and then use degree more control points.
+        if cp.len() < degree + 1 || (cp.len() - 1 - degree) % degree != 0 {
+            return Err(parameters.parameter_error(
+                "P",
+                format!(
+                    "invalid number of control points {}: for the degree {} Bezier basis 3n+1 are required",
+                    cp.len(),
+                    degree
+                ),
+            ));
+        }
+        (cp.len() - 1) / degree
+    } else {
+        if cp.len() < degree + 1 {
+            return Err(parameters.parameter_error(
+                "P",
+                format!(
+                    "must provide at least {} control points for degree {} b-spline curves",
+                    degree + 1,
+                    degree
+                ),
+            ));
+        }
+        cp.len() - degree
+    };
+
+    let curve_type = match parameters
+        .get_string("type", Some("flat".to_string()))?
+        .as_str()
+    {
+        "flat" => CurveType::Flat,
+        "ribbon" => CurveType::Ribbon,
+        "cylinder" => CurveType::Cylinder,
+        _type => {
+            return Err(
+                parameters.parameter_error("type", format!("unknown curve type `{}`", _type))
+            );
+        }
+    };
+
+    let mut n = parameters.get_normal3_array("N");
+    if !n.is_empty() {
+        if curve_type != CurveType::Ribbon {
+            println!("curve normals are only used with `ribbon` type curves, discarding");
+            n = vec![];
+        } else if n.len() != n_segments + 1 {
+            return Err(parameters.parameter_error(
+                "N",
+                format!(
+                    "invalid number of normals {}: must provide {} normals for ribbon curves with {} segments",
+                    n.len(),
+                    n_segments + 1,
+                    n_segments
+                ),
+            ));
+        }
+    } else if curve_type == CurveType::Ribbon {
+        return Err(SceneError::new(
+            SceneErrorKind::MissingParameter("N".to_string()),
+            None,
+        ));
+    }
+
+    let split_depth = parameters.get_one_integer("splitdepth", Some(3))?.max(0) as usize;
+
+    let mut curves = vec![];
+    // index of the first control point for the current segment, updated after
+    // each segment depending on the basis
+    let mut cp_offset = 0;
+    for seg in 0..n_segments {
+        // compute the cubic Bezier control points for the current segment
+        let seg_cp_bezier = if bezier_basis {
+            let seg_cp = if degree == 2 {
+                elevate_quadratic_bezier_to_cubic(&cp[cp_offset..cp_offset + 3])
+            } else {
+                [
+                    cp[cp_offset],
+                    cp[cp_offset + 1],
+                    cp[cp_offset + 2],
+                    cp[cp_offset + 3],
+                ]
+            };
+            cp_offset += degree;
+            seg_cp
+        } else {
+            // uniform b-spline
+            let seg_cp = if degree == 2 {
+                let bezier_cp = quadratic_bspline_to_bezier(&cp[cp_offset..cp_offset + 3]);
+                elevate_quadratic_bezier_to_cubic(&bezier_cp)
+            } else {
+                cubic_bspline_to_bezier(&cp[cp_offset..cp_offset + 4])
+            };
+            cp_offset += 1;
+            seg_cp
+        };
+
+        let seg_n = if n.is_empty() {
+            &n[..]
+        } else {
+            &n[seg..seg + 2]
+        };
+
+        curves.extend(create_curve(
+            render_from_object,
+            object_from_render,
+            reverse_orientation,
+            seg_cp_bezier,
+            lerp(seg as f64 / n_segments as f64, width0, width1),
+            lerp((seg + 1) as f64 / n_segments as f64, width0, width1),
+            curve_type,
+            seg_n,
+            split_depth,
+        ));
+    }
+
+    return Ok(curves);
+}
@@ -1,3 +1,4 @@
+pub mod curve;
 pub mod loop_subdivision;
 pub mod sphere;
 pub mod tri_quad_mesh;
@@ -152,6 +152,7 @@ impl Sphere {
         &self,
         isect: &QuadricIntersection,
         wo: Vector3f,
+        time: f64,
     ) -> SurfaceInteraction {
         let p_hit = isect.p_obj;
         let phi = isect.phi;
@@ -216,6 +217,7 @@ impl Sphere {
                 dpdv,
                 dndu,
                 dndv,
+                time,
             ));
     }
 }
@@ -225,7 +227,11 @@ impl Shape for Sphere {
         return match self.basic_intersect(ray, t_max) {
             None => None,
             Some(quadric_intersection) => {
-                let interaction = self.interaction_from_intersection(&quadric_intersection, -ray.d);
+                let interaction = self.interaction_from_intersection(
+                    &quadric_intersection,
+                    -ray.d,
+                    ray.time,
+                );
 
                 Some(ShapeIntersection {
                     t_hit: quadric_intersection.t_hit,
@@ -8,43 +8,476 @@ pub struct TriQuadMesh {
     pub n: Vec<Normal3f>,
     pub uv: Vec<Point2f>,
     pub tri_indices: Vec<usize>,
+    pub quad_indices: Vec<usize>,
+    // `face_indices` of the PLY file for each triangle and each quad, or empty
+    pub face_indices: Vec<usize>,
+    pub quad_face_indices: Vec<usize>,
 }
 
-pub fn read_ply(ply_file_path: &str) -> TriQuadMesh {
-    let ply_model = {
-        let ply_parser = ply_rs::parser::Parser::<ply::DefaultElement>::new();
+impl TriQuadMesh {
+    // there is no bilinear patch shape: each quad is split into two triangles, which
+    // keep the face index of the quad
+    pub fn convert_to_only_triangles(&mut self) {
+        for quad in self.quad_indices.chunks(4) {
+            self.tri_indices
+                .extend([quad[0], quad[1], quad[2], quad[3], quad[0], quad[2]]);
+        }
+        for face_index in &self.quad_face_indices {
+            self.face_indices.extend([*face_index, *face_index]);
+        }
+
+        self.quad_indices = vec![];
+        self.quad_face_indices = vec![];
+    }
+
+    fn add_face(
+        &mut self,
+        vertex_indices: &[usize],
+        face_index: Option<usize>,
+    ) -> Result<(), String> {
+        let (indices, face_indices) = match vertex_indices.len() {
+            3 => (&mut self.tri_indices, &mut self.face_indices),
+            4 => (&mut self.quad_indices, &mut self.quad_face_indices),
+            _ => {
+                return Err(format!(
+                    "found a face with {} vertices, only triangles and quads are supported",
+                    vertex_indices.len()
+                ));
+            }
+        };
 
-        let _file = &mut match File::open(ply_file_path) {
-            Ok(opened_file) => opened_file,
-            Err(_) => {
-                panic!("couldn't read PLY from `{}`", ply_file_path);
+        indices.extend_from_slice(vertex_indices);
+        if let Some(_face_index) = face_index {
+            face_indices.push(_face_index);
+        }
+
+        return Ok(());
+    }
+
+    fn check_indices(&self) -> Result<(), String> {
+        let has_face_indices = !self.face_indices.is_empty() || !self.quad_face_indices.is_empty();
+        if has_face_indices
+            && (self.face_indices.len() != self.tri_indices.len() / 3
+                || self.quad_face_indices.len() != self.quad_indices.len() / 4)
+        {
+            return Err("`face_indices` is missing on some faces".to_string());
+        }
+
+        for idx in self.tri_indices.iter().chain(self.quad_indices.iter()) {
+            if *idx >= self.p.len() {
+                return Err(format!(
+                    "vertex index {} out of range, there are {} vertices",
+                    idx,
+                    self.p.len()
+                ));
             }
+        }
+
+        return Ok(());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlyScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PlyScalarType {
+    fn parse(name: &str) -> Option<PlyScalarType> {
+        return match name {
+            "char" | "int8" => Some(PlyScalarType::Int8),
+            "uchar" | "uint8" => Some(PlyScalarType::UInt8),
+            "short" | "int16" => Some(PlyScalarType::Int16),
+            "ushort" | "uint16" => Some(PlyScalarType::UInt16),
+            "int" | "int32" => Some(PlyScalarType::Int32),
+            "uint" | "uint32" => Some(PlyScalarType::UInt32),
+            "float" | "float32" => Some(PlyScalarType::Float32),
+            "double" | "float64" => Some(PlyScalarType::Float64),
+            _ => None,
+        };
+    }
+
+    fn size(&self) -> usize {
+        return match self {
+            PlyScalarType::Int8 | PlyScalarType::UInt8 => 1,
+            PlyScalarType::Int16 | PlyScalarType::UInt16 => 2,
+            PlyScalarType::Int32 | PlyScalarType::UInt32 | PlyScalarType::Float32 => 4,
+            PlyScalarType::Float64 => 8,
         };
+    }
+
+    // _bytes_ starts with a little endian value of this type
+    fn read_le(&self, bytes: &[u8]) -> f64 {
+        return match self {
+            PlyScalarType::Int8 => bytes[0] as i8 as f64,
+            PlyScalarType::UInt8 => bytes[0] as f64,
+            PlyScalarType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            PlyScalarType::UInt16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            PlyScalarType::Int32 => i32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64,
+            PlyScalarType::UInt32 => u32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64,
+            PlyScalarType::Float32 => f32::from_le_bytes(bytes[..4].try_into().unwrap()) as f64,
+            PlyScalarType::Float64 => f64::from_le_bytes(bytes[..8].try_into().unwrap()),
+        };
+    }
+}
 
-        let optional_model = if ply_file_path.ends_with(".ply.gz") {
-            let buffer_reader = BufReader::new(_file);
-            let mut gzip_reader = GzDecoder::new(buffer_reader);
-            ply_parser.read_ply(&mut gzip_reader)
-        } else if ply_file_path.ends_with(".ply") {
-            ply_parser.read_ply(_file)
-        } else {
-            panic!("unknown file format: {}", ply_file_path);
+#[derive(Clone, Debug)]
+enum PlyPropertyType {
+    Scalar(PlyScalarType),
+    // type of the element count, type of the elements
+    List(PlyScalarType, PlyScalarType),
+}
+
+#[derive(Clone, Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<(String, PlyPropertyType)>,
+}
+
+impl PlyElement {
+    // size of an element without list properties
+    fn fixed_stride(&self) -> Option<usize> {
+        let mut stride = 0;
+        for (_, property_type) in &self.properties {
+            match property_type {
+                PlyPropertyType::Scalar(_scalar) => {
+                    stride += _scalar.size();
+                }
+                PlyPropertyType::List(_, _) => {
+                    return None;
+                }
+            }
+        }
+
+        return Some(stride);
+    }
+
+    fn scalar_offset(&self, names: &[&str]) -> Option<(usize, PlyScalarType)> {
+        let mut offset = 0;
+        for (name, property_type) in &self.properties {
+            if let PlyPropertyType::Scalar(_scalar) = property_type {
+                if names.contains(&name.as_str()) {
+                    return Some((offset, *_scalar));
+                }
+                offset += _scalar.size();
+            }
+        }
+
+        return None;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+// the format, the elements and the offset of the body
+fn parse_ply_header(data: &[u8]) -> Result<(PlyFormat, Vec<PlyElement>, usize), String> {
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = vec![];
+    let mut offset = 0;
+
+    loop {
+        let line_end = match data[offset..].iter().position(|c| *c == b'\n') {
+            None => {
+                return Err("PLY header without `end_header`".to_string());
+            }
+            Some(_position) => offset + _position,
         };
+        let line = String::from_utf8_lossy(&data[offset..line_end]).to_string();
+        offset = line_end + 1;
+
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        if words.is_empty() {
+            continue;
+        }
+
+        match words[0] {
+            "ply" | "comment" | "obj_info" => {}
+            "format" => {
+                format = match words.get(1) {
+                    Some(&"ascii") => Some(PlyFormat::Ascii),
+                    Some(&"binary_little_endian") => Some(PlyFormat::BinaryLittleEndian),
+                    Some(&"binary_big_endian") => Some(PlyFormat::BinaryBigEndian),
+                    _ => {
+                        return Err(format!("unknown PLY format `{}`", line));
+                    }
+                };
+            }
+            "element" if words.len() == 3 => {
+                elements.push(PlyElement {
+                    name: words[1].to_string(),
+                    count: match words[2].parse::<usize>() {
+                        Err(_) => {
+                            return Err(format!("invalid element count `{}`", line));
+                        }
+                        Ok(_count) => _count,
+                    },
+                    properties: vec![],
+                });
+            }
+            "property" => {
+                let parse_type = |name: &str| match PlyScalarType::parse(name) {
+                    None => Err(format!("unknown PLY type `{}`", name)),
+                    Some(_scalar) => Ok(_scalar),
+                };
+
+                let property = match (words.get(1), words.len()) {
+                    (Some(&"list"), 5) => (
+                        words[4].to_string(),
+                        PlyPropertyType::List(parse_type(words[2])?, parse_type(words[3])?),
+                    ),
+                    (Some(_), 3) => (
+                        words[2].to_string(),
+                        PlyPropertyType::Scalar(parse_type(words[1])?),
+                    ),
+                    _ => {
+                        return Err(format!("invalid PLY property `{}`", line));
+                    }
+                };
 
-        match optional_model {
-            Ok(_model) => _model,
-            Err(msg) => {
-                panic!("fail to read PLY file `{}`:\n{}", ply_file_path, msg);
+                match elements.last_mut() {
+                    None => {
+                        return Err("PLY property outside of an element".to_string());
+                    }
+                    Some(_element) => _element.properties.push(property),
+                }
+            }
+            "end_header" => {
+                break;
+            }
+            _ => {
+                return Err(format!("invalid PLY header line `{}`", line));
             }
         }
+    }
+
+    return match format {
+        None => Err("PLY header without format".to_string()),
+        Some(_format) => Ok((_format, elements, offset)),
+    };
+}
+
+// the properties _names_ of every vertex, each one given with its aliases, decoded
+// by _make_ into the values stored in the mesh
+fn read_vertex_values<T: Send, const N: usize>(
+    element: &PlyElement,
+    vertex_data: &[u8],
+    names: [&[&str]; N],
+    make: impl Fn([f64; N]) -> T + Sync,
+) -> Option<Vec<T>> {
+    // a fixed stride is checked by the caller
+    let stride = element.fixed_stride().unwrap();
+    let offsets = names
+        .iter()
+        .map(|aliases| element.scalar_offset(aliases))
+        .collect::<Option<Vec<(usize, PlyScalarType)>>>()?;
+
+    return Some(
+        vertex_data
+            .par_chunks_exact(stride)
+            .map(|vertex| {
+                let mut values = [0.0; N];
+                for (idx, (offset, scalar)) in offsets.iter().enumerate() {
+                    values[idx] = scalar.read_le(&vertex[*offset..]);
+                }
+                make(values)
+            })
+            .collect(),
+    );
+}
+
+fn read_vertices_binary(
+    mesh: &mut TriQuadMesh,
+    element: &PlyElement,
+    vertex_data: &[u8],
+) -> Result<(), String> {
+    mesh.p = match read_vertex_values(element, vertex_data, [&["x"], &["y"], &["z"]], |v| {
+        Point3f::new(v[0], v[1], v[2])
+    }) {
+        None => {
+            return Err("PLY vertices without x, y and z".to_string());
+        }
+        Some(_p) => _p,
+    };
+
+    let normal_names: [&[&str]; 3] = [&["nx"], &["ny"], &["nz"]];
+    mesh.n = read_vertex_values(element, vertex_data, normal_names, |v| {
+        Normal3f::new(v[0], v[1], v[2])
+    })
+    .unwrap_or_default();
+
+    let uv_names: [&[&str]; 2] = [
+        &["u", "s", "texture_u", "texture_s"],
+        &["v", "t", "texture_v", "texture_t"],
+    ];
+    mesh.uv = read_vertex_values(element, vertex_data, uv_names, |v| Point2f::new(v[0], v[1]))
+        .unwrap_or_default();
+
+    return Ok(());
+}
+
+// indices are stored as integers of any type, a negative one would wrap around
+fn to_ply_index(value: i64, name: &str) -> Result<usize, String> {
+    if value < 0 {
+        return Err(format!("negative {} {}", name, value));
+    }
+
+    return Ok(value as usize);
+}
+
+fn truncated_ply() -> String {
+    return "PLY file is truncated".to_string();
+}
+
+// Binary little endian files are decoded from the bytes of the file, once it is read
+// in memory: vertices with a fixed stride are converted in parallel straight into the
+// vectors of the mesh, faces are scanned once.
+fn read_ply_binary(data: &[u8], elements: &[PlyElement]) -> Result<TriQuadMesh, String> {
+    let mut mesh = TriQuadMesh {
+        p: vec![],
+        n: vec![],
+        uv: vec![],
+        tri_indices: vec![],
+        quad_indices: vec![],
+        face_indices: vec![],
+        quad_face_indices: vec![],
     };
 
-    let mut p: Vec<Point3f> = Vec::new();
-    let mut n: Vec<Normal3f> = Vec::new();
-    let mut uv: Vec<Point2f> = Vec::new();
+    let mut offset = 0;
+    let mut vertex_indices: Vec<usize> = Vec::with_capacity(4);
+    for element in elements {
+        if element.name == "vertex" {
+            let stride = element.fixed_stride().unwrap();
+            let end = offset + stride * element.count;
+            if end > data.len() {
+                return Err(truncated_ply());
+            }
+            read_vertices_binary(&mut mesh, element, &data[offset..end])?;
+            offset = end;
+            continue;
+        }
+
+        if let Some(_stride) = element.fixed_stride() {
+            // nothing to read in elements other than faces without lists
+            offset += _stride * element.count;
+            continue;
+        }
+
+        let is_face = element.name == "face";
+        if is_face {
+            mesh.tri_indices.reserve(element.count * 3);
+        }
+
+        for _ in 0..element.count {
+            let mut face_index = None;
+            vertex_indices.clear();
+
+            for (name, property_type) in &element.properties {
+                match property_type {
+                    PlyPropertyType::Scalar(_scalar) => {
+                        if offset + _scalar.size() > data.len() {
+                            return Err(truncated_ply());
+                        }
+                        if is_face && name == "face_indices" {
+                            face_index = Some(to_ply_index(
+                                _scalar.read_le(&data[offset..]) as i64,
+                                "`face_indices` value",
+                            )?);
+                        }
+                        offset += _scalar.size();
+                    }
+                    PlyPropertyType::List(_count_type, _item_type) => {
+                        if offset + _count_type.size() > data.len() {
+                            return Err(truncated_ply());
+                        }
+                        let count = _count_type.read_le(&data[offset..]) as usize;
+                        offset += _count_type.size();
+
+                        let end = offset + count * _item_type.size();
+                        if end > data.len() {
+                            return Err(truncated_ply());
+                        }
+                        if is_face && (name == "vertex_indices" || name == "vertex_index") {
+                            for item in data[offset..end].chunks_exact(_item_type.size()) {
+                                vertex_indices.push(to_ply_index(
+                                    _item_type.read_le(item) as i64,
+                                    "vertex index",
+                                )?);
+                            }
+                        }
+                        offset = end;
+                    }
+                }
+            }
+
+            if is_face {
+                mesh.add_face(&vertex_indices, face_index)?;
+            }
+        }
+    }
+
+    return Ok(mesh);
+}
+
+fn property_to_f64(property: &ply::Property) -> Option<f64> {
+    return match property {
+        ply::Property::Char(x) => Some(*x as f64),
+        ply::Property::UChar(x) => Some(*x as f64),
+        ply::Property::Short(x) => Some(*x as f64),
+        ply::Property::UShort(x) => Some(*x as f64),
+        ply::Property::Int(x) => Some(*x as f64),
+        ply::Property::UInt(x) => Some(*x as f64),
+        ply::Property::Float(x) => Some(*x as f64),
+        ply::Property::Double(x) => Some(*x),
+        _ => None,
+    };
+}
+
+fn property_to_indices(property: ply::Property) -> Option<Vec<i64>> {
+    return match property {
+        ply::Property::ListChar(li) => Some(li.into_iter().map(|i| i as i64).collect()),
+        ply::Property::ListUChar(li) => Some(li.into_iter().map(|i| i as i64).collect()),
+        ply::Property::ListShort(li) => Some(li.into_iter().map(|i| i as i64).collect()),
+        ply::Property::ListUShort(li) => Some(li.into_iter().map(|i| i as i64).collect()),
+        ply::Property::ListInt(li) => Some(li.into_iter().map(|i| i as i64).collect()),
+        ply::Property::ListUInt(li) => Some(li.into_iter().map(|i| i as i64).collect()),
+        _ => None,
+    };
+}
+
+// ascii and big endian files, parsed by ply_rs
+fn read_ply_generic(data: &[u8]) -> Result<TriQuadMesh, String> {
+    let ply_parser = ply_rs::parser::Parser::<ply::DefaultElement>::new();
+    let ply_model = match ply_parser.read_ply(&mut BufReader::new(data)) {
+        Ok(_model) => _model,
+        Err(msg) => {
+            return Err(msg.to_string());
+        }
+    };
+
+    let mut mesh = TriQuadMesh {
+        p: vec![],
+        n: vec![],
+        uv: vec![],
+        tri_indices: vec![],
+        quad_indices: vec![],
+        face_indices: vec![],
+        quad_face_indices: vec![],
+    };
     let mut has_normals: bool = false;
     let mut has_uvs: bool = false;
-    let mut vertex_indices: Vec<usize> = Vec::new();
 
     for (name, list) in ply_model.payload.into_iter() {
         match name.as_ref() {
@@ -54,154 +487,311 @@ pub fn read_ply(ply_file_path: &str) -> TriQuadMesh {
                     let mut nrm: Normal3f = Normal3f::default();
                     let mut pt2: Point2f = Point2f::default();
                     for (name2, list2) in elem.into_iter() {
-                        match name2.as_ref() {
-                            "x" => {
-                                if let ply::Property::Float(x) = list2 {
-                                    pnt.x = x as f64;
-                                }
-                            }
-                            "y" => {
-                                if let ply::Property::Float(y) = list2 {
-                                    pnt.y = y as f64;
-                                }
-                            }
-                            "z" => {
-                                if let ply::Property::Float(z) = list2 {
-                                    pnt.z = z as f64;
-                                }
+                        let value = match property_to_f64(&list2) {
+                            None => {
+                                continue;
                             }
+                            Some(_value) => _value,
+                        };
+                        match name2.as_ref() {
+                            "x" => pnt.x = value,
+                            "y" => pnt.y = value,
+                            "z" => pnt.z = value,
                             "nx" => {
                                 has_normals = true;
-                                if let ply::Property::Float(x) = list2 {
-                                    nrm.x = x as f64;
-                                }
-                            }
-                            "ny" => {
-                                has_normals = true;
-                                if let ply::Property::Float(y) = list2 {
-                                    nrm.y = y as f64;
-                                }
-                            }
-                            "nz" => {
-                                has_normals = true;
-                                if let ply::Property::Float(z) = list2 {
-                                    nrm.z = z as f64;
-                                }
+                                nrm.x = value;
                             }
-                            "u" | "s" => {
+                            "ny" => nrm.y = value,
+                            "nz" => nrm.z = value,
+                            "u" | "s" | "texture_u" | "texture_s" => {
                                 has_uvs = true;
-                                if let ply::Property::Float(x) = list2 {
-                                    pt2.x = x as f64;
-                                }
-                            }
-                            "v" | "t" => {
-                                has_uvs = true;
-                                if let ply::Property::Float(y) = list2 {
-                                    pt2.y = y as f64;
-                                }
-                            }
-                            _ => {
-                                println!("name2 = {:?}", name2);
-                                unreachable!();
+                                pt2.x = value;
                             }
+                            "v" | "t" | "texture_v" | "texture_t" => pt2.y = value,
+                            // colors and other attributes are not used
+                            _ => {}
                         }
                     }
-                    p.push(pnt);
+                    mesh.p.push(pnt);
                     if has_normals {
-                        n.push(nrm);
+                        mesh.n.push(nrm);
                     }
                     if has_uvs {
-                        uv.push(pt2);
+                        mesh.uv.push(pt2);
                     }
                 }
             }
             "face" => {
                 for elem in list.into_iter() {
-                    let mut nrm: Normal3f = Normal3f::default();
+                    let mut vertex_indices: Vec<usize> = vec![];
+                    let mut face_index = None;
                     for (name2, list2) in elem.into_iter() {
                         match name2.as_ref() {
-                            "vertex_indices" => {
-                                if let ply::Property::ListInt(li) = list2 {
-                                    let mut _v_indices: Vec<usize> = Vec::new();
-                                    for i in li.into_iter() {
-                                        _v_indices.push(i as usize);
-                                    }
-                                    // println!("vertex_indices = {:?}", vertex_indices);
-                                    if _v_indices.len() != 3 {
-                                        if _v_indices.len() == 4 {
-                                            // handle quads (split it into 2 triangles)
-                                            let v1 = _v_indices[0];
-                                            let v3 = _v_indices[2];
-                                            let v4 = _v_indices.pop().unwrap();
-                                            _v_indices.push(v4);
-                                            _v_indices.push(v1);
-                                            _v_indices.push(v3);
-                                        } else {
-                                            panic!("plymesh: Ignoring face with {} vertices (only triangles and quads are supported!)",
-                                                   _v_indices.len());
-                                        }
-                                    }
-                                    // now we can add the indices to the triangle mesh vertex indices
-                                    for vi in _v_indices {
-                                        vertex_indices.push(vi.try_into().unwrap());
-                                    }
-                                } else if let ply::Property::ListUInt(li) = list2 {
-                                    let mut _v_indices: Vec<usize> = Vec::new();
-                                    for i in li.into_iter() {
-                                        _v_indices.push(i as usize);
-                                    }
-                                    // println!("vertex_indices = {:?}", vertex_indices);
-                                    if _v_indices.len() != 3 {
-                                        if _v_indices.len() == 4 {
-                                            // handle quads (split it into 2 triangles)
-                                            let v1 = _v_indices[0];
-                                            let v3 = _v_indices[2];
-                                            let v4 = _v_indices.pop().unwrap();
-                                            _v_indices.push(v4);
-                                            _v_indices.push(v1);
-                                            _v_indices.push(v3);
-                                        } else {
-                                            panic!("plymesh: Ignoring face with {} vertices (only triangles and quads are supported!)",
-                                                   _v_indices.len());
-                                        }
-                                    }
-                                    // now we can add the indices to the triangle mesh vertex indices
-                                    for vi in _v_indices {
-                                        vertex_indices.push(vi.try_into().unwrap());
-                                    }
-                                }
-                            }
-                            "nx" => {
-                                has_normals = true;
-                                if let ply::Property::Float(x) = list2 {
-                                    nrm.x = x as f64;
-                                }
-                            }
-                            "ny" => {
-                                has_normals = true;
-                                if let ply::Property::Float(y) = list2 {
-                                    nrm.y = y as f64;
+                            "vertex_indices" | "vertex_index" => {
+                                if let Some(_indices) = property_to_indices(list2) {
+                                    vertex_indices = _indices
+                                        .into_iter()
+                                        .map(|idx| to_ply_index(idx, "vertex index"))
+                                        .collect::<Result<Vec<usize>, String>>()?;
                                 }
                             }
-                            "nz" => {
-                                has_normals = true;
-                                if let ply::Property::Float(z) = list2 {
-                                    nrm.z = z as f64;
-                                }
+                            "face_indices" => {
+                                face_index = match property_to_f64(&list2) {
+                                    None => None,
+                                    Some(_value) => {
+                                        Some(to_ply_index(_value as i64, "`face_indices` value")?)
+                                    }
+                                };
                             }
-                            _ => panic!("couldn't parse {}", name2),
+                            _ => {}
                         }
                     }
+                    mesh.add_face(&vertex_indices, face_index)?;
                 }
             }
-            _ => panic!("couldn't parse `{}`", name),
+            _ => {}
         }
     }
 
-    return TriQuadMesh {
-        p,
-        n,
-        uv,
-        tri_indices: vertex_indices,
+    return Ok(mesh);
+}
+
+// Triangles and quads of a PLY file (.ply or .ply.gz), with the positions, normals
+// and uv of the vertices and the optional `face_indices` of the faces. Properties and
+// elements not listed here are skipped. Errors are described without _ply_file_path_,
+// the caller reports it.
+pub fn read_ply(ply_file_path: &str) -> Result<TriQuadMesh, String> {
+    let data = if ply_file_path.ends_with(".ply.gz") {
+        // decompressed as it is read
+        let file = match std::fs::File::open(ply_file_path) {
+            Ok(_file) => _file,
+            Err(_err) => {
+                return Err(_err.to_string());
+            }
+        };
+        let mut decompressed = vec![];
+        if let Err(msg) =
+            GzDecoder::new(std::io::BufReader::new(file)).read_to_end(&mut decompressed)
+        {
+            return Err(format!("fail to decompress: {}", msg));
+        }
+        decompressed
+    } else if ply_file_path.ends_with(".ply") {
+        match std::fs::read(ply_file_path) {
+            Ok(_data) => _data,
+            Err(_err) => {
+                return Err(_err.to_string());
+            }
+        }
+    } else {
+        return Err("unknown file format, expected `.ply` or `.ply.gz`".to_string());
+    };
+
+    let (format, elements, body_offset) = parse_ply_header(&data)?;
+
+    let fast_path = format == PlyFormat::BinaryLittleEndian
+        && elements
+            .iter()
+            .all(|element| element.name != "vertex" || element.fixed_stride().is_some());
+
+    let mesh = if fast_path {
+        read_ply_binary(&data[body_offset..], &elements)?
+    } else {
+        read_ply_generic(&data)?
     };
+    mesh.check_indices()?;
+
+    return Ok(mesh);
+}
+
+// binary little endian PLY of the triangles of _mesh_, as read back by _read_ply()_
+pub fn write_ply(ply_file_path: &str, mesh: &TriQuadMesh) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut header = format!(
+        "ply\nformat binary_little_endian 1.0\nelement vertex {}\n",
+        mesh.p.len()
+    );
+    header.push_str("property float x\nproperty float y\nproperty float z\n");
+    if !mesh.n.is_empty() {
+        header.push_str("property float nx\nproperty float ny\nproperty float nz\n");
+    }
+    if !mesh.uv.is_empty() {
+        header.push_str("property float u\nproperty float v\n");
+    }
+    header.push_str(&format!(
+        "element face {}\nproperty list uchar int vertex_indices\n",
+        mesh.tri_indices.len() / 3
+    ));
+    if !mesh.face_indices.is_empty() {
+        header.push_str("property int face_indices\n");
+    }
+    header.push_str("end_header\n");
+
+    let mut writer = std::io::BufWriter::new(File::create(ply_file_path)?);
+    writer.write_all(header.as_bytes())?;
+
+    for idx in 0..mesh.p.len() {
+        let mut values = vec![mesh.p[idx].x, mesh.p[idx].y, mesh.p[idx].z];
+        if !mesh.n.is_empty() {
+            values.extend([mesh.n[idx].x, mesh.n[idx].y, mesh.n[idx].z]);
+        }
+        if !mesh.uv.is_empty() {
+            values.extend([mesh.uv[idx].x, mesh.uv[idx].y]);
+        }
+        for value in values {
+            writer.write_all(&(value as f32).to_le_bytes())?;
+        }
+    }
+
+    for (triangle_idx, triangle) in mesh.tri_indices.chunks(3).enumerate() {
+        writer.write_all(&[3u8])?;
+        for idx in triangle {
+            writer.write_all(&(*idx as i32).to_le_bytes())?;
+        }
+        if !mesh.face_indices.is_empty() {
+            writer.write_all(&(mesh.face_indices[triangle_idx] as i32).to_le_bytes())?;
+        }
+    }
+
+    return writer.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POSITIONS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [2.0, 0.0, 0.0],
+    ];
+    // a quad with face index 7 and a triangle with face index 3
+    const FACES: [(&[i32], i32); 2] = [(&[0, 1, 2, 3], 7), (&[1, 4, 2], 3)];
+
+    fn ply_data(format: &str, faces: &[(&[i32], i32)]) -> Vec<u8> {
+        let mut data = format!(
+            "ply\nformat {} 1.0\nelement vertex {}\n\
+             property float x\nproperty float y\nproperty float z\n\
+             property float u\nproperty float v\n\
+             element face {}\nproperty list uchar int vertex_indices\n\
+             property int face_indices\nend_header\n",
+            format,
+            POSITIONS.len(),
+            faces.len()
+        )
+        .into_bytes();
+
+        for p in POSITIONS {
+            let values = [p[0], p[1], p[2], p[0] / 2.0, p[1]];
+            for value in values {
+                match format {
+                    "ascii" => data.extend(format!("{} ", value).into_bytes()),
+                    "binary_little_endian" => data.extend(value.to_le_bytes()),
+                    _ => data.extend(value.to_be_bytes()),
+                }
+            }
+            if format == "ascii" {
+                data.push(b'\n');
+            }
+        }
+
+        for (vertex_indices, face_index) in faces {
+            match format {
+                "ascii" => {
+                    let mut line = format!("{}", vertex_indices.len());
+                    for idx in vertex_indices.iter().chain([face_index]) {
+                        line.push_str(&format!(" {}", idx));
+                    }
+                    data.extend(format!("{}\n", line).into_bytes());
+                }
+                _ => {
+                    data.push(vertex_indices.len() as u8);
+                    for idx in vertex_indices.iter().chain([face_index]) {
+                        if format == "binary_little_endian" {
+                            data.extend(idx.to_le_bytes());
+                        } else {
+                            data.extend(idx.to_be_bytes());
+                        }
+                    }
+                }
+            }
+        }
+
+        return data;
+    }
+
+    fn read_ply_data(name: &str, data: &[u8]) -> Result<TriQuadMesh, String> {
+        let path = std::env::temp_dir().join(format!(
+            "pbrt-rust-test-{}-{}.ply",
+            std::process::id(),
+            name
+        ));
+        std::fs::write(&path, data).unwrap();
+        let mesh = read_ply(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        return mesh;
+    }
+
+    #[test]
+    fn quads_keep_their_face_indices() {
+        let mut mesh = read_ply_data("quads", &ply_data("ascii", &FACES)).unwrap();
+        assert_eq!(mesh.p.len(), 5);
+        assert_eq!(mesh.uv.len(), 5);
+        assert_eq!(mesh.quad_indices, vec![0, 1, 2, 3]);
+        assert_eq!(mesh.quad_face_indices, vec![7]);
+        assert_eq!(mesh.tri_indices, vec![1, 4, 2]);
+        assert_eq!(mesh.face_indices, vec![3]);
+
+        mesh.convert_to_only_triangles();
+        assert_eq!(mesh.tri_indices, vec![1, 4, 2, 0, 1, 2, 3, 0, 2]);
+        assert_eq!(mesh.face_indices, vec![3, 7, 7]);
+        assert!(mesh.quad_indices.is_empty());
+        assert!(mesh.quad_face_indices.is_empty());
+    }
+
+    #[test]
+    fn binary_fast_path_matches_generic_path() {
+        let fast = read_ply_data("le", &ply_data("binary_little_endian", &FACES)).unwrap();
+
+        for format in ["ascii", "binary_big_endian"] {
+            let generic = read_ply_data(format, &ply_data(format, &FACES)).unwrap();
+
+            assert_eq!(generic.p.len(), fast.p.len());
+            for (p_generic, p_fast) in generic.p.iter().zip(fast.p.iter()) {
+                assert_eq!(
+                    (p_generic.x, p_generic.y, p_generic.z),
+                    (p_fast.x, p_fast.y, p_fast.z)
+                );
+            }
+            assert_eq!(generic.uv.len(), fast.uv.len());
+            for (uv_generic, uv_fast) in generic.uv.iter().zip(fast.uv.iter()) {
+                assert_eq!((uv_generic.x, uv_generic.y), (uv_fast.x, uv_fast.y));
+            }
+            assert!(generic.n.is_empty() && fast.n.is_empty());
+            assert_eq!(generic.tri_indices, fast.tri_indices);
+            assert_eq!(generic.quad_indices, fast.quad_indices);
+            assert_eq!(generic.face_indices, fast.face_indices);
+            assert_eq!(generic.quad_face_indices, fast.quad_face_indices);
+        }
+    }
+
+    #[test]
+    fn negative_indices_are_rejected() {
+        let negative_vertex: [(&[i32], i32); 1] = [(&[0, -1, 2], 0)];
+        let negative_face: [(&[i32], i32); 1] = [(&[0, 1, 2], -1)];
+
+        for format in ["binary_little_endian", "ascii", "binary_big_endian"] {
+            let name = format!("{}-vertex", format);
+            let error = read_ply_data(&name, &ply_data(format, &negative_vertex)).err();
+            assert_eq!(error, Some("negative vertex index -1".to_string()));
+
+            let name = format!("{}-face", format);
+            let error = read_ply_data(&name, &ply_data(format, &negative_face)).err();
+            assert_eq!(error, Some("negative `face_indices` value -1".to_string()));
+        }
+    }
 }
@@ -150,6 +150,7 @@ impl Triangle {
         &self,
         ti: &TriangleIntersection,
         wo: Vector3f,
+        time: f64,
     ) -> SurfaceInteraction {
         // Compute triangle partial derivatives
         // Compute deltas and matrix determinant for triangle partial derivatives
@@ -220,8 +221,9 @@ impl Triangle {
             wo,
             dpdu,
             dpdv,
-            Normal3f::nan(),
-            Normal3f::nan(),
+            Normal3f::new(0.0, 0.0, 0.0),
+            Normal3f::new(0.0, 0.0, 0.0),
+            time,
         );
 
         (isect.interaction.n, isect.shading.n) = {
@@ -250,7 +252,11 @@ impl Shape for Triangle {
 
         return Some(ShapeIntersection {
             t_hit: triangle_intersection.t,
-            surface_interaction: self.interaction_from_intersection(&triangle_intersection, -ray.d),
+            surface_interaction: self.interaction_from_intersection(
+                &triangle_intersection,
+                -ray.d,
+                ray.time,
+            ),
         });
     }
 
@@ -326,6 +332,7 @@ impl Shape for Triangle {
                 n,
                 wo: Vector3f::nan(),
                 uv: uv_sample,
+                time: 0.0,
             },
             pdf: 1.0 / self.area(),
         };
@@ -440,6 +447,7 @@ impl Shape for Triangle {
                 n,
                 wo: Vector3::nan(),
                 uv: uv_sample,
+                time: 0.0,
             },
             pdf,
         };
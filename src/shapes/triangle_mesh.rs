@@ -1,4 +1,5 @@
 use crate::pbrt::*;
+use std::mem::size_of;
 
 pub struct TriangleMesh {
     pub reverse_orientation: bool,
@@ -36,6 +37,14 @@ impl TriangleMesh {
             )
         };
 
+        add_memory(
+            MemoryCategory::Meshes,
+            transformed_points.len() * size_of::<Point3f>()
+                + transformed_normals.len() * size_of::<Normal3f>()
+                + uv.len() * size_of::<Point2f>()
+                + indices.len() * size_of::<usize>(),
+        );
+
         return TriangleMesh {
             reverse_orientation,
             p: transformed_points,
@@ -54,6 +63,11 @@ impl TriangleMesh {
             triangles.push(_triangle);
         }
 
+        add_memory(
+            MemoryCategory::Meshes,
+            triangles.len() * (size_of::<Triangle>() + size_of::<Arc<dyn Shape>>()),
+        );
+
         return triangles;
     }
 }
@@ -98,7 +98,7 @@ impl DenselySampledSpectrum {
 
     // D function in PBRT-v4
     pub fn cie_d(temperature: f64) -> Self {
-        let cct = temperature * 1.4338 / 1.4380;
+        let cct = temperature * 1.4388 / 1.4380;
         if cct < 4000.0 {
             // CIE D ill-defined, use blackbody
             let black_body_spectrum = BlackBodySpectrum::new(cct);
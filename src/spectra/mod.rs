@@ -11,5 +11,6 @@ pub mod rgb_illuminant_spectrum;
 pub mod rgb_sigmoid_polynomial;
 pub mod rgb_to_spectrum_data;
 pub mod rgb_to_spectrum_table;
+pub mod rgb_unbounded_spectrum;
 pub mod sampled_spectrum;
 pub mod sampled_wavelengths;
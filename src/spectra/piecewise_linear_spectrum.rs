@@ -48,6 +48,44 @@ impl PiecewiseLinearSpectrum {
         };
     }
 
+    // errors are described without _filename_, the caller reports it
+    pub fn read(filename: &str) -> Result<Self, String> {
+        // a spectrum file holds (lambda, value) pairs separated by whitespace,
+        // `#` starts a comment that runs to the end of the line
+        let content = match read_to_string(filename) {
+            Ok(_content) => _content,
+            Err(error) => {
+                return Err(error.to_string());
+            }
+        };
+
+        let mut samples = vec![];
+        for line in content.lines() {
+            let data = match line.find('#') {
+                None => line,
+                Some(pos) => &line[..pos],
+            };
+
+            for token in data.split_whitespace() {
+                match token.parse::<f64>() {
+                    Ok(value) => samples.push(value),
+                    Err(_) => {
+                        return Err(format!("illegal number `{}`", token));
+                    }
+                }
+            }
+        }
+
+        if samples.is_empty() || samples.len() % 2 != 0 {
+            return Err(format!(
+                "expect (lambda, value) pairs, found {} values",
+                samples.len()
+            ));
+        }
+
+        return Ok(PiecewiseLinearSpectrum::from_interleaved(samples, false));
+    }
+
     pub fn from_interleaved(samples: Vec<f64>, normalize: bool) -> Self {
         if samples.len() % 2 != 0 {
             panic!("illegal samples number");
@@ -20,6 +20,10 @@ impl RGB {
         };
     }
 
+    pub fn average(&self) -> f64 {
+        return (self.r + self.g + self.b) / 3.0;
+    }
+
     pub fn max_component(&self) -> f64 {
         return self.r.max(self.g).max(self.b);
     }
@@ -20,9 +20,9 @@ impl Spectrum for RGBAlbedoSpectrum {
 }
 
 impl RGBAlbedoSpectrum {
-    pub fn new(rgb: RGB) -> Self {
+    pub fn new(color_space: &RGBColorSpace, rgb: RGB) -> Self {
         return Self {
-            rsp: COLOR_SPACE.to_rgb_coeffs(rgb),
+            rsp: color_space.to_rgb_coeffs(rgb),
         };
     }
 }
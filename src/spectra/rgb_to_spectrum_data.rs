@@ -114,6 +114,42 @@ const SRGB_TO_XYZ: [[f64; 3]; 3] = [
     [0.019334, 0.119193, 0.950227],
 ];
 
+const XYZ_TO_DCI_P3: [[f64; 3]; 3] = [
+    [2.493496911941425, -0.931383617919124, -0.402710784450717],
+    [-0.829488969561575, 1.762664060318346, 0.023624685841944],
+    [0.035845830243784, -0.076172389268041, 0.956884524007687],
+];
+
+const DCI_P3_TO_XYZ: [[f64; 3]; 3] = [
+    [0.486570948648216, 0.265667693169093, 0.198217285234363],
+    [0.228974564069749, 0.691738521836676, 0.079286914093745],
+    [0.000000000000000, 0.045113381858903, 1.043944368900976],
+];
+
+const XYZ_TO_REC2020: [[f64; 3]; 3] = [
+    [1.716651187971268, -0.355670783776392, -0.253366281373660],
+    [-0.666684351832489, 1.616481236634939, 0.015768545813911],
+    [0.017639857445311, -0.042770613257809, 0.942103121235474],
+];
+
+const REC2020_TO_XYZ: [[f64; 3]; 3] = [
+    [0.636958048301291, 0.144616903586208, 0.168880975164172],
+    [0.262700212011267, 0.677998071518871, 0.059301716469862],
+    [0.000000000000000, 0.028072693049087, 1.060985057710791],
+];
+
+const XYZ_TO_ACES2065_1: [[f64; 3]; 3] = [
+    [1.0498110175, 0.0000000000, -0.0000974845],
+    [-0.4959030231, 1.3733130458, 0.0982400361],
+    [0.0000000000, 0.0000000000, 0.9912520182],
+];
+
+const ACES2065_1_TO_XYZ: [[f64; 3]; 3] = [
+    [0.9525523959, 0.0000000000, 0.0000936786],
+    [0.3439664498, 0.7281660966, -0.0721325464],
+    [0.0000000000, 0.0000000000, 1.0088251844],
+];
+
 const fn clamp_usize(val: usize, low: usize, high: usize) -> usize {
     if val < low {
         return low;
@@ -143,9 +179,50 @@ struct Table {
     xyz_white_point: [f64; 3],
 }
 
-const fn init_table(gamut: Gamut) -> Table {
+fn fine_sample_weight(i: usize, h: f64) -> f64 {
+    // composite Simpson's 3/8 rule
+    return 3.0 / 8.0
+        * h
+        * if i == 0 || i == CIE_FINE_SAMPLES - 1 {
+            1.0
+        } else if (i - 1) % 3 == 2 {
+            2.0
+        } else {
+            3.0
+        };
+}
+
+fn coarse_illuminant(spectrum: &dyn Spectrum) -> [f64; CIE_COARSE_SAMPLES] {
+    // sample the illuminant on the coarse CIE grid, normalized to unit luminance
+    let mut illuminant = [0.0; CIE_COARSE_SAMPLES];
+    for i in 0..CIE_COARSE_SAMPLES {
+        let lambda = CIE_LAMBDA_MIN
+            + (i as f64) * (CIE_LAMBDA_MAX - CIE_LAMBDA_MIN) / ((CIE_COARSE_SAMPLES - 1) as f64);
+        illuminant[i] = spectrum.eval(lambda);
+    }
+
+    let h = (CIE_LAMBDA_MAX - CIE_LAMBDA_MIN) / ((CIE_FINE_SAMPLES - 1) as f64);
+    let mut luminance = 0.0;
+    for i in 0..CIE_FINE_SAMPLES {
+        let lambda = CIE_LAMBDA_MIN + (i as f64) * h;
+        luminance += cie_interpolate(&CIE_Y, lambda)
+            * cie_interpolate(&illuminant, lambda)
+            * fine_sample_weight(i, h);
+    }
+
+    return illuminant.map(|v| v / luminance);
+}
+
+fn init_table(gamut: Gamut) -> Table {
     let (illuminant, xyz_to_rgb, rgb_to_xyz) = match gamut {
         Gamut::SRgb => (CIE_D65, XYZ_TO_SRGB, SRGB_TO_XYZ),
+        Gamut::DciP3 => (CIE_D65, XYZ_TO_DCI_P3, DCI_P3_TO_XYZ),
+        Gamut::Rec2020 => (CIE_D65, XYZ_TO_REC2020, REC2020_TO_XYZ),
+        Gamut::Aces2065_1 => (
+            coarse_illuminant(get_named_spectrum("illum-acesD60").unwrap()),
+            XYZ_TO_ACES2065_1,
+            ACES2065_1_TO_XYZ,
+        ),
     };
 
     let h = (CIE_LAMBDA_MAX - CIE_LAMBDA_MIN) / ((CIE_FINE_SAMPLES - 1) as f64);
@@ -167,17 +244,7 @@ const fn init_table(gamut: Gamut) -> Table {
         ];
         let big_i = cie_interpolate(&illuminant, lambda);
 
-        let weight = {
-            3.0 / 8.0
-                * h
-                * if i == 0 || i == CIE_FINE_SAMPLES - 1 {
-                    1.0
-                } else if (i - 1) % 3 == 2 {
-                    2.0
-                } else {
-                    3.0
-                }
-        };
+        let weight = fine_sample_weight(i, h);
 
         lambda_tbl[i] = lambda;
         let mut k = 0;
@@ -11,12 +11,18 @@ pub struct RGBtoSpectrumTable {
 
 pub enum Gamut {
     SRgb,
+    DciP3,
+    Rec2020,
+    Aces2065_1,
 }
 
 impl RGBtoSpectrumTable {
     pub fn new(str_gamut: &str) -> Self {
         let gamut = match str_gamut {
             "sRGB" => Gamut::SRgb,
+            "DCI-P3" => Gamut::DciP3,
+            "Rec2020" => Gamut::Rec2020,
+            "ACES2065-1" => Gamut::Aces2065_1,
             _ => {
                 panic!("gamut `{}` not implemented", str_gamut)
             }
@@ -0,0 +1,36 @@
+use crate::pbrt::*;
+
+pub struct RGBUnboundedSpectrum {
+    scale: f64,
+    rsp: RGBSigmoidPolynomial,
+}
+
+impl Spectrum for RGBUnboundedSpectrum {
+    fn eval(&self, lambda: f64) -> f64 {
+        return self.scale * self.rsp.eval(lambda);
+    }
+
+    fn sample(&self, lambda: &SampledWavelengths) -> SampledSpectrum {
+        let mut values = [f64::NAN; NUM_SPECTRUM_SAMPLES];
+        for i in 0..NUM_SPECTRUM_SAMPLES {
+            values[i] = self.scale * self.rsp.eval(lambda[i]);
+        }
+
+        return SampledSpectrum { values };
+    }
+}
+
+impl RGBUnboundedSpectrum {
+    pub fn new(color_space: &RGBColorSpace, rgb: RGB) -> Self {
+        let m = rgb.max_component();
+        let scale = 2.0 * m;
+
+        let rsp = color_space.to_rgb_coeffs(if scale > 0.0 {
+            rgb / scale
+        } else {
+            RGB::black()
+        });
+
+        return Self { scale, rsp };
+    }
+}
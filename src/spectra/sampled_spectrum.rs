@@ -48,6 +48,37 @@ impl SampledSpectrum {
         return self.values.iter().sum::<f64>() / (NUM_SPECTRUM_SAMPLES as f64);
     }
 
+    pub fn exp(&self) -> Self {
+        let mut values = self.values;
+        for v in &mut values {
+            *v = v.exp();
+        }
+
+        return Self { values };
+    }
+
+    pub fn to_xyz(&self, lambda: &SampledWavelengths) -> CIEXYZ {
+        // Sample the $X$, $Y$, and $Z$ matching curves at _lambda_
+        let x = CIE_X_DENSELY_SAMPLED.sample(lambda);
+        let y = CIE_Y_DENSELY_SAMPLED.sample(lambda);
+        let z = CIE_Z_DENSELY_SAMPLED.sample(lambda);
+
+        // Evaluate estimator to compute $(x,y,z)$ coefficients
+        let pdf = lambda.pdf_as_sampled_spectrum();
+
+        return CIEXYZ {
+            x: (x * *self).safe_div(&pdf).average(),
+            y: (y * *self).safe_div(&pdf).average(),
+            z: (z * *self).safe_div(&pdf).average(),
+        } / CIE_Y_INTEGRAL;
+    }
+
+    pub fn to_rgb(&self, lambda: &SampledWavelengths, color_space: &RGBColorSpace) -> RGB {
+        let xyz = color_space.rgb_from_xyz * self.to_xyz(lambda);
+
+        return RGB::new(xyz.x, xyz.y, xyz.z);
+    }
+
     pub fn clamp(&self, low: f64, high: f64) -> Self {
         let mut values = self.values;
 
@@ -0,0 +1,74 @@
+use crate::pbrt::*;
+
+pub struct FloatImageTexture {
+    image_texture_base: ImageTextureBase,
+}
+
+impl FloatImageTexture {
+    pub fn new(
+        render_from_texture: &Transform,
+        parameters: &ParameterDict,
+    ) -> Result<Self, SceneError> {
+        let map = create_texture_mapping_2d(render_from_texture, parameters)?;
+
+        let max_anisotropy = parameters.get_one_float("maxanisotropy", Some(8.0))?;
+        let filter = parameters.get_string("filter", Some("bilinear".to_string()))?;
+
+        let filter_options = MIPMapFilterOptions {
+            filter: match parse_filter_function(&filter) {
+                None => {
+                    return Err(parameters.parameter_error(
+                        "filter",
+                        format!("unknown filter function `{}`", filter),
+                    ));
+                }
+                Some(_filter) => _filter,
+            },
+            max_anisotropy,
+        };
+
+        let wrap_string = parameters.get_string("wrap", Some("repeat".to_string()))?;
+        let wrap_mode = match parse_wrap_mode(&wrap_string) {
+            None => {
+                return Err(parameters
+                    .parameter_error("wrap", format!("unknown wrap mode `{}`", wrap_string)));
+            }
+            Some(_wrap_mode) => _wrap_mode,
+        };
+
+        let scale = parameters.get_one_float("scale", Some(1.0))?;
+        let invert = parameters.get_one_bool("invert", Some(false))?;
+
+        let filename = parameters.get_string("filename", None)?;
+
+        let image_texture_base =
+            ImageTextureBase::new(map, &filename, filter_options, wrap_mode, scale, invert)?;
+
+        return Ok(Self { image_texture_base });
+    }
+}
+
+impl FloatTexture for FloatImageTexture {
+    fn evaluate(&self, ctx: &TextureEvalContext) -> f64 {
+        let mut c = self.image_texture_base.mapping.map(ctx);
+        c.st[1] = 1.0 - c.st[1];
+
+        // the MIPMap only stores RGB, use the channel average as the float value
+        let v = self.image_texture_base.scale
+            * self
+                .image_texture_base
+                .mipmap
+                .filter(
+                    c.st,
+                    Vector2f::new(c.dsdx, c.dtdx),
+                    Vector2f::new(c.dsdy, c.dtdy),
+                )
+                .average();
+
+        return if self.image_texture_base.invert {
+            (1.0 - v).max(0.0)
+        } else {
+            v
+        };
+    }
+}
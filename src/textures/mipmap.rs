@@ -31,15 +31,13 @@ impl Display for FilterFunction {
     }
 }
 
-pub fn parse_filter_function(filter_function: &str) -> FilterFunction {
+pub fn parse_filter_function(filter_function: &str) -> Option<FilterFunction> {
     return match filter_function {
-        "ewa" => FilterFunction::EWA,
-        "bilinear" => FilterFunction::Bilinear,
-        "trilinear" => FilterFunction::Trilinear,
-        "point" => FilterFunction::Point,
-        _ => {
-            panic!("unknown filter function `{}`", filter_function);
-        }
+        "ewa" => Some(FilterFunction::EWA),
+        "bilinear" => Some(FilterFunction::Bilinear),
+        "trilinear" => Some(FilterFunction::Trilinear),
+        "point" => Some(FilterFunction::Point),
+        _ => None,
     };
 }
 
@@ -95,8 +93,14 @@ pub struct MIPMap {
 
 impl MIPMap {
     fn new(image: Image, wrap_mode: WrapMode, options: MIPMapFilterOptions) -> Self {
+        let pyramid = generate_pyramid(image, wrap_mode);
+        add_memory(
+            MemoryCategory::Textures,
+            pyramid.iter().map(image_memory).sum(),
+        );
+
         return Self {
-            pyramid: generate_pyramid(image, wrap_mode),
+            pyramid,
             wrap_mode,
             options,
         };
@@ -118,9 +122,9 @@ impl MIPMap {
         filename: &str,
         options: MIPMapFilterOptions,
         wrap_mode: WrapMode,
-    ) -> Self {
-        let image = Image::read_from_file(filename);
-        return MIPMap::new(image, wrap_mode, options);
+    ) -> Result<Self, String> {
+        let image = Image::read_from_file(filename)?;
+        return Ok(MIPMap::new(image, wrap_mode, options));
     }
 
     pub fn filter(&self, st: Point2f, dst0: Vector2f, dst1: Vector2f) -> RGB {
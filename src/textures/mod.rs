@@ -1,4 +1,5 @@
 pub mod float_constant_texture;
+pub mod float_image_texture;
 pub mod mipmap;
 pub mod spectrum_constant_texture;
 pub mod spectrum_image_texture;
@@ -2,6 +2,7 @@ use crate::pbrt::*;
 
 pub struct SpectrumImageTexture {
     image_texture_base: ImageTextureBase,
+    color_space: &'static RGBColorSpace,
     // TODO: SpectrumType is currently missing
     spectrum_type: SpectrumType,
 }
@@ -11,32 +12,48 @@ impl SpectrumImageTexture {
         render_from_texture: &Transform,
         parameters: &ParameterDict,
         spectrum_type: SpectrumType,
-    ) -> Self {
-        let map = create_texture_mapping_2d(render_from_texture, parameters);
+    ) -> Result<Self, SceneError> {
+        let map = create_texture_mapping_2d(render_from_texture, parameters)?;
 
-        let max_anisotropy = parameters.get_one_float("maxanisotropy", Some(8.0));
-        let filter = parameters.get_string("filter", Some("bilinear".to_string()));
+        let max_anisotropy = parameters.get_one_float("maxanisotropy", Some(8.0))?;
+        let filter = parameters.get_string("filter", Some("bilinear".to_string()))?;
 
         let filter_options = MIPMapFilterOptions {
-            filter: parse_filter_function(&filter),
+            filter: match parse_filter_function(&filter) {
+                None => {
+                    return Err(parameters.parameter_error(
+                        "filter",
+                        format!("unknown filter function `{}`", filter),
+                    ));
+                }
+                Some(_filter) => _filter,
+            },
             max_anisotropy,
         };
 
-        let wrap_string = parameters.get_string("wrap", Some("repeat".to_string()));
-        let wrap_mode = parse_wrap_mode(&wrap_string);
+        let wrap_string = parameters.get_string("wrap", Some("repeat".to_string()))?;
+        let wrap_mode = match parse_wrap_mode(&wrap_string) {
+            None => {
+                return Err(parameters
+                    .parameter_error("wrap", format!("unknown wrap mode `{}`", wrap_string)));
+            }
+            Some(_wrap_mode) => _wrap_mode,
+        };
 
-        let scale = parameters.get_one_float("scale", Some(1.0));
-        let invert = parameters.get_one_bool("invert", Some(false));
+        let scale = parameters.get_one_float("scale", Some(1.0))?;
+        let invert = parameters.get_one_bool("invert", Some(false))?;
 
-        let filename = parameters.get_string("filename", None);
+        let filename = parameters.get_string("filename", None)?;
+        let color_space = parameters.get_color_space();
 
         let image_texture_base =
-            ImageTextureBase::new(map, &filename, filter_options, wrap_mode, scale, invert);
+            ImageTextureBase::new(map, &filename, filter_options, wrap_mode, scale, invert)?;
 
-        return Self {
+        return Ok(Self {
             image_texture_base,
+            color_space,
             spectrum_type,
-        };
+        });
     }
 }
 
@@ -64,7 +81,8 @@ impl SpectrumTexture for SpectrumImageTexture {
 
         match self.spectrum_type {
             SpectrumType::Albedo => {
-                return RGBAlbedoSpectrum::new(rgb.clamp(0.0, 1.0)).sample(lambda);
+                return RGBAlbedoSpectrum::new(self.color_space, rgb.clamp(0.0, 1.0))
+                    .sample(lambda);
             }
 
             SpectrumType::Illuminant => {
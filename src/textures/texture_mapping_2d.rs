@@ -16,20 +16,21 @@ pub trait TextureMapping2D: Send + Sync {
 pub fn create_texture_mapping_2d(
     render_from_texture: &Transform,
     parameters: &ParameterDict,
-) -> Arc<dyn TextureMapping2D> {
-    let mapping_type = parameters.get_string("mapping", Some("uv".to_string()));
+) -> Result<Arc<dyn TextureMapping2D>, SceneError> {
+    let mapping_type = parameters.get_string("mapping", Some("uv".to_string()))?;
 
     return match mapping_type.as_str() {
         "uv" => {
-            let su = parameters.get_one_float("uscale", Some(1.0));
-            let sv = parameters.get_one_float("vscale", Some(1.0));
-            let du = parameters.get_one_float("udelta", Some(0.0));
-            let dv = parameters.get_one_float("vdelta", Some(0.0));
+            let su = parameters.get_one_float("uscale", Some(1.0))?;
+            let sv = parameters.get_one_float("vscale", Some(1.0))?;
+            let du = parameters.get_one_float("udelta", Some(0.0))?;
+            let dv = parameters.get_one_float("vdelta", Some(0.0))?;
 
-            Arc::new(UVMapping::new(su, sv, du, dv))
-        }
-        _ => {
-            panic!("2D texture mapping `{}` not implemented", mapping_type);
+            Ok(Arc::new(UVMapping::new(su, sv, du, dv)))
         }
+        _ => Err(parameters.parameter_error(
+            "mapping",
+            format!("2D texture mapping `{}` not implemented", mapping_type),
+        )),
     };
 }
@@ -8,6 +8,14 @@ pub trait ColorEncoding: Send + Sync {
     }
 }
 
+pub struct LinearColorEncoding {}
+
+impl ColorEncoding for LinearColorEncoding {
+    fn to_linear(&self, val: u8) -> f64 {
+        return val as f64 / 255.0;
+    }
+}
+
 pub struct SRGBColorEncoding {}
 
 impl SRGBColorEncoding {
@@ -50,6 +50,17 @@ impl RGBColorSpace {
         };
     }
 
+    pub fn get_named(name: &str) -> Option<&'static RGBColorSpace> {
+        // the RGB-to-spectrum table of a color space is only computed on its first use
+        return match name.to_lowercase().as_str() {
+            "srgb" => Some(&*COLOR_SPACE),
+            "dci-p3" => Some(&*DCI_P3_COLOR_SPACE),
+            "rec2020" => Some(&*REC2020_COLOR_SPACE),
+            "aces2065-1" => Some(&*ACES2065_1_COLOR_SPACE),
+            _ => None,
+        };
+    }
+
     pub fn to_rgb_coeffs(&self, rgb: RGB) -> RGBSigmoidPolynomial {
         return self
             .rgb_to_spectrum_table
@@ -60,9 +71,9 @@ impl RGBColorSpace {
         let val = 0.01;
 
         return [
-            RGBAlbedoSpectrum::new(RGB::new(val, 0.0, 0.0)),
-            RGBAlbedoSpectrum::new(RGB::new(0.0, val, 0.0)),
-            RGBAlbedoSpectrum::new(RGB::new(0.0, 0.0, val)),
+            RGBAlbedoSpectrum::new(self, RGB::new(val, 0.0, 0.0)),
+            RGBAlbedoSpectrum::new(self, RGB::new(0.0, val, 0.0)),
+            RGBAlbedoSpectrum::new(self, RGB::new(0.0, 0.0, val)),
         ];
     }
 }
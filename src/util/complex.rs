@@ -0,0 +1,104 @@
+use crate::pbrt::*;
+
+#[derive(Clone, Copy)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        return Complex { re, im };
+    }
+
+    pub fn from_real(re: f64) -> Self {
+        return Complex { re, im: 0.0 };
+    }
+
+    pub fn norm(&self) -> f64 {
+        return sqr(self.re) + sqr(self.im);
+    }
+
+    pub fn abs(&self) -> f64 {
+        return self.norm().sqrt();
+    }
+
+    pub fn sqrt(&self) -> Complex {
+        let n = self.abs();
+        if n == 0.0 {
+            return Complex::from_real(0.0);
+        }
+
+        let t1 = (0.5 * (n + self.re.abs())).sqrt();
+        let t2 = 0.5 * self.im / t1;
+
+        return if self.re >= 0.0 {
+            Complex::new(t1, t2)
+        } else {
+            Complex::new(t2.abs(), t1.copysign(self.im))
+        };
+    }
+}
+
+impl Add<Complex> for Complex {
+    type Output = Complex;
+
+    fn add(self, rhs: Complex) -> Self::Output {
+        return Complex::new(self.re + rhs.re, self.im + rhs.im);
+    }
+}
+
+impl Sub<Complex> for Complex {
+    type Output = Complex;
+
+    fn sub(self, rhs: Complex) -> Self::Output {
+        return Complex::new(self.re - rhs.re, self.im - rhs.im);
+    }
+}
+
+impl Mul<Complex> for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: Complex) -> Self::Output {
+        return Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        );
+    }
+}
+
+impl Mul<f64> for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        return Complex::new(self.re * rhs, self.im * rhs);
+    }
+}
+
+impl Div<Complex> for Complex {
+    type Output = Complex;
+
+    fn div(self, rhs: Complex) -> Self::Output {
+        let scale = 1.0 / rhs.norm();
+        return Complex::new(
+            scale * (self.re * rhs.re + self.im * rhs.im),
+            scale * (self.im * rhs.re - self.re * rhs.im),
+        );
+    }
+}
+
+impl Sub<Complex> for f64 {
+    type Output = Complex;
+
+    fn sub(self, rhs: Complex) -> Self::Output {
+        return Complex::from_real(self) - rhs;
+    }
+}
+
+impl Div<Complex> for f64 {
+    type Output = Complex;
+
+    fn div(self, rhs: Complex) -> Self::Output {
+        return Complex::from_real(self) / rhs;
+    }
+}
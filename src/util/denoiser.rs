@@ -0,0 +1,195 @@
+use crate::pbrt::*;
+
+// half width of the square filter window
+const DENOISER_RADIUS: i32 = 6;
+const SIGMA_SPATIAL: f64 = 3.0;
+const SIGMA_ALBEDO: f64 = 0.1;
+// exponent of the normal similarity, larger values keep geometric edges sharper
+const NORMAL_EXPONENT: i32 = 64;
+// luminance difference, relative to the pixel luminance, tolerated between two pixels
+const SIGMA_LUMINANCE: f64 = 0.3;
+// albedo below this is not divided out of the pixel value
+const MIN_ALBEDO: f64 = 0.01;
+
+struct DenoiserGuides {
+    albedo: Vec<Vec<RGB>>,
+    n: Vec<Vec<Vector3f>>,
+}
+
+fn luminance(rgb: RGB) -> f64 {
+    return 0.2126 * rgb.r + 0.7152 * rgb.g + 0.0722 * rgb.b;
+}
+
+fn demodulate(rgb: RGB, albedo: RGB) -> RGB {
+    let divide = |value: f64, a: f64| if a > MIN_ALBEDO { value / a } else { value };
+
+    return RGB::new(
+        divide(rgb.r, albedo.r),
+        divide(rgb.g, albedo.g),
+        divide(rgb.b, albedo.b),
+    );
+}
+
+fn remodulate(rgb: RGB, albedo: RGB) -> RGB {
+    let multiply = |value: f64, a: f64| if a > MIN_ALBEDO { value * a } else { value };
+
+    return RGB::new(
+        multiply(rgb.r, albedo.r),
+        multiply(rgb.g, albedo.g),
+        multiply(rgb.b, albedo.b),
+    );
+}
+
+fn guide_weight(guides: &DenoiserGuides, p: (usize, usize), q: (usize, usize)) -> f64 {
+    let n_p = guides.n[p.1][p.0];
+    let n_q = guides.n[q.1][q.0];
+
+    // pixels without a visible surface are only averaged among themselves
+    let normal_weight = match (n_p.length() > 0.0, n_q.length() > 0.0) {
+        (false, false) => 1.0,
+        (true, true) => n_p.dot(n_q).max(0.0).powi(NORMAL_EXPONENT),
+        _ => 0.0,
+    };
+    if normal_weight == 0.0 {
+        return 0.0;
+    }
+
+    let albedo_difference = guides.albedo[p.1][p.0] - guides.albedo[q.1][q.0];
+    let albedo_distance_sq = albedo_difference.r * albedo_difference.r
+        + albedo_difference.g * albedo_difference.g
+        + albedo_difference.b * albedo_difference.b;
+
+    return normal_weight * (-albedo_distance_sq / (2.0 * sqr(SIGMA_ALBEDO))).exp();
+}
+
+// weighted average of _values_ over the filter window, weights combine the spatial
+// distance, the guide buffers and optionally the luminance of the _range_ image
+fn joint_bilateral_filter(
+    values: &Vec<Vec<RGB>>,
+    guides: &Option<DenoiserGuides>,
+    range: Option<&Vec<Vec<RGB>>>,
+) -> Vec<Vec<RGB>> {
+    let height = values.len();
+    let width = values[0].len();
+
+    return (0..height)
+        .into_par_iter()
+        .map(|y| {
+            let mut row = Vec::with_capacity(width);
+
+            for x in 0..width {
+                let range_luminance = range.map(|_range| luminance(_range[y][x]));
+
+                let mut sum = RGB::black();
+                let mut weight_sum = 0.0;
+
+                let y_start = (y as i32 - DENOISER_RADIUS).max(0) as usize;
+                let y_end = (y as i32 + DENOISER_RADIUS).min(height as i32 - 1) as usize;
+                let x_start = (x as i32 - DENOISER_RADIUS).max(0) as usize;
+                let x_end = (x as i32 + DENOISER_RADIUS).min(width as i32 - 1) as usize;
+
+                for qy in y_start..=y_end {
+                    for qx in x_start..=x_end {
+                        let distance_sq = sqr(qx as f64 - x as f64) + sqr(qy as f64 - y as f64);
+                        let mut weight = (-distance_sq / (2.0 * sqr(SIGMA_SPATIAL))).exp();
+
+                        match guides {
+                            None => {}
+                            Some(_guides) => {
+                                weight *= guide_weight(_guides, (x, y), (qx, qy));
+                            }
+                        }
+
+                        match (range, range_luminance) {
+                            (Some(_range), Some(_luminance)) => {
+                                let sigma = SIGMA_LUMINANCE * _luminance.abs().max(1e-3);
+                                let difference = luminance(_range[qy][qx]) - _luminance;
+                                weight *= (-sqr(difference) / (2.0 * sqr(sigma))).exp();
+                            }
+                            _ => {}
+                        }
+
+                        if weight > 0.0 {
+                            sum += values[qy][qx] * weight;
+                            weight_sum += weight;
+                        }
+                    }
+                }
+
+                // the center pixel always has a positive weight
+                row.push(sum / weight_sum);
+            }
+
+            return row;
+        })
+        .collect();
+}
+
+// joint-bilateral denoising of the pixel bounds of _film_, guided by its albedo and
+// normal buffers when it has them
+pub fn denoise(film: &dyn Film) -> Image {
+    let pixel_bounds = film.get_pixel_bounds();
+    let extent = pixel_bounds.diagonal();
+    let width = extent.x as usize;
+    let height = extent.y as usize;
+
+    let mut rgb = vec![vec![RGB::black(); width]; height];
+    let mut albedo = vec![vec![RGB::black(); width]; height];
+    let mut n = vec![vec![Vector3f::new(0.0, 0.0, 0.0); width]; height];
+    let mut has_guides = true;
+
+    for y in 0..height {
+        for x in 0..width {
+            let p = Point2i::new(
+                pixel_bounds.p_min.x + x as i32,
+                pixel_bounds.p_min.y + y as i32,
+            );
+
+            rgb[y][x] = film.get_pixel_rgb(p);
+            match film.get_pixel_guides(p) {
+                None => {
+                    has_guides = false;
+                }
+                Some((_albedo, _n)) => {
+                    albedo[y][x] = _albedo;
+                    n[y][x] = _n;
+                }
+            }
+        }
+    }
+
+    let guides = if has_guides {
+        Some(DenoiserGuides { albedo, n })
+    } else {
+        None
+    };
+
+    // Divide out the albedo so that only lighting is filtered and textures stay sharp
+    let lighting = match &guides {
+        None => rgb,
+        Some(_guides) => (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| demodulate(rgb[y][x], _guides.albedo[y][x]))
+                    .collect()
+            })
+            .collect(),
+    };
+
+    // The raw luminance is too noisy to tell edges apart: a first pass without the
+    // luminance term gives the estimate the second pass compares pixels with
+    let estimate = joint_bilateral_filter(&lighting, &guides, None);
+    let filtered = joint_bilateral_filter(&lighting, &guides, Some(&estimate));
+
+    let mut image = Image::new(extent, PixelFormat::f64);
+    for y in 0..height {
+        for x in 0..width {
+            image[y][x] = match &guides {
+                None => filtered[y][x],
+                Some(_guides) => remodulate(filtered[y][x], _guides.albedo[y][x]),
+            };
+        }
+    }
+
+    return image;
+}
@@ -73,18 +73,62 @@ impl Default for ResampleWeight {
     }
 }
 
-pub fn parse_wrap_mode(wrap_string: &str) -> WrapMode {
+pub fn parse_wrap_mode(wrap_string: &str) -> Option<WrapMode> {
     return match wrap_string {
-        "black" => WrapMode::Black,
-        "clamp" => WrapMode::Clamp,
-        "repeat" => WrapMode::Repeat,
-        "octahedralsphere" => WrapMode::OctahedralSphere,
-        &_ => {
-            panic!("unknown wrap mode: `{}`", wrap_string);
-        }
+        "black" => Some(WrapMode::Black),
+        "clamp" => Some(WrapMode::Clamp),
+        "repeat" => Some(WrapMode::Repeat),
+        "octahedralsphere" => Some(WrapMode::OctahedralSphere),
+        &_ => None,
     };
 }
 
+// write named single-channel float layers (row-major, top to bottom) into an EXR file,
+// the layers cover the data window _pixel_bounds_ of a _resolution_ display window
+pub fn export_channels_to_exr(
+    filename: &str,
+    resolution: Point2i,
+    pixel_bounds: Bounds2i,
+    channels: Vec<(String, Vec<f32>)>,
+) {
+    let size = (
+        pixel_bounds.diagonal().x as usize,
+        pixel_bounds.diagonal().y as usize,
+    );
+
+    let mut exr_channels = exr::prelude::SmallVec::new();
+    for (name, values) in channels {
+        assert_eq!(values.len(), size.0 * size.1);
+        exr_channels.push(exr::prelude::AnyChannel::new(
+            name.as_str(),
+            exr::prelude::FlatSamples::F32(values),
+        ));
+    }
+
+    let mut layer_attributes = exr::prelude::LayerAttributes::default();
+    layer_attributes.layer_position = exr::math::Vec2(pixel_bounds.p_min.x, pixel_bounds.p_min.y);
+
+    let layer = exr::prelude::Layer::new(
+        size,
+        layer_attributes,
+        exr::prelude::Encoding::FAST_LOSSLESS,
+        exr::prelude::AnyChannels::sort(exr_channels),
+    );
+
+    let display_window = exr::meta::attribute::IntegerBounds::new(
+        (0, 0),
+        (resolution.x as usize, resolution.y as usize),
+    );
+    let image = exr::prelude::Image::new(exr::prelude::ImageAttributes::new(display_window), layer);
+
+    match exr::prelude::WritableImage::write(&image).to_file(filename) {
+        Ok(_) => {}
+        Err(error) => {
+            panic!("fail to write `{}`: {}", filename, error);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Image {
     pub resolution: Point2i,
@@ -201,15 +245,23 @@ impl Image {
         };
     }
 
-    pub fn read_from_file(filename: &str) -> Self {
+    // errors are described without _filename_, the caller reports it
+    pub fn read_from_file(filename: &str) -> Result<Self, String> {
+        return Image::read_from_file_with_encoding(filename, &*COLOR_ENCODING);
+    }
+
+    pub fn read_from_file_with_encoding(
+        filename: &str,
+        encoding: &dyn ColorEncoding,
+    ) -> Result<Self, String> {
         if get_extension(filename) != "png" {
-            panic!("only PNG file is supported for the moment");
+            return Err("only PNG file is supported for the moment".to_string());
         }
 
         let img = match image::open(filename) {
             Ok(_dynamic_img) => _dynamic_img.into_rgb8(),
-            Err(_) => {
-                panic!("fail to read `{}`", filename)
+            Err(_err) => {
+                return Err(_err.to_string());
             }
         };
 
@@ -220,18 +272,18 @@ impl Image {
                 let rgb_u256 = img[(x, y)].0;
 
                 pixels[y as usize][x as usize] = RGB::new(
-                    COLOR_ENCODING.to_linear(rgb_u256[0]),
-                    COLOR_ENCODING.to_linear(rgb_u256[1]),
-                    COLOR_ENCODING.to_linear(rgb_u256[2]),
+                    encoding.to_linear(rgb_u256[0]),
+                    encoding.to_linear(rgb_u256[1]),
+                    encoding.to_linear(rgb_u256[2]),
                 );
             }
         }
 
-        return Self {
+        return Ok(Self {
             resolution: Point2::new(width as i32, height as i32),
             pixels,
             pixel_format: PixelFormat::U256,
-        };
+        });
     }
 
     pub fn float_resize_up(&self, new_resolution: Point2i, wrap_mode: WrapMode) -> Image {
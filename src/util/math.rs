@@ -6,10 +6,117 @@ pub const PI_OVER_4: f64 = PI / 4.0;
 pub const INV_PI: f64 = 1.0 / PI;
 pub const INV_4PI: f64 = 1.0 / (4.0 * PI);
 
+pub fn mix_bits(v: u64) -> u64 {
+    let mut v = v;
+    v ^= v >> 31;
+    v = v.wrapping_mul(0x7fb5d329728ea185);
+    v ^= v >> 27;
+    v = v.wrapping_mul(0x81dadef4bc2dd44d);
+    v ^= v >> 33;
+
+    return v;
+}
+
+pub fn murmur_hash64a(key: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4a7935bd1e995;
+    const R: u32 = 47;
+
+    let mut h = seed ^ (key.len() as u64).wrapping_mul(M);
+
+    let mut chunks = key.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (idx, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u64) << (8 * idx);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+
+    return h;
+}
+
+// Feeds the bytes of a value to _murmur_hash64a()_. Integers are written in little
+// endian with `usize` widened to 64 bits, so that hashes are the same on every
+// platform and with every Rust release, unlike _DefaultHasher_.
+#[derive(Default)]
+struct MurmurHasher {
+    bytes: Vec<u8>,
+}
+
+impl Hasher for MurmurHasher {
+    fn finish(&self) -> u64 {
+        return murmur_hash64a(&self.bytes, 0);
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+}
+
 pub fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
+    let mut hasher = MurmurHasher::default();
+    t.hash(&mut hasher);
+
+    return hasher.finish();
+}
+
+pub fn hash_float<T: Hash>(t: &T) -> f64 {
+    // map the hash to a float in [0, 1)
+    return (calculate_hash(t) >> 11) as f64 / (1u64 << 53) as f64;
 }
 
 pub fn degree_to_radian(degree: f64) -> f64 {
@@ -100,6 +207,26 @@ pub const fn round_up_pow_2(v: i32) -> i32 {
     return x + 1;
 }
 
+pub fn find_interval(sz: usize, pred: impl Fn(usize) -> bool) -> usize {
+    let mut size = sz as i64 - 2;
+    let mut first: i64 = 1;
+
+    while size > 0 {
+        // Evaluate predicate at midpoint and update _first_ and _size_
+        let half = size >> 1;
+        let middle = first + half;
+        let pred_result = pred(middle as usize);
+
+        (first, size) = if pred_result {
+            (middle + 1, size - (half + 1))
+        } else {
+            (first, half)
+        };
+    }
+
+    return (first - 1).clamp(0, sz as i64 - 2) as usize;
+}
+
 pub fn safe_asin(x: f64) -> f64 {
     debug_assert!(x >= -1.0001 && x <= 1.0001);
 
@@ -151,3 +278,368 @@ pub fn spherical_triangle_area(a: Vector3f, b: Vector3f, c: Vector3f) -> f64 {
         .abs()
         * 2.0;
 }
+
+// returns the offset of the first node and the 4 spline weights, or None if
+// _x_ is out of bounds (offset may be -1 with a zero weight)
+pub fn catmull_rom_weights(nodes: &[f64], x: f64) -> Option<(i64, [f64; 4])> {
+    // Return _None_ if _x_ is out of bounds
+    if !(x >= nodes[0] && x <= nodes[nodes.len() - 1]) {
+        return None;
+    }
+
+    // Search for the interval _idx_ containing _x_
+    let idx = find_interval(nodes.len(), |i| nodes[i] <= x);
+    let offset = idx as i64 - 1;
+    let x0 = nodes[idx];
+    let x1 = nodes[idx + 1];
+
+    // Compute the $t$ parameter and powers
+    let t = (x - x0) / (x1 - x0);
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    // Compute initial node weights $w_1$ and $w_2$
+    let mut weights = [0.0; 4];
+    weights[1] = 2.0 * t3 - 3.0 * t2 + 1.0;
+    weights[2] = -2.0 * t3 + 3.0 * t2;
+
+    // Compute first node weight $w_0$
+    if idx > 0 {
+        let w0 = (t3 - 2.0 * t2 + t) * (x1 - x0) / (x1 - nodes[idx - 1]);
+        weights[0] = -w0;
+        weights[2] += w0;
+    } else {
+        let w0 = t3 - 2.0 * t2 + t;
+        weights[0] = 0.0;
+        weights[1] -= w0;
+        weights[2] += w0;
+    }
+
+    // Compute last node weight $w_3$
+    if idx + 2 < nodes.len() {
+        let w3 = (t3 - t2) * (x1 - x0) / (nodes[idx + 2] - x0);
+        weights[1] -= w3;
+        weights[3] = w3;
+    } else {
+        let w3 = t3 - t2;
+        weights[1] -= w3;
+        weights[2] += w3;
+        weights[3] = 0.0;
+    }
+
+    return Some((offset, weights));
+}
+
+// finite difference approximations of the spline derivatives at both ends of segment _i_
+fn catmull_rom_derivatives(nodes: &[f64], f: &[f64], i: usize) -> (f64, f64) {
+    let x0 = nodes[i];
+    let x1 = nodes[i + 1];
+    let f0 = f[i];
+    let f1 = f[i + 1];
+    let width = x1 - x0;
+
+    let d0 = if i > 0 {
+        width * (f1 - f[i - 1]) / (x1 - nodes[i - 1])
+    } else {
+        f1 - f0
+    };
+    let d1 = if i + 2 < nodes.len() {
+        width * (f[i + 2] - f0) / (nodes[i + 2] - x0)
+    } else {
+        f1 - f0
+    };
+
+    return (d0, d1);
+}
+
+// returns the integral of the spline and fills in _cdf_
+pub fn integrate_catmull_rom(nodes: &[f64], f: &[f64], cdf: &mut [f64]) -> f64 {
+    assert_eq!(nodes.len(), f.len());
+
+    let mut sum = 0.0;
+    cdf[0] = 0.0;
+    for i in 0..(nodes.len() - 1) {
+        // Look up $x_i$ and function values of spline segment _i_
+        let width = nodes[i + 1] - nodes[i];
+        let f0 = f[i];
+        let f1 = f[i + 1];
+
+        // Approximate derivatives using finite differences
+        let (d0, d1) = catmull_rom_derivatives(nodes, f, i);
+
+        // Keep a running sum and build a cumulative distribution function
+        sum += ((d0 - d1) * (1.0 / 12.0) + (f0 + f1) * 0.5) * width;
+        cdf[i + 1] = sum;
+    }
+
+    return sum;
+}
+
+// _f_ returns the function value and its derivative
+pub fn newton_bisection(x0: f64, x1: f64, f: impl Fn(f64) -> (f64, f64)) -> f64 {
+    let x_eps = 1e-6;
+    let f_eps = 1e-6;
+
+    let mut x0 = x0;
+    let mut x1 = x1;
+
+    // Check function endpoints for roots
+    let fx0 = f(x0).0;
+    let fx1 = f(x1).0;
+    if fx0.abs() < f_eps {
+        return x0;
+    }
+    if fx1.abs() < f_eps {
+        return x1;
+    }
+    let start_is_negative = fx0 < 0.0;
+
+    // Set initial midpoint using linear approximation of _f_
+    let mut x_mid = x0 + (x1 - x0) * -fx0 / (fx1 - fx0);
+
+    loop {
+        // Fall back to bisection if _x_mid_ is out of bounds
+        if !(x0 < x_mid && x_mid < x1) {
+            x_mid = (x0 + x1) / 2.0;
+        }
+
+        // Evaluate function and narrow bracket range _[x0, x1]_
+        let fx_mid = f(x_mid);
+        if start_is_negative == (fx_mid.0 < 0.0) {
+            x0 = x_mid;
+        } else {
+            x1 = x_mid;
+        }
+
+        // Stop the iteration if bracket range is sufficiently narrow
+        if (x1 - x0) < x_eps || fx_mid.0.abs() < f_eps {
+            return x_mid;
+        }
+
+        // Perform a Newton step
+        x_mid -= fx_mid.0 / fx_mid.1;
+    }
+}
+
+pub fn invert_catmull_rom(nodes: &[f64], f: &[f64], u: f64) -> f64 {
+    // Stop when _u_ is out of bounds
+    if !(u > f[0]) {
+        return nodes[0];
+    } else if !(u < f[f.len() - 1]) {
+        return nodes[nodes.len() - 1];
+    }
+
+    // Map _u_ to a spline interval by inverting _f_
+    let i = find_interval(f.len(), |i| f[i] <= u);
+
+    // Look up $x_i$ and function values of spline segment _i_
+    let x0 = nodes[i];
+    let width = nodes[i + 1] - x0;
+    let f0 = f[i];
+    let f1 = f[i + 1];
+
+    // Approximate derivatives using finite differences
+    let (d0, d1) = catmull_rom_derivatives(nodes, f, i);
+
+    // Invert the spline interpolant using Newton-Bisection
+    let t = newton_bisection(0.0, 1.0, |t| {
+        // Compute powers of _t_
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        let big_f = (2.0 * t3 - 3.0 * t2 + 1.0) * f0
+            + (-2.0 * t3 + 3.0 * t2) * f1
+            + (t3 - 2.0 * t2 + t) * d0
+            + (t3 - t2) * d1;
+
+        let small_f = (6.0 * t2 - 6.0 * t) * f0
+            + (-6.0 * t2 + 6.0 * t) * f1
+            + (3.0 * t2 - 4.0 * t + 1.0) * d0
+            + (3.0 * t2 - 2.0 * t) * d1;
+
+        (big_f - u, small_f)
+    });
+
+    return x0 + t * width;
+}
+
+// returns the sampled position, the function value there and its PDF
+pub fn sample_catmull_rom_2d(
+    nodes1: &[f64],
+    nodes2: &[f64],
+    values: &[f64],
+    cdf: &[f64],
+    alpha: f64,
+    u: f64,
+) -> Option<(f64, f64, f64)> {
+    // Determine offset and coefficients for the _alpha_ parameter
+    let (offset, weights) = catmull_rom_weights(nodes1, alpha)?;
+
+    // Define a closure to interpolate table entries
+    let size2 = nodes2.len();
+    let interpolate = |array: &[f64], idx: usize| -> f64 {
+        let mut value = 0.0;
+        for i in 0..4 {
+            if weights[i] != 0.0 {
+                value += array[(offset + i as i64) as usize * size2 + idx] * weights[i];
+            }
+        }
+        value
+    };
+
+    // Map _u_ to a spline interval by inverting the interpolated _cdf_
+    let maximum = interpolate(cdf, size2 - 1);
+    if maximum <= 0.0 {
+        return None;
+    }
+    let u = u * maximum;
+    let idx = find_interval(size2, |i| interpolate(cdf, i) <= u);
+
+    // Look up node positions and interpolated function values
+    let f0 = interpolate(values, idx);
+    let f1 = interpolate(values, idx + 1);
+    let x0 = nodes2[idx];
+    let x1 = nodes2[idx + 1];
+    let width = x1 - x0;
+
+    // Re-scale _u_ using the interpolated _cdf_
+    let u = (u - interpolate(cdf, idx)) / width;
+
+    // Approximate derivatives using finite differences of the interpolant
+    let d0 = if idx > 0 {
+        width * (f1 - interpolate(values, idx - 1)) / (x1 - nodes2[idx - 1])
+    } else {
+        f1 - f0
+    };
+    let d1 = if idx + 2 < size2 {
+        width * (interpolate(values, idx + 2) - f0) / (nodes2[idx + 2] - x0)
+    } else {
+        f1 - f0
+    };
+
+    // Invert definite integral over spline segment and return solution
+    // Set initial guess for $t$ by importance sampling a linear interpolant
+    let mut t = if f0 != f1 {
+        (f0 - safe_sqrt((f0 * f0 + 2.0 * u * (f1 - f0)).max(0.0))) / (f0 - f1)
+    } else {
+        u / f0
+    };
+
+    let mut a = 0.0;
+    let mut b = 1.0;
+    let mut small_f;
+    loop {
+        // Fall back to a bisection step when _t_ is out of bounds
+        if !(t >= a && t <= b) {
+            t = 0.5 * (a + b);
+        }
+
+        // Evaluate target function and its derivative in Horner form
+        let big_f = t
+            * (f0
+                + t * (0.5 * d0
+                    + t * ((1.0 / 3.0) * (-2.0 * d0 - d1) + f1 - f0
+                        + t * (0.25 * (d0 + d1) + 0.5 * (f0 - f1)))));
+        small_f = f0
+            + t * (d0 + t * (-2.0 * d0 - d1 + 3.0 * (f1 - f0) + t * (d0 + d1 + 2.0 * (f0 - f1))));
+
+        // Stop the iteration if converged
+        if (big_f - u).abs() < 1e-6 || b - a < 1e-6 {
+            break;
+        }
+
+        // Update bisection bounds using updated _t_
+        if big_f - u < 0.0 {
+            a = t;
+        } else {
+            b = t;
+        }
+
+        // Perform a Newton step
+        t -= (big_f - u) / small_f;
+    }
+
+    // Return the sample position and function value
+    return Some((x0 + width * t, small_f, small_f / maximum));
+}
+
+// Welford's online algorithm for the mean and variance of a stream of samples
+#[derive(Clone, Copy, Default)]
+pub struct VarianceEstimator {
+    mean: f64,
+    s: f64,
+    n: i64,
+}
+
+impl VarianceEstimator {
+    pub fn add(&mut self, x: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        let delta2 = x - self.mean;
+        self.s += delta * delta2;
+    }
+
+    pub fn merge(&mut self, other: &VarianceEstimator) {
+        // Chan et al.'s parallel variant of Welford's algorithm
+        if other.n == 0 {
+            return;
+        }
+
+        let n = self.n + other.n;
+        let delta = other.mean - self.mean;
+        self.s += other.s + sqr(delta) * (self.n * other.n) as f64 / n as f64;
+        self.mean += delta * other.n as f64 / n as f64;
+        self.n = n;
+    }
+
+    pub fn to_array(&self) -> [f64; 3] {
+        return [self.mean, self.s, self.n as f64];
+    }
+
+    pub fn from_array(values: [f64; 3]) -> Self {
+        return VarianceEstimator {
+            mean: values[0],
+            s: values[1],
+            n: values[2] as i64,
+        };
+    }
+
+    pub fn count(&self) -> i64 {
+        return self.n;
+    }
+
+    pub fn mean(&self) -> f64 {
+        return self.mean;
+    }
+
+    pub fn variance(&self) -> f64 {
+        return if self.n > 1 {
+            self.s / (self.n - 1) as f64
+        } else {
+            0.0
+        };
+    }
+
+    pub fn relative_variance(&self) -> f64 {
+        return if self.n < 1 || self.mean == 0.0 {
+            0.0
+        } else {
+            self.variance() / self.mean
+        };
+    }
+
+    pub fn relative_error(&self) -> f64 {
+        // standard error of the mean relative to the mean
+        if self.n < 2 {
+            return f64::INFINITY;
+        }
+
+        let standard_error = (self.variance() / self.n as f64).sqrt();
+        if standard_error == 0.0 {
+            return 0.0;
+        }
+
+        return standard_error / self.mean.abs();
+    }
+}
@@ -1,7 +1,12 @@
 pub mod color;
 pub mod color_encoding;
 pub mod colorspace;
+pub mod complex;
+pub mod denoiser;
 pub mod image;
 pub mod math;
+pub mod rng;
 pub mod sampling;
 pub mod scattering;
+pub mod splines;
+pub mod statistics;
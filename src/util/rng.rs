@@ -0,0 +1,116 @@
+use crate::pbrt::*;
+
+const PCG32_DEFAULT_STATE: u64 = 0x853c49e6748fea9b;
+const PCG32_DEFAULT_STREAM: u64 = 0xda3e39cb94b95bdb;
+const PCG32_MULT: u64 = 0x5851f42d4c957f2d;
+
+// PCG32 random number generator of pbrt-v4: the sequence of numbers only depends on
+// the sequence index and the seed, unlike the generators of the `rand` crate
+#[derive(Clone, Debug)]
+pub struct RNG {
+    state: u64,
+    inc: u64,
+}
+
+impl Default for RNG {
+    fn default() -> Self {
+        return RNG {
+            state: PCG32_DEFAULT_STATE,
+            inc: PCG32_DEFAULT_STREAM,
+        };
+    }
+}
+
+impl RNG {
+    pub fn new(sequence_index: u64) -> Self {
+        let mut rng = RNG::default();
+        rng.set_sequence(sequence_index);
+
+        return rng;
+    }
+
+    pub fn set_sequence(&mut self, sequence_index: u64) {
+        self.set_sequence_with_seed(sequence_index, mix_bits(sequence_index));
+    }
+
+    pub fn set_sequence_with_seed(&mut self, sequence_index: u64, seed: u64) {
+        self.state = 0;
+        self.inc = (sequence_index << 1) | 1;
+        self.uniform_u32();
+        self.state = self.state.wrapping_add(seed);
+        self.uniform_u32();
+    }
+
+    pub fn uniform_u32(&mut self) -> u32 {
+        let old_state = self.state;
+        self.state = old_state.wrapping_mul(PCG32_MULT).wrapping_add(self.inc);
+        let xor_shifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        let rot = (old_state >> 59) as u32;
+
+        return xor_shifted.rotate_right(rot);
+    }
+
+    pub fn uniform_u64(&mut self) -> u64 {
+        let v0 = self.uniform_u32() as u64;
+        let v1 = self.uniform_u32() as u64;
+
+        return (v0 << 32) | v1;
+    }
+
+    // uniform in [0, 1)
+    pub fn uniform_f64(&mut self) -> f64 {
+        return (self.uniform_u64() as f64 * (-64.0_f64).exp2()).min(ONE_MINUS_EPSILON);
+    }
+
+    // skip _delta_ values of the sequence in O(log delta)
+    pub fn advance(&mut self, delta: u64) {
+        let mut cur_mult = PCG32_MULT;
+        let mut cur_plus = self.inc;
+        let mut acc_mult = 1u64;
+        let mut acc_plus = 0u64;
+
+        let mut delta = delta;
+        while delta > 0 {
+            if delta & 1 != 0 {
+                acc_mult = acc_mult.wrapping_mul(cur_mult);
+                acc_plus = acc_plus.wrapping_mul(cur_mult).wrapping_add(cur_plus);
+            }
+            cur_plus = cur_mult.wrapping_add(1).wrapping_mul(cur_plus);
+            cur_mult = cur_mult.wrapping_mul(cur_mult);
+            delta /= 2;
+        }
+
+        self.state = acc_mult.wrapping_mul(self.state).wrapping_add(acc_plus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_pcg32_reference_output() {
+        // first values of pcg32-demo, seeded with 42 on sequence 54
+        let mut rng = RNG::default();
+        rng.set_sequence_with_seed(54, 42);
+
+        let values = (0..6).map(|_| rng.uniform_u32()).collect::<Vec<u32>>();
+        assert_eq!(
+            values,
+            vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+        );
+    }
+
+    #[test]
+    fn advance_skips_values() {
+        let mut rng = RNG::new(7);
+        let mut skipped = rng.clone();
+
+        for _ in 0..1000 {
+            rng.uniform_u32();
+        }
+        skipped.advance(1000);
+
+        assert_eq!(rng.uniform_u32(), skipped.uniform_u32());
+    }
+}
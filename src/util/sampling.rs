@@ -229,6 +229,59 @@ pub fn sample_henyey_greenstein(wo: Vector3f, g: f64, u: Point2f) -> (Vector3f,
     return (wi, pdf);
 }
 
+// returns the sampled index and the sample value remapped to $[0, 1)$
+pub fn sample_discrete(weights: &[f64], u: f64) -> Option<(usize, f64)> {
+    // Handle empty _weights_ for discrete sampling
+    if weights.is_empty() {
+        return None;
+    }
+
+    // Compute sum of _weights_
+    let sum_weights: f64 = weights.iter().sum();
+    if sum_weights == 0.0 {
+        return None;
+    }
+
+    // Compute rescaled $u'$ sample
+    let mut up = u * sum_weights;
+    if up == sum_weights {
+        up = next_float_down(up);
+    }
+
+    // Find offset in _weights_ corresponding to $u'$
+    let mut offset = 0;
+    let mut sum = 0.0;
+    // rounding in _sum_ may step past the last weight
+    while offset < weights.len() - 1 && sum + weights[offset] <= up {
+        sum += weights[offset];
+        offset += 1;
+    }
+
+    let u_remapped = ((up - sum) / weights[offset]).min(ONE_MINUS_EPSILON);
+
+    return Some((offset, u_remapped));
+}
+
+pub fn logistic(x: f64, s: f64) -> f64 {
+    let x = x.abs();
+    return (-x / s).exp() / (s * sqr(1.0 + (-x / s).exp()));
+}
+
+pub fn logistic_cdf(x: f64, s: f64) -> f64 {
+    return 1.0 / (1.0 + (-x / s).exp());
+}
+
+pub fn trimmed_logistic(x: f64, s: f64, a: f64, b: f64) -> f64 {
+    return logistic(x, s) / (logistic_cdf(b, s) - logistic_cdf(a, s));
+}
+
+pub fn sample_trimmed_logistic(u: f64, s: f64, a: f64, b: f64) -> f64 {
+    let k = logistic_cdf(b, s) - logistic_cdf(a, s);
+    let x = -s * (1.0 / (u * k + logistic_cdf(a, s)) - 1.0).ln();
+
+    return x.clamp(a, b);
+}
+
 pub fn power_heuristic(nf: i32, f_pdf: f64, ng: i32, g_pdf: f64) -> f64 {
     let f = (nf as f64) * f_pdf;
     let g = (ng as f64) * g_pdf;
@@ -239,3 +292,459 @@ pub fn power_heuristic(nf: i32, f_pdf: f64, ng: i32, g_pdf: f64) -> f64 {
     }
     return sqr_f / (sqr_f + sqr(g));
 }
+
+pub struct PLSample {
+    pub p: Point2f,
+    pub pdf: f64,
+}
+
+// bilinearly interpolated 2D distribution that may additionally depend on
+// a number of parameters (the C++ version has the number of parameters as a
+// template argument, here it's the length of _param_size_)
+pub struct PiecewiseLinear2D {
+    size: Point2i,
+    patch_size: Vector2f,
+    inv_patch_size: Vector2f,
+
+    param_size: Vec<usize>,
+    param_strides: Vec<usize>,
+    param_values: Vec<Vec<f64>>,
+
+    data: Vec<f64>,
+    marginal_cdf: Vec<f64>,
+    conditional_cdf: Vec<f64>,
+}
+
+impl PiecewiseLinear2D {
+    pub fn new(
+        data: &[f64],
+        x_size: usize,
+        y_size: usize,
+        param_size: Vec<usize>,
+        param_values: Vec<Vec<f64>>,
+        normalize: bool,
+        build_cdf: bool,
+    ) -> Self {
+        if build_cdf && !normalize {
+            panic!("PiecewiseLinear2D: build_cdf implies normalize=true");
+        }
+
+        let dimension = param_size.len();
+        assert_eq!(param_values.len(), dimension);
+
+        // Keep track of the dependence on additional parameters (optional)
+        let mut slices = 1;
+        let mut param_strides = vec![0; dimension];
+        for i in (0..dimension).rev() {
+            if param_size[i] < 1 {
+                panic!("PiecewiseLinear2D: parameter resolution must be >= 1");
+            }
+            param_strides[i] = if param_size[i] > 1 { slices } else { 0 };
+            slices *= param_size[i];
+        }
+
+        let n_values = x_size * y_size;
+        assert!(data.len() >= slices * n_values);
+
+        let mut data_out = vec![0.0; slices * n_values];
+        let mut marginal_cdf = vec![];
+        let mut conditional_cdf = vec![];
+
+        if build_cdf {
+            marginal_cdf = vec![0.0; slices * y_size];
+            conditional_cdf = vec![0.0; slices * n_values];
+
+            for slice in 0..slices {
+                let data = &data[slice * n_values..(slice + 1) * n_values];
+                let marginal = &mut marginal_cdf[slice * y_size..(slice + 1) * y_size];
+                let conditional = &mut conditional_cdf[slice * n_values..(slice + 1) * n_values];
+
+                // Construct conditional CDF
+                for y in 0..y_size {
+                    let mut sum = 0.0;
+                    let mut i = y * x_size;
+                    conditional[i] = 0.0;
+                    for _ in 0..(x_size - 1) {
+                        sum += 0.5 * (data[i] + data[i + 1]);
+                        conditional[i + 1] = sum;
+                        i += 1;
+                    }
+                }
+
+                // Construct marginal CDF
+                marginal[0] = 0.0;
+                let mut sum = 0.0;
+                for y in 0..(y_size - 1) {
+                    sum += 0.5
+                        * (conditional[(y + 1) * x_size - 1] + conditional[(y + 2) * x_size - 1]);
+                    marginal[y + 1] = sum;
+                }
+
+                // Normalize CDFs and PDF (if requested)
+                let normal = 1.0 / marginal[y_size - 1];
+                for v in conditional.iter_mut() {
+                    *v *= normal;
+                }
+                for v in marginal.iter_mut() {
+                    *v *= normal;
+                }
+                for i in 0..n_values {
+                    data_out[slice * n_values + i] = data[i] * normal;
+                }
+            }
+        } else {
+            for slice in 0..slices {
+                let data = &data[slice * n_values..(slice + 1) * n_values];
+
+                let mut normal = 1.0;
+                if normalize {
+                    let mut sum = 0.0;
+                    for y in 0..(y_size - 1) {
+                        let mut i = y * x_size;
+                        for _ in 0..(x_size - 1) {
+                            let v00 = data[i];
+                            let v10 = data[i + 1];
+                            let v01 = data[i + x_size];
+                            let v11 = data[i + 1 + x_size];
+                            sum += 0.25 * (v00 + v10 + v01 + v11);
+                            i += 1;
+                        }
+                    }
+                    normal = 1.0 / sum;
+                }
+
+                for i in 0..n_values {
+                    data_out[slice * n_values + i] = data[i] * normal;
+                }
+            }
+        }
+
+        return PiecewiseLinear2D {
+            size: Point2i::new(x_size as i32, y_size as i32),
+            patch_size: Vector2f::new(1.0 / (x_size as f64 - 1.0), 1.0 / (y_size as f64 - 1.0)),
+            inv_patch_size: Vector2f::new(x_size as f64 - 1.0, y_size as f64 - 1.0),
+            param_size,
+            param_strides,
+            param_values,
+            data: data_out,
+            marginal_cdf,
+            conditional_cdf,
+        };
+    }
+
+    fn lookup(
+        &self,
+        data: &[f64],
+        dim: usize,
+        i0: usize,
+        size: usize,
+        param_weight: &[f64],
+    ) -> f64 {
+        if dim == 0 {
+            return data[i0];
+        }
+
+        let i1 = i0 + self.param_strides[dim - 1] * size;
+        let w0 = param_weight[2 * dim - 2];
+        let w1 = param_weight[2 * dim - 1];
+        let v0 = self.lookup(data, dim - 1, i0, size, param_weight);
+        let v1 = self.lookup(data, dim - 1, i1, size, param_weight);
+
+        return fma(v0, w0, v1 * w1);
+    }
+
+    fn param_weights(&self, params: &[f64]) -> (Vec<f64>, usize) {
+        // Look up parameter-related indices and weights
+        let dimension = self.param_size.len();
+        let mut param_weight = vec![0.0; 2 * dimension];
+        let mut slice_offset = 0;
+
+        for dim in 0..dimension {
+            if self.param_size[dim] == 1 {
+                param_weight[2 * dim] = 1.0;
+                param_weight[2 * dim + 1] = 0.0;
+                continue;
+            }
+
+            let values = &self.param_values[dim];
+            let param_index = find_interval(self.param_size[dim], |idx| values[idx] <= params[dim]);
+
+            let p0 = values[param_index];
+            let p1 = values[param_index + 1];
+
+            param_weight[2 * dim + 1] = ((params[dim] - p0) / (p1 - p0)).clamp(0.0, 1.0);
+            param_weight[2 * dim] = 1.0 - param_weight[2 * dim + 1];
+            slice_offset += self.param_strides[dim] * param_index;
+        }
+
+        return (param_weight, slice_offset);
+    }
+
+    pub fn sample(&self, sample: Point2f, params: &[f64]) -> PLSample {
+        let dimension = self.param_size.len();
+        let size_x = self.size.x as usize;
+        let size_y = self.size.y as usize;
+
+        // Avoid degeneracies at the extrema
+        let mut sample = Point2f::new(
+            sample.x.clamp(1.0 - ONE_MINUS_EPSILON, ONE_MINUS_EPSILON),
+            sample.y.clamp(1.0 - ONE_MINUS_EPSILON, ONE_MINUS_EPSILON),
+        );
+
+        let (param_weight, slice_offset) = self.param_weights(params);
+
+        // Sample the row first
+        let offset = slice_offset * size_y;
+        let fetch_marginal = |idx: usize| -> f64 {
+            self.lookup(
+                &self.marginal_cdf,
+                dimension,
+                offset + idx,
+                size_y,
+                &param_weight,
+            )
+        };
+
+        let row = find_interval(size_y, |idx| fetch_marginal(idx) < sample.y);
+        sample.y -= fetch_marginal(row);
+
+        let slice_size = size_x * size_y;
+        let offset = row * size_x + slice_offset * slice_size;
+
+        let r0 = self.lookup(
+            &self.conditional_cdf,
+            dimension,
+            offset + size_x - 1,
+            slice_size,
+            &param_weight,
+        );
+        let r1 = self.lookup(
+            &self.conditional_cdf,
+            dimension,
+            offset + size_x * 2 - 1,
+            slice_size,
+            &param_weight,
+        );
+
+        let is_const = (r0 - r1).abs() < 1e-4 * (r0 + r1);
+        sample.y = if is_const {
+            2.0 * sample.y
+        } else {
+            r0 - safe_sqrt(r0 * r0 - 2.0 * sample.y * (r0 - r1))
+        };
+        sample.y /= if is_const { r0 + r1 } else { r0 - r1 };
+
+        // Sample the column next
+        sample.x *= (1.0 - sample.y) * r0 + sample.y * r1;
+
+        let fetch_conditional = |idx: usize| -> f64 {
+            let v0 = self.lookup(
+                &self.conditional_cdf,
+                dimension,
+                offset + idx,
+                slice_size,
+                &param_weight,
+            );
+            let v1 = self.lookup(
+                &self.conditional_cdf[size_x..],
+                dimension,
+                offset + idx,
+                slice_size,
+                &param_weight,
+            );
+
+            (1.0 - sample.y) * v0 + sample.y * v1
+        };
+
+        let col = find_interval(size_x, |idx| fetch_conditional(idx) < sample.x);
+        sample.x -= fetch_conditional(col);
+
+        let offset = offset + col;
+
+        let v00 = self.lookup(&self.data, dimension, offset, slice_size, &param_weight);
+        let v10 = self.lookup(
+            &self.data[1..],
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+        let v01 = self.lookup(
+            &self.data[size_x..],
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+        let v11 = self.lookup(
+            &self.data[size_x + 1..],
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+        let c0 = fma(1.0 - sample.y, v00, sample.y * v01);
+        let c1 = fma(1.0 - sample.y, v10, sample.y * v11);
+
+        let is_const = (c0 - c1).abs() < 1e-4 * (c0 + c1);
+        sample.x = if is_const {
+            2.0 * sample.x
+        } else {
+            c0 - safe_sqrt(c0 * c0 - 2.0 * sample.x * (c0 - c1))
+        };
+        let divisor = if is_const { c0 + c1 } else { c0 - c1 };
+        if divisor != 0.0 {
+            sample.x /= divisor;
+        }
+
+        return PLSample {
+            p: Point2f::new(
+                (col as f64 + sample.x) * self.patch_size.x,
+                (row as f64 + sample.y) * self.patch_size.y,
+            ),
+            pdf: ((1.0 - sample.x) * c0 + sample.x * c1)
+                * (self.inv_patch_size.x * self.inv_patch_size.y),
+        };
+    }
+
+    pub fn invert(&self, sample: Point2f, params: &[f64]) -> PLSample {
+        let dimension = self.param_size.len();
+        let size_x = self.size.x as usize;
+        let size_y = self.size.y as usize;
+
+        let (param_weight, slice_offset) = self.param_weights(params);
+
+        // Fetch values at corners of bilinear patch
+        let mut sample = Point2f::new(
+            sample.x * self.inv_patch_size.x,
+            sample.y * self.inv_patch_size.y,
+        );
+        let pos = Point2i::new(
+            (sample.x as i32).min(self.size.x - 2),
+            (sample.y as i32).min(self.size.y - 2),
+        );
+        sample.x -= pos.x as f64;
+        sample.y -= pos.y as f64;
+
+        let slice_size = size_x * size_y;
+        let offset = pos.x as usize + pos.y as usize * size_x + slice_offset * slice_size;
+
+        // Invert the X component
+        let v00 = self.lookup(&self.data, dimension, offset, slice_size, &param_weight);
+        let v10 = self.lookup(
+            &self.data[1..],
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+        let v01 = self.lookup(
+            &self.data[size_x..],
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+        let v11 = self.lookup(
+            &self.data[size_x + 1..],
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+
+        let w1 = sample;
+        let w0 = Point2f::new(1.0 - w1.x, 1.0 - w1.y);
+
+        let c0 = fma(w0.y, v00, w1.y * v01);
+        let c1 = fma(w0.y, v10, w1.y * v11);
+        let pdf = fma(w0.x, c0, w1.x * c1);
+
+        sample.x *= c0 + 0.5 * sample.x * (c1 - c0);
+
+        let v0 = self.lookup(
+            &self.conditional_cdf,
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+        let v1 = self.lookup(
+            &self.conditional_cdf[size_x..],
+            dimension,
+            offset,
+            slice_size,
+            &param_weight,
+        );
+
+        sample.x += (1.0 - sample.y) * v0 + sample.y * v1;
+
+        let offset = pos.y as usize * size_x + slice_offset * slice_size;
+
+        let r0 = self.lookup(
+            &self.conditional_cdf,
+            dimension,
+            offset + size_x - 1,
+            slice_size,
+            &param_weight,
+        );
+        let r1 = self.lookup(
+            &self.conditional_cdf,
+            dimension,
+            offset + size_x * 2 - 1,
+            slice_size,
+            &param_weight,
+        );
+
+        sample.x /= (1.0 - sample.y) * r0 + sample.y * r1;
+
+        // Invert the Y component
+        sample.y *= r0 + 0.5 * sample.y * (r1 - r0);
+
+        let offset = pos.y as usize + slice_offset * size_y;
+        sample.y += self.lookup(&self.marginal_cdf, dimension, offset, size_y, &param_weight);
+
+        return PLSample {
+            p: sample,
+            pdf: pdf * (self.inv_patch_size.x * self.inv_patch_size.y),
+        };
+    }
+
+    pub fn evaluate(&self, pos: Point2f, params: &[f64]) -> f64 {
+        let dimension = self.param_size.len();
+        let size_x = self.size.x as usize;
+        let size_y = self.size.y as usize;
+
+        let (param_weight, slice_offset) = self.param_weights(params);
+
+        // Compute linear interpolation weights
+        let pos = Point2f::new(pos.x * self.inv_patch_size.x, pos.y * self.inv_patch_size.y);
+        let offset = Point2i::new(
+            (pos.x as i32).min(self.size.x - 2),
+            (pos.y as i32).min(self.size.y - 2),
+        );
+
+        let w1 = Point2f::new(pos.x - offset.x as f64, pos.y - offset.y as f64);
+        let w0 = Point2f::new(1.0 - w1.x, 1.0 - w1.y);
+
+        let size = size_x * size_y;
+        let index = offset.x as usize + offset.y as usize * size_x + slice_offset * size;
+
+        let v00 = self.lookup(&self.data, dimension, index, size, &param_weight);
+        let v10 = self.lookup(&self.data[1..], dimension, index, size, &param_weight);
+        let v01 = self.lookup(&self.data[size_x..], dimension, index, size, &param_weight);
+        let v11 = self.lookup(
+            &self.data[size_x + 1..],
+            dimension,
+            index,
+            size,
+            &param_weight,
+        );
+
+        return fma(
+            w0.y,
+            fma(w0.x, v00, w1.x * v10),
+            w1.y * fma(w0.x, v01, w1.x * v11),
+        ) * (self.inv_patch_size.x * self.inv_patch_size.y);
+    }
+}
@@ -52,6 +52,69 @@ pub fn fr_dielectric(cos_theta_i: f64, _eta: f64) -> f64 {
     return (sqr(r_parallel) + sqr(r_perpendicular)) / 2.0;
 }
 
+pub fn fr_complex(cos_theta_i: f64, eta: Complex) -> f64 {
+    let cos_theta_i = cos_theta_i.clamp(0.0, 1.0);
+    // Compute complex $\cos\,\theta_\roman{t}$ for Fresnel equations using Snell's law
+    let sin2_theta_i = 1.0 - sqr(cos_theta_i);
+    let sin2_theta_t = Complex::from_real(sin2_theta_i) / (eta * eta);
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+
+    let r_parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perpendicular = (Complex::from_real(cos_theta_i) - eta * cos_theta_t)
+        / (Complex::from_real(cos_theta_i) + eta * cos_theta_t);
+
+    return (r_parallel.norm() + r_perpendicular.norm()) / 2.0;
+}
+
+pub fn fr_complex_spectrum(
+    cos_theta_i: f64,
+    eta: SampledSpectrum,
+    k: SampledSpectrum,
+) -> SampledSpectrum {
+    let mut result = SampledSpectrum::same_value(0.0);
+    for i in 0..NUM_SPECTRUM_SAMPLES {
+        result[i] = fr_complex(cos_theta_i, Complex::new(eta[i], k[i]));
+    }
+
+    return result;
+}
+
+pub fn fresnel_moment1(eta: f64) -> f64 {
+    let eta2 = eta * eta;
+    let eta3 = eta2 * eta;
+    let eta4 = eta3 * eta;
+    let eta5 = eta4 * eta;
+
+    return if eta < 1.0 {
+        0.45966 - 1.73965 * eta + 3.37668 * eta2 - 3.904945 * eta3 + 2.49277 * eta4 - 0.68441 * eta5
+    } else {
+        -4.61686 + 11.1136 * eta - 10.4646 * eta2 + 5.11455 * eta3 - 1.27198 * eta4 + 0.12746 * eta5
+    };
+}
+
+pub fn fresnel_moment2(eta: f64) -> f64 {
+    let eta2 = eta * eta;
+    let eta3 = eta2 * eta;
+    let eta4 = eta3 * eta;
+    let eta5 = eta4 * eta;
+
+    if eta < 1.0 {
+        return 0.27614 - 0.87350 * eta + 1.12077 * eta2 - 0.65095 * eta3
+            + 0.07883 * eta4
+            + 0.04860 * eta5;
+    }
+
+    let r_eta = 1.0 / eta;
+    let r_eta2 = r_eta * r_eta;
+    let r_eta3 = r_eta2 * r_eta;
+
+    return -547.033 + 45.3087 * r_eta3 - 218.725 * r_eta2 + 458.843 * r_eta + 404.557 * eta
+        - 189.519 * eta2
+        + 54.9327 * eta3
+        - 9.00603 * eta4
+        + 0.63942 * eta5;
+}
+
 pub fn henyey_greenstein(cos_theta: f64, g: f64) -> f64 {
     // The Henyey-Greenstein phase function isn't suitable for |g| \approx
     // 1 so we clamp it before it becomes numerically instable. (It's an
@@ -85,6 +148,10 @@ impl TrowbridgeReitzDistribution {
         return distribution;
     }
 
+    pub fn roughness_to_alpha(roughness: f64) -> f64 {
+        return roughness.sqrt();
+    }
+
     pub fn effectively_smooth(&self) -> bool {
         return self.alpha_x.max(self.alpha_y) < 1e-3;
     }
@@ -0,0 +1,108 @@
+use crate::pbrt::*;
+
+fn lerp_point3(t: f64, a: Point3f, b: Point3f) -> Point3f {
+    return a * (1.0 - t) + b * t;
+}
+
+pub fn blossom_cubic_bezier(cp: &[Point3f], u0: f64, u1: f64, u2: f64) -> Point3f {
+    let a = [
+        lerp_point3(u0, cp[0], cp[1]),
+        lerp_point3(u0, cp[1], cp[2]),
+        lerp_point3(u0, cp[2], cp[3]),
+    ];
+    let b = [lerp_point3(u1, a[0], a[1]), lerp_point3(u1, a[1], a[2])];
+
+    return lerp_point3(u2, b[0], b[1]);
+}
+
+pub fn cubic_bezier_control_points(cp: &[Point3f], u_min: f64, u_max: f64) -> [Point3f; 4] {
+    return [
+        blossom_cubic_bezier(cp, u_min, u_min, u_min),
+        blossom_cubic_bezier(cp, u_min, u_min, u_max),
+        blossom_cubic_bezier(cp, u_min, u_max, u_max),
+        blossom_cubic_bezier(cp, u_max, u_max, u_max),
+    ];
+}
+
+pub fn subdivide_cubic_bezier(cp: &[Point3f]) -> [Point3f; 7] {
+    return [
+        cp[0],
+        (cp[0] + cp[1]) / 2.0,
+        (cp[0] + cp[1] * 2.0 + cp[2]) / 4.0,
+        (cp[0] + cp[1] * 3.0 + cp[2] * 3.0 + cp[3]) / 8.0,
+        (cp[1] + cp[2] * 2.0 + cp[3]) / 4.0,
+        (cp[2] + cp[3]) / 2.0,
+        cp[3],
+    ];
+}
+
+// returns the point on the curve at _u_ and the curve's derivative there
+pub fn evaluate_cubic_bezier(cp: &[Point3f], u: f64) -> (Point3f, Vector3f) {
+    let cp1 = [
+        lerp_point3(u, cp[0], cp[1]),
+        lerp_point3(u, cp[1], cp[2]),
+        lerp_point3(u, cp[2], cp[3]),
+    ];
+    let cp2 = [
+        lerp_point3(u, cp1[0], cp1[1]),
+        lerp_point3(u, cp1[1], cp1[2]),
+    ];
+
+    let deriv = if (cp2[1] - cp2[0]).length_squared() > 0.0 {
+        3.0 * (cp2[1] - cp2[0])
+    } else {
+        // For a cubic Bezier, if the first three control points (say) are
+        // coincident, then the derivative of the curve is legitimately
+        // (0,0,0) at u=0. This is problematic for us, though, since we'd
+        // like to be able to compute a surface normal there. In that case,
+        // just punt and take the difference between the first and last
+        // control points, which ain't great, but will hopefully do.
+        cp[3] - cp[0]
+    };
+
+    return (lerp_point3(u, cp2[0], cp2[1]), deriv);
+}
+
+pub fn bound_cubic_bezier(cp: &[Point3f]) -> Bounds3f {
+    return Bounds3f::from_multiple_points(&cp[0..4]);
+}
+
+pub fn cubic_bspline_to_bezier(cp: &[Point3f]) -> [Point3f; 4] {
+    // Blossom from p012, p123, p234, and p345 to the Bezier control points
+    // p222, p223, p233, and p333.
+    let p012 = cp[0];
+    let p123 = cp[1];
+    let p234 = cp[2];
+    let p345 = cp[3];
+
+    let p122 = lerp_point3(2.0 / 3.0, p012, p123);
+    let p223 = lerp_point3(1.0 / 3.0, p123, p234);
+    let p233 = lerp_point3(2.0 / 3.0, p123, p234);
+    let p334 = lerp_point3(1.0 / 3.0, p234, p345);
+
+    let p222 = lerp_point3(0.5, p122, p223);
+    let p333 = lerp_point3(0.5, p233, p334);
+
+    return [p222, p223, p233, p333];
+}
+
+pub fn quadratic_bspline_to_bezier(cp: &[Point3f]) -> [Point3f; 3] {
+    // We can compute equivalent Bezier control points via some blossoming.
+    // We have three control points and a uniform knot vector; we will label
+    // the points p01, p12, and p23. We want the Bezier control points of
+    // the equivalent curve, which are p11, p12, and p22. We already have
+    // p12.
+    let p11 = lerp_point3(0.5, cp[0], cp[1]);
+    let p22 = lerp_point3(0.5, cp[1], cp[2]);
+
+    return [p11, cp[1], p22];
+}
+
+pub fn elevate_quadratic_bezier_to_cubic(cp: &[Point3f]) -> [Point3f; 4] {
+    return [
+        cp[0],
+        lerp_point3(2.0 / 3.0, cp[0], cp[1]),
+        lerp_point3(1.0 / 3.0, cp[1], cp[2]),
+        cp[2],
+    ];
+}
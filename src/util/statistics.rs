@@ -0,0 +1,441 @@
+use crate::pbrt::*;
+use std::cell::{Cell, RefCell};
+use std::mem::size_of;
+
+// events counted while rendering, in the order they are reported
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq)]
+pub enum StatCounter {
+    CameraRays,
+    ShadowRays,
+    IndirectRays,
+    BVHNodesVisited,
+    PrimitiveTests,
+    BSDFSamples,
+    LightSamples,
+}
+
+impl StatCounter {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            StatCounter::CameraRays => "camera rays",
+            StatCounter::ShadowRays => "shadow rays",
+            StatCounter::IndirectRays => "indirect rays",
+            StatCounter::BVHNodesVisited => "BVH nodes visited",
+            StatCounter::PrimitiveTests => "primitive tests",
+            StatCounter::BSDFSamples => "BSDF samples",
+            StatCounter::LightSamples => "light samples",
+        };
+    }
+
+    fn json_key(&self) -> &'static str {
+        return match self {
+            StatCounter::CameraRays => "camera_rays",
+            StatCounter::ShadowRays => "shadow_rays",
+            StatCounter::IndirectRays => "indirect_rays",
+            StatCounter::BVHNodesVisited => "bvh_nodes_visited",
+            StatCounter::PrimitiveTests => "primitive_tests",
+            StatCounter::BSDFSamples => "bsdf_samples",
+            StatCounter::LightSamples => "light_samples",
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, PartialEq)]
+pub enum MemoryCategory {
+    Meshes,
+    BVH,
+    Textures,
+    Film,
+}
+
+impl MemoryCategory {
+    pub fn name(&self) -> &'static str {
+        return match self {
+            MemoryCategory::Meshes => "meshes",
+            MemoryCategory::BVH => "BVH",
+            MemoryCategory::Textures => "textures",
+            MemoryCategory::Film => "film",
+        };
+    }
+}
+
+const NUM_COUNTERS: usize = 7;
+const NUM_MEMORY_CATEGORIES: usize = 4;
+
+// Counters are incremented without synchronization in thread local storage and moved
+// to the _RenderCounters_ of the render by _add_thread_counts()_, once per tile. Memory
+// is recorded where objects are created, in the account of the calling thread.
+thread_local! {
+    static THREAD_COUNTERS: [Cell<u64>; NUM_COUNTERS] = Default::default();
+    static THREAD_PATH_LENGTHS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+    static THREAD_MEMORY: [Cell<usize>; NUM_MEMORY_CATEGORIES] = Default::default();
+}
+
+pub fn add_stat(counter: StatCounter, count: u64) {
+    THREAD_COUNTERS.with(|counters| {
+        let cell = &counters[counter as usize];
+        cell.set(cell.get() + count);
+    });
+}
+
+pub fn record_path_length(length: usize) {
+    THREAD_PATH_LENGTHS.with(|path_lengths| {
+        let mut path_lengths = path_lengths.borrow_mut();
+        if path_lengths.len() <= length {
+            path_lengths.resize(length + 1, 0);
+        }
+        path_lengths[length] += 1;
+    });
+}
+
+pub fn add_memory(category: MemoryCategory, bytes: usize) {
+    THREAD_MEMORY.with(|memory| {
+        let cell = &memory[category as usize];
+        cell.set(cell.get() + bytes);
+    });
+}
+
+pub fn image_memory(image: &Image) -> usize {
+    return (image.resolution.x * image.resolution.y) as usize * size_of::<RGB>();
+}
+
+// the result of _f_ and the memory it recorded on the calling thread, an enclosing
+// measurement doesn't see it
+pub fn measure_memory<T>(f: impl FnOnce() -> T) -> (T, [usize; NUM_MEMORY_CATEGORIES]) {
+    let outer = THREAD_MEMORY.with(|memory| memory.each_ref().map(|cell| cell.replace(0)));
+    let result = f();
+    let recorded = THREAD_MEMORY.with(|memory| {
+        let mut recorded = [0; NUM_MEMORY_CATEGORIES];
+        for idx in 0..NUM_MEMORY_CATEGORIES {
+            recorded[idx] = memory[idx].replace(outer[idx]);
+        }
+        recorded
+    });
+
+    return (result, recorded);
+}
+
+// memory and timings recorded while building a scene, kept by its _SceneBuilder_ and
+// then by its _Renderer_, which adds the timings of the render
+#[derive(Clone, Default)]
+pub struct SceneStatistics {
+    memory: [usize; NUM_MEMORY_CATEGORIES],
+    phases: Vec<(String, Duration)>,
+}
+
+impl SceneStatistics {
+    pub fn add_memory(&mut self, category: MemoryCategory, bytes: usize) {
+        self.memory[category as usize] += bytes;
+    }
+
+    // memory returned by _measure_memory()_
+    pub fn add_measured_memory(&mut self, memory: &[usize; NUM_MEMORY_CATEGORIES]) {
+        for idx in 0..NUM_MEMORY_CATEGORIES {
+            self.memory[idx] += memory[idx];
+        }
+    }
+
+    pub fn record_phase(&mut self, name: &str, duration: Duration) {
+        self.phases.push((name.to_string(), duration));
+    }
+
+    // the memory of a scene merged into this one, its timings are part of ours
+    pub fn merge(&mut self, other: &SceneStatistics) {
+        self.add_measured_memory(&other.memory);
+    }
+}
+
+// events counted by the rendering threads of a single render
+#[derive(Default)]
+pub struct RenderCounters {
+    counters: [u64; NUM_COUNTERS],
+    // number of paths by number of scattering events
+    path_lengths: Vec<u64>,
+}
+
+impl RenderCounters {
+    // move the counts of the calling thread into _self_
+    pub fn add_thread_counts(&mut self) {
+        THREAD_COUNTERS.with(|counters| {
+            for idx in 0..NUM_COUNTERS {
+                self.counters[idx] += counters[idx].replace(0);
+            }
+        });
+
+        THREAD_PATH_LENGTHS.with(|path_lengths| {
+            let path_lengths = std::mem::take(&mut *path_lengths.borrow_mut());
+            if self.path_lengths.len() < path_lengths.len() {
+                self.path_lengths.resize(path_lengths.len(), 0);
+            }
+            for (length, count) in path_lengths.into_iter().enumerate() {
+                self.path_lengths[length] += count;
+            }
+        });
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    let bytes = bytes as f64;
+    if bytes >= 1024.0 * 1024.0 * 1024.0 {
+        return format!("{:.2} GiB", bytes / (1024.0 * 1024.0 * 1024.0));
+    }
+    if bytes >= 1024.0 * 1024.0 {
+        return format!("{:.2} MiB", bytes / (1024.0 * 1024.0));
+    }
+    return format!("{:.2} KiB", bytes / 1024.0);
+}
+
+fn json_string(value: &str) -> String {
+    return format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+// everything known about a finished render, printed by `--stats` or written as JSON
+pub struct RenderStatistics {
+    pub resolution: Point2i,
+    pub pixel_bounds: Bounds2i,
+    pub num_threads: usize,
+    pub camera_samples: usize,
+    pub rendering_time: Duration,
+
+    pub counters: Vec<(StatCounter, u64)>,
+    pub path_lengths: Vec<u64>,
+    pub memory: Vec<(MemoryCategory, usize)>,
+    pub phases: Vec<(String, Duration)>,
+}
+
+impl RenderStatistics {
+    // the render description completed with what was counted and recorded
+    pub fn collect(
+        resolution: Point2i,
+        pixel_bounds: Bounds2i,
+        num_threads: usize,
+        camera_samples: usize,
+        rendering_time: Duration,
+        render_counters: &RenderCounters,
+        scene_statistics: &SceneStatistics,
+    ) -> Self {
+        return RenderStatistics {
+            resolution,
+            pixel_bounds,
+            num_threads,
+            camera_samples,
+            rendering_time,
+            counters: StatCounter::iter()
+                .map(|counter| (counter, render_counters.counters[counter as usize]))
+                .collect(),
+            path_lengths: render_counters.path_lengths.clone(),
+            memory: MemoryCategory::iter()
+                .map(|category| (category, scene_statistics.memory[category as usize]))
+                .collect(),
+            phases: scene_statistics.phases.clone(),
+        };
+    }
+
+    pub fn get_counter(&self, counter: StatCounter) -> u64 {
+        return self.counters[counter as usize].1;
+    }
+
+    pub fn total_rays(&self) -> u64 {
+        return self.get_counter(StatCounter::CameraRays)
+            + self.get_counter(StatCounter::ShadowRays)
+            + self.get_counter(StatCounter::IndirectRays);
+    }
+
+    fn per_second(&self, count: f64) -> f64 {
+        return count / self.rendering_time.as_secs_f64().max(1e-6);
+    }
+
+    fn per_ray(&self, count: u64) -> f64 {
+        return count as f64 / (self.total_rays() as f64).max(1.0);
+    }
+
+    pub fn print(&self) {
+        let num_pixels = self.pixel_bounds.area();
+
+        println!("statistics:");
+        println!(
+            "    {:<24}{} x {}",
+            "resolution", self.resolution.x, self.resolution.y
+        );
+        println!(
+            "    {:<24}[{}, {}) x [{}, {})",
+            "pixel bounds",
+            self.pixel_bounds.p_min.x,
+            self.pixel_bounds.p_max.x,
+            self.pixel_bounds.p_min.y,
+            self.pixel_bounds.p_max.y
+        );
+        println!("    {:<24}{}", "threads", self.num_threads);
+        println!("    {:<24}{}", "camera samples", self.camera_samples);
+        println!(
+            "    {:<24}{:.1}",
+            "samples per pixel",
+            self.camera_samples as f64 / num_pixels as f64
+        );
+        println!(
+            "    {:<24}{:.2} seconds",
+            "rendering time",
+            self.rendering_time.as_secs_f64()
+        );
+        println!(
+            "    {:<24}{:.0}",
+            "samples per second",
+            self.per_second(self.camera_samples as f64)
+        );
+
+        println!("rays:");
+        for counter in [
+            StatCounter::CameraRays,
+            StatCounter::ShadowRays,
+            StatCounter::IndirectRays,
+        ] {
+            println!("    {:<24}{}", counter.name(), self.get_counter(counter));
+        }
+        println!("    {:<24}{}", "total", self.total_rays());
+        println!(
+            "    {:<24}{:.0}",
+            "rays per second",
+            self.per_second(self.total_rays() as f64)
+        );
+
+        println!("intersection:");
+        for counter in [StatCounter::BVHNodesVisited, StatCounter::PrimitiveTests] {
+            let count = self.get_counter(counter);
+            println!(
+                "    {:<24}{} ({:.1} per ray)",
+                counter.name(),
+                count,
+                self.per_ray(count)
+            );
+        }
+
+        println!("sampling:");
+        for counter in [StatCounter::BSDFSamples, StatCounter::LightSamples] {
+            println!("    {:<24}{}", counter.name(), self.get_counter(counter));
+        }
+
+        let num_paths = self.path_lengths.iter().sum::<u64>();
+        if num_paths > 0 {
+            println!("path length:");
+            for (length, count) in self.path_lengths.iter().enumerate() {
+                if *count == 0 {
+                    continue;
+                }
+                println!(
+                    "    {:<24}{} ({:.1}%)",
+                    length,
+                    count,
+                    100.0 * *count as f64 / num_paths as f64
+                );
+            }
+        }
+
+        println!("memory:");
+        for (category, bytes) in &self.memory {
+            println!("    {:<24}{}", category.name(), format_bytes(*bytes));
+        }
+
+        if !self.phases.is_empty() {
+            println!("timings:");
+            for (name, duration) in &self.phases {
+                println!("    {:<24}{:.2} seconds", name, duration.as_secs_f64());
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![
+            format!(
+                "\"resolution\": [{}, {}]",
+                self.resolution.x, self.resolution.y
+            ),
+            format!(
+                "\"pixel_bounds\": [{}, {}, {}, {}]",
+                self.pixel_bounds.p_min.x,
+                self.pixel_bounds.p_max.x,
+                self.pixel_bounds.p_min.y,
+                self.pixel_bounds.p_max.y
+            ),
+            format!("\"threads\": {}", self.num_threads),
+            format!("\"camera_samples\": {}", self.camera_samples),
+            format!(
+                "\"rendering_seconds\": {}",
+                self.rendering_time.as_secs_f64()
+            ),
+        ];
+
+        let counters = self
+            .counters
+            .iter()
+            .map(|(counter, count)| format!("\"{}\": {}", counter.json_key(), count))
+            .collect::<Vec<String>>();
+        fields.push(format!("\"counters\": {{{}}}", counters.join(", ")));
+
+        let path_lengths = self
+            .path_lengths
+            .iter()
+            .map(|count| count.to_string())
+            .collect::<Vec<String>>();
+        fields.push(format!("\"path_lengths\": [{}]", path_lengths.join(", ")));
+
+        let memory = self
+            .memory
+            .iter()
+            .map(|(category, bytes)| format!("{}: {}", json_string(category.name()), bytes))
+            .collect::<Vec<String>>();
+        fields.push(format!("\"memory_bytes\": {{{}}}", memory.join(", ")));
+
+        let phases = self
+            .phases
+            .iter()
+            .map(|(name, duration)| format!("{}: {}", json_string(name), duration.as_secs_f64()))
+            .collect::<Vec<String>>();
+        fields.push(format!("\"phase_seconds\": {{{}}}", phases.join(", ")));
+
+        return format!("{{\n  {}\n}}\n", fields.join(",\n  "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measurements_and_counters_are_kept_apart() {
+        let (_, outer) = measure_memory(|| {
+            add_memory(MemoryCategory::Film, 64);
+            let (_, inner) = measure_memory(|| add_memory(MemoryCategory::Meshes, 16));
+            assert_eq!(inner[MemoryCategory::Meshes as usize], 16);
+            add_memory(MemoryCategory::Film, 32);
+        });
+        assert_eq!(outer[MemoryCategory::Film as usize], 96);
+        assert_eq!(outer[MemoryCategory::Meshes as usize], 0);
+
+        let mut scene_statistics = SceneStatistics::default();
+        scene_statistics.add_measured_memory(&outer);
+        scene_statistics.record_phase("parsing", Duration::from_secs(2));
+
+        // every render gets its own counters, tiles of another render don't reach them
+        let mut render_counters = RenderCounters::default();
+        add_stat(StatCounter::ShadowRays, 2);
+        record_path_length(3);
+        RenderCounters::default().add_thread_counts();
+        add_stat(StatCounter::CameraRays, 1);
+        render_counters.add_thread_counts();
+
+        let statistics = RenderStatistics::collect(
+            Point2i::new(1, 1),
+            Bounds2i::from_min_max(Point2i::new(0, 0), Point2i::new(1, 1)),
+            1,
+            1,
+            Duration::from_secs(1),
+            &render_counters,
+            &scene_statistics,
+        );
+        assert_eq!(statistics.get_counter(StatCounter::CameraRays), 1);
+        assert_eq!(statistics.get_counter(StatCounter::ShadowRays), 0);
+        assert!(statistics.path_lengths.is_empty());
+        assert_eq!(statistics.memory[MemoryCategory::Film as usize].1, 96);
+        assert_eq!(statistics.phases.len(), 1);
+    }
+}